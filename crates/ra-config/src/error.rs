@@ -60,6 +60,17 @@ pub enum ConfigError {
     #[error("could not determine home directory")]
     NoHomeDirectory,
 
+    /// A tree's synonym file could not be read or parsed.
+    #[error("failed to load synonyms for tree '{tree}' from {path}: {message}")]
+    SynonymFile {
+        /// Name of the tree.
+        tree: String,
+        /// Path to the synonym file.
+        path: PathBuf,
+        /// What went wrong.
+        message: String,
+    },
+
     /// Failed to canonicalize a path.
     #[error("failed to resolve path {path}: {source}")]
     PathResolution {
@@ -12,12 +12,16 @@ mod merge;
 mod parse;
 mod patterns;
 mod resolve;
+mod synonyms;
 mod templates;
 #[cfg(test)]
 mod test_support;
 mod validate;
 
-use std::path::{Component, Path, PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Component, Path, PathBuf},
+};
 
 use directories::BaseDirs;
 pub use discovery::{CONFIG_FILENAME, discover_config_files, global_config_path, is_global_config};
@@ -208,6 +212,7 @@ pub const DEFAULT_SAMPLE_SIZE: usize = 50_000;
 pub use error::ConfigError;
 pub use patterns::{CompiledContextRules, CompiledPatterns, MatchedRules};
 use serde::{Deserialize, Serialize};
+pub use synonyms::Synonyms;
 pub use templates::{global_template, local_template};
 pub use validate::ConfigWarning;
 use validate::validate_config;
@@ -270,6 +275,8 @@ pub struct Config {
     pub context: ContextSettings,
     /// Resolved trees with their absolute paths and patterns.
     pub trees: Vec<Tree>,
+    /// Saved queries by name, referenced as `@name` in search queries.
+    pub queries: HashMap<String, String>,
     /// Directory containing the most specific config file (determines index location).
     pub config_root: Option<PathBuf>,
 }
@@ -330,6 +337,7 @@ impl Config {
     /// - Trees that are defined but not referenced by any include pattern
     /// - Include patterns that reference undefined trees
     /// - Empty configuration (no trees defined)
+    /// - Saved query names that can't be referenced as `@name`
     pub fn validate(&self) -> Vec<ConfigWarning> {
         validate_config(self)
     }
@@ -337,13 +345,14 @@ impl Config {
     /// Serializes the effective settings to TOML format.
     ///
     /// This outputs the merged configuration settings in the same format as a `.ra.toml` file,
-    /// making it easy to see the effective configuration. Trees and include patterns are not
-    /// included since they have resolved paths and additional metadata.
+    /// making it easy to see the effective configuration, including any saved queries. Trees and
+    /// include patterns are not included since they have resolved paths and additional metadata.
     pub fn settings_to_toml(&self) -> String {
         let serializable = SerializableSettings {
             settings: self.settings.clone(),
             search: self.search.clone(),
             context: SerializableContextSettings::from(&self.context),
+            queries: self.queries.clone().into_iter().collect(),
         };
         toml::to_string_pretty(&serializable).expect("settings serialization should not fail")
     }
@@ -433,6 +442,10 @@ pub struct SearchSettings {
     pub markdown_h4_h6: f32,
     /// Weight for terms extracted from body text.
     pub markdown_body: f32,
+
+    /// Query-time synonyms: each term also searches for its expansions.
+    #[serde(skip_serializing_if = "Synonyms::is_empty")]
+    pub synonyms: Synonyms,
 }
 
 impl Default for SearchSettings {
@@ -456,6 +469,7 @@ impl Default for SearchSettings {
             markdown_h2_h3: DEFAULT_MARKDOWN_H2_H3,
             markdown_h4_h6: DEFAULT_MARKDOWN_H4_H6,
             markdown_body: DEFAULT_MARKDOWN_BODY,
+            synonyms: Synonyms::new(),
         }
     }
 }
@@ -592,6 +606,9 @@ struct SerializableSettings {
     search: SearchSettings,
     /// Context command settings.
     context: SerializableContextSettings,
+    /// Saved queries, sorted by name.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    queries: BTreeMap<String, String>,
 }
 
 /// Context settings serializable to TOML.
@@ -696,6 +713,8 @@ pub struct Tree {
     pub include: Vec<String>,
     /// Exclude patterns for files to skip (defaults to empty).
     pub exclude: Vec<String>,
+    /// Synonyms loaded from the tree's synonym file, applied only to its documents.
+    pub synonyms: Synonyms,
 }
 
 #[cfg(test)]
@@ -781,6 +800,7 @@ mod tests {
             is_global: false,
             include: vec!["**/*.md".into()],
             exclude: vec![],
+            synonyms: Default::default(),
         };
         assert_eq!(tree.name, "docs");
         assert!(!tree.is_global);
@@ -809,6 +829,18 @@ mod tests {
         assert!(parsed.get("settings").is_some());
         assert!(parsed.get("search").is_some());
         assert!(parsed.get("context").is_some());
+        assert!(parsed.get("queries").is_none());
+    }
+
+    #[test]
+    fn test_settings_to_toml_includes_queries() {
+        let config = Config {
+            queries: HashMap::from([("security".to_string(), "auth -deprecated".to_string())]),
+            ..Default::default()
+        };
+        let toml = config.settings_to_toml();
+        assert!(toml.contains("[queries]"));
+        assert!(toml.contains("security = \"auth -deprecated\""));
     }
 
     #[test]
@@ -12,7 +12,8 @@ use crate::{
     parse::{
         RawConfig, RawContextRule, RawContextSettings, RawSearchSettings, RawSettings, RawTree,
     },
-    resolve::resolve_tree_path,
+    resolve::{resolve_file_path, resolve_tree_path},
+    synonyms::{Synonyms, load_synonym_file},
 };
 
 /// Default include patterns when none are specified.
@@ -35,6 +36,8 @@ pub struct ParsedConfig {
 /// - Scalar settings: first defined value wins (highest precedence)
 /// - Trees: merged by name, first definition wins completely (path, include, exclude)
 /// - Context patterns: merged, first definition for each key wins
+/// - Saved queries: merged by name, first definition wins
+/// - Synonyms: merged by term, first definition wins
 pub fn merge_configs(configs: &[ParsedConfig]) -> Result<Config, ConfigError> {
     if configs.is_empty() {
         return Ok(Config::default());
@@ -44,6 +47,7 @@ pub fn merge_configs(configs: &[ParsedConfig]) -> Result<Config, ConfigError> {
     let search = merge_search_settings(configs);
     let context = merge_context_settings(configs);
     let trees = merge_trees(configs)?;
+    let queries = merge_queries(configs);
     let config_root = configs
         .first()
         .map(|c| c.path.parent().unwrap().to_path_buf());
@@ -53,6 +57,7 @@ pub fn merge_configs(configs: &[ParsedConfig]) -> Result<Config, ConfigError> {
         search,
         context,
         trees,
+        queries,
         config_root,
     })
 }
@@ -121,6 +126,9 @@ fn apply_raw_search(result: &mut SearchSettings, raw: &RawSearchSettings) {
     apply_opt(&mut result.aggregation_pool_size, raw.aggregation_pool_size);
     apply_opt(&mut result.cutoff_ratio, raw.cutoff_ratio);
     apply_opt(&mut result.aggregation_threshold, raw.aggregation_threshold);
+    if let Some(ref synonyms) = raw.synonyms {
+        result.synonyms.extend(synonyms.0.clone());
+    }
 }
 
 /// Merges context settings.
@@ -179,6 +187,20 @@ fn convert_context_rule(raw: &RawContextRule) -> ContextRule {
     }
 }
 
+/// Merges saved queries from all configs by name.
+fn merge_queries(configs: &[ParsedConfig]) -> HashMap<String, String> {
+    let mut result = HashMap::new();
+
+    // Iterate in reverse (lowest precedence first) so higher precedence overwrites
+    for parsed in configs.iter().rev() {
+        if let Some(ref queries) = parsed.config.queries {
+            result.extend(queries.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+    }
+
+    result
+}
+
 /// Merges trees from all configs, resolving paths.
 ///
 /// Trees are merged by name - first definition wins completely.
@@ -202,10 +224,14 @@ fn merge_trees(configs: &[ParsedConfig]) -> Result<Vec<Tree>, ConfigError> {
             }
 
             let resolved_path = resolve_tree_path(&raw_tree.path, config_dir)?;
+            let synonyms = match raw_tree.synonyms {
+                Some(ref path) => load_synonym_file(name, &resolve_file_path(path, config_dir)?)?,
+                None => Synonyms::new(),
+            };
 
             seen.insert(
                 name.clone(),
-                convert_tree(name, raw_tree, resolved_path, is_global),
+                convert_tree(name, raw_tree, resolved_path, is_global, synonyms),
             );
         }
     }
@@ -217,7 +243,13 @@ fn merge_trees(configs: &[ParsedConfig]) -> Result<Vec<Tree>, ConfigError> {
 }
 
 /// Converts a raw tree to the final type with defaults applied.
-fn convert_tree(name: &str, raw: &RawTree, resolved_path: PathBuf, is_global: bool) -> Tree {
+fn convert_tree(
+    name: &str,
+    raw: &RawTree,
+    resolved_path: PathBuf,
+    is_global: bool,
+    synonyms: Synonyms,
+) -> Tree {
     let include = raw.include.clone().unwrap_or_else(|| {
         DEFAULT_INCLUDE_PATTERNS
             .iter()
@@ -233,12 +265,13 @@ fn convert_tree(name: &str, raw: &RawTree, resolved_path: PathBuf, is_global: bo
         is_global,
         include,
         exclude,
+        synonyms,
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::path::Path;
+    use std::{fs, path::Path};
 
     use super::*;
     use crate::{parse::parse_config_str, test_support::TestDir};
@@ -383,6 +416,42 @@ include = ["**/*"]
         assert_eq!(result.trees[0].include, vec!["**/*.md"]);
     }
 
+    #[test]
+    fn test_merge_queries_by_name() {
+        let test_dir = TestDir::new();
+
+        let high_prec = ParsedConfig {
+            path: test_dir.path().join("project/.ra.toml"),
+            config: parse_config_str(
+                r#"
+[queries]
+security = "auth -deprecated"
+"#,
+                Path::new("test"),
+            )
+            .unwrap(),
+        };
+
+        let low_prec = ParsedConfig {
+            path: test_dir.path().join(".ra.toml"),
+            config: parse_config_str(
+                r#"
+[queries]
+security = "auth OR token"
+api = "title:api @security"
+"#,
+                Path::new("test"),
+            )
+            .unwrap(),
+        };
+
+        let result = merge_configs(&[high_prec, low_prec]).unwrap();
+
+        assert_eq!(result.queries.len(), 2);
+        assert_eq!(result.queries["security"], "auth -deprecated");
+        assert_eq!(result.queries["api"], "title:api @security");
+    }
+
     #[test]
     fn test_merge_trees_different_names() {
         let test_dir = TestDir::new();
@@ -583,4 +652,50 @@ stemmer = "german"
 
         assert_eq!(result.search.stemmer, "french"); // high prec wins
     }
+
+    #[test]
+    fn test_merge_synonyms() {
+        let test_dir = TestDir::new();
+        test_dir.create_dir("project/docs");
+        let synonyms = test_dir.create_file("project/ops-synonyms.toml");
+        fs::write(synonyms, "k8s = \"kube\"\n").unwrap();
+
+        let high_prec = ParsedConfig {
+            path: test_dir.path().join("project/.ra.toml"),
+            config: parse_config_str(
+                r#"
+[tree.docs]
+path = "./docs"
+synonyms = "ops-synonyms.toml"
+
+[search.synonyms]
+authn = ["authentication", "login"]
+"#,
+                Path::new("test"),
+            )
+            .unwrap(),
+        };
+
+        let low_prec = ParsedConfig {
+            path: test_dir.path().join(".ra.toml"),
+            config: parse_config_str(
+                r#"
+[search.synonyms]
+authn = "auth"
+k8s = "kubernetes"
+"#,
+                Path::new("test"),
+            )
+            .unwrap(),
+        };
+
+        let result = merge_configs(&[high_prec, low_prec]).unwrap();
+
+        assert_eq!(
+            result.search.synonyms["authn"],
+            vec!["authentication", "login"]
+        );
+        assert_eq!(result.search.synonyms["k8s"], vec!["kubernetes"]);
+        assert_eq!(result.trees[0].synonyms["k8s"], vec!["kube"]);
+    }
 }
@@ -10,7 +10,7 @@ use serde_with::{OneOrMany, serde_as};
 #[cfg(test)]
 use toml::de::Error as TomlError;
 
-use crate::ConfigError;
+use crate::{ConfigError, synonyms::RawSynonyms};
 
 /// Raw configuration as parsed directly from a TOML file.
 ///
@@ -29,6 +29,8 @@ pub struct RawConfig {
     pub context: Option<RawContextSettings>,
     /// Tree definitions: name -> tree config.
     pub tree: Option<HashMap<String, RawTree>>,
+    /// Saved queries: name -> query expression.
+    pub queries: Option<HashMap<String, String>>,
 }
 
 /// Raw tree definition from TOML.
@@ -40,6 +42,8 @@ pub struct RawTree {
     pub include: Option<Vec<String>>,
     /// Exclude patterns (optional, defaults to none).
     pub exclude: Option<Vec<String>>,
+    /// Path to a synonym file applied to this tree's documents (optional).
+    pub synonyms: Option<String>,
 }
 
 /// Raw general settings.
@@ -74,6 +78,8 @@ pub struct RawSearchSettings {
     pub cutoff_ratio: Option<f32>,
     /// Sibling ratio threshold for hierarchical aggregation.
     pub aggregation_threshold: Option<f32>,
+    /// Query-time synonyms: term -> expansions.
+    pub synonyms: Option<RawSynonyms>,
 }
 
 /// Raw context settings.
@@ -290,6 +290,7 @@ mod tests {
             is_global: false,
             include: include.into_iter().map(String::from).collect(),
             exclude: exclude.into_iter().map(String::from).collect(),
+            synonyms: Default::default(),
         }
     }
 
@@ -41,6 +41,19 @@ pub fn resolve_tree_path(path: &str, config_dir: &Path) -> Result<PathBuf, Confi
     Ok(canonical)
 }
 
+/// Resolves a file path from a config file, expanding `~` and making relative paths relative to
+/// `config_dir`.
+///
+/// Unlike [`resolve_tree_path`], the file need not exist yet.
+pub fn resolve_file_path(path: &str, config_dir: &Path) -> Result<PathBuf, ConfigError> {
+    let expanded = expand_tilde(path)?;
+    Ok(if expanded.is_absolute() {
+        expanded
+    } else {
+        config_dir.join(expanded)
+    })
+}
+
 /// Expands a tilde prefix to the home directory.
 ///
 /// - `~` alone becomes the home directory
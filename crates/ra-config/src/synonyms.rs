@@ -0,0 +1,76 @@
+//! Synonym tables.
+//!
+//! Synonyms map a query term to the terms it also searches for, such as `k8s` to
+//! `kubernetes`. They come from the `[search.synonyms]` table or from a tree's synonym file, a
+//! TOML file holding just such a table:
+//!
+//! ```toml
+//! k8s = "kubernetes"
+//! authn = ["authentication", "login"]
+//! ```
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use serde::Deserialize;
+use serde_with::{OneOrMany, serde_as};
+
+use crate::ConfigError;
+
+/// Synonyms by term: each term maps to the terms it expands to.
+pub type Synonyms = BTreeMap<String, Vec<String>>;
+
+/// A synonym table as written in TOML, where each value is one term or a list of terms.
+#[serde_as]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct RawSynonyms(#[serde_as(as = "BTreeMap<_, OneOrMany<_>>")] pub Synonyms);
+
+/// Loads a tree's synonym file.
+pub fn load_synonym_file(tree: &str, path: &Path) -> Result<Synonyms, ConfigError> {
+    let error = |message: String| ConfigError::SynonymFile {
+        tree: tree.to_string(),
+        path: path.to_path_buf(),
+        message,
+    };
+    let contents = fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+    let raw: RawSynonyms = toml::from_str(&contents).map_err(|e| error(e.message().into()))?;
+    Ok(raw.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::test_support::TestDir;
+
+    #[test]
+    fn loads_single_and_list_values() {
+        let test_dir = TestDir::new();
+        let path = test_dir.path().join("synonyms.toml");
+        fs::write(
+            &path,
+            "k8s = \"kubernetes\"\nauthn = [\"authentication\", \"login\"]\n",
+        )
+        .unwrap();
+
+        let synonyms = load_synonym_file("docs", &path).unwrap();
+        assert_eq!(synonyms["k8s"], vec!["kubernetes"]);
+        assert_eq!(synonyms["authn"], vec!["authentication", "login"]);
+    }
+
+    #[test]
+    fn reports_missing_and_malformed_files() {
+        let err =
+            load_synonym_file("docs", &PathBuf::from("/nonexistent/synonyms.toml")).unwrap_err();
+        assert!(err.to_string().contains("tree 'docs'"));
+
+        let test_dir = TestDir::new();
+        let path = test_dir.path().join("synonyms.toml");
+        fs::write(&path, "k8s = 8\n").unwrap();
+        assert!(matches!(
+            load_synonym_file("docs", &path),
+            Err(ConfigError::SynonymFile { .. })
+        ));
+    }
+}
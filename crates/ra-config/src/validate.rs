@@ -34,6 +34,11 @@ pub enum ConfigWarning {
     },
     /// No trees are defined.
     NoTreesDefined,
+    /// A saved query's name cannot be written as an `@name` reference.
+    QueryNameInvalid {
+        /// Name of the saved query.
+        name: String,
+    },
 }
 
 impl fmt::Display for ConfigWarning {
@@ -54,6 +59,13 @@ impl fmt::Display for ConfigWarning {
             Self::NoTreesDefined => {
                 write!(f, "no trees are defined in configuration")
             }
+            Self::QueryNameInvalid { name } => {
+                write!(
+                    f,
+                    "saved query '{name}' cannot be referenced: names must start with a letter \
+                     and contain only letters, digits, '_' and '-'"
+                )
+            }
         }
     }
 }
@@ -64,9 +76,18 @@ impl fmt::Display for ConfigWarning {
 /// - Tree paths that don't exist or aren't directories
 /// - Include patterns that don't match any files
 /// - Empty configuration (no trees defined)
+/// - Saved query names that can't be referenced as `@name`
 pub fn validate_config(config: &Config) -> Vec<ConfigWarning> {
     let mut warnings = Vec::new();
 
+    let mut query_names: Vec<&String> = config.queries.keys().collect();
+    query_names.sort();
+    for name in query_names {
+        if !is_query_name(name) {
+            warnings.push(ConfigWarning::QueryNameInvalid { name: name.clone() });
+        }
+    }
+
     // Check for empty configuration
     if config.trees.is_empty() {
         warnings.push(ConfigWarning::NoTreesDefined);
@@ -81,6 +102,14 @@ pub fn validate_config(config: &Config) -> Vec<ConfigWarning> {
     warnings
 }
 
+/// Returns true if a saved query name matches the `@name` syntax of the query language.
+fn is_query_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
 /// Validates a single tree and its include patterns.
 fn validate_tree(tree: &Tree) -> Vec<ConfigWarning> {
     let mut warnings = Vec::new();
@@ -165,6 +194,7 @@ mod tests {
             is_global: false,
             include: include.into_iter().map(String::from).collect(),
             exclude: exclude.into_iter().map(String::from).collect(),
+            synonyms: Default::default(),
         }
     }
 
@@ -176,6 +206,27 @@ mod tests {
         assert!(matches!(warnings[0], ConfigWarning::NoTreesDefined));
     }
 
+    #[test]
+    fn test_validate_query_names() {
+        let config = Config {
+            queries: [("security", "auth"), ("my query", "x"), ("2fa", "otp")]
+                .into_iter()
+                .map(|(name, body)| (name.to_string(), body.to_string()))
+                .collect(),
+            ..Default::default()
+        };
+
+        let warnings = config.validate();
+        let invalid: Vec<&str> = warnings
+            .iter()
+            .filter_map(|w| match w {
+                ConfigWarning::QueryNameInvalid { name } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(invalid, vec!["2fa", "my query"]);
+    }
+
     #[test]
     fn test_validate_tree_path_missing() {
         let config = Config {
@@ -5,9 +5,10 @@
 
 use std::path::Path;
 
-use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
 
 use crate::{
+    links::extract_links,
     node::{HeadingParams, Node},
     slug::Slugifier,
     tree::ChunkTree,
@@ -27,8 +28,12 @@ pub struct HeadingInfo {
 }
 
 /// Extracts all headings from markdown content with byte offsets for the heading line.
+///
+/// Lines inside `---` or `+++` frontmatter are never treated as headings.
 pub fn extract_headings(content: &str) -> Vec<HeadingInfo> {
-    let parser = Parser::new(content);
+    let options = Options::ENABLE_YAML_STYLE_METADATA_BLOCKS
+        | Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS;
+    let parser = Parser::new_ext(content, options);
     let mut headings = Vec::new();
     let mut current_heading: Option<(HeadingLevel, usize, String)> = None;
 
@@ -92,6 +97,7 @@ struct HeadingWithSpan {
 /// 5. Discards headings with empty spans (consecutive headings with no content between)
 /// 6. Assigns positions via pre-order traversal
 /// 7. Computes sibling counts
+/// 8. Records links, which are later assigned to the chunk whose body contains them
 pub fn build_chunk_tree(content: &str, tree_name: &str, path: &Path, doc_title: &str) -> ChunkTree {
     let headings = extract_headings(content);
     let mut slugifier = Slugifier::default();
@@ -105,6 +111,7 @@ pub fn build_chunk_tree(content: &str, tree_name: &str, path: &Path, doc_title:
     if headings.is_empty() {
         // No headings - just return document with no children
         let mut tree = ChunkTree::new(root, content.to_string());
+        tree.set_links(extract_links(content, path));
         tree.assign_positions();
         tree.assign_sibling_counts();
         return tree;
@@ -164,6 +171,7 @@ pub fn build_chunk_tree(content: &str, tree_name: &str, path: &Path, doc_title:
         Some(start) => ChunkTree::with_first_heading(root, content.to_string(), start),
         None => ChunkTree::new(root, content.to_string()),
     };
+    tree.set_links(extract_links(content, path));
     tree.assign_positions();
     tree.assign_sibling_counts();
     tree
@@ -569,6 +577,28 @@ Analysis text.
         assert_eq!(results.children[0].title, "Analysis");
     }
 
+    #[test]
+    fn test_build_chunk_tree_links() {
+        let content = "See [[Intro]].\n\n# Setup\n\nUse [install](install.md#steps).\n\n\
+                       ## Verify\n\nCheck [[#Setup]] and [[#Setup]].";
+        let path = PathBuf::from("guide/start.md");
+        let tree = build_chunk_tree(content, "docs", &path, "Doc");
+
+        let links: Vec<Vec<String>> = tree
+            .extract_chunks("Doc")
+            .iter()
+            .map(|c| c.links.iter().map(|l| l.target.clone()).collect())
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                vec!["Intro".to_string()],
+                vec!["guide/install.md".to_string()],
+                vec![String::new()],
+            ]
+        );
+    }
+
     #[test]
     fn test_span_calculation() {
         // Test with H1 followed by another H1 (same level terminates span)
@@ -4,6 +4,8 @@ use std::{io, path::PathBuf};
 
 use thiserror::Error;
 
+use crate::frontmatter::FrontmatterFormat;
+
 /// Errors that can occur when parsing documents.
 #[derive(Debug, Error)]
 pub enum DocumentError {
@@ -23,3 +25,13 @@ pub enum DocumentError {
         path: PathBuf,
     },
 }
+
+/// Frontmatter that was recognised by its delimiters but could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("malformed {format} frontmatter: {message}")]
+pub struct FrontmatterError {
+    /// The frontmatter format indicated by the delimiters.
+    pub format: FrontmatterFormat,
+    /// Description of the parse failure.
+    pub message: String,
+}
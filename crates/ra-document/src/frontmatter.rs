@@ -1,6 +1,7 @@
-//! YAML frontmatter parsing for markdown documents.
+//! Frontmatter parsing for markdown documents.
 //!
-//! Frontmatter is optional metadata at the start of a markdown file, delimited by `---`:
+//! Frontmatter is optional metadata at the start of a markdown file. Three formats are
+//! recognised, all parsed into the same [`Frontmatter`] structure:
 //!
 //! ```markdown
 //! ---
@@ -10,80 +11,246 @@
 //!
 //! # Content starts here
 //! ```
+//!
+//! TOML between `+++` fences, as used by Hugo and Zola:
+//!
+//! ```markdown
+//! +++
+//! title = "My Document"
+//! tags = ["rust", "tutorial"]
+//! +++
+//! ```
+//!
+//! And a leading JSON object:
+//!
+//! ```markdown
+//! {
+//!   "title": "My Document",
+//!   "tags": ["rust", "tutorial"]
+//! }
+//! ```
+//!
+//! Any other keys with scalar or list values are kept as [`Metadata`].
+
+use std::{collections::BTreeMap, fmt};
 
 use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
+
+use crate::{
+    error::FrontmatterError,
+    meta::{MetaValue, Metadata},
+};
 
 /// Parsed frontmatter from a markdown document.
 #[derive(Debug, Clone, Default, Deserialize)]
+#[serde(from = "RawFrontmatter")]
 pub struct Frontmatter {
     /// Document title.
     pub title: Option<String>,
     /// Document tags (supports both array and Obsidian-style inline tags).
-    #[serde(default)]
     pub tags: Vec<String>,
+    /// Remaining keys with scalar or list values.
+    pub meta: Metadata,
 }
 
-/// Parses YAML frontmatter from markdown content.
+/// Frontmatter as written, with every key besides `title` and `tags` still untyped.
+#[derive(Deserialize)]
+struct RawFrontmatter {
+    /// Document title.
+    title: Option<String>,
+    /// Document tags.
+    #[serde(default)]
+    tags: Vec<String>,
+    /// All other keys.
+    #[serde(flatten)]
+    rest: BTreeMap<String, Value>,
+}
+
+impl From<RawFrontmatter> for Frontmatter {
+    fn from(raw: RawFrontmatter) -> Self {
+        Self {
+            title: raw.title,
+            tags: raw.tags,
+            meta: raw
+                .rest
+                .into_iter()
+                .filter_map(|(key, value)| Some((key, MetaValue::from_yaml(value)?)))
+                .collect(),
+        }
+    }
+}
+
+/// The syntax a frontmatter block is written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrontmatterFormat {
+    /// YAML between `---` fences.
+    Yaml,
+    /// TOML between `+++` fences.
+    Toml,
+    /// A JSON object at the start of the file.
+    Json,
+}
+
+impl FrontmatterFormat {
+    /// Parses a frontmatter block written in this format.
+    fn parse(self, block: &str) -> Result<Frontmatter, String> {
+        match self {
+            Self::Yaml => serde_yaml::from_str(block).map_err(|e| e.to_string()),
+            Self::Toml => {
+                let table: toml::Table =
+                    toml::from_str(block).map_err(|e| e.message().to_string())?;
+                let value = toml_to_yaml(toml::Value::Table(table));
+                serde_yaml::from_value(value).map_err(|e| e.to_string())
+            }
+            Self::Json => serde_json::from_str(block).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for FrontmatterFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Yaml => "YAML",
+            Self::Toml => "TOML",
+            Self::Json => "JSON",
+        })
+    }
+}
+
+/// Converts a TOML value into the YAML value model shared by all formats.
+///
+/// TOML datetimes have no YAML equivalent and are kept as strings.
+fn toml_to_yaml(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::Number(i.into()),
+        toml::Value::Float(x) => Value::Number(x.into()),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(dt) => Value::String(dt.to_string()),
+        toml::Value::Array(items) => Value::Sequence(items.into_iter().map(toml_to_yaml).collect()),
+        toml::Value::Table(table) => Value::Mapping(
+            table
+                .into_iter()
+                .map(|(key, value)| (Value::String(key), toml_to_yaml(value)))
+                .collect::<Mapping>(),
+        ),
+    }
+}
+
+/// Parses frontmatter from markdown content.
 ///
 /// Returns the parsed frontmatter (if valid) and the remaining content after the frontmatter.
 /// If no frontmatter is present or it's malformed, returns `None` and the original content.
-///
-/// Frontmatter must:
-/// - Start at the beginning of the content
-/// - Be delimited by `---` on its own line
-/// - Contain valid YAML
+/// Use [`try_parse_frontmatter`] to find out why frontmatter was rejected.
 pub fn parse_frontmatter(content: &str) -> (Option<Frontmatter>, &str) {
-    // Frontmatter must start with ---
-    let content = content.trim_start_matches('\u{feff}'); // Strip BOM if present
-    if !content.starts_with("---") {
-        return (None, content);
+    match try_parse_frontmatter(content) {
+        Ok(parsed) => parsed,
+        Err(_) => (None, strip_bom(content)),
     }
+}
+
+/// Parses frontmatter from markdown content, reporting malformed frontmatter as an error.
+///
+/// Frontmatter must start at the beginning of the content and be one of:
+/// - YAML delimited by `---` lines
+/// - TOML delimited by `+++` lines
+/// - A JSON object whose opening `{` is followed by a key or the closing `}`
+///
+/// Returns `Ok((None, content))` if there is no frontmatter, or if an opening fence is never
+/// closed. Returns an error if the delimiters are present but the block does not parse.
+pub fn try_parse_frontmatter(
+    content: &str,
+) -> Result<(Option<Frontmatter>, &str), FrontmatterError> {
+    let content = strip_bom(content);
+
+    let split = if content.starts_with("---") {
+        split_fenced(content, "---").map(|(block, rest)| (FrontmatterFormat::Yaml, block, rest))
+    } else if content.starts_with("+++") {
+        split_fenced(content, "+++").map(|(block, rest)| (FrontmatterFormat::Toml, block, rest))
+    } else if starts_json_object(content) {
+        let (block, rest) = split_json(content);
+        Some((FrontmatterFormat::Json, block, rest))
+    } else {
+        None
+    };
+
+    let Some((format, block, remaining)) = split else {
+        return Ok((None, content));
+    };
+
+    let frontmatter = format
+        .parse(block)
+        .map_err(|message| FrontmatterError { format, message })?;
+    Ok((Some(frontmatter), skip_blank_lines(remaining)))
+}
+
+/// Strips a leading byte order mark.
+fn strip_bom(content: &str) -> &str {
+    content.trim_start_matches('\u{feff}')
+}
 
-    // Find the closing ---
-    let after_opening = &content[3..];
+/// Splits fenced frontmatter into the block between the fences and the content after the
+/// closing fence.
+///
+/// Returns `None` if the closing fence is missing.
+fn split_fenced<'a>(content: &'a str, fence: &str) -> Option<(&'a str, &'a str)> {
+    let after_opening = &content[fence.len()..];
     let after_opening = after_opening
         .strip_prefix('\n')
         .unwrap_or(after_opening.strip_prefix("\r\n").unwrap_or(after_opening));
 
-    // Look for closing delimiter
-    let closing_pos = find_closing_delimiter(after_opening);
-    let Some(closing_pos) = closing_pos else {
-        // No closing delimiter found
-        return (None, content);
-    };
+    let closing_pos = find_closing_delimiter(after_opening, fence)?;
+    let block = &after_opening[..closing_pos];
+    let remaining = &after_opening[closing_pos + fence.len()..];
+    Some((block, remaining))
+}
 
-    let yaml_content = &after_opening[..closing_pos];
-    let remaining = &after_opening[closing_pos..];
+/// Returns true if the content opens with what looks like a JSON object: a `{` followed by
+/// a quoted key or the closing `}`.
+fn starts_json_object(content: &str) -> bool {
+    content
+        .strip_prefix('{')
+        .map(str::trim_start)
+        .is_some_and(|rest| rest.starts_with('"') || rest.starts_with('}'))
+}
+
+/// Splits a leading JSON object from the content after it.
+///
+/// If the object is never closed, the whole content is returned as the block so that parsing
+/// reports the error.
+fn split_json(content: &str) -> (&str, &str) {
+    let mut stream = serde_json::Deserializer::from_str(content).into_iter::<serde_json::Value>();
+    match stream.next() {
+        Some(Ok(_)) => content.split_at(stream.byte_offset()),
+        _ => (content, ""),
+    }
+}
 
-    // Skip the closing --- and any immediately following blank line
-    let remaining = remaining.strip_prefix("---").unwrap_or(remaining);
+/// Skips the rest of the closing delimiter's line and one following blank line.
+fn skip_blank_lines(remaining: &str) -> &str {
+    let remaining = remaining.trim_start_matches([' ', '\t']);
     let remaining = remaining
         .strip_prefix("\r\n")
         .or_else(|| remaining.strip_prefix('\n'))
         .unwrap_or(remaining);
     // Strip one more blank line if present (common pattern: --- followed by blank line before content)
-    let remaining = remaining
+    remaining
         .strip_prefix("\r\n")
         .or_else(|| remaining.strip_prefix('\n'))
-        .unwrap_or(remaining);
-
-    // Parse the YAML
-    match serde_yaml::from_str::<Frontmatter>(yaml_content) {
-        Ok(fm) => (Some(fm), remaining),
-        Err(_) => (None, content), // Malformed YAML, return original content
-    }
+        .unwrap_or(remaining)
 }
 
-/// Finds the position of the closing `---` delimiter.
+/// Finds the position of the closing delimiter.
 ///
-/// The delimiter must be at the start of a line.
-fn find_closing_delimiter(content: &str) -> Option<usize> {
+/// The delimiter must be on its own line.
+fn find_closing_delimiter(content: &str, fence: &str) -> Option<usize> {
     let mut pos = 0;
-    for line in content.lines() {
-        if line == "---" {
+    for line in content.split_inclusive('\n') {
+        if line.trim_end_matches(['\r', '\n']) == fence {
             return Some(pos);
         }
-        pos += line.len() + 1; // +1 for newline (approximate, handles both \n and \r\n)
+        pos += line.len();
     }
     None
 }
@@ -173,6 +340,84 @@ Content"#;
         let (fm, remaining) = parse_frontmatter(content);
         assert!(fm.is_none(), "malformed YAML should return None");
         assert_eq!(remaining, content, "should return original content");
+
+        let err = try_parse_frontmatter(content).unwrap_err();
+        assert_eq!(err.format, FrontmatterFormat::Yaml);
+        assert!(err.to_string().starts_with("malformed YAML frontmatter: "));
+    }
+
+    #[test]
+    fn test_toml_frontmatter() {
+        let content = r#"+++
+title = "Hugo Page"
+tags = ["hugo", "docs"]
+weight = 10
+date = 2024-01-01T10:00:00Z
++++
+
+# Content"#;
+
+        let (fm, remaining) = parse_frontmatter(content);
+        let fm = fm.expect("should parse TOML frontmatter");
+        assert_eq!(fm.title, Some("Hugo Page".into()));
+        assert_eq!(fm.tags, vec!["hugo", "docs"]);
+        assert_eq!(fm.meta["weight"], MetaValue::Integer(10));
+        assert_eq!(
+            fm.meta["date"],
+            MetaValue::String("2024-01-01T10:00:00Z".into())
+        );
+        assert!(remaining.starts_with("# Content"));
+    }
+
+    #[test]
+    fn test_malformed_toml() {
+        let content = "+++\ntitle = unquoted\n+++\n\nContent";
+
+        let err = try_parse_frontmatter(content).unwrap_err();
+        assert_eq!(err.format, FrontmatterFormat::Toml);
+
+        let (fm, remaining) = parse_frontmatter(content);
+        assert!(fm.is_none());
+        assert_eq!(remaining, content);
+    }
+
+    #[test]
+    fn test_json_frontmatter() {
+        let content = r#"{
+  "title": "JSON Page",
+  "tags": ["json"],
+  "draft": true
+}
+
+# Content"#;
+
+        let (fm, remaining) = parse_frontmatter(content);
+        let fm = fm.expect("should parse JSON frontmatter");
+        assert_eq!(fm.title, Some("JSON Page".into()));
+        assert_eq!(fm.tags, vec!["json"]);
+        assert_eq!(fm.meta["draft"], MetaValue::Bool(true));
+        assert!(remaining.starts_with("# Content"));
+    }
+
+    #[test]
+    fn test_malformed_json() {
+        let content = "{\n  \"title\": \"Unclosed\",\n\n# Content";
+
+        let err = try_parse_frontmatter(content).unwrap_err();
+        assert_eq!(err.format, FrontmatterFormat::Json);
+
+        let content = "{\"title\": 42}\n\nContent";
+        let err = try_parse_frontmatter(content).unwrap_err();
+        assert_eq!(err.format, FrontmatterFormat::Json);
+    }
+
+    #[test]
+    fn test_braces_in_prose_are_not_json() {
+        for content in ["{placeholder} is replaced", "{{< shortcode >}}\n"] {
+            let (fm, remaining) = try_parse_frontmatter(content).unwrap();
+            assert!(fm.is_none());
+            assert_eq!(remaining, content);
+        }
     }
 
     #[test]
@@ -197,7 +442,7 @@ title: No closing delimiter
     }
 
     #[test]
-    fn test_extra_fields_ignored() {
+    fn test_extra_fields_kept_as_meta() {
         let content = r#"---
 title: My Doc
 tags: [test]
@@ -212,6 +457,10 @@ Content"#;
         let fm = fm.expect("should parse frontmatter with extra fields");
         assert_eq!(fm.title, Some("My Doc".into()));
         assert_eq!(fm.tags, vec!["test"]);
+        assert_eq!(fm.meta["author"], MetaValue::String("Someone".into()));
+        assert_eq!(fm.meta["date"], MetaValue::String("2024-01-01".into()));
+        assert_eq!(fm.meta["custom_field"], MetaValue::String("value".into()));
+        assert!(!fm.meta.contains_key("title"));
         assert!(remaining.starts_with("Content"));
     }
 
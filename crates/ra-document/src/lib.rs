@@ -2,7 +2,9 @@
 //!
 //! This crate handles parsing markdown and plain text files into hierarchical chunk trees
 //! suitable for indexing. It supports:
-//! - YAML frontmatter extraction (title, tags)
+//! - YAML, TOML and JSON frontmatter extraction (title, tags, other keys as metadata)
+//! - Obsidian-style inline `#tags`, including nested `#area/subarea` tags
+//! - Markdown links and `[[wiki-links]]` to other documents and sections
 //! - Hierarchical chunking based on heading structure
 //! - GitHub-compatible slug generation for chunk IDs
 //! - Hierarchy path generation for search and display
@@ -13,18 +15,24 @@ mod build;
 mod error;
 mod frontmatter;
 mod id;
+mod links;
+mod meta;
 mod node;
 mod parse;
 mod slug;
+mod tags;
 mod tree;
 
 use std::path::PathBuf;
 
 pub use build::{HeadingInfo, extract_headings};
-pub use error::DocumentError;
-pub use frontmatter::{Frontmatter, parse_frontmatter};
+pub use error::{DocumentError, FrontmatterError};
+pub use frontmatter::{Frontmatter, FrontmatterFormat, parse_frontmatter, try_parse_frontmatter};
 pub use id::{ChunkId, DocId, IdError};
+pub use links::{Link, LinkKind};
+pub use meta::{MetaValue, Metadata};
 pub use parse::{ParseResult, parse_file, parse_markdown, parse_text};
+pub use tags::{extract_inline_tags, tag_hierarchy};
 pub use tree::{ChunkTree, TreeChunk};
 
 /// A parsed document ready for indexing.
@@ -36,8 +44,10 @@ pub struct Document {
     pub tree: String,
     /// Document title (from frontmatter, first h1, or filename).
     pub title: String,
-    /// Tags from frontmatter.
+    /// Tags from frontmatter, followed by inline `#tags` from the body.
     pub tags: Vec<String>,
+    /// Other frontmatter keys with scalar or list values.
+    pub meta: Metadata,
     /// The hierarchical chunk tree for this document.
     pub(crate) chunk_tree: ChunkTree,
 }
@@ -48,6 +58,11 @@ impl Document {
         self.chunk_tree.extract_chunks(&self.title)
     }
 
+    /// Returns every link in the document, in order of appearance.
+    pub fn links(&self) -> impl Iterator<Item = &Link> {
+        self.chunk_tree.all_links()
+    }
+
     /// Returns the total number of nodes in the document tree.
     pub fn node_count(&self) -> usize {
         self.chunk_tree.node_count()
@@ -68,6 +83,7 @@ mod tests {
             tree: "docs".into(),
             title: "Getting Started".into(),
             tags: vec!["intro".into(), "setup".into()],
+            meta: Metadata::new(),
             chunk_tree,
         };
         assert_eq!(doc.title, "Getting Started");
@@ -87,6 +103,7 @@ mod tests {
             byte_start: 0,
             byte_end: 100,
             sibling_count: 1,
+            links: Vec::new(),
         };
         assert!(chunk.id.contains('#'));
         assert_eq!(chunk.title(), "Installation");
@@ -106,6 +123,7 @@ mod tests {
             byte_start: 0,
             byte_end: 50,
             sibling_count: 1,
+            links: Vec::new(),
         };
         assert!(!chunk.id.contains('#'));
         assert_eq!(chunk.title(), "Getting Started");
@@ -0,0 +1,262 @@
+//! Links from document bodies to other documents and sections.
+//!
+//! Two kinds of links are recognized:
+//!
+//! ```markdown
+//! See the [retry policy](../ops/retries.md#backoff) and [[Timeouts#Defaults|the defaults]].
+//! ```
+//!
+//! Markdown links are resolved against the linking document's path here, giving a target path
+//! relative to the tree root. Wiki-links name a page rather than a path, so they keep the page
+//! name as written and are matched against indexed files later. URLs with a scheme, images and
+//! anything inside code or frontmatter are ignored. Heading fragments are converted to slugs so
+//! they line up with chunk IDs.
+
+use std::path::Path;
+
+use percent_encoding::percent_decode_str;
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+
+use crate::slug::Slugifier;
+
+/// How a link names its target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkKind {
+    /// A markdown `[text](path#fragment)` link.
+    Markdown,
+    /// An Obsidian-style `[[Page#Heading|alias]]` link.
+    Wiki,
+}
+
+/// A link to another document or section, before it is resolved to a chunk ID.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Link {
+    /// How the link names its target.
+    pub kind: LinkKind,
+    /// For markdown links, the target's path relative to the tree root. For wiki-links, the page
+    /// name as written. Empty for links within the same document.
+    pub target: String,
+    /// Slug of the linked heading, if the link names one.
+    pub fragment: Option<String>,
+}
+
+/// Extracts links from markdown content, each with the byte offset where it appears.
+///
+/// `path` is the linking document's path within its tree, used to resolve relative links.
+pub fn extract_links(content: &str, path: &Path) -> Vec<(usize, Link)> {
+    let options = Options::ENABLE_YAML_STYLE_METADATA_BLOCKS
+        | Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS;
+    let mut links = Vec::new();
+    let mut skip_depth = 0usize;
+    // Adjacent text events, merged so a `[[wiki-link]]` split by the parser is seen whole
+    let mut text: Option<(usize, String)> = None;
+
+    for (event, range) in Parser::new_ext(content, options).into_offset_iter() {
+        if let Event::Text(t) = &event {
+            if skip_depth == 0 {
+                text.get_or_insert_with(|| (range.start, String::new()))
+                    .1
+                    .push_str(t);
+            }
+            continue;
+        }
+        if let Some((offset, text)) = text.take() {
+            links.extend(wiki_links(&text).map(|link| (offset, link)));
+        }
+
+        match event {
+            Event::Start(Tag::CodeBlock(_) | Tag::MetadataBlock(_)) => skip_depth += 1,
+            Event::End(TagEnd::CodeBlock | TagEnd::MetadataBlock(_)) => {
+                skip_depth = skip_depth.saturating_sub(1);
+            }
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                if let Some(link) = markdown_link(&dest_url, path) {
+                    links.push((range.start, link));
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some((offset, text)) = text {
+        links.extend(wiki_links(&text).map(|link| (offset, link)));
+    }
+
+    links
+}
+
+/// Converts a markdown link destination into a link, or `None` for external and empty links.
+fn markdown_link(dest: &str, path: &Path) -> Option<Link> {
+    let dest = dest.trim();
+    if has_scheme(dest) {
+        return None;
+    }
+    let (rest, fragment) = match dest.split_once('#') {
+        Some((rest, fragment)) => (
+            rest,
+            slug(&percent_decode_str(fragment).decode_utf8_lossy()),
+        ),
+        None => (dest, None),
+    };
+    let rest = rest.split_once('?').map_or(rest, |(rest, _)| rest);
+
+    let target = if rest.is_empty() {
+        // A bare `#` links nowhere
+        fragment.as_ref()?;
+        String::new()
+    } else {
+        resolve_path(path, &percent_decode_str(rest).decode_utf8_lossy())?
+    };
+
+    Some(Link {
+        kind: LinkKind::Markdown,
+        target,
+        fragment,
+    })
+}
+
+/// Finds the `[[wiki-links]]` in a run of plain text.
+fn wiki_links(text: &str) -> impl Iterator<Item = Link> + '_ {
+    text.match_indices("[[").filter_map(|(i, _)| {
+        let inner = &text[i + 2..];
+        let inner = &inner[..inner.find("]]")?];
+        if inner.contains(['\n', '[']) {
+            return None;
+        }
+        let inner = inner.split_once('|').map_or(inner, |(target, _)| target);
+        let (page, fragment) = match inner.split_once('#') {
+            Some((page, heading)) => (page.trim(), slug(heading)),
+            None => (inner.trim(), None),
+        };
+        if page.is_empty() && fragment.is_none() {
+            return None;
+        }
+        Some(Link {
+            kind: LinkKind::Wiki,
+            target: page.to_string(),
+            fragment,
+        })
+    })
+}
+
+/// Returns true if a link destination starts with a URL scheme such as `https:` or `mailto:`.
+fn has_scheme(dest: &str) -> bool {
+    dest.split_once(':').is_some_and(|(scheme, _)| {
+        scheme
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
+/// Resolves a link path against the linking document's path, returning a normalized path
+/// relative to the tree root. Absolute paths start at the tree root. Returns `None` for paths
+/// that climb out of the tree.
+fn resolve_path(from: &Path, target: &str) -> Option<String> {
+    let base = if target.starts_with('/') {
+        String::new()
+    } else {
+        from.parent()
+            .map(|dir| dir.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default()
+    };
+
+    let mut parts: Vec<&str> = Vec::new();
+    for part in base.split('/').chain(target.split('/')) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            part => parts.push(part),
+        }
+    }
+
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+/// Converts heading text or an anchor into a chunk ID slug, or `None` if nothing is left.
+fn slug(heading: &str) -> Option<String> {
+    let heading = heading.trim();
+    (!heading.is_empty()).then(|| Slugifier::default().slugify(heading))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Extracts links from `content` as if it were `notes/guide.md`, dropping offsets.
+    fn links(content: &str) -> Vec<Link> {
+        extract_links(content, Path::new("notes/guide.md"))
+            .into_iter()
+            .map(|(_, link)| link)
+            .collect()
+    }
+
+    /// Builds an expected link.
+    fn link(kind: LinkKind, target: &str, fragment: Option<&str>) -> Link {
+        Link {
+            kind,
+            target: target.to_string(),
+            fragment: fragment.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn resolves_markdown_links_against_the_document() {
+        let content = "See [retries](../ops/retry%20policy.md#Back-off), [here](#Setup-Steps), \
+                       [root](/index.md?x=1) and [sibling](./faq.md).";
+        assert_eq!(
+            links(content),
+            vec![
+                link(LinkKind::Markdown, "ops/retry policy.md", Some("back-off")),
+                link(LinkKind::Markdown, "", Some("setup-steps")),
+                link(LinkKind::Markdown, "index.md", None),
+                link(LinkKind::Markdown, "notes/faq.md", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_external_and_escaping_links() {
+        let content = "[web](https://example.com/a.md) [mail](mailto:a@b.c) [up](../../x.md) \
+                       [empty](#) ![img](pic.png)";
+        assert!(links(content).is_empty());
+    }
+
+    #[test]
+    fn extracts_wiki_links() {
+        let content = "Read [[Retry Policy]], [[ops/timeouts#Default Values|defaults]] and \
+                       [[#Local Section]]. Not [[]] or [[a\nb]].";
+        assert_eq!(
+            links(content),
+            vec![
+                link(LinkKind::Wiki, "Retry Policy", None),
+                link(LinkKind::Wiki, "ops/timeouts", Some("default-values")),
+                link(LinkKind::Wiki, "", Some("local-section")),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_code_and_frontmatter() {
+        let content = "---\nsee: \"[[Hidden]]\"\n---\n\n`[[Code]]` and\n\n```\n[x](a.md)\n```\n\n\
+                       Real [[Page]].";
+        assert_eq!(links(content), vec![link(LinkKind::Wiki, "Page", None)]);
+    }
+
+    #[test]
+    fn records_link_offsets() {
+        let content = "Intro.\n\n# Section\n\nSee [[Page]].";
+        let offsets: Vec<usize> = extract_links(content, Path::new("a.md"))
+            .into_iter()
+            .map(|(offset, _)| offset)
+            .collect();
+        assert_eq!(offsets.len(), 1);
+        assert!(offsets[0] >= content.find("See").unwrap());
+    }
+}
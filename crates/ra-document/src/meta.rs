@@ -0,0 +1,121 @@
+//! Document metadata from frontmatter.
+//!
+//! Frontmatter keys other than `title` and `tags` are kept as metadata when their values are
+//! scalars or lists of scalars:
+//!
+//! ```markdown
+//! ---
+//! status: approved
+//! version: 2
+//! audience: [developers, operators]
+//! ---
+//! ```
+//!
+//! Null values, nested mappings and nested lists are dropped.
+
+use std::{collections::BTreeMap, fmt};
+
+use serde::Serialize;
+use serde_yaml::Value;
+
+/// Metadata values by frontmatter key.
+pub type Metadata = BTreeMap<String, MetaValue>;
+
+/// A metadata value: a scalar or a list of scalars.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum MetaValue {
+    /// A boolean.
+    Bool(bool),
+    /// An integer.
+    Integer(i64),
+    /// A floating-point number.
+    Float(f64),
+    /// A string.
+    String(String),
+    /// A list of scalar values.
+    List(Vec<Self>),
+}
+
+impl MetaValue {
+    /// Converts a frontmatter value, or returns `None` if it cannot be kept as metadata.
+    pub(crate) fn from_yaml(value: Value) -> Option<Self> {
+        match value {
+            Value::Sequence(items) => {
+                let items: Vec<Self> = items.into_iter().filter_map(Self::scalar).collect();
+                (!items.is_empty()).then_some(Self::List(items))
+            }
+            value => Self::scalar(value),
+        }
+    }
+
+    /// Converts a scalar frontmatter value.
+    fn scalar(value: Value) -> Option<Self> {
+        match value {
+            Value::Bool(b) => Some(Self::Bool(b)),
+            Value::Number(n) => n
+                .as_i64()
+                .map(Self::Integer)
+                .or_else(|| n.as_f64().map(Self::Float)),
+            Value::String(s) => Some(Self::String(s)),
+            Value::Tagged(tagged) => Self::scalar(tagged.value),
+            Value::Null | Value::Sequence(_) | Value::Mapping(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for MetaValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Integer(i) => write!(f, "{i}"),
+            Self::Float(x) => write!(f, "{x}"),
+            Self::String(s) => f.write_str(s),
+            Self::List(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(yaml: &str) -> Option<MetaValue> {
+        MetaValue::from_yaml(serde_yaml::from_str(yaml).unwrap())
+    }
+
+    #[test]
+    fn keeps_scalars_and_lists() {
+        assert_eq!(convert("draft"), Some(MetaValue::String("draft".into())));
+        assert_eq!(convert("2"), Some(MetaValue::Integer(2)));
+        assert_eq!(convert("1.5"), Some(MetaValue::Float(1.5)));
+        assert_eq!(convert("true"), Some(MetaValue::Bool(true)));
+        assert_eq!(
+            convert("[a, 1, [nested], {k: v}]"),
+            Some(MetaValue::List(vec![
+                MetaValue::String("a".into()),
+                MetaValue::Integer(1)
+            ]))
+        );
+    }
+
+    #[test]
+    fn drops_nulls_and_mappings() {
+        assert_eq!(convert("~"), None);
+        assert_eq!(convert("{owner: me}"), None);
+        assert_eq!(convert("[]"), None);
+    }
+
+    #[test]
+    fn displays_lists_comma_separated() {
+        assert_eq!(convert("[dev, ops]").unwrap().to_string(), "dev, ops");
+    }
+}
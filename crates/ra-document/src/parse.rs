@@ -11,8 +11,10 @@ use std::{
 use crate::{
     Document, DocumentError,
     build::{build_chunk_tree, extract_headings},
-    frontmatter::{Frontmatter, parse_frontmatter},
+    frontmatter::{Frontmatter, try_parse_frontmatter},
+    meta::Metadata,
     node::Node,
+    tags::extract_inline_tags,
     tree::ChunkTree,
 };
 
@@ -21,6 +23,8 @@ use crate::{
 pub struct ParseResult {
     /// The parsed document.
     pub document: Document,
+    /// Problems that did not stop parsing, such as malformed frontmatter.
+    pub warnings: Vec<String>,
 }
 
 /// Parses a markdown string into a document.
@@ -30,13 +34,27 @@ pub struct ParseResult {
 /// * `path` - Relative path within the tree (used for chunk IDs)
 /// * `tree` - Name of the tree this document belongs to
 pub fn parse_markdown(content: &str, path: &Path, tree: &str) -> ParseResult {
-    // Parse frontmatter
-    let (frontmatter, _body) = parse_frontmatter(content);
-    let frontmatter = frontmatter.unwrap_or_default();
+    // Parse frontmatter, keeping going without it if it is malformed
+    let mut warnings = Vec::new();
+    let frontmatter = match try_parse_frontmatter(content) {
+        Ok((frontmatter, _body)) => frontmatter.unwrap_or_default(),
+        Err(e) => {
+            warnings.push(e.to_string());
+            Frontmatter::default()
+        }
+    };
 
     // Determine document title
     let title = determine_title(&frontmatter, content, path);
 
+    // Frontmatter tags come first, followed by any new inline #tags from the body
+    let mut tags = frontmatter.tags;
+    for tag in extract_inline_tags(content) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+
     // Build the hierarchical chunk tree
     // Note: We use the full content (including frontmatter) as the spec says
     // frontmatter bytes are included in the document node's body
@@ -46,11 +64,12 @@ pub fn parse_markdown(content: &str, path: &Path, tree: &str) -> ParseResult {
         path: path.to_path_buf(),
         tree: tree.to_string(),
         title,
-        tags: frontmatter.tags,
+        tags,
+        meta: frontmatter.meta,
         chunk_tree,
     };
 
-    ParseResult { document }
+    ParseResult { document, warnings }
 }
 
 /// Parses a plain text file into a document.
@@ -72,10 +91,14 @@ pub fn parse_text(content: &str, path: &Path, tree: &str) -> ParseResult {
         tree: tree.to_string(),
         title,
         tags: vec![],
+        meta: Metadata::new(),
         chunk_tree,
     };
 
-    ParseResult { document }
+    ParseResult {
+        document,
+        warnings: Vec::new(),
+    }
 }
 
 /// Parses a file from disk, detecting type by extension.
@@ -128,6 +151,7 @@ mod tests {
         let content = r#"---
 title: My Guide
 tags: [rust, tutorial]
+status: draft
 ---
 
 # Introduction
@@ -142,6 +166,10 @@ Let's begin."#;
 
         assert_eq!(result.document.title, "My Guide");
         assert_eq!(result.document.tags, vec!["rust", "tutorial"]);
+        assert_eq!(
+            result.document.meta["status"],
+            crate::MetaValue::String("draft".into())
+        );
         assert_eq!(result.document.tree, "docs");
         assert!(result.document.chunk_tree.chunk_count() > 0);
 
@@ -161,6 +189,42 @@ Let's begin."#;
         assert!(ids.iter().any(|id| id.contains("#getting-started")));
     }
 
+    #[test]
+    fn test_parse_markdown_with_toml_frontmatter() {
+        let content = "+++\n# Hugo settings\ntitle = \"Hugo Guide\"\n+++\n\n## Install\n\nSteps.";
+
+        let result = parse_markdown(content, Path::new("guide.md"), "docs");
+
+        assert_eq!(result.document.title, "Hugo Guide");
+        assert!(result.warnings.is_empty());
+        let chunks = result
+            .document
+            .chunk_tree
+            .extract_chunks(&result.document.title);
+        let ids: Vec<&str> = chunks.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["docs:guide.md", "docs:guide.md#install"]);
+    }
+
+    #[test]
+    fn test_parse_markdown_malformed_frontmatter_warns() {
+        let content = "---\ntitle: [unclosed\n---\n\n# Heading\n\nText.";
+
+        let result = parse_markdown(content, Path::new("doc.md"), "docs");
+
+        assert_eq!(result.document.title, "Heading");
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].starts_with("malformed YAML frontmatter"));
+    }
+
+    #[test]
+    fn test_parse_markdown_inline_tags() {
+        let content = "---\ntags: [rust]\n---\n\n# Notes\n\nSee #rust and #area/subarea.";
+
+        let result = parse_markdown(content, Path::new("notes.md"), "vault");
+
+        assert_eq!(result.document.tags, vec!["rust", "area/subarea"]);
+    }
+
     #[test]
     fn test_parse_markdown_title_from_h1() {
         let content = "# My Document\n\nSome content.\n\n# Another Section\n\nMore content.";
@@ -259,6 +323,7 @@ Let's begin."#;
         let fm_with_title = Frontmatter {
             title: Some("Frontmatter Title".to_string()),
             tags: vec![],
+            meta: Metadata::new(),
         };
         let title = determine_title(
             &fm_with_title,
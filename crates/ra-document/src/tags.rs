@@ -0,0 +1,117 @@
+//! Obsidian-style inline tags.
+//!
+//! Besides frontmatter `tags`, a document can tag itself inline by writing `#tag` in its body
+//! text. Tags may be nested with `/`, as in `#area/subarea`:
+//!
+//! ```markdown
+//! Retry budgets are shared across services. #reliability #ops/oncall
+//! ```
+//!
+//! A tag starts with `#` at the start of a word and runs over letters, digits, `_`, `-` and
+//! `/`. Purely numeric tokens such as `#123` are not tags. Headings, code spans, code blocks
+//! and frontmatter are never scanned.
+
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd, TextMergeStream};
+
+/// Extracts inline `#tags` from markdown content, in order of first appearance.
+pub fn extract_inline_tags(content: &str) -> Vec<String> {
+    let options = Options::ENABLE_YAML_STYLE_METADATA_BLOCKS
+        | Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS;
+    let mut tags: Vec<String> = Vec::new();
+    let mut skip_depth = 0usize;
+
+    for event in TextMergeStream::new(Parser::new_ext(content, options)) {
+        match event {
+            Event::Start(Tag::Heading { .. } | Tag::CodeBlock(_) | Tag::MetadataBlock(_)) => {
+                skip_depth += 1;
+            }
+            Event::End(TagEnd::Heading(_) | TagEnd::CodeBlock | TagEnd::MetadataBlock(_)) => {
+                skip_depth = skip_depth.saturating_sub(1);
+            }
+            Event::Text(text) if skip_depth == 0 => {
+                for tag in tags_in_text(&text) {
+                    if !tags.iter().any(|t| t == tag) {
+                        tags.push(tag.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    tags
+}
+
+/// Returns a tag followed by each of its ancestors, from the tag itself up to the root.
+///
+/// `area/subarea/topic` yields `area/subarea/topic`, `area/subarea` and `area`.
+pub fn tag_hierarchy(tag: &str) -> impl Iterator<Item = &str> {
+    let tag = tag.trim_matches('/');
+    let ancestors = tag.rmatch_indices('/').map(move |(i, _)| &tag[..i]);
+    (!tag.is_empty())
+        .then_some(tag)
+        .into_iter()
+        .chain(ancestors)
+}
+
+/// Returns true if `c` can appear in a tag after the `#`.
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '/')
+}
+
+/// Finds the inline tags in a run of plain text.
+fn tags_in_text(text: &str) -> impl Iterator<Item = &str> {
+    text.match_indices('#').filter_map(|(i, _)| {
+        let preceded_by_word = text[..i]
+            .chars()
+            .next_back()
+            .is_some_and(|c| !c.is_whitespace() && !matches!(c, '(' | '[' | ',' | ';'));
+        // `[[#Heading]]` is a wiki-link to a section, not a tag
+        if preceded_by_word || text[..i].ends_with("[[") {
+            return None;
+        }
+        let rest = &text[i + 1..];
+        let end = rest.find(|c| !is_tag_char(c)).unwrap_or(rest.len());
+        let tag = rest[..end].trim_end_matches('/');
+        let is_tag = !tag.starts_with('/')
+            && tag
+                .chars()
+                .any(|c| !c.is_ascii_digit() && c != '/' && c != '-' && c != '_');
+        is_tag.then_some(tag)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_inline_and_nested_tags() {
+        let content = "Some text #rust and #area/subarea, also #rust again.\n\n- item #todo";
+        assert_eq!(
+            extract_inline_tags(content),
+            vec!["rust", "area/subarea", "todo"]
+        );
+    }
+
+    #[test]
+    fn skips_code_headings_and_frontmatter() {
+        let content = "---\ntitle: x #notatag\n---\n\n# Heading #nottag\n\n\
+                       Use `#inline` code.\n\n```\n#comment\n```\n\nReal #tag here.";
+        assert_eq!(extract_inline_tags(content), vec!["tag"]);
+    }
+
+    #[test]
+    fn ignores_numbers_anchors_and_mid_word_hashes() {
+        let content = "Issue #123, see [link](#anchor), [[#Section]] and C# or a#b. (#paren)";
+        assert_eq!(extract_inline_tags(content), vec!["paren"]);
+    }
+
+    #[test]
+    fn lists_tag_ancestors() {
+        let tags: Vec<&str> = tag_hierarchy("area/subarea/topic").collect();
+        assert_eq!(tags, vec!["area/subarea/topic", "area/subarea", "area"]);
+        assert_eq!(tag_hierarchy("rust").collect::<Vec<_>>(), vec!["rust"]);
+        assert_eq!(tag_hierarchy("").count(), 0);
+    }
+}
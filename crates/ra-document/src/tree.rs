@@ -4,12 +4,16 @@
 //! as a hierarchical tree of nodes. The tree supports traversal, node lookup, and
 //! iteration over chunks (nodes with non-empty body text).
 
+use std::ops::Range;
 #[cfg(test)]
 use std::path::{Path, PathBuf};
 
 #[cfg(test)]
 use crate::node::HeadingParams;
-use crate::node::{Node, NodeKind};
+use crate::{
+    links::Link,
+    node::{Node, NodeKind},
+};
 
 /// A hierarchical tree of nodes representing a parsed document.
 ///
@@ -25,6 +29,8 @@ pub struct ChunkTree {
     /// Byte offset where the first heading line starts, if any.
     /// Used to compute the preamble even when all headings are filtered out.
     first_heading_start: Option<usize>,
+    /// Links in the content, each with the byte offset where it appears.
+    links: Vec<(usize, Link)>,
 }
 
 impl ChunkTree {
@@ -34,6 +40,7 @@ impl ChunkTree {
             root,
             content,
             first_heading_start: None,
+            links: Vec::new(),
         }
     }
 
@@ -47,6 +54,7 @@ impl ChunkTree {
             root,
             content,
             first_heading_start: Some(first_heading_start),
+            links: Vec::new(),
         }
     }
 
@@ -102,7 +110,24 @@ impl ChunkTree {
     /// before the first heading). For heading nodes, this is content after the
     /// heading line minus child spans.
     pub fn body(&self, node: &Node) -> &str {
-        self.body_impl(node, &self.content)
+        &self.content[self.body_range(node)]
+    }
+
+    /// Returns the distinct links that appear in a node's body, in order of appearance.
+    pub fn links(&self, node: &Node) -> Vec<Link> {
+        let range = self.body_range(node);
+        let mut links: Vec<Link> = Vec::new();
+        for (_, link) in self.links.iter().filter(|(at, _)| range.contains(at)) {
+            if !links.contains(link) {
+                links.push(link.clone());
+            }
+        }
+        links
+    }
+
+    /// Returns every link in the content, in order of appearance.
+    pub fn all_links(&self) -> impl Iterator<Item = &Link> {
+        self.links.iter().map(|(_, link)| link)
     }
 
     /// Checks if a node has non-empty body text (contains non-whitespace).
@@ -110,8 +135,8 @@ impl ChunkTree {
         !self.body(node).trim().is_empty()
     }
 
-    /// Internal body extraction implementation.
-    fn body_impl<'a>(&self, node: &Node, content: &'a str) -> &'a str {
+    /// Returns the byte range of a node's body within the content.
+    fn body_range(&self, node: &Node) -> Range<usize> {
         if node.children.is_empty() {
             // Leaf node: body is the entire span, unless this is the document node
             // and we know where the first heading starts (even if filtered out)
@@ -119,17 +144,22 @@ impl ChunkTree {
                 && let Some(first_heading) = self.first_heading_start
             {
                 // Document's preamble ends at the first heading line
-                return &content[node.byte_start..first_heading];
+                return node.byte_start..first_heading;
             }
-            &content[node.byte_start..node.byte_end]
+            node.byte_start..node.byte_end
         } else {
             // Non-leaf: body is span content before first child's heading line
             // Use heading_line_start to exclude the child's heading line from parent body
             let first_child_heading_start = node.children[0].heading_line_start;
-            &content[node.byte_start..first_child_heading_start]
+            node.byte_start..first_child_heading_start
         }
     }
 
+    /// Sets the links found in the content, each with the byte offset where it appears.
+    pub(crate) fn set_links(&mut self, links: Vec<(usize, Link)>) {
+        self.links = links;
+    }
+
     /// Assigns position values to all nodes via pre-order traversal.
     ///
     /// This should be called after the tree structure is fully built.
@@ -170,6 +200,7 @@ impl ChunkTree {
                     byte_start: node.byte_start,
                     byte_end: node.byte_end,
                     sibling_count: node.sibling_count,
+                    links: self.links(node),
                 }
             })
             .collect()
@@ -264,6 +295,8 @@ pub struct TreeChunk {
     pub byte_end: usize,
     /// Number of siblings including this chunk.
     pub sibling_count: usize,
+    /// Links in the chunk body, unresolved.
+    pub links: Vec<Link>,
 }
 
 impl TreeChunk {
@@ -409,6 +409,7 @@ mod tests {
             is_global: false,
             include: Vec::new(),
             exclude: Vec::new(),
+            synonyms: Default::default(),
         }];
 
         let mut searcher = Searcher::open(index_dir.path(), "english", &trees, 1.0, 1).unwrap();
@@ -446,6 +447,7 @@ mod tests {
             is_global: false,
             include: Vec::new(),
             exclude: Vec::new(),
+            synonyms: Default::default(),
         }];
 
         let mut searcher = Searcher::open(index_dir.path(), "english", &trees, 1.0, 1).unwrap();
@@ -479,6 +481,7 @@ mod tests {
             is_global: false,
             include: Vec::new(),
             exclude: Vec::new(),
+            synonyms: Default::default(),
         }];
 
         let mut searcher = Searcher::open(index_dir.path(), "english", &trees, 1.0, 1).unwrap();
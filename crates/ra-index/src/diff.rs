@@ -3,7 +3,11 @@
 //! Compares the current filesystem state against the stored manifest to
 //! determine which files need to be indexed, reindexed, or removed.
 
-use std::{collections::HashSet, path::PathBuf, time::UNIX_EPOCH};
+use std::{
+    collections::HashSet,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use crate::{
     discovery::DiscoveredFile,
@@ -37,7 +41,7 @@ impl ManifestDiff {
 ///
 /// Returns a `ManifestDiff` containing:
 /// - `added`: Files present in `discovered` but not in `manifest`
-/// - `modified`: Files present in both but with different mtimes
+/// - `modified`: Files present in both but with a different mtime or size
 /// - `removed`: Files present in `manifest` but not in `discovered`
 pub fn diff_manifest(manifest: &Manifest, discovered: &[DiscoveredFile]) -> ManifestDiff {
     let mut diff = ManifestDiff::default();
@@ -54,19 +58,7 @@ pub fn diff_manifest(manifest: &Manifest, discovered: &[DiscoveredFile]) -> Mani
                 diff.added.push(file.clone());
             }
             Some(entry) => {
-                // Check if modified (compare mtime at second resolution to match serialization)
-                let stored_secs = entry
-                    .mtime
-                    .duration_since(UNIX_EPOCH)
-                    .map(|d| d.as_secs())
-                    .unwrap_or(0);
-                let current_secs = file
-                    .mtime
-                    .duration_since(UNIX_EPOCH)
-                    .map(|d| d.as_secs())
-                    .unwrap_or(0);
-
-                if stored_secs != current_secs {
+                if is_modified(entry, file) {
                     diff.modified.push(file.clone());
                 }
             }
@@ -83,6 +75,57 @@ pub fn diff_manifest(manifest: &Manifest, discovered: &[DiscoveredFile]) -> Mani
     diff
 }
 
+/// Computes the difference for a set of paths reported as changed.
+///
+/// `discovered` must hold the indexable files at or below `changed`. As in [`diff_manifest`],
+/// known files count as modified only when their mtime or size differs from the manifest, so
+/// notifications for reads (including the indexer's own) don't trigger reindexing. Manifest
+/// entries at or below a changed path that were not rediscovered are removed.
+pub fn diff_changed_paths(
+    manifest: &Manifest,
+    discovered: &[DiscoveredFile],
+    changed: &[PathBuf],
+) -> ManifestDiff {
+    let mut diff = ManifestDiff::default();
+    let mut seen_paths: HashSet<&PathBuf> = HashSet::new();
+
+    for file in discovered {
+        seen_paths.insert(&file.abs_path);
+
+        match manifest.get(&file.abs_path) {
+            None => diff.added.push(file.clone()),
+            Some(entry) => {
+                if is_modified(entry, file) {
+                    diff.modified.push(file.clone());
+                }
+            }
+        }
+    }
+
+    for (abs_path, _) in manifest.entries() {
+        if !seen_paths.contains(abs_path) && changed.iter().any(|p| abs_path.starts_with(p)) {
+            diff.removed.push(abs_path.clone());
+        }
+    }
+
+    diff
+}
+
+/// Returns true if a file's mtime or size differs from its manifest entry.
+///
+/// Modification times are compared at second resolution to match manifest serialization.
+fn is_modified(entry: &ManifestEntry, file: &DiscoveredFile) -> bool {
+    mtime_secs(entry.mtime) != mtime_secs(file.mtime) || entry.size != file.size
+}
+
+/// Returns a modification time in whole seconds since the epoch, matching manifest serialization.
+fn mtime_secs(mtime: SystemTime) -> u64 {
+    mtime
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
 /// Updates the manifest to reflect the current state after processing a diff.
 ///
 /// - Adds entries for newly indexed files
@@ -102,6 +145,8 @@ pub fn apply_diff(manifest: &mut Manifest, diff: &ManifestDiff) {
                 tree: file.tree.clone(),
                 path: file.rel_path.clone(),
                 mtime: file.mtime,
+                size: file.size,
+                links: Vec::new(),
             },
         );
     }
@@ -122,6 +167,7 @@ mod test {
             rel_path: PathBuf::from(rel),
             abs_path: PathBuf::from(abs),
             mtime: SystemTime::UNIX_EPOCH + Duration::from_secs(secs),
+            size: 0,
         }
     }
 
@@ -130,6 +176,8 @@ mod test {
             tree: tree.to_string(),
             path: PathBuf::from(rel),
             mtime: SystemTime::UNIX_EPOCH + Duration::from_secs(secs),
+            size: 0,
+            links: Vec::new(),
         }
     }
 
@@ -256,4 +304,47 @@ mod test {
         let files: Vec<_> = diff.files_to_index().collect();
         assert_eq!(files.len(), 2);
     }
+
+    #[test]
+    fn changed_paths_detect_modification_and_removal() {
+        let mut manifest = Manifest::new();
+        manifest.insert(
+            PathBuf::from("/docs/same.md"),
+            make_entry("docs", "same.md", 1000),
+        );
+        manifest.insert(
+            PathBuf::from("/docs/sub/gone.md"),
+            make_entry("docs", "sub/gone.md", 1000),
+        );
+        manifest.insert(
+            PathBuf::from("/docs/grown.md"),
+            make_entry("docs", "grown.md", 1000),
+        );
+        manifest.insert(
+            PathBuf::from("/docs/untouched.md"),
+            make_entry("docs", "untouched.md", 1000),
+        );
+
+        // same.md was only read; grown.md was rewritten within the same second
+        let mut grown = make_file("docs", "grown.md", "/docs/grown.md", 1000);
+        grown.size = 42;
+        let discovered = vec![
+            make_file("docs", "same.md", "/docs/same.md", 1000),
+            grown,
+            make_file("docs", "sub/new.md", "/docs/sub/new.md", 1000),
+        ];
+        let changed = vec![
+            PathBuf::from("/docs/same.md"),
+            PathBuf::from("/docs/grown.md"),
+            PathBuf::from("/docs/sub"),
+        ];
+
+        let diff = diff_changed_paths(&manifest, &discovered, &changed);
+
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].abs_path, Path::new("/docs/sub/new.md"));
+        assert_eq!(diff.modified.len(), 1);
+        assert_eq!(diff.modified[0].abs_path, Path::new("/docs/grown.md"));
+        assert_eq!(diff.removed, vec![PathBuf::from("/docs/sub/gone.md")]);
+    }
 }
@@ -4,7 +4,12 @@
 //! applying include/exclude patterns and filtering out binaries and
 //! directory symlinks.
 
-use std::{ffi::OsStr, path::PathBuf, time::SystemTime};
+use std::{
+    ffi::OsStr,
+    fs::{self, Metadata},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 
 use ra_config::{CompiledPatterns, Tree};
 use ra_context::is_binary_file;
@@ -23,6 +28,8 @@ pub struct DiscoveredFile {
     pub rel_path: PathBuf,
     /// File modification time.
     pub mtime: SystemTime,
+    /// File size in bytes.
+    pub size: u64,
 }
 
 /// Discovers all files that should be indexed from the given trees.
@@ -42,61 +49,114 @@ pub fn discover_files(
         if !tree.path.exists() {
             continue;
         }
+        walk_tree(tree, &tree.path, patterns, &mut files);
+    }
 
-        for entry in WalkDir::new(&tree.path)
-            .follow_links(false)
-            .into_iter()
-            .filter_entry(|e| !is_hidden(e.file_name()))
-        {
-            let entry = match entry {
-                Ok(e) => e,
-                Err(_) => continue,
-            };
+    Ok(files)
+}
+
+/// Discovers indexable files at or below the given paths.
+///
+/// Each path is matched against the trees that contain it. Directories are walked, files are
+/// checked individually, and paths that no longer exist yield nothing. The same rules as
+/// [`discover_files`] apply, so the result is the subset of a full discovery that lies under
+/// `paths`.
+pub fn discover_paths(
+    trees: &[Tree],
+    patterns: &CompiledPatterns,
+    paths: &[PathBuf],
+) -> Vec<DiscoveredFile> {
+    let mut files = Vec::new();
 
-            // Skip directories
-            if entry.file_type().is_dir() {
+    for path in paths {
+        for tree in trees {
+            let Ok(rel_path) = path.strip_prefix(&tree.path) else {
+                continue;
+            };
+            if rel_path.iter().any(is_hidden) {
                 continue;
             }
-
-            // Skip symlinks (we don't follow them)
-            if entry.file_type().is_symlink() {
+            let Ok(metadata) = fs::symlink_metadata(path) else {
                 continue;
+            };
+
+            if metadata.is_dir() {
+                walk_tree(tree, path, patterns, &mut files);
+            } else if metadata.is_file()
+                && let Some(file) = discovered_file(tree, patterns, path, rel_path, &metadata)
+            {
+                files.push(file);
             }
+        }
+    }
 
-            let abs_path = entry.path().to_path_buf();
+    files.sort_by(|a, b| (&a.tree, &a.abs_path).cmp(&(&b.tree, &b.abs_path)));
+    files.dedup_by(|a, b| a.tree == b.tree && a.abs_path == b.abs_path);
+    files
+}
 
-            // Compute relative path from tree root
-            let rel_path = match abs_path.strip_prefix(&tree.path) {
-                Ok(p) => p.to_path_buf(),
-                Err(_) => continue,
-            };
+/// Walks `root` (the tree root or a directory inside it) collecting indexable files.
+fn walk_tree(
+    tree: &Tree,
+    root: &Path,
+    patterns: &CompiledPatterns,
+    files: &mut Vec<DiscoveredFile>,
+) {
+    for entry in WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| !is_hidden(e.file_name()))
+    {
+        let entry = match entry {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
 
-            // Check if file matches patterns
-            if !patterns.matches(&tree.name, &rel_path) {
-                continue;
-            }
+        // Skip directories and symlinks (we don't follow them)
+        if entry.file_type().is_dir() || entry.file_type().is_symlink() {
+            continue;
+        }
 
-            // Skip binary files
-            if is_binary_file(&abs_path) {
-                continue;
-            }
+        // Compute relative path from tree root
+        let Ok(rel_path) = entry.path().strip_prefix(&tree.path) else {
+            continue;
+        };
 
-            // Get modification time
-            let mtime = match entry.metadata() {
-                Ok(m) => m.modified().unwrap_or(SystemTime::UNIX_EPOCH),
-                Err(_) => continue,
-            };
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
 
-            files.push(DiscoveredFile {
-                tree: tree.name.clone(),
-                abs_path,
-                rel_path,
-                mtime,
-            });
+        if let Some(file) = discovered_file(tree, patterns, entry.path(), rel_path, &metadata) {
+            files.push(file);
         }
     }
+}
 
-    Ok(files)
+/// Checks a single regular file against the tree's patterns, returning it if indexable.
+fn discovered_file(
+    tree: &Tree,
+    patterns: &CompiledPatterns,
+    abs_path: &Path,
+    rel_path: &Path,
+    metadata: &Metadata,
+) -> Option<DiscoveredFile> {
+    // Check if file matches patterns
+    if !patterns.matches(&tree.name, rel_path) {
+        return None;
+    }
+
+    // Skip binary files
+    if is_binary_file(abs_path) {
+        return None;
+    }
+
+    Some(DiscoveredFile {
+        tree: tree.name.clone(),
+        abs_path: abs_path.to_path_buf(),
+        rel_path: rel_path.to_path_buf(),
+        mtime: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+        size: metadata.len(),
+    })
 }
 
 /// Checks if a filename represents a hidden file (starts with '.').
@@ -106,7 +166,7 @@ fn is_hidden(name: &OsStr) -> bool {
 
 #[cfg(test)]
 mod test {
-    use std::{fs, slice};
+    use std::slice;
 
     use tempfile::TempDir;
 
@@ -122,6 +182,7 @@ mod test {
             is_global: false,
             include: vec!["**/*.md".to_string(), "**/*.txt".to_string()],
             exclude: vec![],
+            synonyms: Default::default(),
         };
 
         (tree, tree_path)
@@ -162,6 +223,7 @@ mod test {
             is_global: false,
             include: vec!["**/*".to_string()],
             exclude: vec![],
+            synonyms: Default::default(),
         };
 
         // Create binary files
@@ -206,6 +268,7 @@ mod test {
             is_global: false,
             include: vec!["**/*.md".to_string()],
             exclude: vec!["**/drafts/**".to_string()],
+            synonyms: Default::default(),
         };
 
         fs::write(tree_path.join("published.md"), "Published").unwrap();
@@ -226,6 +289,7 @@ mod test {
             is_global: false,
             include: vec!["**/*.md".to_string()],
             exclude: vec![],
+            synonyms: Default::default(),
         };
 
         let patterns = CompiledPatterns::compile(slice::from_ref(&tree)).unwrap();
@@ -233,4 +297,39 @@ mod test {
 
         assert!(files.is_empty());
     }
+
+    #[test]
+    fn discover_paths_limits_to_changed_paths() {
+        let temp = TempDir::new().unwrap();
+        let (tree, tree_path) = create_test_tree(&temp);
+
+        fs::create_dir(tree_path.join("subdir")).unwrap();
+        fs::create_dir(tree_path.join(".hidden")).unwrap();
+        fs::write(tree_path.join("readme.md"), "# Readme").unwrap();
+        fs::write(tree_path.join("other.md"), "# Other").unwrap();
+        fs::write(tree_path.join("image.png"), "fake png").unwrap();
+        fs::write(tree_path.join("subdir/nested.md"), "Nested").unwrap();
+        fs::write(tree_path.join(".hidden/secret.md"), "Secret").unwrap();
+
+        let patterns = CompiledPatterns::compile(slice::from_ref(&tree)).unwrap();
+        let changed = vec![
+            tree_path.join("readme.md"),
+            tree_path.join("image.png"),
+            tree_path.join("subdir"),
+            tree_path.join("subdir/nested.md"),
+            tree_path.join(".hidden/secret.md"),
+            tree_path.join("deleted.md"),
+            temp.path().join("outside.md"),
+        ];
+        let files = discover_paths(slice::from_ref(&tree), &patterns, &changed);
+
+        let paths: Vec<_> = files.iter().map(|f| f.rel_path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("readme.md"),
+                PathBuf::from("subdir/nested.md")
+            ]
+        );
+    }
 }
@@ -1,12 +1,14 @@
 //! Document types for indexing.
 //!
 //! The [`ChunkDocument`] struct represents a chunk ready for indexing, combining
-//! chunk-level data with document-level metadata (tags, path, tree) and
-//! hierarchical information (position, parent_id, etc.).
+//! chunk-level data with document-level metadata (tags, path, tree, frontmatter),
+//! hierarchical information (position, parent_id, etc.) and resolved links.
 
 use std::time::SystemTime;
 
-use ra_document::{Document, TreeChunk};
+use ra_document::{DocId, Document, Metadata, TreeChunk};
+
+use crate::links::LinkResolver;
 
 /// A chunk ready for indexing, combining chunk data with document metadata.
 ///
@@ -46,6 +48,10 @@ pub struct ChunkDocument {
     pub sibling_count: usize,
     /// File modification time.
     pub mtime: SystemTime,
+    /// Other frontmatter keys of the document.
+    pub meta: Metadata,
+    /// IDs of the chunks this chunk links to, in order of first appearance.
+    pub links: Vec<String>,
 }
 
 impl ChunkDocument {
@@ -61,9 +67,27 @@ impl ChunkDocument {
     /// * `chunk` - The tree chunk containing body, hierarchy, etc.
     /// * `document` - The parent document containing metadata (tags, path, tree)
     /// * `mtime` - File modification time
-    pub fn from_tree_chunk(chunk: &TreeChunk, document: &Document, mtime: SystemTime) -> Self {
+    /// * `resolver` - Resolves the chunk's links to chunk IDs; unresolved links are dropped
+    pub fn from_tree_chunk(
+        chunk: &TreeChunk,
+        document: &Document,
+        mtime: SystemTime,
+        resolver: &LinkResolver,
+    ) -> Self {
         let path_str = document.path.to_string_lossy().to_string();
 
+        let from = DocId::from_path(&document.tree, &document.path);
+        let mut links: Vec<String> = Vec::new();
+        for id in chunk
+            .links
+            .iter()
+            .filter_map(|l| resolver.resolve(&from, l))
+        {
+            if id != chunk.id && !links.contains(&id) {
+                links.push(id);
+            }
+        }
+
         Self {
             id: chunk.id.clone(),
             doc_id: chunk.doc_id.clone(),
@@ -79,6 +103,8 @@ impl ChunkDocument {
             byte_end: chunk.byte_end,
             sibling_count: chunk.sibling_count,
             mtime,
+            meta: document.meta.clone(),
+            links,
         }
     }
 
@@ -90,18 +116,23 @@ impl ChunkDocument {
     /// # Arguments
     /// * `document` - The document to index
     /// * `mtime` - File modification time
-    pub fn from_document(document: &Document, mtime: SystemTime) -> Vec<Self> {
+    /// * `resolver` - Resolves links to chunk IDs
+    pub fn from_document(
+        document: &Document,
+        mtime: SystemTime,
+        resolver: &LinkResolver,
+    ) -> Vec<Self> {
         let chunks = document.extract_chunks();
         chunks
             .iter()
-            .map(|chunk| Self::from_tree_chunk(chunk, document, mtime))
+            .map(|chunk| Self::from_tree_chunk(chunk, document, mtime, resolver))
             .collect()
     }
 }
 
 #[cfg(test)]
 mod test {
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
 
     use ra_document::parse_markdown;
 
@@ -136,7 +167,8 @@ How to handle errors in API handlers."#;
         let doc = make_test_document();
         let mtime = SystemTime::UNIX_EPOCH;
         let chunks = doc.extract_chunks();
-        let chunk_doc = ChunkDocument::from_tree_chunk(&chunks[0], &doc, mtime);
+        let chunk_doc =
+            ChunkDocument::from_tree_chunk(&chunks[0], &doc, mtime, &LinkResolver::default());
 
         // First chunk is the document node (preamble)
         assert_eq!(chunk_doc.id, "local:docs/api/handlers.md");
@@ -153,11 +185,29 @@ How to handle errors in API handlers."#;
         assert_eq!(chunk_doc.mtime, SystemTime::UNIX_EPOCH);
     }
 
+    #[test]
+    fn from_document_resolves_links() {
+        let path = PathBuf::from("guide.md");
+        let content = "# Setup\n\nSee [[Handlers]], [here](#setup), [[Missing]] and [[handlers]].";
+        let doc = parse_markdown(content, &path, "local").document;
+        let resolver = LinkResolver::new([
+            ("local", Path::new("guide.md")),
+            ("local", Path::new("docs/api/handlers.md")),
+        ]);
+        let chunk_docs = ChunkDocument::from_document(&doc, SystemTime::UNIX_EPOCH, &resolver);
+
+        let setup = chunk_docs
+            .iter()
+            .find(|c| c.id == "local:guide.md#setup")
+            .unwrap();
+        assert_eq!(setup.links, vec!["local:docs/api/handlers.md"]);
+    }
+
     #[test]
     fn from_document_creates_all_chunks() {
         let doc = make_test_document();
         let mtime = SystemTime::UNIX_EPOCH;
-        let chunk_docs = ChunkDocument::from_document(&doc, mtime);
+        let chunk_docs = ChunkDocument::from_document(&doc, mtime, &LinkResolver::default());
 
         assert_eq!(chunk_docs.len(), 2);
         // Document node (preamble)
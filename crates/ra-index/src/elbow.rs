@@ -184,6 +184,8 @@ pub fn elbow_cutoff_results_with_stats(
 
 #[cfg(test)]
 mod test {
+    use ra_document::Metadata;
+
     use super::*;
 
     fn make_candidate(id: &str, score: f32) -> SearchCandidate {
@@ -200,12 +202,15 @@ mod test {
             byte_start: 0,
             byte_end: 100,
             sibling_count: 1,
+            meta: Metadata::new(),
+            links: Vec::new(),
             score,
             snippet: None,
             match_ranges: vec![],
             hierarchy_match_ranges: vec![],
             path_match_ranges: vec![],
             match_details: None,
+            via_link: None,
         }
     }
 
@@ -41,6 +41,10 @@ pub enum IndexError {
     /// Configuration error.
     #[error("configuration error: {0}")]
     Config(String),
+
+    /// A MoreLikeThis source file could not be used.
+    #[error("{0}")]
+    InvalidSource(String),
 }
 
 impl IndexError {
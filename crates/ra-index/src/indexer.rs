@@ -3,27 +3,31 @@
 //! The [`Indexer`] orchestrates the complete indexing flow:
 //! 1. Discover files matching tree patterns
 //! 2. Compare against manifest to find changes
-//! 3. Parse changed files with ra-document
-//! 4. Convert to [`ChunkDocument`]s and write to index
-//! 5. Update manifest with new state
+//! 3. Update manifest with new state
+//! 4. Parse changed files with ra-document
+//! 5. Resolve links against the manifest, convert to [`ChunkDocument`]s and write to index
+//! 6. Reindex unchanged files whose links resolve differently now that files were added or removed
 
 use std::{
+    collections::HashSet,
     fs,
     io::{Error as IoError, ErrorKind},
     path::{Path, PathBuf},
 };
 
 use ra_config::{CompiledPatterns, Config};
+use ra_document::{DocId, Link};
 
 use crate::{
     IndexError,
     config_hash::compute_config_hash,
-    diff::{ManifestDiff, apply_diff, diff_manifest},
-    discovery::{DiscoveredFile, discover_files},
+    diff::{ManifestDiff, apply_diff, diff_changed_paths, diff_manifest},
+    discovery::{DiscoveredFile, discover_files, discover_paths},
     document::ChunkDocument,
+    links::{LinkResolver, link_targets},
     location::{index_directory, manifest_path},
     manifest::Manifest,
-    status::write_config_hash,
+    status::{IndexStatus, detect_index_status, write_config_hash},
     writer::IndexWriter,
 };
 
@@ -44,6 +48,9 @@ pub struct IndexStats {
     pub files_removed: usize,
     /// Errors encountered during parsing (file path, error message).
     pub parse_errors: Vec<(PathBuf, String)>,
+    /// Problems in files that were still indexed, such as malformed frontmatter
+    /// (file path, warning message).
+    pub parse_warnings: Vec<(PathBuf, String)>,
 }
 
 impl IndexStats {
@@ -69,6 +76,9 @@ pub trait ProgressReporter {
     /// Called when a file could not be parsed.
     fn on_file_error(&mut self, path: &Path, error: &str);
 
+    /// Called when a file was indexed despite a problem, such as malformed frontmatter.
+    fn on_file_warning(&mut self, path: &Path, warning: &str);
+
     /// Called when a file was removed from the index.
     fn on_file_removed(&mut self, path: &Path);
 
@@ -83,6 +93,7 @@ impl ProgressReporter for SilentReporter {
     fn on_file_start(&mut self, _path: &Path, _current: usize, _total: usize) {}
     fn on_file_done(&mut self, _path: &Path, _chunks: usize) {}
     fn on_file_error(&mut self, _path: &Path, _error: &str) {}
+    fn on_file_warning(&mut self, _path: &Path, _warning: &str) {}
     fn on_file_removed(&mut self, _path: &Path) {}
     fn on_complete(&mut self, _stats: &IndexStats) {}
 }
@@ -162,6 +173,35 @@ impl<'a> Indexer<'a> {
         self.index_with_diff(manifest, &diff, reporter, false)
     }
 
+    /// Brings the index up to date with the files on disk.
+    ///
+    /// A missing index or changed configuration triggers a full rebuild; otherwise only changed
+    /// files are reindexed.
+    pub fn refresh<R: ProgressReporter>(&self, reporter: &mut R) -> Result<IndexStats, IndexError> {
+        match detect_index_status(self.config) {
+            IndexStatus::Missing | IndexStatus::ConfigChanged => self.full_reindex(reporter),
+            IndexStatus::Current | IndexStatus::Stale => self.incremental_update(reporter),
+        }
+    }
+
+    /// Reindexes only the given paths, as reported by filesystem notifications.
+    ///
+    /// Paths may name files or directories, including ones that no longer exist. Files at or
+    /// below each path are added, reindexed, or removed; the rest of the index is untouched.
+    pub fn update_paths<R: ProgressReporter>(
+        &self,
+        paths: &[PathBuf],
+        reporter: &mut R,
+    ) -> Result<IndexStats, IndexError> {
+        let manifest_file = manifest_path(&self.index_dir);
+        let manifest = Manifest::load(&manifest_file)?;
+
+        let discovered = discover_paths(&self.config.trees, &self.patterns, paths);
+        let diff = diff_changed_paths(&manifest, &discovered, paths);
+
+        self.index_with_diff(manifest, &diff, reporter, false)
+    }
+
     /// Internal method that performs indexing given a manifest and diff.
     fn index_with_diff<R: ProgressReporter>(
         &self,
@@ -199,23 +239,41 @@ impl<'a> Indexer<'a> {
             }
         }
 
-        // Handle added and modified files
-        let files_to_index: Vec<_> = diff.files_to_index().collect();
+        // Update manifest first, so links resolve against every file in the new state. Adding or
+        // removing files can change where unchanged files' links point, so those are reindexed too.
+        let old_resolver =
+            (!diff.added.is_empty() || !diff.removed.is_empty()).then(|| link_resolver(&manifest));
+        apply_diff(&mut manifest, diff);
+        let resolver = link_resolver(&manifest);
+        let relinked = old_resolver
+            .map(|old| relinked_files(&manifest, diff, &old, &resolver))
+            .unwrap_or_default();
+        stats.files_updated += relinked.len();
+
+        // Handle added, modified and relinked files
+        let files_to_index: Vec<_> = diff.files_to_index().chain(&relinked).collect();
         let total_files = files_to_index.len();
 
         for (idx, file) in files_to_index.iter().enumerate() {
             reporter.on_file_start(&file.abs_path, idx + 1, total_files);
 
-            // For modified files, delete old chunks first
-            if diff.modified.iter().any(|f| f.abs_path == file.abs_path) {
+            // For modified and relinked files, delete old chunks first
+            if !diff.added.iter().any(|f| f.abs_path == file.abs_path) {
                 writer.delete_by_path(&file.tree, file.rel_path.to_string_lossy().as_ref());
             }
 
             // Parse and index the file
-            match self.index_file(&mut writer, file) {
-                Ok(chunk_count) => {
+            match self.index_file(&mut writer, file, &resolver) {
+                Ok((chunk_count, warnings, links)) => {
                     stats.files_processed += 1;
                     stats.chunks_indexed += chunk_count;
+                    if let Some(entry) = manifest.get_mut(&file.abs_path) {
+                        entry.links = links;
+                    }
+                    for warning in warnings {
+                        reporter.on_file_warning(&file.abs_path, &warning);
+                        stats.parse_warnings.push((file.abs_path.clone(), warning));
+                    }
                     reporter.on_file_done(&file.abs_path, chunk_count);
                 }
                 Err(e) => {
@@ -233,9 +291,6 @@ impl<'a> Indexer<'a> {
         // Commit the index
         writer.commit()?;
 
-        // Update manifest
-        apply_diff(&mut manifest, diff);
-
         // Remove errored files from manifest so they get retried next time
         for (path, _) in &stats.parse_errors {
             manifest.remove(path);
@@ -253,12 +308,14 @@ impl<'a> Indexer<'a> {
         Ok(stats)
     }
 
-    /// Parses and indexes a single file, returning the number of chunks indexed.
+    /// Parses and indexes a single file, returning the number of chunks indexed, any parse
+    /// warnings, and the file's [`link_targets`].
     fn index_file(
         &self,
         writer: &mut IndexWriter,
         file: &DiscoveredFile,
-    ) -> Result<usize, IndexError> {
+        resolver: &LinkResolver,
+    ) -> Result<(usize, Vec<String>, Vec<Link>), IndexError> {
         // Read file content to get mtime-independent parsing
         let content = fs::read_to_string(&file.abs_path)?;
 
@@ -285,12 +342,16 @@ impl<'a> Indexer<'a> {
         };
 
         // Convert to ChunkDocuments and index
-        let chunk_docs = ChunkDocument::from_document(&result.document, file.mtime);
+        let chunk_docs = ChunkDocument::from_document(&result.document, file.mtime, resolver);
         let chunk_count = chunk_docs.len();
 
         writer.add_documents(&chunk_docs)?;
 
-        Ok(chunk_count)
+        Ok((
+            chunk_count,
+            result.warnings,
+            link_targets(result.document.links()),
+        ))
     }
 
     /// Returns the path to the index directory.
@@ -299,6 +360,46 @@ impl<'a> Indexer<'a> {
     }
 }
 
+/// Builds a link resolver over the files in a manifest.
+fn link_resolver(manifest: &Manifest) -> LinkResolver {
+    LinkResolver::new(
+        manifest
+            .entries()
+            .map(|(_, entry)| (entry.tree.as_str(), entry.path.as_path())),
+    )
+}
+
+/// Returns the files not being indexed by `diff` that have a link resolving differently with
+/// `new` than with `old`, in path order.
+fn relinked_files(
+    manifest: &Manifest,
+    diff: &ManifestDiff,
+    old: &LinkResolver,
+    new: &LinkResolver,
+) -> Vec<DiscoveredFile> {
+    let indexed: HashSet<&PathBuf> = diff.files_to_index().map(|f| &f.abs_path).collect();
+    let mut files: Vec<DiscoveredFile> = manifest
+        .entries()
+        .filter(|(abs_path, _)| !indexed.contains(abs_path))
+        .filter(|(_, entry)| {
+            let from = DocId::from_path(&entry.tree, &entry.path);
+            entry
+                .links
+                .iter()
+                .any(|link| old.resolve(&from, link) != new.resolve(&from, link))
+        })
+        .map(|(abs_path, entry)| DiscoveredFile {
+            tree: entry.tree.clone(),
+            abs_path: abs_path.clone(),
+            rel_path: entry.path.clone(),
+            mtime: entry.mtime,
+            size: entry.size,
+        })
+        .collect();
+    files.sort_by(|a, b| a.abs_path.cmp(&b.abs_path));
+    files
+}
+
 #[cfg(test)]
 mod test {
     use std::{cell::RefCell, thread, time::Duration};
@@ -307,6 +408,7 @@ mod test {
     use tempfile::TempDir;
 
     use super::*;
+    use crate::Searcher;
 
     /// Test reporter that records all events.
     #[derive(Default)]
@@ -336,6 +438,12 @@ mod test {
                 .push(format!("error: {} - {}", path.display(), error));
         }
 
+        fn on_file_warning(&mut self, path: &Path, warning: &str) {
+            self.events
+                .borrow_mut()
+                .push(format!("warning: {} - {}", path.display(), warning));
+        }
+
         fn on_file_removed(&mut self, path: &Path) {
             self.events
                 .borrow_mut()
@@ -361,6 +469,7 @@ mod test {
                 is_global: false,
                 include: vec!["**/*.md".to_string(), "**/*.txt".to_string()],
                 exclude: vec![],
+                synonyms: Default::default(),
             }],
             config_root: Some(temp.path().to_path_buf()),
             ..Default::default()
@@ -387,6 +496,39 @@ mod test {
         assert!(stats.chunks_indexed >= 2);
     }
 
+    #[test]
+    fn malformed_frontmatter_is_indexed_with_warning() {
+        let temp = TempDir::new().unwrap();
+        let config = create_test_config(&temp);
+        let tree_path = temp.path().join("docs");
+
+        fs::write(
+            tree_path.join("broken.md"),
+            "+++\ntitle = unquoted\n+++\n\n# Broken\n\nContent.",
+        )
+        .unwrap();
+
+        let indexer = Indexer::new(&config).unwrap();
+        let mut reporter = TestReporter::default();
+        let stats = indexer.full_reindex(&mut reporter).unwrap();
+
+        assert_eq!(stats.files_processed, 1);
+        assert!(stats.is_success());
+        assert_eq!(stats.parse_warnings.len(), 1);
+        assert!(
+            stats.parse_warnings[0]
+                .1
+                .starts_with("malformed TOML frontmatter")
+        );
+        assert!(
+            reporter
+                .events
+                .borrow()
+                .iter()
+                .any(|e| e.starts_with("warning: ") && e.contains("broken.md"))
+        );
+    }
+
     #[test]
     fn incremental_update_skips_unchanged_files() {
         let temp = TempDir::new().unwrap();
@@ -526,4 +668,79 @@ mod test {
         let manifest = Manifest::load(&manifest_file).unwrap();
         assert_eq!(manifest.len(), 1);
     }
+
+    #[test]
+    fn update_paths_reindexes_only_changed_paths() {
+        let temp = TempDir::new().unwrap();
+        let config = create_test_config(&temp);
+        let tree_path = temp.path().join("docs");
+
+        fs::write(tree_path.join("keep.md"), "# Keep").unwrap();
+        fs::write(tree_path.join("edit.md"), "# Edit").unwrap();
+        fs::write(tree_path.join("remove.md"), "# Remove").unwrap();
+
+        let indexer = Indexer::new(&config).unwrap();
+        let mut reporter = SilentReporter;
+        indexer.full_reindex(&mut reporter).unwrap();
+
+        // Rewrite within the same second: mtime comparison alone would miss this
+        fs::write(tree_path.join("edit.md"), "# Edited").unwrap();
+        fs::remove_file(tree_path.join("remove.md")).unwrap();
+        fs::write(tree_path.join("new.md"), "# New").unwrap();
+
+        let changed = vec![
+            tree_path.join("edit.md"),
+            tree_path.join("remove.md"),
+            tree_path.join("new.md"),
+        ];
+        let stats = indexer.update_paths(&changed, &mut reporter).unwrap();
+        assert_eq!(stats.files_added, 1);
+        assert_eq!(stats.files_updated, 1);
+        assert_eq!(stats.files_removed, 1);
+        assert_eq!(stats.files_processed, 2);
+
+        let manifest = Manifest::load(&manifest_path(indexer.index_dir())).unwrap();
+        assert_eq!(manifest.len(), 3);
+    }
+
+    #[test]
+    fn links_follow_their_targets_being_added_and_removed() {
+        let temp = TempDir::new().unwrap();
+        let config = create_test_config(&temp);
+        let tree_path = temp.path().join("docs");
+
+        fs::write(tree_path.join("guide.md"), "See [[Setup]] first.").unwrap();
+        fs::write(tree_path.join("other.md"), "# Other").unwrap();
+
+        let indexer = Indexer::new(&config).unwrap();
+        let mut reporter = SilentReporter;
+        indexer.full_reindex(&mut reporter).unwrap();
+        let links = || {
+            Searcher::open_with_config(indexer.index_dir(), &config)
+                .unwrap()
+                .get_outgoing_links("docs:guide.md")
+                .unwrap()
+        };
+        assert!(links().is_empty());
+
+        // Creating the target reindexes the unchanged file that links to it
+        fs::write(tree_path.join("setup.md"), "# Setup").unwrap();
+        let stats = indexer.incremental_update(&mut reporter).unwrap();
+        assert_eq!(stats.files_added, 1);
+        assert_eq!(stats.files_updated, 1);
+        assert_eq!(links(), vec!["docs:setup.md"]);
+
+        // Files whose links don't change are left alone
+        fs::remove_file(tree_path.join("other.md")).unwrap();
+        let stats = indexer.incremental_update(&mut reporter).unwrap();
+        assert_eq!(stats.files_updated, 0);
+
+        fs::remove_file(tree_path.join("setup.md")).unwrap();
+        let stats = indexer
+            .update_paths(&[tree_path.join("setup.md")], &mut reporter)
+            .unwrap();
+        assert_eq!(stats.files_removed, 1);
+        assert_eq!(stats.files_updated, 1);
+        assert!(links().is_empty());
+    }
 }
@@ -40,13 +40,16 @@ mod document;
 mod elbow;
 mod error;
 mod indexer;
+mod links;
 mod location;
 mod manifest;
+mod meta;
 mod query;
 mod result;
 mod schema;
 mod search;
 mod status;
+mod tokens;
 mod writer;
 
 // Core public API - types and functions used by the ra CLI
@@ -55,11 +58,16 @@ pub use elbow::{ElbowReason, ElbowStats};
 pub use error::IndexError;
 pub use indexer::{IndexStats, Indexer, ProgressReporter, SilentReporter};
 pub use location::index_directory;
-pub use query::{QueryError, QueryErrorKind, QueryExpr, parse as parse_query};
+pub use query::{
+    LenientParse, MAX_EXPANSIONS, QueryError, QueryErrorKind, QueryExpr, SYNONYM_WEIGHT,
+    SynonymExpansion, TermExpansion, parse as parse_query, parse_json as parse_query_json,
+};
 pub use ra_context::is_binary_file;
 pub use result::SearchResult;
 pub use search::{
-    MatchDetails, MoreLikeThisExplanation, MoreLikeThisParams, PipelineStats, SearchCandidate,
-    SearchParams, Searcher, TreeFilteredSearcher, merge_ranges, open_searcher,
+    FacetCounts, FacetField, MatchDetails, MoreLikeThisExplanation, MoreLikeThisParams,
+    Neighborhood, PipelineStats, SearchCandidate, SearchParams, Searcher, TreeFilteredSearcher,
+    merge_ranges, open_searcher,
 };
-pub use status::{IndexStatus, detect_index_status};
+pub use status::{IndexStatus, detect_index_status, indexed_file_counts};
+pub use tokens::count_tokens;
@@ -0,0 +1,212 @@
+//! Resolution of document links to chunk IDs.
+//!
+//! Links extracted by ra-document name their targets by path or, for wiki-links, by page name.
+//! The [`LinkResolver`] maps them to chunk IDs using the set of files known to the index, so
+//! the index can store each chunk's outgoing links as IDs and answer backlink queries with a
+//! term lookup. Because resolution depends on which files exist, the indexer also records each
+//! file's [`link_targets`] and reindexes files whose links resolve differently after files are
+//! added or removed.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use ra_document::{ChunkId, DocId, Link, LinkKind};
+
+/// Maps links to the chunk IDs of indexed documents.
+#[derive(Debug, Clone, Default)]
+pub struct LinkResolver {
+    /// Indexed document paths by tree, with `/` separators.
+    paths: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl LinkResolver {
+    /// Creates a resolver for the given `(tree, path)` pairs of indexed files.
+    pub fn new<'a>(files: impl IntoIterator<Item = (&'a str, &'a Path)>) -> Self {
+        let mut paths: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for (tree, path) in files {
+            paths
+                .entry(tree.to_string())
+                .or_default()
+                .insert(DocId::from_path(tree, path).path);
+        }
+        Self { paths }
+    }
+
+    /// Resolves a link found in document `from` to a chunk ID.
+    ///
+    /// Markdown links must name an indexed file in the same tree, optionally without its `.md`
+    /// extension. Wiki-links match a file by name or by the end of its path, ignoring case and
+    /// extension, preferring the same tree and then the shortest path. Returns `None` if no
+    /// indexed file matches.
+    pub fn resolve(&self, from: &DocId, link: &Link) -> Option<String> {
+        let doc_id = if link.target.is_empty() {
+            from.clone()
+        } else {
+            match link.kind {
+                LinkKind::Markdown => self.resolve_path(&from.tree, &link.target)?,
+                LinkKind::Wiki => self.resolve_page(&from.tree, &link.target)?,
+            }
+        };
+
+        Some(
+            ChunkId {
+                doc_id,
+                slug: link.fragment.clone(),
+            }
+            .to_string(),
+        )
+    }
+
+    /// Finds an indexed file by its exact path within a tree.
+    fn resolve_path(&self, tree: &str, target: &str) -> Option<DocId> {
+        let paths = self.paths.get(tree)?;
+        [target.to_string(), format!("{target}.md")]
+            .into_iter()
+            .find(|path| paths.contains(path))
+            .map(|path| DocId {
+                tree: tree.to_string(),
+                path,
+            })
+    }
+
+    /// Finds an indexed file by wiki page name.
+    fn resolve_page(&self, tree: &str, page: &str) -> Option<DocId> {
+        let page = page.trim_matches('/').to_lowercase();
+        let suffix = format!("/{page}");
+        let matches = |path: &str| {
+            let path = path.to_lowercase();
+            let stem = strip_extension(&path);
+            [path.as_str(), stem]
+                .iter()
+                .any(|p| *p == page || p.ends_with(&suffix))
+        };
+
+        // Same tree first, then the others in name order
+        let mut trees = self
+            .paths
+            .get_key_value(tree)
+            .into_iter()
+            .chain(self.paths.iter().filter(|(name, _)| *name != tree));
+        trees.find_map(|(tree, paths)| {
+            paths
+                .iter()
+                .filter(|path| matches(path))
+                .min_by_key(|path| path.len())
+                .map(|path| DocId {
+                    tree: tree.clone(),
+                    path: path.clone(),
+                })
+        })
+    }
+}
+
+/// Returns the distinct links to other documents, without their fragments.
+///
+/// These are what a document's resolved links depend on besides its own content, so they are
+/// recorded in the manifest to find documents whose links change when files are added or removed.
+pub fn link_targets<'a>(links: impl IntoIterator<Item = &'a Link>) -> Vec<Link> {
+    let mut targets: Vec<Link> = Vec::new();
+    for link in links.into_iter().filter(|l| !l.target.is_empty()) {
+        let target = Link {
+            fragment: None,
+            ..link.clone()
+        };
+        if !targets.contains(&target) {
+            targets.push(target);
+        }
+    }
+    targets
+}
+
+/// Removes the file extension from a path, if the final component has one.
+fn strip_extension(path: &str) -> &str {
+    let name_start = path.rfind('/').map_or(0, |i| i + 1);
+    match path[name_start..].rfind('.') {
+        Some(dot) if dot > 0 => &path[..name_start + dot],
+        _ => path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a resolver over a few files in two trees.
+    fn resolver() -> LinkResolver {
+        LinkResolver::new([
+            ("docs", Path::new("guide.md")),
+            ("docs", Path::new("ops/Retry Policy.md")),
+            ("docs", Path::new("archive/ops/retry policy.md")),
+            ("docs", Path::new("notes.txt")),
+            ("wiki", Path::new("Timeouts.md")),
+        ])
+    }
+
+    /// Builds an unresolved link.
+    fn link(kind: LinkKind, target: &str, fragment: Option<&str>) -> Link {
+        Link {
+            kind,
+            target: target.to_string(),
+            fragment: fragment.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn resolves_markdown_links_by_path() {
+        let resolver = resolver();
+        let from = DocId::parse("docs:ops/Retry Policy.md").unwrap();
+        let resolve =
+            |target, fragment| resolver.resolve(&from, &link(LinkKind::Markdown, target, fragment));
+
+        assert_eq!(
+            resolve("guide.md", Some("setup")).as_deref(),
+            Some("docs:guide.md#setup")
+        );
+        assert_eq!(resolve("guide", None).as_deref(), Some("docs:guide.md"));
+        assert_eq!(
+            resolve("", Some("backoff")).as_deref(),
+            Some("docs:ops/Retry Policy.md#backoff")
+        );
+        assert_eq!(resolve("missing.md", None), None);
+        assert_eq!(resolve("Timeouts.md", None), None);
+    }
+
+    #[test]
+    fn resolves_wiki_links_by_name() {
+        let resolver = resolver();
+        let from = DocId::parse("docs:guide.md").unwrap();
+        let resolve = |target| resolver.resolve(&from, &link(LinkKind::Wiki, target, None));
+
+        assert_eq!(
+            resolve("retry policy").as_deref(),
+            Some("docs:ops/Retry Policy.md")
+        );
+        assert_eq!(
+            resolve("archive/ops/Retry Policy").as_deref(),
+            Some("docs:archive/ops/retry policy.md")
+        );
+        assert_eq!(resolve("notes").as_deref(), Some("docs:notes.txt"));
+        assert_eq!(resolve("Timeouts").as_deref(), Some("wiki:Timeouts.md"));
+        assert_eq!(resolve("policy"), None);
+    }
+
+    #[test]
+    fn link_targets_drop_fragments_and_local_links() {
+        let links = [
+            link(LinkKind::Markdown, "guide.md", Some("setup")),
+            link(LinkKind::Markdown, "", Some("backoff")),
+            link(LinkKind::Markdown, "guide.md", None),
+            link(LinkKind::Wiki, "guide.md", None),
+        ];
+
+        assert_eq!(
+            link_targets(&links),
+            vec![
+                link(LinkKind::Markdown, "guide.md", None),
+                link(LinkKind::Wiki, "guide.md", None),
+            ]
+        );
+    }
+}
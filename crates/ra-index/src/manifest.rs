@@ -1,16 +1,17 @@
 //! Manifest tracking for indexed files.
 //!
 //! The manifest stores metadata about all indexed files including their paths, tree names,
-//! and modification times. It is used for incremental updates to determine which files
+//! modification times and sizes. It is used for incremental updates to determine which files
 //! need reindexing.
 
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fs, io,
     path::{Path, PathBuf},
     time::SystemTime,
 };
 
+use ra_document::Link;
 use serde::{Deserialize, Serialize};
 
 use crate::IndexError;
@@ -25,6 +26,12 @@ pub struct ManifestEntry {
     /// File modification time when last indexed.
     #[serde(with = "system_time_serde")]
     pub mtime: SystemTime,
+    /// File size in bytes when last indexed.
+    pub size: u64,
+    /// Links to other documents as written, without fragments, so the file can be reindexed
+    /// when the files they name are added or removed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<Link>,
 }
 
 /// Tracks indexed files and their modification times.
@@ -96,11 +103,25 @@ impl Manifest {
         self.entries.get(abs_path)
     }
 
+    /// Gets a mutable entry by absolute path.
+    pub fn get_mut(&mut self, abs_path: &Path) -> Option<&mut ManifestEntry> {
+        self.entries.get_mut(abs_path)
+    }
+
     /// Returns an iterator over all entries.
     pub fn entries(&self) -> impl Iterator<Item = (&PathBuf, &ManifestEntry)> {
         self.entries.iter()
     }
 
+    /// Counts indexed files per tree.
+    pub fn file_counts_by_tree(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for entry in self.entries.values() {
+            *counts.entry(entry.tree.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
     /// Returns the number of entries in the manifest.
     #[cfg(test)]
     pub fn len(&self) -> usize {
@@ -147,6 +168,32 @@ mod test {
 
     use super::*;
 
+    #[test]
+    fn file_counts_by_tree() {
+        let mut manifest = Manifest::new();
+        for (abs, tree) in [
+            ("/project/docs/a.md", "docs"),
+            ("/project/docs/b.md", "docs"),
+            ("/project/notes/c.md", "notes"),
+        ] {
+            manifest.insert(
+                PathBuf::from(abs),
+                ManifestEntry {
+                    tree: tree.to_string(),
+                    path: PathBuf::from(abs),
+                    mtime: SystemTime::UNIX_EPOCH,
+                    size: 0,
+                    links: Vec::new(),
+                },
+            );
+        }
+
+        let counts = manifest.file_counts_by_tree();
+        assert_eq!(counts.get("docs"), Some(&2));
+        assert_eq!(counts.get("notes"), Some(&1));
+        assert_eq!(counts.len(), 2);
+    }
+
     #[test]
     fn manifest_round_trip() {
         let temp = TempDir::new().unwrap();
@@ -159,6 +206,8 @@ mod test {
                 tree: "docs".to_string(),
                 path: PathBuf::from("test.md"),
                 mtime: SystemTime::UNIX_EPOCH + Duration::from_secs(1234567890),
+                size: 0,
+                links: Vec::new(),
             },
         );
         manifest.insert(
@@ -167,6 +216,8 @@ mod test {
                 tree: "notes".to_string(),
                 path: PathBuf::from("note.txt"),
                 mtime: SystemTime::UNIX_EPOCH + Duration::from_secs(9876543210),
+                size: 0,
+                links: Vec::new(),
             },
         );
 
@@ -200,6 +251,8 @@ mod test {
                 tree: "test".to_string(),
                 path: PathBuf::from("file.md"),
                 mtime: SystemTime::now(),
+                size: 0,
+                links: Vec::new(),
             },
         );
         assert_eq!(manifest.len(), 1);
@@ -0,0 +1,82 @@
+//! Conversion of frontmatter metadata to and from the index's `meta` JSON field.
+
+use std::collections::BTreeMap;
+
+use ra_document::{MetaValue, Metadata};
+use tantivy::schema::OwnedValue;
+
+/// Converts document metadata into a JSON object for the `meta` field.
+pub fn to_object(meta: &Metadata) -> BTreeMap<String, OwnedValue> {
+    meta.iter()
+        .map(|(key, value)| (key.clone(), to_value(value)))
+        .collect()
+}
+
+/// Converts one metadata value into an index value.
+fn to_value(value: &MetaValue) -> OwnedValue {
+    match value {
+        MetaValue::Bool(b) => OwnedValue::Bool(*b),
+        MetaValue::Integer(i) => OwnedValue::I64(*i),
+        MetaValue::Float(x) => OwnedValue::F64(*x),
+        MetaValue::String(s) => OwnedValue::Str(s.clone()),
+        MetaValue::List(items) => OwnedValue::Array(items.iter().map(to_value).collect()),
+    }
+}
+
+/// Reads document metadata back from a stored `meta` field value.
+///
+/// Returns empty metadata if the value is not an object.
+pub fn from_value(value: Option<&OwnedValue>) -> Metadata {
+    match value {
+        Some(OwnedValue::Object(object)) => object
+            .iter()
+            .filter_map(|(key, value)| Some((key.clone(), from_owned(value)?)))
+            .collect(),
+        _ => Metadata::new(),
+    }
+}
+
+/// Converts a stored index value back into a metadata value.
+fn from_owned(value: &OwnedValue) -> Option<MetaValue> {
+    match value {
+        OwnedValue::Bool(b) => Some(MetaValue::Bool(*b)),
+        OwnedValue::I64(i) => Some(MetaValue::Integer(*i)),
+        OwnedValue::U64(u) => i64::try_from(*u)
+            .map(MetaValue::Integer)
+            .ok()
+            .or(Some(MetaValue::Float(*u as f64))),
+        OwnedValue::F64(x) => Some(MetaValue::Float(*x)),
+        OwnedValue::Str(s) => Some(MetaValue::String(s.clone())),
+        OwnedValue::Array(items) => Some(MetaValue::List(
+            items.iter().filter_map(from_owned).collect(),
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_metadata() {
+        let meta: Metadata = [
+            ("status".to_string(), MetaValue::String("draft".into())),
+            ("version".to_string(), MetaValue::Integer(2)),
+            ("score".to_string(), MetaValue::Float(0.5)),
+            ("public".to_string(), MetaValue::Bool(false)),
+            (
+                "audience".to_string(),
+                MetaValue::List(vec![
+                    MetaValue::String("dev".into()),
+                    MetaValue::String("ops".into()),
+                ]),
+            ),
+        ]
+        .into();
+
+        let stored = OwnedValue::Object(to_object(&meta));
+        assert_eq!(from_value(Some(&stored)), meta);
+        assert!(from_value(None).is_empty());
+    }
+}
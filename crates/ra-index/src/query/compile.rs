@@ -2,19 +2,32 @@
 //!
 //! Compiles a query AST into Tantivy queries.
 
-use std::{error::Error, fmt};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    ops::Bound,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use ra_config::FieldBoosts;
-use ra_query::QueryExpr;
+use ra_config::{FieldBoosts, Synonyms, Tree};
+use ra_query::{ChunkFilter, Comparison, DateFilter, QueryExpr};
 use tantivy::{
-    Term,
+    DateTime, Searcher as TvSearcher, Term,
+    json_utils::{JsonTermWriter, convert_to_fast_value_and_get_term},
     query::{
-        AllQuery, BooleanQuery, BoostQuery, FuzzyTermQuery, Occur, PhraseQuery, Query, TermQuery,
+        AllQuery, BooleanQuery, BoostQuery, ConstScoreQuery, EmptyQuery, FuzzyTermQuery, Occur,
+        PhraseQuery, Query, RangeQuery, TermQuery,
     },
     schema::{Field, IndexRecordOption},
     tokenizer::{TextAnalyzer, TokenStream},
 };
 
+use super::{
+    regex::StoredRegexQuery,
+    synonyms::{SYNONYM_WEIGHT, SynonymExpansion, SynonymTable},
+    wildcard::{self, TermExpansion, WildcardWords},
+};
 use crate::{IndexError, analyzer::build_analyzer_from_name, schema::IndexSchema};
 
 /// Error during query compilation.
@@ -32,6 +45,20 @@ impl fmt::Display for CompileError {
 
 impl Error for CompileError {}
 
+/// A compiled query, with what compiling it expanded.
+#[derive(Debug, Default)]
+pub struct CompiledQuery {
+    /// The Tantivy query, or `None` if the expression has nothing to search for.
+    pub query: Option<Box<dyn Query>>,
+    /// Wildcard expansions, in query order.
+    pub expansions: Vec<TermExpansion>,
+    /// Edit distances given to individual terms with `term~N`, keyed by analyzed token. Only
+    /// distances differing from the compiler's configured distance are kept.
+    pub fuzzy_overrides: HashMap<String, u8>,
+    /// Synonym expansions, in query order.
+    pub synonym_expansions: Vec<SynonymExpansion>,
+}
+
 /// Compiles query AST nodes into Tantivy queries.
 pub struct QueryCompiler {
     /// Index schema for field references.
@@ -42,6 +69,10 @@ pub struct QueryCompiler {
     fuzzy_distance: u8,
     /// Field boost weights.
     boosts: FieldBoosts,
+    /// Index snapshot whose term dictionaries wildcard terms expand against.
+    index: Option<TvSearcher>,
+    /// Synonyms that plain terms expand to.
+    synonyms: SynonymTable,
 }
 
 impl QueryCompiler {
@@ -58,25 +89,212 @@ impl QueryCompiler {
             analyzer,
             fuzzy_distance,
             boosts,
+            index: None,
+            synonyms: SynonymTable::default(),
         })
     }
 
+    /// Sets the synonyms that apply to every tree.
+    pub fn set_synonyms(&mut self, synonyms: &Synonyms) {
+        self.synonyms.set_global(synonyms);
+    }
+
+    /// Sets the synonyms loaded from each tree's synonym file.
+    pub fn set_tree_synonyms(&mut self, trees: &[Tree]) {
+        self.synonyms.set_trees(trees);
+    }
+
+    /// Sets the index snapshot that wildcard terms expand against.
+    ///
+    /// Without an index, wildcard terms match nothing.
+    pub fn set_index(&mut self, index: TvSearcher) {
+        self.index = Some(index);
+    }
+
     /// Compiles a query expression into a Tantivy query.
     ///
-    /// Returns `None` for empty queries, `Some(query)` for valid queries,
-    /// or an error for invalid constructs (e.g., NOT-only queries).
-    pub fn compile(&mut self, expr: &QueryExpr) -> Result<Option<Box<dyn Query>>, CompileError> {
+    /// The result holds no query for empty queries. Invalid constructs (e.g., NOT-only
+    /// queries) are an error.
+    pub fn compile(&mut self, expr: &QueryExpr) -> Result<CompiledQuery, CompileError> {
+        let mut compiled = CompiledQuery::default();
+        compiled.query = self.compile_expr(expr, &mut compiled)?;
+        Ok(compiled)
+    }
+
+    /// Compiles one expression, recording its expansions in `compiled`.
+    fn compile_expr(
+        &mut self,
+        expr: &QueryExpr,
+        compiled: &mut CompiledQuery,
+    ) -> Result<Option<Box<dyn Query>>, CompileError> {
         match expr {
-            QueryExpr::Term(text) => Ok(self.compile_term(text)),
+            QueryExpr::Term(text) => {
+                self.compile_with_synonyms(text, compiled, |compiler, compiled, word| {
+                    Ok(compiler.compile_term(word, compiler.fuzzy_distance, compiled))
+                })
+            }
+            QueryExpr::Fuzzy { term, distance } => Ok(self.compile_term(term, *distance, compiled)),
+            QueryExpr::Wildcard(pattern) => {
+                let fields = self.fields_with_boosts();
+                self.compile_wildcard(&fields, pattern, compiled)
+            }
             QueryExpr::Phrase(words) => Ok(self.compile_phrase(words)),
-            QueryExpr::Not(inner) => self.compile_not(inner),
-            QueryExpr::And(exprs) => self.compile_and(exprs),
-            QueryExpr::Or(exprs) => self.compile_or(exprs),
-            QueryExpr::Field { name, expr } => self.compile_field(name, expr),
-            QueryExpr::Boost { expr, factor } => self.compile_boost(expr, *factor),
+            QueryExpr::Proximity { words, slop } => {
+                let tokens = self.tokenize_words(words);
+                let fields = self.positional_fields_with_boosts();
+                Ok(self.build_proximity_query(&fields, &[tokens], *slop))
+            }
+            QueryExpr::Near {
+                left,
+                right,
+                distance,
+            } => {
+                let sequences = self.near_sequences(left, right)?;
+                let fields = self.positional_fields_with_boosts();
+                Ok(self.build_proximity_query(&fields, &sequences, *distance))
+            }
+            QueryExpr::Not(inner) => self.compile_not(inner, compiled),
+            QueryExpr::And(exprs) => self.compile_and(exprs, compiled),
+            QueryExpr::Or(exprs) => self.compile_or(exprs, compiled),
+            QueryExpr::Field { name, expr } => self.compile_field(name, expr, compiled),
+            QueryExpr::Boost { expr, factor } => self.compile_boost(expr, *factor, compiled),
+            QueryExpr::Modified(filter) => Ok(Some(self.compile_modified(filter))),
+            QueryExpr::Filter(filter) => Ok(Some(self.compile_chunk_filter(filter))),
+            QueryExpr::Regex(pattern) => Err(CompileError {
+                message: format!("regex /{pattern}/ must be scoped to a field, e.g. body:/.../"),
+            }),
         }
     }
 
+    /// Compiles a wildcard term by expanding it against the term dictionaries of `fields`.
+    ///
+    /// Each expansion becomes an exact term query, or a phrase query when the pattern spans
+    /// several words. A pattern that matches no terms falls back to its literal text, so a
+    /// question mark ending a natural-language query does not empty the results.
+    fn compile_wildcard(
+        &mut self,
+        fields: &[(Field, f32)],
+        pattern: &str,
+        compiled: &mut CompiledQuery,
+    ) -> Result<Option<Box<dyn Query>>, CompileError> {
+        let words = WildcardWords::split(pattern)?;
+        let before = words.before.iter().flat_map(|w| self.tokenize(w)).collect();
+        let after: Vec<String> = words.after.iter().flat_map(|w| self.tokenize(w)).collect();
+
+        let middles = match &words.wildcard {
+            Some(word) => {
+                let (terms, truncated) = match &self.index {
+                    Some(index) => {
+                        let field_ids: Vec<Field> = fields.iter().map(|(f, _)| *f).collect();
+                        wildcard::expand(index, &field_ids, word)
+                    }
+                    None => (Vec::new(), false),
+                };
+                compiled.expansions.push(TermExpansion {
+                    pattern: pattern.to_string(),
+                    terms: terms.clone(),
+                    truncated,
+                });
+                let literal = self.tokenize(&word.replace(['*', '?'], ""));
+                let mut middles: Vec<Vec<String>> = terms.into_iter().map(|t| vec![t]).collect();
+                // A trailing `?` is usually the end of a question, so the word as typed matches
+                // alongside its expansions
+                if middles.is_empty() || (word.ends_with('?') && !middles.contains(&literal)) {
+                    middles.push(literal);
+                }
+                middles
+            }
+            None => vec![Vec::new()],
+        };
+
+        let sequences: Vec<Vec<String>> = middles
+            .into_iter()
+            .map(|middle| {
+                [&before, &middle, &after]
+                    .into_iter()
+                    .flatten()
+                    .cloned()
+                    .collect()
+            })
+            .collect();
+
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        for &(field, boost_value) in fields {
+            for tokens in &sequences {
+                let query: Box<dyn Query> = match tokens.as_slice() {
+                    [] => continue,
+                    [token] => Box::new(TermQuery::new(
+                        Term::from_field_text(field, token),
+                        IndexRecordOption::WithFreqs,
+                    )),
+                    _ => Box::new(PhraseQuery::new(
+                        tokens
+                            .iter()
+                            .map(|t| Term::from_field_text(field, t))
+                            .collect(),
+                    )),
+                };
+                clauses.push((Occur::Should, Box::new(BoostQuery::new(query, boost_value))));
+            }
+        }
+
+        if clauses.is_empty() {
+            Ok(Some(Box::new(EmptyQuery)))
+        } else {
+            Ok(Some(Box::new(BooleanQuery::new(clauses))))
+        }
+    }
+
+    /// Compiles a modification-time filter into a range query on the `mtime` field.
+    ///
+    /// Relative ages are measured from the current time.
+    fn compile_modified(&self, filter: &DateFilter) -> Box<dyn Query> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        let (start, end) = filter.bounds(now);
+        let to_date = DateTime::from_timestamp_secs;
+        let field = self.schema.schema().get_field_name(self.schema.mtime);
+        Box::new(RangeQuery::new_date_bounds(
+            field.to_string(),
+            start.map_or(Bound::Unbounded, |s| Bound::Included(to_date(s))),
+            end.map_or(Bound::Unbounded, |e| Bound::Excluded(to_date(e))),
+        ))
+    }
+
+    /// Compiles a structural filter into a constant-score query, so that filtering does not
+    /// change how matching chunks rank against each other.
+    fn compile_chunk_filter(&self, filter: &ChunkFilter) -> Box<dyn Query> {
+        match filter {
+            ChunkFilter::Depth { op, depth } => self.compile_depth(*op, *depth),
+            ChunkFilter::Root(true) => self.compile_depth(Comparison::Eq, 0),
+            ChunkFilter::Root(false) => self.compile_depth(Comparison::Gt, 0),
+            ChunkFilter::Doc(id) => self.compile_exact(self.schema.doc_id, id),
+            ChunkFilter::Id(id) => self.compile_exact(self.schema.id, id),
+        }
+    }
+
+    /// Compiles a depth comparison into a range query on the `depth` fast field.
+    fn compile_depth(&self, op: Comparison, depth: u64) -> Box<dyn Query> {
+        let (lower, upper) = match op {
+            Comparison::Eq => (Bound::Included(depth), Bound::Included(depth)),
+            Comparison::Lt => (Bound::Unbounded, Bound::Excluded(depth)),
+            Comparison::Le => (Bound::Unbounded, Bound::Included(depth)),
+            Comparison::Gt => (Bound::Excluded(depth), Bound::Unbounded),
+            Comparison::Ge => (Bound::Included(depth), Bound::Unbounded),
+        };
+        let field = self.schema.schema().get_field_name(self.schema.depth);
+        Box::new(RangeQuery::new_u64_bounds(field.to_string(), lower, upper))
+    }
+
+    /// Compiles an exact match on an untokenized ID field.
+    fn compile_exact(&self, field: Field, value: &str) -> Box<dyn Query> {
+        let term = Term::from_field_text(field, value);
+        let query = TermQuery::new(term, IndexRecordOption::Basic);
+        Box::new(ConstScoreQuery::new(Box::new(query), 1.0))
+    }
+
     /// Compiles a boosted expression.
     ///
     /// Wraps the inner query with a `BoostQuery` that multiplies the score.
@@ -84,16 +302,79 @@ impl QueryCompiler {
         &mut self,
         expr: &QueryExpr,
         factor: f32,
+        compiled: &mut CompiledQuery,
     ) -> Result<Option<Box<dyn Query>>, CompileError> {
-        match self.compile(expr)? {
+        match self.compile_expr(expr, compiled)? {
             Some(inner) => Ok(Some(Box::new(BoostQuery::new(inner, factor)))),
             None => Ok(None),
         }
     }
 
-    /// Compiles a term into a multi-field query with boosts.
-    fn compile_term(&mut self, text: &str) -> Option<Box<dyn Query>> {
-        let tokens = self.tokenize(text);
+    /// Compiles a term and any synonyms it has, using `compile` for each word.
+    ///
+    /// With synonyms, the term matches as an OR of itself and each synonym, where synonyms
+    /// weigh [`SYNONYM_WEIGHT`] and those from a tree's synonym file only match that tree.
+    fn compile_with_synonyms(
+        &mut self,
+        text: &str,
+        compiled: &mut CompiledQuery,
+        mut compile: impl FnMut(
+            &mut Self,
+            &mut CompiledQuery,
+            &str,
+        ) -> Result<Option<Box<dyn Query>>, CompileError>,
+    ) -> Result<Option<Box<dyn Query>>, CompileError> {
+        let expansions = self.synonyms.lookup(text);
+        let original = compile(self, compiled, text)?;
+        if expansions.is_empty() {
+            return Ok(original);
+        }
+
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        clauses.extend(original.map(|q| (Occur::Should, q)));
+        for expansion in &expansions {
+            let tree_filter = expansion.tree.as_ref().map(|tree| {
+                let term = Term::from_field_text(self.schema.tree, tree);
+                TermQuery::new(term, IndexRecordOption::Basic)
+            });
+            for synonym in &expansion.synonyms {
+                let Some(query) = compile(self, compiled, synonym)? else {
+                    continue;
+                };
+                let query: Box<dyn Query> = match &tree_filter {
+                    Some(filter) => Box::new(BooleanQuery::new(vec![
+                        (Occur::Must, query),
+                        (
+                            Occur::Must,
+                            Box::new(ConstScoreQuery::new(Box::new(filter.clone()), 0.0)),
+                        ),
+                    ])),
+                    None => query,
+                };
+                clauses.push((
+                    Occur::Should,
+                    Box::new(BoostQuery::new(query, SYNONYM_WEIGHT)),
+                ));
+            }
+        }
+        compiled.synonym_expansions.extend(expansions);
+
+        if clauses.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(Box::new(BooleanQuery::new(clauses))))
+        }
+    }
+
+    /// Compiles a term into a multi-field query with boosts, matching within `distance`
+    /// edits.
+    fn compile_term(
+        &mut self,
+        text: &str,
+        distance: u8,
+        compiled: &mut CompiledQuery,
+    ) -> Option<Box<dyn Query>> {
+        let tokens = self.analyze_term(text, distance, compiled);
         if tokens.is_empty() {
             return None;
         }
@@ -103,15 +384,101 @@ impl QueryCompiler {
             return self.compile_phrase_from_tokens(&tokens);
         }
 
-        self.build_multi_field_term_query(&tokens[0])
+        self.build_multi_field_term_query(&tokens[0], distance)
+    }
+
+    /// Tokenizes a term, recording `distance` as an override if the term is a single token
+    /// matched with other than the configured distance.
+    ///
+    /// Terms that split into several tokens match as exact phrases, so no override applies.
+    fn analyze_term(
+        &mut self,
+        text: &str,
+        distance: u8,
+        compiled: &mut CompiledQuery,
+    ) -> Vec<String> {
+        let tokens = self.tokenize(text);
+        if let [token] = tokens.as_slice()
+            && distance != self.fuzzy_distance
+        {
+            compiled.fuzzy_overrides.insert(token.clone(), distance);
+        }
+        tokens
     }
 
     /// Compiles a phrase into multi-field phrase queries with boosts.
     fn compile_phrase(&mut self, words: &[String]) -> Option<Box<dyn Query>> {
-        let tokens: Vec<String> = words.iter().flat_map(|w| self.tokenize(w)).collect();
+        let tokens = self.tokenize_words(words);
         self.compile_phrase_from_tokens(&tokens)
     }
 
+    /// Returns the token sequences matching `left NEAR/n right`: both operands in either
+    /// order, so that a phrase query with slop `n` allows up to `n` words between them.
+    fn near_sequences(
+        &mut self,
+        left: &QueryExpr,
+        right: &QueryExpr,
+    ) -> Result<Vec<Vec<String>>, CompileError> {
+        let left = self.near_operand_tokens(left)?;
+        let right = self.near_operand_tokens(right)?;
+        if left.is_empty() || right.is_empty() {
+            // An operand made only of stopwords leaves a plain phrase
+            return Ok(vec![[left, right].concat()]);
+        }
+        Ok(vec![
+            [left.clone(), right.clone()].concat(),
+            [right, left].concat(),
+        ])
+    }
+
+    /// Tokenizes a NEAR operand, which must be a term or phrase.
+    fn near_operand_tokens(&mut self, expr: &QueryExpr) -> Result<Vec<String>, CompileError> {
+        match expr {
+            QueryExpr::Term(text) => Ok(self.tokenize(text)),
+            QueryExpr::Phrase(words) => Ok(self.tokenize_words(words)),
+            _ => Err(CompileError {
+                message: "NEAR operands must be terms or phrases".into(),
+            }),
+        }
+    }
+
+    /// Builds phrase queries with slop for each token sequence in each field.
+    ///
+    /// Sequences of a single token become plain term queries. Returns `None` if every
+    /// sequence is empty.
+    fn build_proximity_query(
+        &self,
+        fields: &[(Field, f32)],
+        sequences: &[Vec<String>],
+        slop: u32,
+    ) -> Option<Box<dyn Query>> {
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        for &(field, boost_value) in fields {
+            for tokens in sequences {
+                let query: Box<dyn Query> = match tokens.as_slice() {
+                    [] => continue,
+                    [token] => self.build_field_term_query(field, token, self.fuzzy_distance),
+                    _ => {
+                        let terms = tokens
+                            .iter()
+                            .map(|t| Term::from_field_text(field, t))
+                            .collect();
+                        let mut phrase_query = PhraseQuery::new(terms);
+                        phrase_query.set_slop(slop);
+                        Box::new(phrase_query)
+                    }
+                };
+                clauses.push((Occur::Should, Box::new(BoostQuery::new(query, boost_value))));
+            }
+        }
+
+        if clauses.is_empty() {
+            None
+        } else {
+            Some(Box::new(BooleanQuery::new(clauses)))
+        }
+    }
+
     /// Compiles pre-tokenized terms into phrase queries.
     fn compile_phrase_from_tokens(&self, tokens: &[String]) -> Option<Box<dyn Query>> {
         if tokens.is_empty() {
@@ -119,7 +486,7 @@ impl QueryCompiler {
         }
 
         if tokens.len() == 1 {
-            return self.build_multi_field_term_query(&tokens[0]);
+            return self.build_multi_field_term_query(&tokens[0], self.fuzzy_distance);
         }
 
         let clauses: Vec<(Occur, Box<dyn Query>)> = self
@@ -144,9 +511,13 @@ impl QueryCompiler {
     ///
     /// NOT alone is invalid (nothing to exclude from). This is handled at the
     /// And level where we separate positive and negative clauses.
-    fn compile_not(&mut self, inner: &QueryExpr) -> Result<Option<Box<dyn Query>>, CompileError> {
+    fn compile_not(
+        &mut self,
+        inner: &QueryExpr,
+        compiled: &mut CompiledQuery,
+    ) -> Result<Option<Box<dyn Query>>, CompileError> {
         // A standalone NOT requires an AllQuery to exclude from
-        let inner_query = self.compile(inner)?;
+        let inner_query = self.compile_expr(inner, compiled)?;
         match inner_query {
             Some(q) => {
                 // Wrap in a boolean with AllQuery MUST and inner MUST_NOT
@@ -165,7 +536,11 @@ impl QueryCompiler {
     /// Separates positive and negative clauses. Negative clauses (NOT) become
     /// MUST_NOT in the boolean query. If all clauses are negative, we use
     /// AllQuery as the base to exclude from.
-    fn compile_and(&mut self, exprs: &[QueryExpr]) -> Result<Option<Box<dyn Query>>, CompileError> {
+    fn compile_and(
+        &mut self,
+        exprs: &[QueryExpr],
+        compiled: &mut CompiledQuery,
+    ) -> Result<Option<Box<dyn Query>>, CompileError> {
         if exprs.is_empty() {
             return Ok(None);
         }
@@ -176,12 +551,12 @@ impl QueryCompiler {
         for expr in exprs {
             match expr {
                 QueryExpr::Not(inner) => {
-                    if let Some(q) = self.compile(inner)? {
+                    if let Some(q) = self.compile_expr(inner, compiled)? {
                         negative_clauses.push(q);
                     }
                 }
                 other => {
-                    if let Some(q) = self.compile(other)? {
+                    if let Some(q) = self.compile_expr(other, compiled)? {
                         positive_clauses.push(q);
                     }
                 }
@@ -213,7 +588,11 @@ impl QueryCompiler {
     }
 
     /// Compiles an OR expression.
-    fn compile_or(&mut self, exprs: &[QueryExpr]) -> Result<Option<Box<dyn Query>>, CompileError> {
+    fn compile_or(
+        &mut self,
+        exprs: &[QueryExpr],
+        compiled: &mut CompiledQuery,
+    ) -> Result<Option<Box<dyn Query>>, CompileError> {
         if exprs.is_empty() {
             return Ok(None);
         }
@@ -221,7 +600,7 @@ impl QueryCompiler {
         let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
 
         for expr in exprs {
-            if let Some(q) = self.compile(expr)? {
+            if let Some(q) = self.compile_expr(expr, compiled)? {
                 clauses.push((Occur::Should, q));
             }
         }
@@ -240,20 +619,87 @@ impl QueryCompiler {
         &mut self,
         name: &str,
         expr: &QueryExpr,
+        compiled: &mut CompiledQuery,
     ) -> Result<Option<Box<dyn Query>>, CompileError> {
         match name {
             // "title" is an alias for "hierarchy" for backwards compatibility
-            "title" | "hierarchy" => {
-                self.compile_single_field_query(self.schema.hierarchy, self.boosts.hierarchy, expr)
+            "title" | "hierarchy" => self.compile_single_field_query(
+                self.schema.hierarchy,
+                self.boosts.hierarchy,
+                expr,
+                compiled,
+            ),
+            "tags" => {
+                self.compile_single_field_query(self.schema.tags, self.boosts.tags, expr, compiled)
             }
-            "tags" => self.compile_single_field_query(self.schema.tags, self.boosts.tags, expr),
-            "body" => self.compile_single_field_query(self.schema.body, self.boosts.body, expr),
-            "path" => self.compile_single_field_query(self.schema.path, self.boosts.path, expr),
-            "tree" => self.compile_tree_query(expr),
-            _ => Err(CompileError {
-                message: format!("unknown field: {}", name),
-            }),
+            "body" => {
+                self.compile_single_field_query(self.schema.body, self.boosts.body, expr, compiled)
+            }
+            "path" => {
+                self.compile_single_field_query(self.schema.path, self.boosts.path, expr, compiled)
+            }
+            "tree" => self.compile_keyword_query("tree", self.schema.tree, expr),
+            "tag" => self.compile_keyword_query("tag", self.schema.tag, expr),
+            _ => match name.strip_prefix("meta.") {
+                Some(key) if !key.is_empty() => self.compile_meta_query(name, key, expr),
+                _ => Err(CompileError {
+                    message: format!("unknown field: {}", name),
+                }),
+            },
+        }
+    }
+
+    /// Compiles an exact-match query on one key of the frontmatter metadata field.
+    ///
+    /// A value matches the key's string values exactly, and also its number or boolean values
+    /// when it reads as one, so `meta.version:2` matches `version: 2`.
+    fn compile_meta_query(
+        &self,
+        name: &str,
+        key: &str,
+        expr: &QueryExpr,
+    ) -> Result<Option<Box<dyn Query>>, CompileError> {
+        let value = match expr {
+            QueryExpr::Term(text) => text.clone(),
+            QueryExpr::Phrase(words) => words.join(" "),
+            QueryExpr::Or(exprs) => {
+                let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+                for e in exprs {
+                    if let Some(q) = self.compile_meta_query(name, key, e)? {
+                        clauses.push((Occur::Should, q));
+                    }
+                }
+                return Ok(Some(Box::new(BooleanQuery::new(clauses))));
+            }
+            _ => {
+                return Err(CompileError {
+                    message: format!("{name}: only supports terms, phrases, or OR of them"),
+                });
+            }
+        };
+
+        let mut term = Term::from_field_text(self.schema.meta, "");
+        let mut writer = JsonTermWriter::wrap(&mut term, false);
+        writer.push_path_segment(key);
+        let mut terms = vec![];
+        if let Some(typed) = convert_to_fast_value_and_get_term(&mut writer, &value) {
+            terms.push(typed);
         }
+        writer.set_str(&value);
+        terms.push(writer.term().clone());
+
+        let clauses: Vec<(Occur, Box<dyn Query>)> = terms
+            .into_iter()
+            .map(|term| {
+                let query: Box<dyn Query> =
+                    Box::new(TermQuery::new(term, IndexRecordOption::Basic));
+                (Occur::Should, query)
+            })
+            .collect();
+        Ok(Some(Box::new(ConstScoreQuery::new(
+            Box::new(BooleanQuery::new(clauses)),
+            1.0,
+        ))))
     }
 
     /// Compiles a query for a single text field.
@@ -262,26 +708,55 @@ impl QueryCompiler {
         field: Field,
         boost_value: f32,
         expr: &QueryExpr,
+        compiled: &mut CompiledQuery,
     ) -> Result<Option<Box<dyn Query>>, CompileError> {
         match expr {
             QueryExpr::Term(text) => {
-                let tokens = self.tokenize(text);
-                if tokens.is_empty() {
-                    return Ok(None);
-                }
-                if tokens.len() > 1 {
-                    return self.compile_single_field_phrase(field, boost_value, &tokens);
-                }
-                Ok(self.build_single_field_term_query(field, boost_value, &tokens[0]))
+                self.compile_with_synonyms(text, compiled, |compiler, compiled, word| {
+                    compiler.compile_single_field_term(
+                        field,
+                        boost_value,
+                        word,
+                        compiler.fuzzy_distance,
+                        compiled,
+                    )
+                })
+            }
+            QueryExpr::Fuzzy { term, distance } => {
+                self.compile_single_field_term(field, boost_value, term, *distance, compiled)
             }
             QueryExpr::Phrase(words) => {
-                let tokens: Vec<String> = words.iter().flat_map(|w| self.tokenize(w)).collect();
+                let tokens = self.tokenize_words(words);
                 self.compile_single_field_phrase(field, boost_value, &tokens)
             }
+            QueryExpr::Wildcard(pattern) => {
+                self.compile_wildcard(&[(field, boost_value)], pattern, compiled)
+            }
+            QueryExpr::Regex(pattern) => {
+                let query = StoredRegexQuery::new(field, pattern)?;
+                Ok(Some(Box::new(BoostQuery::new(
+                    Box::new(query),
+                    boost_value,
+                ))))
+            }
+            QueryExpr::Proximity { words, slop } => {
+                let tokens = self.tokenize_words(words);
+                Ok(self.build_proximity_query(&[(field, boost_value)], &[tokens], *slop))
+            }
+            QueryExpr::Near {
+                left,
+                right,
+                distance,
+            } => {
+                let sequences = self.near_sequences(left, right)?;
+                Ok(self.build_proximity_query(&[(field, boost_value)], &sequences, *distance))
+            }
             QueryExpr::Or(exprs) => {
                 let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
                 for e in exprs {
-                    if let Some(q) = self.compile_single_field_query(field, boost_value, e)? {
+                    if let Some(q) =
+                        self.compile_single_field_query(field, boost_value, e, compiled)?
+                    {
                         clauses.push((Occur::Should, q));
                     }
                 }
@@ -294,7 +769,9 @@ impl QueryCompiler {
             QueryExpr::And(exprs) => {
                 let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
                 for e in exprs {
-                    if let Some(q) = self.compile_single_field_query(field, boost_value, e)? {
+                    if let Some(q) =
+                        self.compile_single_field_query(field, boost_value, e, compiled)?
+                    {
                         clauses.push((Occur::Must, q));
                     }
                 }
@@ -305,7 +782,9 @@ impl QueryCompiler {
                 }
             }
             QueryExpr::Not(inner) => {
-                if let Some(q) = self.compile_single_field_query(field, boost_value, inner)? {
+                if let Some(q) =
+                    self.compile_single_field_query(field, boost_value, inner, compiled)?
+                {
                     let clauses = vec![
                         (Occur::Must, Box::new(AllQuery) as Box<dyn Query>),
                         (Occur::MustNot, q),
@@ -315,15 +794,19 @@ impl QueryCompiler {
                     Ok(None)
                 }
             }
-            QueryExpr::Field { .. } => Err(CompileError {
-                message: "nested field queries not supported".into(),
-            }),
+            QueryExpr::Field { .. } | QueryExpr::Modified(_) | QueryExpr::Filter(_) => {
+                Err(CompileError {
+                    message: "nested field queries not supported".into(),
+                })
+            }
             QueryExpr::Boost {
                 expr: inner,
                 factor,
             } => {
                 // Apply both the field boost and the explicit boost
-                if let Some(q) = self.compile_single_field_query(field, boost_value, inner)? {
+                if let Some(q) =
+                    self.compile_single_field_query(field, boost_value, inner, compiled)?
+                {
                     Ok(Some(Box::new(BoostQuery::new(q, *factor))))
                 } else {
                     Ok(None)
@@ -332,6 +815,25 @@ impl QueryCompiler {
         }
     }
 
+    /// Compiles a term query for a single field, matching within `distance` edits.
+    fn compile_single_field_term(
+        &mut self,
+        field: Field,
+        boost_value: f32,
+        text: &str,
+        distance: u8,
+        compiled: &mut CompiledQuery,
+    ) -> Result<Option<Box<dyn Query>>, CompileError> {
+        let tokens = self.analyze_term(text, distance, compiled);
+        if tokens.is_empty() {
+            return Ok(None);
+        }
+        if tokens.len() > 1 {
+            return self.compile_single_field_phrase(field, boost_value, &tokens);
+        }
+        Ok(self.build_single_field_term_query(field, boost_value, &tokens[0], distance))
+    }
+
     /// Compiles a phrase query for a single field.
     fn compile_single_field_phrase(
         &self,
@@ -343,7 +845,12 @@ impl QueryCompiler {
             return Ok(None);
         }
         if tokens.len() == 1 {
-            return Ok(self.build_single_field_term_query(field, boost_value, &tokens[0]));
+            return Ok(self.build_single_field_term_query(
+                field,
+                boost_value,
+                &tokens[0],
+                self.fuzzy_distance,
+            ));
         }
 
         let terms: Vec<Term> = tokens
@@ -356,25 +863,28 @@ impl QueryCompiler {
         Ok(Some(boosted))
     }
 
-    /// Compiles a tree filter query.
+    /// Compiles an exact-match query on a keyword field such as `tree` or `tag`.
     ///
-    /// The tree field is STRING (not tokenized), so we use exact matching.
-    fn compile_tree_query(
+    /// Keyword fields are STRING (not tokenized), so we use exact matching.
+    fn compile_keyword_query(
         &mut self,
+        name: &str,
+        field: Field,
         expr: &QueryExpr,
     ) -> Result<Option<Box<dyn Query>>, CompileError> {
         match expr {
             QueryExpr::Term(text) => {
-                // Tree field uses raw tokenizer, so no stemming/lowercasing
-                let term = Term::from_field_text(self.schema.tree, text);
+                // Keyword fields use the raw tokenizer, so no stemming/lowercasing
+                let term = Term::from_field_text(field, text);
                 let query: Box<dyn Query> =
                     Box::new(TermQuery::new(term, IndexRecordOption::Basic));
                 Ok(Some(query))
             }
+            QueryExpr::Regex(pattern) => Ok(Some(Box::new(StoredRegexQuery::new(field, pattern)?))),
             QueryExpr::Or(exprs) => {
                 let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
                 for e in exprs {
-                    if let Some(q) = self.compile_tree_query(e)? {
+                    if let Some(q) = self.compile_keyword_query(name, field, e)? {
                         clauses.push((Occur::Should, q));
                     }
                 }
@@ -385,18 +895,22 @@ impl QueryCompiler {
                 }
             }
             _ => Err(CompileError {
-                message: "tree: only supports terms or OR of terms".into(),
+                message: format!("{name}: only supports terms, regexes, or OR of them"),
             }),
         }
     }
 
-    /// Builds a multi-field term query with boosts and optional fuzzy matching.
-    fn build_multi_field_term_query(&self, term_text: &str) -> Option<Box<dyn Query>> {
+    /// Builds a multi-field term query with boosts, fuzzy within `distance` edits.
+    fn build_multi_field_term_query(
+        &self,
+        term_text: &str,
+        distance: u8,
+    ) -> Option<Box<dyn Query>> {
         let clauses: Vec<(Occur, Box<dyn Query>)> = self
             .fields_with_boosts()
             .into_iter()
             .map(|(field, boost_value)| {
-                let query = self.build_field_term_query(field, term_text);
+                let query = self.build_field_term_query(field, term_text, distance);
                 let boosted: Box<dyn Query> = Box::new(BoostQuery::new(query, boost_value));
                 (Occur::Should, boosted)
             })
@@ -405,14 +919,15 @@ impl QueryCompiler {
         Some(Box::new(BooleanQuery::new(clauses)))
     }
 
-    /// Builds a single-field term query with boost and optional fuzzy matching.
+    /// Builds a single-field term query with boost, fuzzy within `distance` edits.
     fn build_single_field_term_query(
         &self,
         field: Field,
         boost_value: f32,
         term_text: &str,
+        distance: u8,
     ) -> Option<Box<dyn Query>> {
-        let query = self.build_field_term_query(field, term_text);
+        let query = self.build_field_term_query(field, term_text, distance);
         let boosted: Box<dyn Query> = Box::new(BoostQuery::new(query, boost_value));
         Some(boosted)
     }
@@ -427,11 +942,25 @@ impl QueryCompiler {
         ]
     }
 
-    /// Builds a term query for a specific field, applying fuzzy matching if configured.
-    fn build_field_term_query(&self, field: Field, term_text: &str) -> Box<dyn Query> {
+    /// Returns the fields with positions used for proximity queries, with their boost
+    /// weights.
+    fn positional_fields_with_boosts(&self) -> [(Field, f32); 2] {
+        [
+            (self.schema.hierarchy, self.boosts.hierarchy),
+            (self.schema.body, self.boosts.body),
+        ]
+    }
+
+    /// Builds a term query for a specific field, fuzzy within `distance` edits if non-zero.
+    fn build_field_term_query(
+        &self,
+        field: Field,
+        term_text: &str,
+        distance: u8,
+    ) -> Box<dyn Query> {
         let term = Term::from_field_text(field, term_text);
-        if self.fuzzy_distance > 0 {
-            Box::new(FuzzyTermQuery::new(term, self.fuzzy_distance, true))
+        if distance > 0 {
+            Box::new(FuzzyTermQuery::new(term, distance, true))
         } else {
             Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs))
         }
@@ -446,6 +975,11 @@ impl QueryCompiler {
         }
         tokens
     }
+
+    /// Tokenizes each word of a phrase, concatenating the tokens.
+    fn tokenize_words(&mut self, words: &[String]) -> Vec<String> {
+        words.iter().flat_map(|w| self.tokenize(w)).collect()
+    }
 }
 
 #[cfg(test)]
@@ -459,7 +993,7 @@ mod tests {
         let mut compiler =
             QueryCompiler::new(schema, "english", 0, FieldBoosts::default()).unwrap();
         let expr = parse(input).unwrap()?;
-        compiler.compile(&expr).unwrap()
+        compiler.compile(&expr).unwrap().query
     }
 
     fn compile_query_fuzzy(input: &str, fuzzy: u8) -> Option<Box<dyn Query>> {
@@ -467,7 +1001,7 @@ mod tests {
         let mut compiler =
             QueryCompiler::new(schema, "english", fuzzy, FieldBoosts::default()).unwrap();
         let expr = parse(input).unwrap()?;
-        compiler.compile(&expr).unwrap()
+        compiler.compile(&expr).unwrap().query
     }
 
     #[test]
@@ -555,6 +1089,41 @@ mod tests {
         assert!(q.is_some());
     }
 
+    #[test]
+    fn field_tag() {
+        assert!(compile_query("tag:rust-async").is_some());
+        assert!(compile_query("tag:(rust OR go)").is_some());
+        assert!(compile_query("tag:/rust-.*/").is_some());
+    }
+
+    #[test]
+    fn field_tag_rejects_phrases() {
+        let schema = IndexSchema::new();
+        let mut compiler =
+            QueryCompiler::new(schema, "english", 0, FieldBoosts::default()).unwrap();
+        let expr = parse("tag:\"rust async\"").unwrap().unwrap();
+        let err = compiler.compile(&expr).unwrap_err();
+        assert!(err.message.starts_with("tag:"));
+    }
+
+    #[test]
+    fn field_meta() {
+        assert!(compile_query("meta.status:approved").is_some());
+        assert!(compile_query("meta.version:2").is_some());
+        assert!(compile_query("meta.owner:\"Jane Doe\"").is_some());
+        assert!(compile_query("meta.status:(approved OR review)").is_some());
+
+        let schema = IndexSchema::new();
+        let mut compiler =
+            QueryCompiler::new(schema, "english", 0, FieldBoosts::default()).unwrap();
+        let expr = parse("meta.status:/draft/").unwrap().unwrap();
+        let err = compiler.compile(&expr).unwrap_err();
+        assert!(err.message.starts_with("meta.status:"));
+        let expr = parse("meta.:draft").unwrap().unwrap();
+        let err = compiler.compile(&expr).unwrap_err();
+        assert!(err.message.contains("unknown field"));
+    }
+
     #[test]
     fn unknown_field_error() {
         let schema = IndexSchema::new();
@@ -632,6 +1201,114 @@ mod tests {
         assert!(q.is_some());
     }
 
+    #[test]
+    fn proximity_queries() {
+        for query in [
+            "\"token refresh\"~5",
+            "\"refresh\"~5",
+            "retry NEAR/3 backoff",
+            "\"token refresh\" NEAR/2 expiry",
+            "body:(retry NEAR/3 backoff)",
+            "title:\"getting started\"~2",
+        ] {
+            assert!(compile_query(query).is_some(), "{query}");
+        }
+    }
+
+    #[test]
+    fn wildcard_without_index_falls_back_to_literal() {
+        let schema = IndexSchema::new();
+        let mut compiler =
+            QueryCompiler::new(schema, "english", 0, FieldBoosts::default()).unwrap();
+        let expr = parse("auth* title:config?").unwrap().unwrap();
+        let compiled = compiler.compile(&expr).unwrap();
+        assert!(compiled.query.is_some());
+
+        let patterns: Vec<&str> = compiled
+            .expansions
+            .iter()
+            .map(|e| e.pattern.as_str())
+            .collect();
+        assert_eq!(patterns, ["auth*", "config?"]);
+        assert!(compiled.expansions.iter().all(|e| e.terms.is_empty()));
+    }
+
+    #[test]
+    fn wildcard_with_several_wild_words_is_error() {
+        let schema = IndexSchema::new();
+        let mut compiler =
+            QueryCompiler::new(schema, "english", 0, FieldBoosts::default()).unwrap();
+        let expr = parse("a*_b?").unwrap().unwrap();
+        assert!(compiler.compile(&expr).is_err());
+    }
+
+    #[test]
+    fn modified_filters() {
+        for query in [
+            "modified:>2025-01-01",
+            "modified:2025-01..2025-03",
+            "modified:<30d",
+            "auth modified:..2024 -modified:2023",
+        ] {
+            assert!(compile_query(query).is_some(), "{query}");
+        }
+    }
+
+    #[test]
+    fn modified_in_field_is_error() {
+        let schema = IndexSchema::new();
+        let mut compiler =
+            QueryCompiler::new(schema, "english", 0, FieldBoosts::default()).unwrap();
+        let expr = parse("title:(guide modified:<1d)").unwrap().unwrap();
+        assert!(compiler.compile(&expr).is_err());
+    }
+
+    #[test]
+    fn per_term_fuzziness() {
+        assert!(compile_query_fuzzy("kubernetes~2 pod~0 title:api~0", 1).is_some());
+
+        let schema = IndexSchema::new();
+        let mut compiler =
+            QueryCompiler::new(schema, "english", 1, FieldBoosts::default()).unwrap();
+        let expr = parse("kubernetes~2 pod~1 body:api~0 max_conn~0")
+            .unwrap()
+            .unwrap();
+        let overrides = compiler.compile(&expr).unwrap().fuzzy_overrides;
+        assert_eq!(overrides.get("kubernet"), Some(&2));
+        assert_eq!(overrides.get("pod"), None);
+        assert_eq!(overrides.get("api"), Some(&0));
+        // Multi-token terms match as exact phrases, so record no override.
+        assert_eq!(overrides.get("max"), None);
+    }
+
+    #[test]
+    fn regex_queries() {
+        assert!(compile_query(r"body:/retr(y|ies)\s+policy/").is_some());
+        assert!(compile_query(r"rust path:/^api\/v[0-9]/^2").is_some());
+        assert!(compile_query("tree:/^doc/ OR tree:local").is_some());
+
+        let schema = IndexSchema::new();
+        let mut compiler =
+            QueryCompiler::new(schema, "english", 0, FieldBoosts::default()).unwrap();
+        let err = compiler
+            .compile(&QueryExpr::Regex("retry".into()))
+            .unwrap_err();
+        assert!(err.message.contains("must be scoped to a field"));
+    }
+
+    #[test]
+    fn chunk_filters() {
+        assert!(compile_query("depth:<=2").is_some());
+        assert!(compile_query("auth doc:docs:api/auth.md -id:docs:api/auth.md#intro").is_some());
+        assert!(compile_query("root:true OR root:false").is_some());
+
+        let schema = IndexSchema::new();
+        let mut compiler =
+            QueryCompiler::new(schema, "english", 0, FieldBoosts::default()).unwrap();
+        let expr = parse("body:(auth depth:1)").unwrap().unwrap();
+        assert!(compiler.compile(&expr).is_err());
+    }
+
     #[test]
     fn boosted_in_complex_query() {
         let q = compile_query("title:guide^2.0 (rust^3.0 OR golang^2.5) -deprecated");
@@ -4,11 +4,17 @@
 //!
 //! - **Terms**: `rust` - words that must appear
 //! - **Phrases**: `"error handling"` - exact sequences
+//! - **Fuzziness**: `kubernetes~2`, `api~0` - per-term edit distance
+//! - **Proximity**: `"token refresh"~5`, `retry NEAR/3 backoff` - words close together
 //! - **Negation**: `-deprecated` - terms that must NOT appear
 //! - **OR**: `rust OR golang` - alternatives
 //! - **Grouping**: `(a b) OR (c d)` - precedence control
 //! - **Fields**: `title:guide` - search specific fields
+//! - **Wildcards**: `auth*`, `config?` - expanded against the term dictionary
+//! - **Synonyms**: `k8s` also searching `kubernetes` - expanded from configuration
+//! - **Regexes**: `body:/retr(y|ies)/` - match a field's text
 //! - **Boosting**: `rust^2.5` - adjust term importance
+//! - **Dates**: `modified:<30d` - filter by modification time
 //!
 //! # Example
 //!
@@ -19,10 +25,18 @@
 //! ```
 
 mod compile;
+mod regex;
+mod synonyms;
+mod wildcard;
 
 // Re-export query types from ra-query
-pub use compile::{CompileError, QueryCompiler};
-pub use ra_query::{QueryError, QueryErrorKind, QueryExpr, parse};
+pub use compile::{CompileError, CompiledQuery, QueryCompiler};
+pub use ra_query::{
+    LenientParse, QueryError, QueryErrorKind, QueryExpr, parse, parse_json, parse_lenient,
+    parse_with_macros,
+};
+pub use synonyms::{SYNONYM_WEIGHT, SynonymExpansion};
+pub use wildcard::{MAX_EXPANSIONS, TermExpansion};
 
 impl From<CompileError> for QueryError {
     fn from(err: CompileError) -> Self {
@@ -0,0 +1,106 @@
+//! Regular-expression queries over stored field text.
+//!
+//! Indexed terms are lowercased and stemmed, and a pattern such as `retr(y|ies)\s+policy`
+//! spans several of them, so matching against the term dictionary would not find what a
+//! reader sees in the document. Instead a regex is matched against the stored text of each
+//! chunk. Every regex query scans and decompresses the whole document store of each segment,
+//! whatever the other clauses of the query match, so its cost grows with the size of the index.
+
+use std::fmt;
+
+use regex::Regex;
+use tantivy::{
+    DocId, Score, SegmentReader, TantivyDocument, TantivyError,
+    query::{BitSetDocSet, ConstScorer, EnableScoring, Explanation, Query, Scorer, Weight},
+    schema::{Field, Value},
+};
+use tantivy_common::BitSet;
+
+use super::compile::CompileError;
+
+/// Number of document store blocks cached while scanning a segment. Documents are read in
+/// order, so one block at a time is enough.
+const STORE_CACHE_BLOCKS: usize = 1;
+
+/// Matches documents where any stored value of a text field matches a regex.
+///
+/// Every match scores the same.
+#[derive(Clone)]
+pub(super) struct StoredRegexQuery {
+    /// The stored text field to match.
+    field: Field,
+    /// The compiled pattern.
+    regex: Regex,
+}
+
+impl StoredRegexQuery {
+    /// Compiles `pattern` into a query on `field`.
+    pub fn new(field: Field, pattern: &str) -> Result<Self, CompileError> {
+        let regex = Regex::new(pattern).map_err(|e| CompileError {
+            message: format!("invalid regex /{pattern}/: {e}"),
+        })?;
+        Ok(Self { field, regex })
+    }
+
+    /// Returns true if any stored value of the field in `doc` matches.
+    fn matches(&self, doc: &TantivyDocument) -> bool {
+        doc.get_all(self.field)
+            .filter_map(|value| value.as_str())
+            .any(|text| self.regex.is_match(text))
+    }
+}
+
+impl fmt::Debug for StoredRegexQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StoredRegexQuery")
+            .field("field", &self.field)
+            .field("regex", &self.regex.as_str())
+            .finish()
+    }
+}
+
+impl Query for StoredRegexQuery {
+    fn weight(&self, _: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        Ok(Box::new(self.clone()))
+    }
+}
+
+impl Weight for StoredRegexQuery {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        let store = reader.get_store_reader(STORE_CACHE_BLOCKS)?;
+        let mut matching = BitSet::with_max_value(reader.max_doc());
+        for doc_id in reader.doc_ids_alive() {
+            let doc: TantivyDocument = store.get(doc_id)?;
+            if self.matches(&doc) {
+                matching.insert(doc_id);
+            }
+        }
+        let docs = BitSetDocSet::from(matching);
+        Ok(Box::new(ConstScorer::new(docs, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let store = reader.get_store_reader(STORE_CACHE_BLOCKS)?;
+        let stored: TantivyDocument = store.get(doc)?;
+        if !self.matches(&stored) {
+            return Err(TantivyError::InvalidArgument(format!(
+                "document {doc} does not match /{}/",
+                self.regex.as_str()
+            )));
+        }
+        Ok(Explanation::new("StoredRegexQuery", 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_pattern_is_error() {
+        let field = Field::from_field_id(0);
+        let err = StoredRegexQuery::new(field, "retr(y").unwrap_err();
+        assert!(err.message.contains("invalid regex /retr(y/"));
+        assert!(StoredRegexQuery::new(field, r"retr(y|ies)\s+policy").is_ok());
+    }
+}
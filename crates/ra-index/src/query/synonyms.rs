@@ -0,0 +1,160 @@
+//! Query-time synonym expansion.
+//!
+//! A query term with configured synonyms matches as an OR of the term and its synonyms, such
+//! as `k8s` searching for `kubernetes` too. Synonyms weigh [`SYNONYM_WEIGHT`] times the term
+//! as written, so documents using the query's own vocabulary rank first. Synonyms from a
+//! tree's synonym file only match that tree's documents.
+//!
+//! Terms are looked up as written, ignoring case, before stemming.
+
+use std::collections::HashMap;
+
+use ra_config::{Synonyms, Tree};
+use serde::Serialize;
+
+/// Weight of a synonym relative to the term it expands.
+pub const SYNONYM_WEIGHT: f32 = 0.5;
+
+/// The synonyms a query term expanded to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SynonymExpansion {
+    /// The term as written in the query.
+    pub term: String,
+    /// The synonyms searched for alongside it.
+    pub synonyms: Vec<String>,
+    /// The tree whose documents the synonyms apply to, or `None` for every tree.
+    pub tree: Option<String>,
+}
+
+/// Synonyms from configuration, keyed by lowercased term.
+#[derive(Debug, Clone, Default)]
+pub(super) struct SynonymTable {
+    /// Synonyms that apply to every tree.
+    global: HashMap<String, Vec<String>>,
+    /// Synonyms that apply to one tree's documents, by tree name.
+    trees: Vec<(String, HashMap<String, Vec<String>>)>,
+}
+
+impl SynonymTable {
+    /// Replaces the synonyms that apply to every tree.
+    pub fn set_global(&mut self, synonyms: &Synonyms) {
+        self.global = lowercase_keys(synonyms);
+    }
+
+    /// Replaces the per-tree synonyms with those loaded for `trees`.
+    pub fn set_trees(&mut self, trees: &[Tree]) {
+        self.trees = trees
+            .iter()
+            .filter(|tree| !tree.synonyms.is_empty())
+            .map(|tree| (tree.name.clone(), lowercase_keys(&tree.synonyms)))
+            .collect();
+    }
+
+    /// Returns the expansions of `term`: global synonyms first, then each tree's synonyms
+    /// that are not already global. Synonyms equal to the term itself are skipped.
+    pub fn lookup(&self, term: &str) -> Vec<SynonymExpansion> {
+        let key = term.to_lowercase();
+        let global = self.global.get(&key);
+        let is_new = |synonym: &&String| {
+            !synonym.eq_ignore_ascii_case(term) && global.is_none_or(|g| !g.contains(synonym))
+        };
+
+        let mut expansions = Vec::new();
+        if let Some(synonyms) = global {
+            let synonyms: Vec<String> = synonyms
+                .iter()
+                .filter(|s| !s.eq_ignore_ascii_case(term))
+                .cloned()
+                .collect();
+            if !synonyms.is_empty() {
+                expansions.push(SynonymExpansion {
+                    term: term.to_string(),
+                    synonyms,
+                    tree: None,
+                });
+            }
+        }
+        for (tree, table) in &self.trees {
+            let Some(synonyms) = table.get(&key) else {
+                continue;
+            };
+            let synonyms: Vec<String> = synonyms.iter().filter(is_new).cloned().collect();
+            if !synonyms.is_empty() {
+                expansions.push(SynonymExpansion {
+                    term: term.to_string(),
+                    synonyms,
+                    tree: Some(tree.clone()),
+                });
+            }
+        }
+        expansions
+    }
+}
+
+/// Copies a synonym table with its terms lowercased.
+fn lowercase_keys(synonyms: &Synonyms) -> HashMap<String, Vec<String>> {
+    let mut table: HashMap<String, Vec<String>> = HashMap::new();
+    for (term, expansions) in synonyms {
+        table
+            .entry(term.to_lowercase())
+            .or_default()
+            .extend(expansions.iter().cloned());
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn synonyms(entries: &[(&str, &[&str])]) -> Synonyms {
+        entries
+            .iter()
+            .map(|(term, expansions)| {
+                let expansions = expansions.iter().map(|s| s.to_string()).collect();
+                (term.to_string(), expansions)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn looks_up_global_and_tree_synonyms() {
+        let mut table = SynonymTable::default();
+        table.set_global(&synonyms(&[("K8s", &["kubernetes"])]));
+        table.set_trees(&[Tree {
+            name: "ops".into(),
+            path: PathBuf::from("/tmp/ops"),
+            is_global: false,
+            include: vec![],
+            exclude: vec![],
+            synonyms: synonyms(&[("k8s", &["kubernetes", "kube"]), ("authn", &["login"])]),
+        }]);
+
+        assert_eq!(
+            table.lookup("k8s"),
+            vec![
+                SynonymExpansion {
+                    term: "k8s".into(),
+                    synonyms: vec!["kubernetes".into()],
+                    tree: None,
+                },
+                SynonymExpansion {
+                    term: "k8s".into(),
+                    synonyms: vec!["kube".into()],
+                    tree: Some("ops".into()),
+                },
+            ]
+        );
+        assert_eq!(table.lookup("AuthN")[0].synonyms, vec!["login"]);
+        assert!(table.lookup("rust").is_empty());
+    }
+
+    #[test]
+    fn skips_synonyms_equal_to_term() {
+        let mut table = SynonymTable::default();
+        table.set_global(&synonyms(&[("api", &["API"])]));
+        assert!(table.lookup("api").is_empty());
+    }
+}
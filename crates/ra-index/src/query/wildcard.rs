@@ -0,0 +1,224 @@
+//! Wildcard term expansion.
+//!
+//! A wildcard term such as `auth*` or `config?` is expanded against the index's term
+//! dictionary into the concrete terms it matches. Expansion is capped at
+//! [`MAX_EXPANSIONS`] terms per pattern, keeping the terms that appear in the most chunks.
+//!
+//! Patterns are matched against indexed terms, which are lowercased and stemmed, so the
+//! literal parts of a pattern are lowercased but not stemmed.
+
+use std::{collections::HashMap, str};
+
+use serde::Serialize;
+use tantivy::{Searcher as TvSearcher, schema::Field};
+
+use super::compile::CompileError;
+
+/// Maximum number of terms a single wildcard pattern expands to.
+pub const MAX_EXPANSIONS: usize = 50;
+
+/// The index terms a wildcard pattern expanded to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TermExpansion {
+    /// The wildcard term as written in the query.
+    pub pattern: String,
+    /// Matching index terms, most frequent first.
+    pub terms: Vec<String>,
+    /// True if more terms matched than [`MAX_EXPANSIONS`].
+    pub truncated: bool,
+}
+
+/// A wildcard term split into words, the way the analyzer splits text.
+///
+/// `max_conn*` becomes the literal word `max` followed by the wildcard word `conn*`, and
+/// matches as a phrase. Words made only of `*` match any word, so they are dropped.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct WildcardWords<'a> {
+    /// Literal words before the wildcard word.
+    pub before: Vec<&'a str>,
+    /// The lowercased word containing wildcards, if any remains.
+    pub wildcard: Option<String>,
+    /// Literal words after the wildcard word.
+    pub after: Vec<&'a str>,
+}
+
+impl<'a> WildcardWords<'a> {
+    /// Splits a wildcard term into words.
+    ///
+    /// Fails if more than one word contains wildcards.
+    pub fn split(pattern: &'a str) -> Result<Self, CompileError> {
+        let is_wild = |c: char| c == '*' || c == '?';
+        let mut words = Self {
+            before: Vec::new(),
+            wildcard: None,
+            after: Vec::new(),
+        };
+
+        let parts = pattern
+            .split(|c: char| !c.is_alphanumeric() && !is_wild(c))
+            .filter(|w| !w.is_empty() && !w.chars().all(|c| c == '*'));
+        for word in parts {
+            if !word.contains(is_wild) {
+                match words.wildcard {
+                    Some(_) => words.after.push(word),
+                    None => words.before.push(word),
+                }
+            } else if words.wildcard.is_none() {
+                words.wildcard = Some(word.to_lowercase());
+            } else {
+                return Err(CompileError {
+                    message: format!("only one word of '{pattern}' may contain wildcards"),
+                });
+            }
+        }
+
+        Ok(words)
+    }
+}
+
+/// Expands a wildcard word against the term dictionaries of the given fields.
+///
+/// Returns the matching terms, most frequent first, and whether any were dropped to stay
+/// within [`MAX_EXPANSIONS`].
+pub(super) fn expand(index: &TvSearcher, fields: &[Field], word: &str) -> (Vec<String>, bool) {
+    let prefix: String = word.chars().take_while(|&c| c != '*' && c != '?').collect();
+    let pattern: Vec<char> = word.chars().collect();
+    let upper = prefix_successor(prefix.as_bytes());
+
+    let mut doc_freqs: HashMap<String, u64> = HashMap::new();
+    for segment_reader in index.segment_readers() {
+        for &field in fields {
+            let Ok(inverted_index) = segment_reader.inverted_index(field) else {
+                continue;
+            };
+            let mut range = inverted_index.terms().range().ge(prefix.as_bytes());
+            if let Some(upper) = &upper {
+                range = range.lt(upper);
+            }
+            let Ok(mut stream) = range.into_stream() else {
+                continue;
+            };
+            while stream.advance() {
+                if let Ok(term) = str::from_utf8(stream.key())
+                    && glob_match(&pattern, &term.chars().collect::<Vec<_>>())
+                {
+                    *doc_freqs.entry(term.to_string()).or_default() +=
+                        u64::from(stream.value().doc_freq);
+                }
+            }
+        }
+    }
+
+    let mut terms: Vec<(String, u64)> = doc_freqs.into_iter().collect();
+    terms.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let truncated = terms.len() > MAX_EXPANSIONS;
+    terms.truncate(MAX_EXPANSIONS);
+    (terms.into_iter().map(|(term, _)| term).collect(), truncated)
+}
+
+/// Returns the smallest byte string greater than every string starting with `prefix`, or
+/// `None` if there is no such bound.
+fn prefix_successor(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut upper = prefix.to_vec();
+    while let Some(last) = upper.pop() {
+        if last < u8::MAX {
+            upper.push(last + 1);
+            return Some(upper);
+        }
+    }
+    None
+}
+
+/// Matches `text` against a glob pattern where `*` matches any run of characters and `?`
+/// matches exactly one.
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` in the pattern, and the text position it is matched up to.
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                // Let the last `*` absorb one more character and retry.
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, text: &str) -> bool {
+        glob_match(
+            &pattern.chars().collect::<Vec<_>>(),
+            &text.chars().collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn glob_matching() {
+        assert!(matches("auth*", "auth"));
+        assert!(matches("auth*", "authent"));
+        assert!(!matches("auth*", "oauth"));
+        assert!(matches("*auth*", "oauth"));
+        assert!(matches("config?", "configs"));
+        assert!(!matches("config?", "config"));
+        assert!(!matches("config?", "configur"));
+        assert!(matches("c*f?g", "config"));
+        assert!(matches("a*b*c", "axxbyyc"));
+        assert!(!matches("a*b*c", "axxbyy"));
+    }
+
+    #[test]
+    fn prefix_successors() {
+        assert_eq!(prefix_successor(b"auth"), Some(b"auti".to_vec()));
+        assert_eq!(prefix_successor(b"a\xff"), Some(b"b".to_vec()));
+        assert_eq!(prefix_successor(b""), None);
+    }
+
+    #[test]
+    fn splits_words() {
+        assert_eq!(
+            WildcardWords::split("Auth*").unwrap(),
+            WildcardWords {
+                before: vec![],
+                wildcard: Some("auth*".into()),
+                after: vec![],
+            }
+        );
+        assert_eq!(
+            WildcardWords::split("max_conn*_limit").unwrap(),
+            WildcardWords {
+                before: vec!["max"],
+                wildcard: Some("conn*".into()),
+                after: vec!["limit"],
+            }
+        );
+        assert_eq!(
+            WildcardWords::split("http_*").unwrap(),
+            WildcardWords {
+                before: vec!["http"],
+                wildcard: None,
+                after: vec![],
+            }
+        );
+        assert!(WildcardWords::split("a*_b?").is_err());
+    }
+}
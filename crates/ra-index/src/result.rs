@@ -110,6 +110,8 @@ impl Serialize for SearchResult {
 
 #[cfg(test)]
 mod test {
+    use ra_document::Metadata;
+
     use super::*;
 
     fn make_candidate(id: &str, score: f32, depth: u64) -> SearchCandidate {
@@ -142,12 +144,15 @@ mod test {
             byte_start: 0,
             byte_end: 100,
             sibling_count: 1,
+            meta: Metadata::new(),
+            links: Vec::new(),
             score,
             snippet: None,
             match_ranges: vec![],
             hierarchy_match_ranges: vec![],
             path_match_ranges: vec![],
             match_details: None,
+            via_link: None,
         }
     }
 
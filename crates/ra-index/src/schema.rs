@@ -2,10 +2,11 @@
 //!
 //! Defines the Tantivy schema with all fields needed for chunk indexing:
 //! - `id`: Unique chunk identifier (stored only)
-//! - `doc_id`: Document identifier (stored)
+//! - `doc_id`: Document identifier (string, stored, fast)
 //! - `parent_id`: Parent chunk identifier (stored, optional)
 //! - `hierarchy`: Hierarchy path as multi-value text (text, stored)
 //! - `tags`: Document tags (text, stored)
+//! - `tag`: Document tags as exact keywords (multi-value string, stored, fast)
 //! - `path`: File path within tree (text, stored)
 //! - `tree`: Tree name (string, stored, fast)
 //! - `body`: Chunk content (text, stored)
@@ -15,10 +16,12 @@
 //! - `byte_end`: Content span end (u64, stored)
 //! - `sibling_count`: Number of siblings (u64, stored)
 //! - `mtime`: File modification time (date, indexed, fast)
+//! - `meta`: Other frontmatter keys (JSON object, stored, values indexed untokenized)
+//! - `links`: IDs of linked chunks (multi-value string, stored)
 
 use tantivy::schema::{
-    DateOptions, FAST, Field, INDEXED, IndexRecordOption, STORED, STRING, Schema,
-    TextFieldIndexing, TextOptions,
+    DateOptions, FAST, Field, INDEXED, IndexRecordOption, JsonObjectOptions, STORED, STRING,
+    Schema, TextFieldIndexing, TextOptions,
 };
 
 use crate::analyzer::RA_TOKENIZER;
@@ -39,6 +42,8 @@ pub struct IndexSchema {
     pub hierarchy: Field,
     /// Document tags from frontmatter.
     pub tags: Field,
+    /// Document tags as exact keywords, one value per tag, for filtering and facet counts.
+    pub tag: Field,
     /// File path within the tree.
     pub path: Field,
     /// Tree name this chunk belongs to.
@@ -57,6 +62,10 @@ pub struct IndexSchema {
     pub sibling_count: Field,
     /// File modification time.
     pub mtime: Field,
+    /// Frontmatter metadata: a JSON object of the document's other frontmatter keys.
+    pub meta: Field,
+    /// IDs of the chunks this chunk links to, one value per link, for backlink lookups.
+    pub links: Field,
 }
 
 impl IndexSchema {
@@ -67,8 +76,8 @@ impl IndexSchema {
         // ID field: stored only, not indexed (we use exact term queries for lookup)
         let id = builder.add_text_field("id", STRING | STORED);
 
-        // Doc ID field: stored, for grouping chunks by document
-        let doc_id = builder.add_text_field("doc_id", STRING | STORED);
+        // Doc ID field: stored, for grouping chunks by document; fast for per-document facet counts
+        let doc_id = builder.add_text_field("doc_id", STRING | STORED | FAST);
 
         // Parent ID field: stored, for hierarchy traversal (empty string for root nodes)
         let parent_id = builder.add_text_field("parent_id", STORED);
@@ -94,6 +103,10 @@ impl IndexSchema {
             .set_stored();
         let tags = builder.add_text_field("tags", tags_options);
 
+        // Tag field: multi-value string (one untokenized value per tag), stored for regex
+        // matching, fast for facet counts
+        let tag = builder.add_text_field("tag", STRING | STORED | FAST);
+
         // Path field: text with positions, stored
         let path_options = TextOptions::default()
             .set_indexing_options(
@@ -134,6 +147,17 @@ impl IndexSchema {
         let mtime_options = DateOptions::default().set_indexed().set_fast();
         let mtime = builder.add_date_field("mtime", mtime_options);
 
+        // Meta field: JSON object, stored; string values indexed whole for exact filtering
+        let meta_options = JsonObjectOptions::from(STORED).set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("raw")
+                .set_index_option(IndexRecordOption::Basic),
+        );
+        let meta = builder.add_json_field("meta", meta_options);
+
+        // Links field: multi-value string (one untokenized chunk ID per link), stored
+        let links = builder.add_text_field("links", STRING | STORED);
+
         let schema = builder.build();
 
         Self {
@@ -143,6 +167,7 @@ impl IndexSchema {
             parent_id,
             hierarchy,
             tags,
+            tag,
             path,
             tree,
             body,
@@ -152,6 +177,8 @@ impl IndexSchema {
             byte_end,
             sibling_count,
             mtime,
+            meta,
+            links,
         }
     }
 
@@ -184,6 +211,7 @@ mod test {
         assert!(tantivy_schema.get_field("parent_id").is_ok());
         assert!(tantivy_schema.get_field("hierarchy").is_ok());
         assert!(tantivy_schema.get_field("tags").is_ok());
+        assert!(tantivy_schema.get_field("tag").is_ok());
         assert!(tantivy_schema.get_field("path").is_ok());
         assert!(tantivy_schema.get_field("tree").is_ok());
         assert!(tantivy_schema.get_field("body").is_ok());
@@ -193,6 +221,8 @@ mod test {
         assert!(tantivy_schema.get_field("byte_end").is_ok());
         assert!(tantivy_schema.get_field("sibling_count").is_ok());
         assert!(tantivy_schema.get_field("mtime").is_ok());
+        assert!(tantivy_schema.get_field("meta").is_ok());
+        assert!(tantivy_schema.get_field("links").is_ok());
     }
 
     #[test]
@@ -256,6 +286,23 @@ mod test {
         }
     }
 
+    #[test]
+    fn tag_field_is_string_stored_and_fast() {
+        let schema = IndexSchema::new();
+        let entry = schema.schema().get_field_entry(schema.tag);
+
+        assert!(entry.is_indexed());
+        assert!(entry.is_stored());
+        assert!(entry.is_fast());
+
+        if let FieldType::Str(opts) = entry.field_type() {
+            let indexing = opts.get_indexing_options().unwrap();
+            assert_eq!(indexing.tokenizer(), "raw");
+        } else {
+            panic!("tag field should be text type");
+        }
+    }
+
     #[test]
     fn mtime_field_is_indexed_and_fast() {
         let schema = IndexSchema::new();
@@ -270,6 +317,22 @@ mod test {
         );
     }
 
+    #[test]
+    fn meta_field_is_stored_json_with_raw_values() {
+        let schema = IndexSchema::new();
+        let entry = schema.schema().get_field_entry(schema.meta);
+
+        assert!(entry.is_indexed());
+        assert!(entry.is_stored());
+
+        if let FieldType::JsonObject(opts) = entry.field_type() {
+            let indexing = opts.get_text_indexing_options().unwrap();
+            assert_eq!(indexing.tokenizer(), "raw");
+        } else {
+            panic!("meta field should be JSON type");
+        }
+    }
+
     #[test]
     fn hierarchical_fields_have_correct_types() {
         let schema = IndexSchema::new();
@@ -398,7 +398,7 @@ where
 
 #[cfg(test)]
 mod tests {
-    use ra_document::ChunkId;
+    use ra_document::{ChunkId, Metadata};
 
     use super::*;
 
@@ -429,12 +429,15 @@ mod tests {
             byte_start: 0,
             byte_end: 100,
             sibling_count,
+            meta: Metadata::new(),
+            links: Vec::new(),
             score,
             snippet: None,
             match_ranges: vec![],
             hierarchy_match_ranges: vec![],
             path_match_ranges: vec![],
             match_details: None,
+            via_link: None,
         }
     }
 
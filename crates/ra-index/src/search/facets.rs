@@ -0,0 +1,166 @@
+//! Facet counts over search matches.
+//!
+//! A facet counts, for each value of a keyword field, how many documents have a chunk that
+//! matches the query. Counts cover every match, not only the results that survive
+//! aggregation and the result limit, so they show which tags and trees a query touches.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    str::FromStr,
+};
+
+use serde::Serialize;
+use tantivy::{
+    DocAddress, Searcher as TvSearcher, collector::DocSetCollector, columnar::StrColumn,
+    query::Query,
+};
+
+use crate::IndexError;
+
+/// A keyword field that search matches can be counted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FacetField {
+    /// Document tags from frontmatter.
+    Tags,
+    /// Tree names.
+    Tree,
+}
+
+impl FacetField {
+    /// Returns the facet's name, as accepted by [`FromStr`].
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Tags => "tags",
+            Self::Tree => "tree",
+        }
+    }
+
+    /// Returns the name of the fast index field holding the facet's values.
+    fn index_field(self) -> &'static str {
+        match self {
+            Self::Tags => "tag",
+            Self::Tree => "tree",
+        }
+    }
+}
+
+impl fmt::Display for FacetField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for FacetField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tags" | "tag" => Ok(Self::Tags),
+            "tree" => Ok(Self::Tree),
+            _ => Err(format!("unknown facet: {s} (expected tags or tree)")),
+        }
+    }
+}
+
+/// Matching document counts for each value of each requested facet.
+pub type FacetCounts = BTreeMap<FacetField, BTreeMap<String, usize>>;
+
+/// Reads the first value of a string column for a document.
+fn first_value(
+    column: &StrColumn,
+    doc: u32,
+    buf: &mut String,
+) -> Result<Option<String>, IndexError> {
+    match column.term_ords(doc).next() {
+        Some(ord) if column.ord_to_str(ord, buf)? => Ok(Some(buf.clone())),
+        _ => Ok(None),
+    }
+}
+
+/// Counts the documents matching `query` for each value of each facet in `fields`.
+///
+/// A document counts once per value, however many of its chunks match.
+pub(super) fn count_facets(
+    searcher: &TvSearcher,
+    query: &dyn Query,
+    fields: &[FacetField],
+) -> Result<FacetCounts, IndexError> {
+    if fields.is_empty() {
+        return Ok(FacetCounts::new());
+    }
+
+    let matches = searcher
+        .search(query, &DocSetCollector)
+        .map_err(|e| IndexError::write(&e))?;
+
+    let mut by_segment: HashMap<u32, Vec<u32>> = HashMap::new();
+    for DocAddress {
+        segment_ord,
+        doc_id,
+    } in matches
+    {
+        by_segment.entry(segment_ord).or_default().push(doc_id);
+    }
+
+    let mut documents: HashMap<FacetField, HashMap<String, HashSet<String>>> = HashMap::new();
+    let mut buf = String::new();
+    for (segment_ord, docs) in by_segment {
+        let fast_fields = searcher.segment_reader(segment_ord).fast_fields();
+        let Some(doc_ids) = fast_fields
+            .str("doc_id")
+            .map_err(|e| IndexError::write(&e))?
+        else {
+            continue;
+        };
+        for &field in fields {
+            let Some(column) = fast_fields
+                .str(field.index_field())
+                .map_err(|e| IndexError::write(&e))?
+            else {
+                continue;
+            };
+            let values = documents.entry(field).or_default();
+            for &doc in &docs {
+                let Some(doc_id) = first_value(&doc_ids, doc, &mut buf)? else {
+                    continue;
+                };
+                for ord in column.term_ords(doc) {
+                    if column.ord_to_str(ord, &mut buf)? {
+                        values
+                            .entry(buf.clone())
+                            .or_default()
+                            .insert(doc_id.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(fields
+        .iter()
+        .map(|field| {
+            let counts = documents
+                .remove(field)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(value, docs)| (value, docs.len()))
+                .collect();
+            (*field, counts)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_facet_names() {
+        assert_eq!("tags".parse(), Ok(FacetField::Tags));
+        assert_eq!("tag".parse(), Ok(FacetField::Tags));
+        assert_eq!("tree".parse(), Ok(FacetField::Tree));
+        assert!("body".parse::<FacetField>().is_err());
+    }
+}
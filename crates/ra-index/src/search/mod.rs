@@ -27,6 +27,8 @@
 
 mod aggregation;
 mod execute;
+mod facets;
+mod neighborhood;
 mod params;
 mod pipeline;
 mod query;
@@ -43,15 +45,18 @@ use std::{
 
 pub use execute::merge_ranges;
 use execute::{ExecutionOptions, extract_match_ranges};
+pub use facets::{FacetCounts, FacetField};
 use levenshtein_automata::LevenshteinAutomatonBuilder;
+pub use neighborhood::Neighborhood;
 pub use params::{MoreLikeThisParams, SearchParams};
 pub use pipeline::PipelineStats;
-use pipeline::{process_candidates, process_candidates_with_stats};
+use pipeline::process_candidates_with_stats;
 use ra_config::FieldBoosts;
-use ra_context::IdfProvider;
+use ra_context::{IdfProvider, is_binary_file};
+use ra_document::parse_file;
 use serde::Serialize;
 use tantivy::{
-    DocAddress, Index, Searcher as TvSearcher, TantivyDocument, Term,
+    DocAddress, Index, IndexReader, ReloadPolicy, Searcher as TvSearcher, TantivyDocument, Term,
     collector::{Count, TopDocs},
     directory::MmapDirectory,
     query::{
@@ -68,9 +73,13 @@ pub use types::{MatchDetails, SearchCandidate};
 use crate::{
     IndexError, QueryError,
     analyzer::{RA_TOKENIZER, build_analyzer_from_name},
-    query::{QueryCompiler, parse},
+    meta,
+    query::{
+        CompileError, CompiledQuery, LenientParse, QueryCompiler, parse_lenient, parse_with_macros,
+    },
     result::SearchResult,
     schema::IndexSchema,
+    tokens::count_tokens,
 };
 
 /// Maximum number of documents to retrieve in bulk lookup operations.
@@ -121,8 +130,8 @@ impl MoreLikeThisParams {
 
 /// Primary search entry point for the index.
 pub struct Searcher {
-    /// Tantivy index handle used for searching.
-    pub(crate) index: Index,
+    /// Index reader kept open across searches; reloads when the index is committed.
+    pub(crate) reader: IndexReader,
     /// Schema describing indexed fields.
     pub(crate) schema: IndexSchema,
     /// Compiles parsed queries into Tantivy queries.
@@ -141,6 +150,8 @@ pub struct Searcher {
     pub(crate) local_boost: f32,
     /// Field boost weights for scoring.
     pub(crate) boosts: FieldBoosts,
+    /// Saved queries that `@name` references expand to.
+    pub(crate) query_macros: HashMap<String, String>,
 }
 
 /// Inputs needed to build verbose match details.
@@ -212,7 +223,15 @@ impl Searcher {
         let analyzer = build_analyzer_from_name(language)?;
         index.tokenizers().register(RA_TOKENIZER, analyzer.clone());
 
-        let query_compiler = QueryCompiler::new(schema.clone(), language, fuzzy_distance, boosts)?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()
+            .map_err(|e| IndexError::open_index(path.to_path_buf(), &e))?;
+
+        let mut query_compiler =
+            QueryCompiler::new(schema.clone(), language, fuzzy_distance, boosts)?;
+        query_compiler.set_tree_synonyms(trees);
 
         let lev_builder = LevenshteinAutomatonBuilder::new(fuzzy_distance, true);
 
@@ -226,7 +245,7 @@ impl Searcher {
             .collect();
 
         Ok(Self {
-            index,
+            reader,
             schema,
             query_compiler,
             analyzer,
@@ -236,19 +255,46 @@ impl Searcher {
             tree_paths,
             local_boost,
             boosts,
+            query_macros: HashMap::new(),
         })
     }
 
     /// Opens an existing index for searching using configuration.
     pub fn open_with_config(path: &Path, config: &ra_config::Config) -> Result<Self, IndexError> {
-        Self::open_with_boosts(
+        let mut searcher = Self::open_with_boosts(
             path,
             &config.search.stemmer,
             &config.trees,
             config.settings.local_boost,
             config.search.fuzzy_distance,
             config.search.field_boosts(),
-        )
+        )?;
+        searcher.set_query_macros(config.queries.clone());
+        searcher.set_synonyms(&config.search.synonyms);
+        Ok(searcher)
+    }
+
+    /// Sets the synonyms that query terms expand to in every tree. Synonyms from each tree's
+    /// synonym file are set when the searcher is opened.
+    pub fn set_synonyms(&mut self, synonyms: &ra_config::Synonyms) {
+        self.query_compiler.set_synonyms(synonyms);
+    }
+
+    /// Sets the saved queries that `@name` references in query strings expand to.
+    pub fn set_query_macros(&mut self, macros: HashMap<String, String>) {
+        self.query_macros = macros;
+    }
+
+    /// Parses a query string, expanding `@name` references to saved queries.
+    pub fn parse_query(&self, query_str: &str) -> Result<Option<ra_query::QueryExpr>, QueryError> {
+        parse_with_macros(query_str, &self.query_macros).map_err(|e| e.with_query(query_str))
+    }
+
+    /// Parses a query string as far as possible, repairing mistakes such as unbalanced
+    /// parentheses or a dangling `OR` instead of failing. Search the repaired expression with
+    /// [`Self::search_aggregated_expr_with_stats`].
+    pub fn parse_query_lenient(&self, query_str: &str) -> LenientParse {
+        parse_lenient(query_str, &self.query_macros)
     }
 
     /// Computes doc IDs to exclude from results based on input file paths.
@@ -311,6 +357,25 @@ impl Searcher {
         Ok(content[start..end].to_string())
     }
 
+    /// Counts the tokens in a result's full content, as it will be shown to the reader, or
+    /// returns None if it would not fit in `remaining` tokens.
+    ///
+    /// The indexed body is part of the full content, so a result whose body alone does not fit
+    /// is rejected without reading the source file. Falls back to the indexed body if the
+    /// source file can't be read.
+    fn result_tokens(&self, result: &SearchResult, remaining: usize) -> Option<usize> {
+        let c = result.candidate();
+        let body_tokens = count_tokens(&c.body);
+        if body_tokens > remaining {
+            return None;
+        }
+        let tokens = match self.read_full_content(&c.tree, &c.path, c.byte_start, c.byte_end) {
+            Ok(content) => count_tokens(&content),
+            Err(_) => body_tokens,
+        };
+        (tokens <= remaining).then_some(tokens)
+    }
+
     /// Searches using the hierarchical algorithm with per-tree score normalization.
     pub fn search_aggregated(
         &mut self,
@@ -326,19 +391,24 @@ impl Searcher {
         query_str: &str,
         params: &SearchParams,
     ) -> Result<(Vec<SearchResult>, PipelineStats), IndexError> {
-        let content_query = match self.build_query(query_str)? {
-            Some(q) => q,
-            None => {
-                return Ok((
-                    Vec::new(),
-                    PipelineStats::empty(params.cutoff_ratio, params.aggregation_pool_size),
-                ));
-            }
+        let empty = || {
+            (
+                Vec::new(),
+                PipelineStats::empty(params.cutoff_ratio, params.aggregation_pool_size),
+            )
+        };
+        let Some(expr) = self.parse_query(query_str).map_err(IndexError::Query)? else {
+            return Ok(empty());
         };
+        let compiled = self.compile_expr(&expr).map_err(|e| {
+            let query_err: QueryError = e.into();
+            IndexError::Query(query_err.with_query(query_str))
+        })?;
 
-        let query_terms = self.tokenize_query(query_str);
+        let mut query_terms = self.tokenize_query(&expr);
+        query_terms.extend(self.expanded_terms(&compiled));
 
-        self.run_aggregated_search_with_stats(content_query, &query_terms, query_str, params)
+        self.run_aggregated_search_with_stats(compiled, &query_terms, query_str, params)
     }
 
     /// Searches using a pre-built query expression.
@@ -356,33 +426,40 @@ impl Searcher {
         expr: &ra_query::QueryExpr,
         params: &SearchParams,
     ) -> Result<(Vec<SearchResult>, PipelineStats), IndexError> {
-        let content_query = match self.query_compiler.compile(expr).map_err(|e| {
+        let compiled = self.compile_expr(expr).map_err(|e| {
             let query_err: QueryError = e.into();
             IndexError::Query(query_err)
-        })? {
-            Some(q) => q,
-            None => {
-                return Ok((
-                    Vec::new(),
-                    PipelineStats::empty(params.cutoff_ratio, params.aggregation_pool_size),
-                ));
-            }
-        };
+        })?;
 
-        let query_terms = expr.extract_terms();
+        let mut query_terms = expr.extract_terms();
+        query_terms.extend(self.expanded_terms(&compiled));
         let display_query = expr.to_query_string();
 
-        self.run_aggregated_search_with_stats(content_query, &query_terms, &display_query, params)
+        self.run_aggregated_search_with_stats(compiled, &query_terms, &display_query, params)
     }
 
-    /// Executes query and processes results through the unified pipeline.
+    /// Executes a compiled query and processes results through the unified pipeline.
+    ///
+    /// The stats report the wildcard and synonym expansions made while compiling.
     fn run_aggregated_search_with_stats(
         &self,
-        content_query: Box<dyn Query>,
+        compiled: CompiledQuery,
         query_terms: &[String],
         display_query: &str,
         params: &SearchParams,
     ) -> Result<(Vec<SearchResult>, PipelineStats), IndexError> {
+        let CompiledQuery {
+            query,
+            expansions,
+            fuzzy_overrides,
+            synonym_expansions,
+        } = compiled;
+        let Some(content_query) = query else {
+            let mut stats = PipelineStats::empty(params.cutoff_ratio, params.aggregation_pool_size);
+            stats.wildcard_expansions = expansions;
+            stats.synonym_expansions = synonym_expansions;
+            return Ok((Vec::new(), stats));
+        };
         let query = self.apply_tree_filter(content_query, &params.trees);
 
         // Execute query and get raw candidates
@@ -404,24 +481,33 @@ impl Searcher {
             }
         };
 
-        let candidates =
-            self.execute_query(&*query, query_terms, effective_candidate_limit, &options)?;
+        let candidates = self.execute_query(
+            &*query,
+            query_terms,
+            &fuzzy_overrides,
+            effective_candidate_limit,
+            &options,
+        )?;
 
         // Process through unified pipeline
-        Ok(process_candidates_with_stats(
+        let (results, mut stats) = process_candidates_with_stats(
             candidates,
             params,
             |parent_id| self.lookup_parent(parent_id),
-        ))
+            |result, remaining| self.result_tokens(result, remaining),
+        );
+        stats.facets = facets::count_facets(&self.reader.searcher(), &*query, &params.facets)?;
+        stats.wildcard_expansions = expansions;
+        stats.synonym_expansions = synonym_expansions;
+        Ok((results, stats))
     }
 
-    /// Looks up a parent node by ID for aggregation.
+    /// Looks up a chunk by ID for aggregation and link following.
     ///
     /// Returns a `SearchCandidate` with zero score and empty match data, suitable
-    /// for use as a parent node during hierarchical aggregation.
+    /// for use as a parent node during hierarchical aggregation or as a linked chunk.
     pub(super) fn lookup_parent(&self, parent_id: &str) -> Option<SearchCandidate> {
-        let reader = self.index.reader().ok()?;
-        let searcher = reader.searcher();
+        let searcher = self.reader.searcher();
 
         let term = Term::from_field_text(self.schema.id, parent_id);
         let query = TermQuery::new(term, IndexRecordOption::Basic);
@@ -433,13 +519,19 @@ impl Searcher {
         Some(self.read_candidate_from_doc(&doc))
     }
 
+    /// Reloads the index reader so searches see the latest commit immediately.
+    ///
+    /// The reader also reloads on its own shortly after each commit; call this after writing to
+    /// the index to avoid that delay.
+    pub fn reload(&self) -> Result<(), IndexError> {
+        self.reader
+            .reload()
+            .map_err(|e| IndexError::Write(e.to_string()))
+    }
+
     /// Returns the number of documents in the index.
     pub fn num_docs(&self) -> Result<u64, IndexError> {
-        let reader = self
-            .index
-            .reader()
-            .map_err(|e| IndexError::Write(e.to_string()))?;
-        Ok(reader.searcher().num_docs())
+        Ok(self.reader.searcher().num_docs())
     }
 
     /// Computes the IDF (Inverse Document Frequency) for a term.
@@ -453,11 +545,7 @@ impl Searcher {
         term: &str,
         trees: &[String],
     ) -> Result<Option<f32>, IndexError> {
-        let reader = self
-            .index
-            .reader()
-            .map_err(|e| IndexError::Write(e.to_string()))?;
-        let searcher = reader.searcher();
+        let searcher = self.reader.searcher();
 
         let mut analyzer = self.analyzer.clone();
         let mut stream = analyzer.token_stream(term);
@@ -502,12 +590,7 @@ impl Searcher {
 
     /// Retrieves a chunk by its exact ID.
     pub fn get_by_id(&self, id: &str) -> Result<Option<SearchCandidate>, IndexError> {
-        let reader = self
-            .index
-            .reader()
-            .map_err(|e| IndexError::Write(e.to_string()))?;
-
-        let searcher = reader.searcher();
+        let searcher = self.reader.searcher();
 
         let term = Term::from_field_text(self.schema.id, id);
         let query = TermQuery::new(term, IndexRecordOption::Basic);
@@ -529,12 +612,7 @@ impl Searcher {
 
     /// Lists all chunks in the index, ordered by ID.
     pub fn list_all(&self) -> Result<Vec<SearchCandidate>, IndexError> {
-        let reader = self
-            .index
-            .reader()
-            .map_err(|e| IndexError::Write(e.to_string()))?;
-
-        let searcher = reader.searcher();
+        let searcher = self.reader.searcher();
 
         let all_docs = searcher
             .search(&AllQuery, &TopDocs::with_limit(MAX_BULK_LOOKUP))
@@ -555,12 +633,7 @@ impl Searcher {
 
     /// Retrieves all chunks from a document by path.
     pub fn get_by_path(&self, tree: &str, path: &str) -> Result<Vec<SearchCandidate>, IndexError> {
-        let reader = self
-            .index
-            .reader()
-            .map_err(|e| IndexError::Write(e.to_string()))?;
-
-        let searcher = reader.searcher();
+        let searcher = self.reader.searcher();
 
         let doc_id = format!("{tree}:{path}");
         let term = Term::from_field_text(self.schema.doc_id, &doc_id);
@@ -583,6 +656,94 @@ impl Searcher {
         Ok(results)
     }
 
+    /// Retrieves a chunk together with the surrounding chunks selected by `neighborhood`.
+    ///
+    /// Results are ordered by document position. Returns an empty list if no chunk has `id`.
+    pub fn get_neighborhood(
+        &self,
+        id: &str,
+        neighborhood: &Neighborhood,
+    ) -> Result<Vec<SearchCandidate>, IndexError> {
+        let Some(target) = self.get_by_id(id)? else {
+            return Ok(Vec::new());
+        };
+        let chunks = self.get_by_path(&target.tree, &target.path)?;
+        Ok(neighborhood
+            .select(chunks, id)
+            .unwrap_or_else(|| vec![target]))
+    }
+
+    /// Returns the IDs of the chunks that `id` links to, in order of first appearance.
+    ///
+    /// For a document ID, links from every chunk of the document count, except links back into
+    /// the document itself. Returns an empty list if no chunk has `id`.
+    pub fn get_outgoing_links(&self, id: &str) -> Result<Vec<String>, IndexError> {
+        let Some(target) = self.get_by_id(id)? else {
+            return Ok(Vec::new());
+        };
+        if target.id != target.doc_id {
+            return Ok(target.links);
+        }
+
+        let mut chunks = self.get_by_path(&target.tree, &target.path)?;
+        chunks.sort_by_key(|c| c.position);
+        let mut links: Vec<String> = Vec::new();
+        for link in chunks.into_iter().flat_map(|c| c.links) {
+            let internal = link
+                .strip_prefix(target.doc_id.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('#'));
+            if !internal && !links.contains(&link) {
+                links.push(link);
+            }
+        }
+        Ok(links)
+    }
+
+    /// Retrieves the chunks that link to `id`, ordered by ID.
+    ///
+    /// For a document ID, links to any chunk of the document count, except links from within
+    /// the document itself.
+    pub fn get_backlinks(&self, id: &str) -> Result<Vec<SearchCandidate>, IndexError> {
+        let target = self.get_by_id(id)?;
+        let (targets, doc_id) = match target {
+            Some(t) if t.id == t.doc_id => {
+                let chunks = self.get_by_path(&t.tree, &t.path)?;
+                (chunks.into_iter().map(|c| c.id).collect(), Some(t.doc_id))
+            }
+            _ => (vec![id.to_string()], None),
+        };
+
+        let searcher = self.reader.searcher();
+        let query = BooleanQuery::new(
+            targets
+                .iter()
+                .map(|target| {
+                    let term = Term::from_field_text(self.schema.links, target);
+                    let query: Box<dyn Query> =
+                        Box::new(TermQuery::new(term, IndexRecordOption::Basic));
+                    (Occur::Should, query)
+                })
+                .collect(),
+        );
+
+        let matching_docs = searcher
+            .search(&query, &TopDocs::with_limit(MAX_BULK_LOOKUP))
+            .map_err(|e| IndexError::Write(e.to_string()))?;
+
+        let mut results: Vec<SearchCandidate> = matching_docs
+            .into_iter()
+            .filter_map(|(_, doc_address)| {
+                let doc: tantivy::TantivyDocument = searcher.doc(doc_address).ok()?;
+                Some(self.read_candidate_from_doc(&doc))
+            })
+            .filter(|c| doc_id.as_ref() != Some(&c.doc_id))
+            .collect();
+
+        results.sort_by(|a, b| a.id.cmp(&b.id));
+
+        Ok(results)
+    }
+
     /// Finds documents similar to an indexed document by ID.
     pub fn search_more_like_this_by_id(
         &mut self,
@@ -590,6 +751,18 @@ impl Searcher {
         mlt_params: &MoreLikeThisParams,
         search_params: &SearchParams,
     ) -> Result<Vec<SearchResult>, IndexError> {
+        Ok(self
+            .search_more_like_this_by_id_with_stats(id, mlt_params, search_params)?
+            .0)
+    }
+
+    /// Finds documents similar to an indexed document by ID and returns pipeline statistics.
+    pub fn search_more_like_this_by_id_with_stats(
+        &mut self,
+        id: &str,
+        mlt_params: &MoreLikeThisParams,
+        search_params: &SearchParams,
+    ) -> Result<(Vec<SearchResult>, PipelineStats), IndexError> {
         let doc_address = self.get_doc_address(id)?.ok_or_else(|| {
             IndexError::Query(QueryError::compile(format!("document not found: {id}")))
         })?;
@@ -608,21 +781,93 @@ impl Searcher {
         search_params: &SearchParams,
         exclude_doc_ids: &HashSet<String>,
     ) -> Result<Vec<SearchResult>, IndexError> {
+        Ok(self
+            .search_more_like_this_by_fields_with_stats(
+                fields,
+                mlt_params,
+                search_params,
+                exclude_doc_ids,
+            )?
+            .0)
+    }
+
+    /// Finds documents similar to arbitrary field content and returns pipeline statistics.
+    pub fn search_more_like_this_by_fields_with_stats(
+        &mut self,
+        fields: Vec<(&str, String)>,
+        mlt_params: &MoreLikeThisParams,
+        search_params: &SearchParams,
+        exclude_doc_ids: &HashSet<String>,
+    ) -> Result<(Vec<SearchResult>, PipelineStats), IndexError> {
         let tantivy_fields = self.convert_field_names_to_tantivy(fields)?;
         let query = mlt_params.build_query_from_fields(tantivy_fields);
 
         self.run_mlt_search(Box::new(query), exclude_doc_ids, search_params)
     }
 
+    /// Finds documents similar to a file on disk, which need not be indexed.
+    ///
+    /// The file is parsed like an indexed document and its title, body and tags form the
+    /// query. If the file lies inside a tree, its own chunks are excluded from the results.
+    pub fn search_more_like_this_by_file_with_stats(
+        &mut self,
+        path: &Path,
+        mlt_params: &MoreLikeThisParams,
+        search_params: &SearchParams,
+    ) -> Result<(Vec<SearchResult>, PipelineStats), IndexError> {
+        if !path.exists() {
+            return Err(IndexError::InvalidSource(format!(
+                "file not found: {}",
+                path.display()
+            )));
+        }
+        if is_binary_file(path) {
+            return Err(IndexError::InvalidSource(format!(
+                "binary file not supported: {}",
+                path.display()
+            )));
+        }
+
+        let parsed = parse_file(path, "likethis")
+            .map_err(|e| IndexError::InvalidSource(format!("failed to parse file: {e}")))?;
+        let doc = &parsed.document;
+
+        let mut fields: Vec<(&str, String)> = Vec::new();
+        if !doc.title.is_empty() {
+            fields.push(("title", doc.title.clone()));
+        }
+        let body = doc
+            .extract_chunks()
+            .iter()
+            .map(|c| c.body.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        if !body.is_empty() {
+            fields.push(("body", body));
+        }
+        if !doc.tags.is_empty() {
+            fields.push(("tags", doc.tags.join(" ")));
+        }
+        if fields.is_empty() {
+            return Err(IndexError::InvalidSource(
+                "no content extracted from file".to_string(),
+            ));
+        }
+
+        let exclude_doc_ids = self.compute_exclude_doc_ids(&[path]);
+        self.search_more_like_this_by_fields_with_stats(
+            fields,
+            mlt_params,
+            search_params,
+            &exclude_doc_ids,
+        )
+    }
+
     /// Looks up a document's address by its ID.
     ///
     /// The ID is the unique chunk identifier in the format `tree:path#slug` or `tree:path`.
     pub fn get_doc_address(&self, id: &str) -> Result<Option<DocAddress>, IndexError> {
-        let reader = self
-            .index
-            .reader()
-            .map_err(|e| IndexError::Write(e.to_string()))?;
-        let searcher = reader.searcher();
+        let searcher = self.reader.searcher();
 
         let term = Term::from_field_text(self.schema.id, id);
         let query = TermQuery::new(term, IndexRecordOption::Basic);
@@ -679,11 +924,7 @@ impl Searcher {
             IndexError::Query(QueryError::compile(format!("document not found: {id}")))
         })?;
 
-        let reader = self
-            .index
-            .reader()
-            .map_err(|e| IndexError::Write(e.to_string()))?;
-        let searcher = reader.searcher();
+        let searcher = self.reader.searcher();
 
         let doc: tantivy::TantivyDocument = searcher
             .doc(doc_address)
@@ -715,7 +956,7 @@ impl Searcher {
         query: Box<dyn Query>,
         exclude_ids: &HashSet<String>,
         params: &SearchParams,
-    ) -> Result<Vec<SearchResult>, IndexError> {
+    ) -> Result<(Vec<SearchResult>, PipelineStats), IndexError> {
         let query = self.apply_tree_filter(query, &params.trees);
         let effective_candidate_limit = params.effective_candidate_limit();
 
@@ -726,38 +967,48 @@ impl Searcher {
             include_explanation: false,
         };
 
-        let raw_results = self.execute_query(&*query, &[], effective_candidate_limit, &options)?;
+        let raw_results = self.execute_query(
+            &*query,
+            &[],
+            &HashMap::new(),
+            effective_candidate_limit,
+            &options,
+        )?;
 
         let candidates: Vec<SearchCandidate> = raw_results
             .into_iter()
             .filter(|c| !exclude_ids.contains(&c.id) && !exclude_ids.contains(&c.doc_id))
             .collect();
 
-        Ok(process_candidates(candidates, params, |parent_id| {
-            self.lookup_parent(parent_id)
-        }))
+        Ok(process_candidates_with_stats(
+            candidates,
+            params,
+            |parent_id| self.lookup_parent(parent_id),
+            |result, remaining| self.result_tokens(result, remaining),
+        ))
     }
 
-    /// Parses and compiles a query string into a Tantivy query.
-    pub(crate) fn build_query(
-        &mut self,
-        query_str: &str,
-    ) -> Result<Option<Box<dyn Query>>, IndexError> {
-        let expr = parse(query_str).map_err(|e| {
-            let query_err: QueryError = e;
-            IndexError::Query(query_err.with_query(query_str))
-        })?;
+    /// Compiles a query expression, expanding wildcard terms against the current index.
+    fn compile_expr(&mut self, expr: &ra_query::QueryExpr) -> Result<CompiledQuery, CompileError> {
+        self.query_compiler.set_index(self.reader.searcher());
+        self.query_compiler.compile(expr)
+    }
 
-        match expr {
-            Some(e) => {
-                let result = self.query_compiler.compile(&e).map_err(|e| {
-                    let query_err: QueryError = e.into();
-                    IndexError::Query(query_err.with_query(query_str))
-                })?;
-                Ok(result)
+    /// Returns every term that wildcards and synonyms in a compiled query expanded to, for
+    /// highlighting.
+    fn expanded_terms(&mut self, compiled: &CompiledQuery) -> Vec<String> {
+        let mut terms: Vec<String> = compiled
+            .expansions
+            .iter()
+            .flat_map(|e| e.terms.iter().cloned())
+            .collect();
+        for synonym in compiled.synonym_expansions.iter().flat_map(|e| &e.synonyms) {
+            let mut stream = self.analyzer.token_stream(synonym);
+            while let Some(token) = stream.next() {
+                terms.push(token.text.clone());
             }
-            None => Ok(None),
         }
+        terms
     }
 
     /// Builds a tree filter query for the given tree names.
@@ -799,17 +1050,27 @@ impl Searcher {
         }
     }
 
-    /// Tokenizes a query string to extract individual search terms.
+    /// Tokenizes a parsed query to extract individual search terms.
     ///
-    /// Filters out query syntax elements (OR, AND, NOT, field prefixes) before
-    /// tokenizing to avoid treating keywords as search terms.
-    pub(crate) fn tokenize_query(&mut self, query_str: &str) -> Vec<String> {
-        let filtered: String = query_str
+    /// Works from the expression written out as query syntax, so `@name` references appear as
+    /// the saved queries they stand for. Filters out query syntax elements (OR, AND, NOT, field
+    /// prefixes, `~N` suffixes) before tokenizing to avoid treating keywords as search terms.
+    pub(crate) fn tokenize_query(&mut self, expr: &ra_query::QueryExpr) -> Vec<String> {
+        let expanded = expr.to_query_string();
+        let filtered: String = expanded
             .split_whitespace()
             .filter(|word| {
                 let upper = word.to_uppercase();
                 upper != "OR" && upper != "AND" && upper != "NOT" && !word.contains(':')
             })
+            .map(|word| match word.rsplit_once('~') {
+                Some((text, count))
+                    if !count.is_empty() && count.bytes().all(|b| b.is_ascii_digit()) =>
+                {
+                    text
+                }
+                _ => word,
+            })
             .collect::<Vec<_>>()
             .join(" ");
 
@@ -824,20 +1085,35 @@ impl Searcher {
     /// Finds term mappings from query terms to indexed terms (including fuzzy matches).
     ///
     /// Returns a map where keys are query terms and values are the indexed terms
-    /// they matched across the specified fields.
+    /// they matched across the specified fields. Each term is matched within its own
+    /// `term~N` edit distance if `fuzzy_overrides` gives it one.
     pub(crate) fn find_term_mappings(
         &self,
         searcher: &TvSearcher,
         query_terms: &[String],
+        fuzzy_overrides: &HashMap<String, u8>,
         fields: &[Field],
     ) -> HashMap<String, Vec<String>> {
         let mut mappings: HashMap<String, Vec<String>> = HashMap::new();
 
-        if self.fuzzy_distance == 0 {
-            for term in query_terms {
+        // Builders are costly to create, so make one per distinct distance beyond the default
+        let mut builders: HashMap<u8, LevenshteinAutomatonBuilder> = HashMap::new();
+        let mut fuzzy_terms = Vec::new();
+        for term in query_terms {
+            let distance = fuzzy_overrides
+                .get(term)
+                .copied()
+                .unwrap_or(self.fuzzy_distance);
+            if distance == 0 {
                 mappings.insert(term.clone(), vec![term.clone()]);
+                continue;
+            }
+            if distance != self.fuzzy_distance {
+                builders
+                    .entry(distance)
+                    .or_insert_with(|| LevenshteinAutomatonBuilder::new(distance, true));
             }
-            return mappings;
+            fuzzy_terms.push((term, distance));
         }
 
         for segment_reader in searcher.segment_readers() {
@@ -847,8 +1123,9 @@ impl Searcher {
                 };
                 let term_dict = inverted_index.terms();
 
-                for query_term in query_terms {
-                    let dfa = query::LevenshteinDfa(self.lev_builder.build_dfa(query_term));
+                for &(query_term, distance) in &fuzzy_terms {
+                    let builder = builders.get(&distance).unwrap_or(&self.lev_builder);
+                    let dfa = query::LevenshteinDfa(builder.build_dfa(query_term));
                     let mut stream = term_dict.search(dfa).into_stream().unwrap();
 
                     let entry = mappings.entry(query_term.clone()).or_default();
@@ -877,27 +1154,29 @@ impl Searcher {
         &self,
         query: &dyn Query,
         query_terms: &[String],
+        fuzzy_overrides: &HashMap<String, u8>,
         limit: usize,
         options: &ExecutionOptions<'_>,
     ) -> Result<Vec<SearchCandidate>, IndexError> {
-        let reader = self
-            .index
-            .reader()
-            .map_err(|e| IndexError::Write(e.to_string()))?;
-
-        let searcher = reader.searcher();
+        let searcher = self.reader.searcher();
 
         let top_docs = searcher
             .search(query, &TopDocs::with_limit(limit))
             .map_err(|e| IndexError::Write(e.to_string()))?;
 
         let (matched_terms, term_mappings) = if options.with_details {
-            let mappings = self.find_term_mappings(&searcher, query_terms, &[self.schema.body]);
+            let mappings = self.find_term_mappings(
+                &searcher,
+                query_terms,
+                fuzzy_overrides,
+                &[self.schema.body],
+            );
             let mut terms: HashSet<String> = mappings.values().flatten().cloned().collect();
 
             let extra = self.find_term_mappings(
                 &searcher,
                 query_terms,
+                fuzzy_overrides,
                 &[self.schema.hierarchy, self.schema.path],
             );
             terms.extend(extra.values().flatten().cloned());
@@ -907,6 +1186,7 @@ impl Searcher {
             let mappings = self.find_term_mappings(
                 &searcher,
                 query_terms,
+                fuzzy_overrides,
                 &[self.schema.body, self.schema.hierarchy, self.schema.path],
             );
             let terms: HashSet<String> = mappings.values().flatten().cloned().collect();
@@ -1154,6 +1434,12 @@ impl Searcher {
         let byte_start = self.get_u64_field(doc, self.schema.byte_start);
         let byte_end = self.get_u64_field(doc, self.schema.byte_end);
         let sibling_count = self.get_u64_field(doc, self.schema.sibling_count);
+        let meta = meta::from_value(doc.get_first(self.schema.meta));
+        let links: Vec<String> = doc
+            .get_all(self.schema.links)
+            .filter_map(|v| v.as_str())
+            .map(|s| s.to_string())
+            .collect();
 
         SearchCandidate {
             id,
@@ -1168,12 +1454,15 @@ impl Searcher {
             byte_start,
             byte_end,
             sibling_count,
+            meta,
+            links,
             score: 0.0,
             snippet: None,
             match_ranges: vec![],
             hierarchy_match_ranges: vec![],
             path_match_ranges: vec![],
             match_details: None,
+            via_link: None,
         }
     }
 
@@ -1275,12 +1564,15 @@ pub fn open_searcher(
     })?;
 
     let fuzzy_distance = fuzzy_override.unwrap_or(config.search.fuzzy_distance);
-    Searcher::open_with_boosts(
+    let mut searcher = Searcher::open_with_boosts(
         &index_dir,
         &config.search.stemmer,
         &config.trees,
         config.settings.local_boost,
         fuzzy_distance,
         config.search.field_boosts(),
-    )
+    )?;
+    searcher.set_query_macros(config.queries.clone());
+    searcher.set_synonyms(&config.search.synonyms);
+    Ok(searcher)
 }
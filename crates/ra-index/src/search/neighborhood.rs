@@ -0,0 +1,67 @@
+//! Retrieval of the chunks surrounding a chunk.
+//!
+//! A chunk's neighborhood is resolved from the stored hierarchy fields of the chunks in its
+//! document: `parent_id` links a chunk to its parent, `position` gives pre-order document
+//! order, and `sibling_count` tells whether a chunk has any siblings at all.
+
+use super::SearchCandidate;
+
+/// Which chunks around a target chunk to retrieve.
+///
+/// The target chunk itself is always included.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Neighborhood {
+    /// Include the target's parent chunk.
+    pub parent: bool,
+    /// Include chunks that share the target's parent.
+    pub siblings: bool,
+    /// Include the target's direct children.
+    pub children: bool,
+    /// Number of chunks following the target in document order to include.
+    pub next: usize,
+    /// Number of chunks preceding the target in document order to include.
+    pub prev: usize,
+}
+
+impl Neighborhood {
+    /// Returns true if only the target chunk is requested.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Selects the target and its requested neighbors from the chunks of its document.
+    ///
+    /// Returns `None` if no chunk has `target_id`. Chunks are returned in document order.
+    pub(crate) fn select(
+        &self,
+        mut chunks: Vec<SearchCandidate>,
+        target_id: &str,
+    ) -> Option<Vec<SearchCandidate>> {
+        chunks.sort_by_key(|c| c.position);
+        let index = chunks.iter().position(|c| c.id == target_id)?;
+        let target = &chunks[index];
+
+        let first = index.saturating_sub(self.prev);
+        let last = index.saturating_add(self.next).min(chunks.len() - 1);
+        let has_siblings = target.parent_id.is_some() && target.sibling_count > 1;
+
+        let keep: Vec<bool> = chunks
+            .iter()
+            .enumerate()
+            .map(|(i, chunk)| {
+                (first..=last).contains(&i)
+                    || (self.parent && target.parent_id.as_deref() == Some(chunk.id.as_str()))
+                    || (self.siblings && has_siblings && chunk.parent_id == target.parent_id)
+                    || (self.children && chunk.parent_id.as_deref() == Some(target_id))
+            })
+            .collect();
+
+        Some(
+            chunks
+                .into_iter()
+                .zip(keep)
+                .filter_map(|(chunk, keep)| keep.then_some(chunk))
+                .collect(),
+        )
+    }
+}
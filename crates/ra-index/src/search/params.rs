@@ -2,7 +2,7 @@
 
 use serde::Serialize;
 
-use super::aggregation::DEFAULT_AGGREGATION_THRESHOLD;
+use super::{aggregation::DEFAULT_AGGREGATION_THRESHOLD, facets::FacetField};
 use crate::elbow::DEFAULT_CUTOFF_RATIO;
 
 /// Default final result limit after aggregation.
@@ -29,7 +29,8 @@ pub const CANDIDATE_LIMIT_MULTIPLIER: usize = 50;
 /// 2. **Phase 2 (Normalize)**: Normalize scores across trees (multi-tree only)
 /// 3. **Phase 3 (Aggregate)**: Aggregate sibling matches using `aggregation_threshold`
 /// 4. **Phase 4 (Elbow)**: Apply relevance cutoff using `cutoff_ratio` and `aggregation_pool_size`
-/// 5. **Phase 5 (Limit)**: Truncate to final `limit` results
+/// 5. **Phase 5 (Limit)**: Truncate to final `limit` results, within `max_tokens` if set
+/// 6. **Phase 6 (Links)**: Add chunks linked from the top `follow_links` results
 ///
 /// When `candidate_limit` is not explicitly set, it defaults to `limit * 50` to ensure
 /// enough candidates flow through the pipeline for effective aggregation.
@@ -52,6 +53,10 @@ pub struct SearchParams {
     pub disable_aggregation: bool,
     /// Final result limit after aggregation. Default: 10.
     pub limit: usize,
+    /// Token budget for the final results. If set, results are added in score order and
+    /// any result that would overflow the budget is skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<usize>,
     /// Limit results to these trees. If empty, search all trees.
     ///
     /// Note: BM25 scoring uses corpus-wide statistics (document frequency, average
@@ -60,6 +65,14 @@ pub struct SearchParams {
     /// this is acceptable since relative ranking within results remains meaningful.
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub trees: Vec<String>,
+    /// Facets to count matching documents by. Counts cover every match, before aggregation
+    /// and limits.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub facets: Vec<FacetField>,
+    /// Follow links from this many top results, adding the linked chunks at a discounted
+    /// score. If None, links are not followed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub follow_links: Option<usize>,
     /// Verbosity level for match details (0 = none, 1 = summary, 2+ = full).
     pub verbosity: u8,
 }
@@ -73,7 +86,10 @@ impl Default for SearchParams {
             aggregation_threshold: DEFAULT_AGGREGATION_THRESHOLD,
             disable_aggregation: false,
             limit: DEFAULT_LIMIT,
+            max_tokens: None,
             trees: Vec::new(),
+            facets: Vec::new(),
+            follow_links: None,
             verbosity: 0,
         }
     }
@@ -17,7 +17,12 @@
 //!    results. Applied AFTER aggregation so that aggregated results (with boosted
 //!    RSS scores) compete fairly.
 //!
-//! 4. **Final Limit**: Truncate to the requested number of results.
+//! 4. **Final Limit**: Truncate to the requested number of results. When a token budget
+//!    is set, results are taken in score order and any result that would overflow the
+//!    budget is skipped.
+//!
+//! 5. **Link Following**: When `follow_links` is set, add the chunks linked from the top
+//!    results at a discounted score. See [`follow_links`].
 //!
 //! # Why Aggregate-First?
 //!
@@ -39,17 +44,22 @@
 
 use std::{
     cmp::Ordering,
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
 };
 
 use serde::Serialize;
 
-use super::{SearchCandidate, SearchParams, aggregation::adaptive_aggregate};
+use super::{SearchCandidate, SearchParams, aggregation::adaptive_aggregate, facets::FacetCounts};
 use crate::{
     elbow::{ElbowStats, elbow_cutoff_results_with_stats},
+    query::{SynonymExpansion, TermExpansion},
     result::SearchResult as AggregatedSearchResult,
+    tokens::fill_token_budget,
 };
 
+/// Score of a chunk added by following a link, relative to the result that links to it.
+const LINK_SCORE_FACTOR: f32 = 0.5;
+
 /// Statistics about the search pipeline execution.
 ///
 /// This provides visibility into what happened during each phase of the pipeline,
@@ -62,10 +72,24 @@ pub struct PipelineStats {
     pub post_aggregation_count: usize,
     /// Number of results after elbow cutoff.
     pub post_elbow_count: usize,
-    /// Final number of results after limit applied.
+    /// Final number of results after limit applied, including linked chunks.
     pub final_count: usize,
+    /// Number of chunks added by following links from the top results.
+    pub linked_count: usize,
+    /// Tokens used by the final results, when a token budget was set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens_used: Option<usize>,
     /// Statistics from the elbow cutoff phase.
     pub elbow: ElbowStats,
+    /// Matching document counts for the requested facets.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub facets: FacetCounts,
+    /// Wildcard terms in the query and the words they expanded to.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub wildcard_expansions: Vec<TermExpansion>,
+    /// Terms in the query and the synonyms they expanded to.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub synonym_expansions: Vec<SynonymExpansion>,
 }
 
 impl PipelineStats {
@@ -77,6 +101,8 @@ impl PipelineStats {
             post_aggregation_count: 0,
             post_elbow_count: 0,
             final_count: 0,
+            linked_count: 0,
+            tokens_used: None,
             elbow: ElbowStats {
                 input_count: 0,
                 output_count: 0,
@@ -85,6 +111,9 @@ impl PipelineStats {
                 cutoff_ratio,
                 max_results,
             },
+            facets: FacetCounts::new(),
+            wildcard_expansions: Vec::new(),
+            synonym_expansions: Vec::new(),
         }
     }
 }
@@ -151,39 +180,29 @@ fn normalize_scores_across_trees(
 /// Processes raw search candidates through the result pipeline.
 ///
 /// This is the unified pipeline used by all search entry points. It takes
-/// raw candidates from query execution and produces final aggregated results.
+/// raw candidates from query execution and produces final aggregated results,
+/// along with [`PipelineStats`] describing what happened at each phase.
 ///
 /// # Arguments
 ///
 /// * `candidates` - Raw search candidates from query execution (sorted by score)
 /// * `params` - Search parameters controlling pipeline behavior
-/// * `parent_lookup` - Function to look up parent nodes by ID for aggregation
+/// * `lookup` - Function to look up chunks by ID, for aggregation parents and linked chunks
+/// * `token_count` - Function to measure a result in tokens given the tokens left in the budget,
+///   returning None if it does not fit; used when `max_tokens` is set
 ///
 /// # Returns
 ///
 /// Final search results after normalization, aggregation, and elbow cutoff.
-pub fn process_candidates<F>(
-    candidates: Vec<SearchCandidate>,
-    params: &SearchParams,
-    parent_lookup: F,
-) -> Vec<AggregatedSearchResult>
-where
-    F: Fn(&str) -> Option<SearchCandidate>,
-{
-    process_candidates_with_stats(candidates, params, parent_lookup).0
-}
-
-/// Processes raw search candidates and returns pipeline statistics.
-///
-/// Like [`process_candidates`], but also returns [`PipelineStats`] describing
-/// what happened at each phase of the pipeline.
-pub fn process_candidates_with_stats<F>(
+pub fn process_candidates_with_stats<F, T>(
     candidates: Vec<SearchCandidate>,
     params: &SearchParams,
-    parent_lookup: F,
+    lookup: F,
+    token_count: T,
 ) -> (Vec<AggregatedSearchResult>, PipelineStats)
 where
     F: Fn(&str) -> Option<SearchCandidate>,
+    T: Fn(&AggregatedSearchResult, usize) -> Option<usize>,
 {
     let raw_candidate_count = candidates.len();
 
@@ -207,7 +226,7 @@ where
     let aggregated = if params.disable_aggregation {
         single_results_from_candidates(normalized)
     } else {
-        adaptive_aggregate(normalized, params.aggregation_threshold, parent_lookup)
+        adaptive_aggregate(normalized, params.aggregation_threshold, &lookup)
     };
     let post_aggregation_count = aggregated.len();
 
@@ -220,8 +239,22 @@ where
     );
     let post_elbow_count = relevant.len();
 
-    // Phase 4: Apply final limit
-    let results: Vec<_> = relevant.into_iter().take(params.limit).collect();
+    // Phase 4: Apply final limit and token budget
+    let (results, tokens_used) = match params.max_tokens {
+        Some(max_tokens) => {
+            let (results, used) =
+                fill_token_budget(relevant, params.limit, max_tokens, &token_count);
+            (results, Some(used))
+        }
+        None => (relevant.into_iter().take(params.limit).collect(), None),
+    };
+
+    // Phase 5: Add chunks linked from the top results
+    let limited_count = results.len();
+    let (results, tokens_used) = match params.follow_links {
+        Some(follow) => follow_links(results, follow, params, tokens_used, &lookup, &token_count),
+        None => (results, tokens_used),
+    };
     let final_count = results.len();
 
     let stats = PipelineStats {
@@ -229,12 +262,98 @@ where
         post_aggregation_count,
         post_elbow_count,
         final_count,
+        linked_count: final_count - limited_count,
+        tokens_used,
         elbow: elbow_stats,
+        facets: FacetCounts::new(),
+        wildcard_expansions: Vec::new(),
+        synonym_expansions: Vec::new(),
     };
 
     (results, stats)
 }
 
+/// Adds the chunks linked from the top `follow` results.
+///
+/// Each linked chunk becomes a single result scored at [`LINK_SCORE_FACTOR`] times the result
+/// that links to it, with that result's ID in `via_link`. The links of an aggregated result
+/// include those of its constituents. Chunks that overlap a result already present, and chunks
+/// outside the searched trees, are skipped. When a token budget is set, linked chunks that would
+/// overflow it are skipped as well. Linked chunks do not count against `limit`.
+///
+/// Returns the results re-sorted by score, and the updated token usage.
+fn follow_links<F, T>(
+    mut results: Vec<AggregatedSearchResult>,
+    follow: usize,
+    params: &SearchParams,
+    mut tokens_used: Option<usize>,
+    lookup: F,
+    token_count: T,
+) -> (Vec<AggregatedSearchResult>, Option<usize>)
+where
+    F: Fn(&str) -> Option<SearchCandidate>,
+    T: Fn(&AggregatedSearchResult, usize) -> Option<usize>,
+{
+    let sources: Vec<(String, f32, Vec<String>)> = results
+        .iter()
+        .take(follow)
+        .map(|result| {
+            let candidate = result.candidate();
+            let constituent_links = result
+                .constituents()
+                .unwrap_or_default()
+                .iter()
+                .flat_map(|c| c.links.iter());
+            let links = candidate
+                .links
+                .iter()
+                .chain(constituent_links)
+                .cloned()
+                .collect();
+            (candidate.id.clone(), candidate.score, links)
+        })
+        .collect();
+
+    for (source_id, source_score, links) in sources {
+        for link in links {
+            let Some(mut linked) = lookup(&link) else {
+                continue;
+            };
+            if !params.trees.is_empty() && !params.trees.contains(&linked.tree) {
+                continue;
+            }
+            if results.iter().any(|r| overlaps(r.candidate(), &linked)) {
+                continue;
+            }
+
+            linked.score = source_score * LINK_SCORE_FACTOR;
+            linked.via_link = Some(source_id.clone());
+            let result = AggregatedSearchResult::single(linked);
+            if let (Some(max_tokens), Some(used)) = (params.max_tokens, tokens_used.as_mut()) {
+                let Some(tokens) = token_count(&result, max_tokens - *used) else {
+                    continue;
+                };
+                *used += tokens;
+            }
+            results.push(result);
+        }
+    }
+
+    // Stable sort keeps linked chunks after results with the same score
+    results.sort_by(|a, b| {
+        b.candidate()
+            .score
+            .partial_cmp(&a.candidate().score)
+            .unwrap_or(Ordering::Equal)
+    });
+    (results, tokens_used)
+}
+
+/// Returns true if two chunks are in the same document and their content spans overlap.
+fn overlaps(a: &SearchCandidate, b: &SearchCandidate) -> bool {
+    a.doc_id == b.doc_id && a.byte_start < b.byte_end && b.byte_start < a.byte_end
+}
+
 /// Converts raw candidates into single (non-aggregated) results.
 ///
 /// Used when aggregation is disabled via `SearchParams::disable_aggregation`.
@@ -247,9 +366,26 @@ fn single_results_from_candidates(candidates: Vec<SearchCandidate>) -> Vec<Aggre
 
 #[cfg(test)]
 mod tests {
-    use ra_document::{ChunkId, DocId};
+    use ra_document::{ChunkId, DocId, Metadata};
 
     use super::*;
+    use crate::tokens::count_tokens;
+
+    fn body_tokens(result: &AggregatedSearchResult, remaining: usize) -> Option<usize> {
+        Some(count_tokens(&result.candidate().body)).filter(|&tokens| tokens <= remaining)
+    }
+
+    /// Runs the pipeline without statistics, as most tests only check the results.
+    fn process_candidates<F>(
+        candidates: Vec<SearchCandidate>,
+        params: &SearchParams,
+        lookup: F,
+    ) -> Vec<AggregatedSearchResult>
+    where
+        F: Fn(&str) -> Option<SearchCandidate>,
+    {
+        process_candidates_with_stats(candidates, params, lookup, body_tokens).0
+    }
 
     fn make_candidate(
         id: &str,
@@ -292,12 +428,15 @@ mod tests {
             byte_start: 0,
             byte_end: 100,
             sibling_count,
+            meta: Metadata::new(),
+            links: Vec::new(),
             score,
             snippet: None,
             match_ranges: vec![],
             hierarchy_match_ranges: vec![],
             path_match_ranges: vec![],
             match_details: None,
+            via_link: None,
         }
     }
 
@@ -340,6 +479,98 @@ mod tests {
         assert_eq!(results[1].candidate().id, "local:b.md");
     }
 
+    #[test]
+    fn respects_token_budget() {
+        let params = SearchParams {
+            cutoff_ratio: 0.0,
+            max_tokens: Some(8),
+            ..Default::default()
+        };
+
+        // Each body is "Body of local:x.md", which is well under 8 tokens, but two won't fit.
+        let candidates = vec![
+            make_candidate("local:a.md", "local", None, 5.0, 1),
+            make_candidate("local:b.md", "local", None, 4.0, 1),
+        ];
+
+        let (results, stats) =
+            process_candidates_with_stats(candidates, &params, |_| None, body_tokens);
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].candidate().id, "local:a.md");
+        let used = stats.tokens_used.unwrap();
+        assert!(used > 0 && used <= 8);
+    }
+
+    #[test]
+    fn follows_links_from_top_results() {
+        let params = SearchParams {
+            cutoff_ratio: 0.0,
+            follow_links: Some(1),
+            ..Default::default()
+        };
+
+        let mut top = make_candidate("local:a.md", "local", None, 10.0, 1);
+        top.links = vec!["local:c.md#setup".to_string(), "other:e.md".to_string()];
+        let mut second = make_candidate("local:b.md", "local", None, 8.0, 1);
+        second.links = vec!["local:d.md".to_string()];
+        let linked = [
+            make_candidate("local:c.md#setup", "local", Some("local:c.md"), 0.0, 1),
+            make_candidate("local:d.md", "local", None, 0.0, 1),
+            make_candidate("other:e.md", "other", None, 0.0, 1),
+        ];
+
+        let (results, stats) = process_candidates_with_stats(
+            vec![top, second],
+            &params,
+            |id| linked.iter().find(|c| c.id == id).cloned(),
+            body_tokens,
+        );
+
+        // Only the top result's links are followed, at half its score, and the link into
+        // another tree is followed because no tree filter is set
+        let ids: Vec<&str> = results.iter().map(|r| r.candidate().id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec!["local:a.md", "local:b.md", "local:c.md#setup", "other:e.md"]
+        );
+        let followed = results[2].candidate();
+        assert_eq!(followed.score, 5.0);
+        assert_eq!(followed.via_link.as_deref(), Some("local:a.md"));
+        assert_eq!(results[0].candidate().via_link, None);
+        assert_eq!(stats.linked_count, 2);
+        assert_eq!(stats.final_count, 4);
+    }
+
+    #[test]
+    fn linked_chunks_skip_existing_results_and_other_trees() {
+        let params = SearchParams {
+            cutoff_ratio: 0.0,
+            trees: vec!["local".to_string()],
+            follow_links: Some(2),
+            ..Default::default()
+        };
+
+        let mut top = make_candidate("local:a.md", "local", None, 10.0, 1);
+        top.links = vec!["local:b.md".to_string(), "other:e.md".to_string()];
+        let second = make_candidate("local:b.md", "local", None, 8.0, 1);
+        let linked = [
+            make_candidate("local:b.md", "local", None, 0.0, 1),
+            make_candidate("other:e.md", "other", None, 0.0, 1),
+        ];
+
+        let (results, stats) = process_candidates_with_stats(
+            vec![top, second],
+            &params,
+            |id| linked.iter().find(|c| c.id == id).cloned(),
+            body_tokens,
+        );
+
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.candidate().via_link.is_none()));
+        assert_eq!(stats.linked_count, 0);
+    }
+
     #[test]
     fn aggregation_disabled_returns_singles() {
         let params = SearchParams {
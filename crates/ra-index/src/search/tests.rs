@@ -1,9 +1,16 @@
-use std::{collections::HashMap, path::PathBuf, time::SystemTime};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
 
+use ra_document::{MetaValue, Metadata};
 use tempfile::TempDir;
 
-use super::{SearchParams, Searcher};
-use crate::{document::ChunkDocument, result::SearchResult, writer::IndexWriter};
+use super::{FacetField, SearchParams, Searcher};
+use crate::{
+    MAX_EXPANSIONS, QueryError, document::ChunkDocument, result::SearchResult, writer::IndexWriter,
+};
 
 /// Helper to create search params that disable aggregation and elbow cutoff.
 /// This provides behavior equivalent to the legacy `search()` method.
@@ -29,6 +36,7 @@ fn make_trees() -> Vec<ra_config::Tree> {
             is_global: false,
             include: vec![],
             exclude: vec![],
+            synonyms: Default::default(),
         },
         ra_config::Tree {
             name: "global".to_string(),
@@ -36,6 +44,7 @@ fn make_trees() -> Vec<ra_config::Tree> {
             is_global: true,
             include: vec![],
             exclude: vec![],
+            synonyms: Default::default(),
         },
     ]
 }
@@ -61,6 +70,8 @@ fn create_test_index(temp: &TempDir) -> Vec<ChunkDocument> {
             byte_end: 200,
             sibling_count: 2,
             mtime: SystemTime::UNIX_EPOCH,
+            meta: Metadata::new(),
+            links: Vec::new(),
         },
         ChunkDocument {
             id: "local:docs/async.md#basics".to_string(),
@@ -81,6 +92,8 @@ fn create_test_index(temp: &TempDir) -> Vec<ChunkDocument> {
             byte_end: 150,
             sibling_count: 1,
             mtime: SystemTime::UNIX_EPOCH,
+            meta: Metadata::new(),
+            links: Vec::new(),
         },
         ChunkDocument {
             id: "global:reference/errors.md#handling".to_string(),
@@ -97,6 +110,8 @@ fn create_test_index(temp: &TempDir) -> Vec<ChunkDocument> {
             byte_end: 100,
             sibling_count: 3,
             mtime: SystemTime::UNIX_EPOCH,
+            meta: Metadata::new(),
+            links: Vec::new(),
         },
     ];
 
@@ -278,12 +293,15 @@ fn normalizes_multi_tree_scores_when_trees_unspecified() {
             byte_start: 0,
             byte_end: 1,
             sibling_count: 0,
+            meta: Metadata::new(),
+            links: Vec::new(),
             score: 10.0,
             snippet: None,
             match_ranges: vec![],
             hierarchy_match_ranges: vec![],
             path_match_ranges: vec![],
             match_details: None,
+            via_link: None,
         },
         SearchCandidate {
             id: "global:docs/b.md".to_string(),
@@ -298,12 +316,15 @@ fn normalizes_multi_tree_scores_when_trees_unspecified() {
             byte_start: 0,
             byte_end: 1,
             sibling_count: 0,
+            meta: Metadata::new(),
+            links: Vec::new(),
             score: 5.0,
             snippet: None,
             match_ranges: vec![],
             hierarchy_match_ranges: vec![],
             path_match_ranges: vec![],
             match_details: None,
+            via_link: None,
         },
     ];
 
@@ -316,8 +337,12 @@ fn normalizes_multi_tree_scores_when_trees_unspecified() {
         ..Default::default()
     };
 
-    let (results, _) =
-        super::pipeline::process_candidates_with_stats(candidates, &params, |_| None);
+    let (results, _) = super::pipeline::process_candidates_with_stats(
+        candidates,
+        &params,
+        |_| None,
+        |_, _| Some(0),
+    );
 
     // Scores should be normalized per tree (both top hits become 1.0).
     let scores: Vec<f32> = results.iter().map(|r| r.candidate().score).collect();
@@ -366,6 +391,8 @@ fn fuzzy_typos_match_and_highlight_actual_terms() {
         byte_end: 100,
         sibling_count: 1,
         mtime: SystemTime::UNIX_EPOCH,
+        meta: Metadata::new(),
+        links: Vec::new(),
     };
 
     let (_temp, mut searcher) = build_index_with_docs(&[doc]);
@@ -404,6 +431,8 @@ fn fuzzy_stemming_ranges_cover_variants() {
         byte_end: 64,
         sibling_count: 1,
         mtime: SystemTime::UNIX_EPOCH,
+        meta: Metadata::new(),
+        links: Vec::new(),
     };
 
     let (_temp, mut searcher) = build_index_with_docs(&[doc]);
@@ -442,6 +471,8 @@ fn hierarchical_fields_roundtrip() {
             byte_end: 30,
             sibling_count: 1,
             mtime: SystemTime::UNIX_EPOCH,
+            meta: Metadata::new(),
+            links: Vec::new(),
         },
         ChunkDocument {
             id: "local:docs/guide.md#section-one".to_string(),
@@ -458,6 +489,8 @@ fn hierarchical_fields_roundtrip() {
             byte_end: 100,
             sibling_count: 2,
             mtime: SystemTime::UNIX_EPOCH,
+            meta: Metadata::new(),
+            links: Vec::new(),
         },
     ];
 
@@ -529,6 +562,522 @@ fn search_aggregated_filters_by_tree() {
     assert_eq!(global[0].candidate().tree, "global");
 }
 
+#[test]
+fn proximity_queries_respect_distance() {
+    let temp = TempDir::new().unwrap();
+    let mut docs = create_test_index(&temp);
+    docs[0].body = "Every retry waits with exponential backoff.".into();
+    docs[1].body = "A retry is attempted at once. Later, a separate section covers the \
+                    scheduling of work, queue sizing and finally backoff."
+        .into();
+    docs[2].body = "Backoff grows after each retry.".into();
+    let (_temp, mut searcher) = build_index_with_docs(&docs);
+    let params = raw_search_params(10);
+
+    let mut ids = |query: &str| {
+        let mut ids: Vec<String> = candidates(searcher.search_aggregated(query, &params).unwrap())
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        ids.sort();
+        ids
+    };
+
+    assert_eq!(ids("retry backoff").len(), 3);
+    assert_eq!(
+        ids("retry NEAR/3 backoff"),
+        vec![docs[2].id.clone(), docs[0].id.clone()]
+    );
+    assert!(ids("\"retry backoff\"").is_empty());
+    assert_eq!(ids("\"retry backoff\"~3"), vec![docs[0].id.clone()]);
+    assert_eq!(ids("\"retry backoff\"~20").len(), 3);
+}
+
+#[test]
+fn wildcard_terms_expand_against_dictionary() {
+    let temp = TempDir::new().unwrap();
+    let mut docs = create_test_index(&temp);
+    docs[0].body = "Authentication uses tokens.".into();
+    docs[1].body = "Authorization checks roles. See max_connections.".into();
+    docs[2].body = "The author wrote a config file for each user.".into();
+    let (_temp, mut searcher) = build_index_with_docs(&docs);
+    let params = raw_search_params(10);
+
+    let (results, stats) = searcher
+        .search_aggregated_with_stats("auth*", &params)
+        .unwrap();
+    assert_eq!(results.len(), 3);
+    let expansion = &stats.wildcard_expansions[0];
+    assert_eq!(expansion.pattern, "auth*");
+    assert!(expansion.terms.contains(&"authent".to_string()));
+    assert!(expansion.terms.contains(&"author".to_string()));
+    assert!(!expansion.truncated);
+
+    // Matches are highlighted through the expanded terms.
+    let authn = results
+        .iter()
+        .find(|r| r.candidate().id == docs[0].id)
+        .unwrap();
+    assert!(!authn.candidate().match_ranges.is_empty());
+
+    let ids = |results: Vec<SearchResult>| -> Vec<String> {
+        candidates(results).into_iter().map(|c| c.id).collect()
+    };
+    assert_eq!(
+        ids(searcher.search_aggregated("body:wrot?", &params).unwrap()),
+        vec![docs[2].id.clone()]
+    );
+    assert_eq!(
+        ids(searcher.search_aggregated("max_conn*", &params).unwrap()),
+        vec![docs[1].id.clone()]
+    );
+    assert!(
+        searcher
+            .search_aggregated("rust auth* -body:auth*", &params)
+            .unwrap()
+            .is_empty()
+    );
+
+    // A pattern matching nothing falls back to its literal text.
+    let (results, stats) = searcher
+        .search_aggregated_with_stats("config?", &params)
+        .unwrap();
+    assert_eq!(ids(results), vec![docs[2].id.clone()]);
+    assert!(stats.wildcard_expansions[0].terms.is_empty());
+
+    // A trailing `?` also keeps the literal word alongside its expansions.
+    let (results, stats) = searcher
+        .search_aggregated_with_stats("use?", &params)
+        .unwrap();
+    let found = ids(results);
+    assert_eq!(found.len(), 2);
+    assert!(found.contains(&docs[0].id) && found.contains(&docs[2].id));
+    assert_eq!(stats.wildcard_expansions[0].terms, vec!["user".to_string()]);
+}
+
+#[test]
+fn wildcard_expansion_is_capped() {
+    let temp = TempDir::new().unwrap();
+    let mut docs = create_test_index(&temp);
+    docs[0].body = (0..MAX_EXPANSIONS + 10)
+        .map(|i| format!("w{i:03}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let (_temp, mut searcher) = build_index_with_docs(&docs);
+
+    let (results, stats) = searcher
+        .search_aggregated_with_stats("w0*", &raw_search_params(10))
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    let expansion = &stats.wildcard_expansions[0];
+    assert_eq!(expansion.terms.len(), MAX_EXPANSIONS);
+    assert!(expansion.truncated);
+}
+
+#[test]
+fn per_term_fuzziness_overrides_default() {
+    let temp = TempDir::new().unwrap();
+    create_test_index(&temp);
+    let mut searcher = searcher(&temp, 1.5);
+    let params = raw_search_params(10);
+
+    // "sistems" is one edit from "systems", "sustams" two.
+    let mut count = |query: &str| searcher.search_aggregated(query, &params).unwrap().len();
+    assert_eq!(count("sistems"), 1);
+    assert_eq!(count("sistems~0"), 0);
+    assert_eq!(count("sustams"), 0);
+    assert_eq!(count("sustams~2"), 1);
+    assert_eq!(count("body:sustams~2 rust~0"), 1);
+
+    // Highlighting follows the per-term distance.
+    let results = searcher.search_aggregated("sustams~2", &params).unwrap();
+    assert!(!results[0].candidate().match_ranges.is_empty());
+}
+
+#[test]
+fn regex_queries_match_stored_text() {
+    let temp = TempDir::new().unwrap();
+    let mut docs = create_test_index(&temp);
+    docs[0].body = "The retry policy backs off.".into();
+    docs[1].body = "Retries   policy is configured per client.".into();
+    docs[2].body = "Never retry without a policy.".into();
+    let (_temp, mut searcher) = build_index_with_docs(&docs);
+    let params = raw_search_params(10);
+
+    let mut ids = |query: &str| {
+        let mut ids: Vec<String> = candidates(searcher.search_aggregated(query, &params).unwrap())
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        ids.sort();
+        ids
+    };
+
+    // Regexes see the original text, not the stemmed and lowercased tokens.
+    assert_eq!(
+        ids(r"body:/retr(y|ies)\s+policy/"),
+        vec![docs[0].id.clone()]
+    );
+    assert_eq!(
+        ids(r"body:/(?i)retr(y|ies)\s+policy/"),
+        vec![docs[1].id.clone(), docs[0].id.clone()]
+    );
+    assert_eq!(ids(r"path:/^docs\/r/"), vec![docs[0].id.clone()]);
+    assert_eq!(ids(r"policy -path:/rust\.md$/").len(), 2);
+}
+
+#[test]
+fn chunk_filters_select_by_structure() {
+    let temp = TempDir::new().unwrap();
+    let mut docs = create_test_index(&temp);
+    docs[0].depth = 0;
+    docs[2].depth = 3;
+    let (_temp, mut searcher) = build_index_with_docs(&docs);
+    let params = raw_search_params(10);
+
+    let mut ids = |query: &str| {
+        let mut ids: Vec<String> = candidates(searcher.search_aggregated(query, &params).unwrap())
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        ids.sort();
+        ids
+    };
+
+    assert_eq!(ids("rust root:true"), vec![docs[0].id.clone()]);
+    assert_eq!(
+        ids("rust depth:<=1"),
+        vec![docs[1].id.clone(), docs[0].id.clone()]
+    );
+    assert_eq!(ids("depth:>1"), vec![docs[2].id.clone()]);
+    assert_eq!(
+        ids("rust doc:local:docs/async.md"),
+        vec![docs[1].id.clone()]
+    );
+    assert_eq!(
+        ids("rust -id:local:docs/rust.md#intro root:false"),
+        vec![docs[2].id.clone(), docs[1].id.clone()]
+    );
+    assert_eq!(
+        ids("id:local:docs/rust.md#intro OR doc:global:reference/errors.md"),
+        vec![docs[2].id.clone(), docs[0].id.clone()]
+    );
+}
+
+#[test]
+fn tag_filters_match_whole_tags() {
+    let temp = TempDir::new().unwrap();
+    let mut docs = create_test_index(&temp);
+    docs[1].tags = vec!["rust-async".to_string()];
+    let (_temp, mut searcher) = build_index_with_docs(&docs);
+    let params = raw_search_params(10);
+
+    let mut ids = |query: &str| {
+        let mut ids: Vec<String> = candidates(searcher.search_aggregated(query, &params).unwrap())
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        ids.sort();
+        ids
+    };
+
+    assert_eq!(ids("tag:rust-async"), vec![docs[1].id.clone()]);
+    assert!(ids("tag:async").is_empty());
+    assert!(ids("tag:Rust").is_empty());
+    assert_eq!(
+        ids("tag:(errors OR programming)"),
+        vec![docs[2].id.clone(), docs[0].id.clone()]
+    );
+    assert_eq!(ids("safety -tag:errors"), vec![docs[0].id.clone()]);
+}
+
+#[test]
+fn nested_tags_match_their_ancestors() {
+    let temp = TempDir::new().unwrap();
+    let mut docs = create_test_index(&temp);
+    docs[1].tags = vec!["area/subarea".to_string()];
+    let (_temp, mut searcher) = build_index_with_docs(&docs);
+    let params = raw_search_params(10);
+
+    let mut ids = |query: &str| {
+        let mut ids: Vec<String> = candidates(searcher.search_aggregated(query, &params).unwrap())
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        ids.sort();
+        ids
+    };
+
+    assert_eq!(ids("tags:area"), vec![docs[1].id.clone()]);
+    assert_eq!(ids("tag:area"), vec![docs[1].id.clone()]);
+    assert_eq!(ids("tag:area/subarea"), vec![docs[1].id.clone()]);
+    assert!(ids("tag:subarea").is_empty());
+}
+
+#[test]
+fn meta_filters_match_frontmatter_values() {
+    let temp = TempDir::new().unwrap();
+    let mut docs = create_test_index(&temp);
+    docs[0].meta = [
+        ("status".to_string(), MetaValue::String("approved".into())),
+        ("version".to_string(), MetaValue::Integer(2)),
+        (
+            "audience".to_string(),
+            MetaValue::List(vec![
+                MetaValue::String("dev".into()),
+                MetaValue::String("ops".into()),
+            ]),
+        ),
+    ]
+    .into();
+    docs[1].meta = [("status".to_string(), MetaValue::String("draft".into()))].into();
+    let (_temp, mut searcher) = build_index_with_docs(&docs);
+    let params = raw_search_params(10);
+
+    let mut ids = |query: &str| {
+        let mut ids: Vec<String> = candidates(searcher.search_aggregated(query, &params).unwrap())
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        ids.sort();
+        ids
+    };
+
+    assert_eq!(ids("meta.status:approved"), vec![docs[0].id.clone()]);
+    assert_eq!(ids("meta.version:2"), vec![docs[0].id.clone()]);
+    assert_eq!(ids("meta.audience:ops"), vec![docs[0].id.clone()]);
+    assert!(ids("meta.status:Approved").is_empty());
+    assert_eq!(
+        ids("rust -meta.status:draft"),
+        vec![docs[2].id.clone(), docs[0].id.clone()]
+    );
+
+    let results = candidates(
+        searcher
+            .search_aggregated("meta.status:approved", &params)
+            .unwrap(),
+    );
+    assert_eq!(results[0].meta, docs[0].meta);
+}
+
+#[test]
+fn links_are_followed_in_both_directions() {
+    let temp = TempDir::new().unwrap();
+    let mut docs = create_test_index(&temp);
+    docs[0].links = vec![docs[1].id.clone(), docs[2].id.clone()];
+    docs[1].links = vec![docs[0].id.clone()];
+    let (_temp, searcher) = build_index_with_docs(&docs);
+
+    assert_eq!(
+        searcher.get_outgoing_links(&docs[0].id).unwrap(),
+        vec![docs[1].id.clone(), docs[2].id.clone()]
+    );
+    assert!(searcher.get_outgoing_links(&docs[2].id).unwrap().is_empty());
+
+    let backlinks = |id: &str| -> Vec<String> {
+        searcher
+            .get_backlinks(id)
+            .unwrap()
+            .into_iter()
+            .map(|c| c.id)
+            .collect()
+    };
+    assert_eq!(backlinks(&docs[2].id), vec![docs[0].id.clone()]);
+    assert_eq!(backlinks(&docs[0].id), vec![docs[1].id.clone()]);
+    assert!(backlinks("local:docs/missing.md").is_empty());
+}
+
+#[test]
+fn facets_count_matching_documents() {
+    let temp = TempDir::new().unwrap();
+    let mut docs = create_test_index(&temp);
+    // A second chunk of the Rust document only counts the document once
+    let mut section = docs[0].clone();
+    section.id = "local:docs/rust.md#safety".to_string();
+    section.body = "Rust guarantees memory safety without a garbage collector.".to_string();
+    docs.push(section);
+    let (_temp, mut searcher) = build_index_with_docs(&docs);
+    let params = SearchParams {
+        facets: vec![FacetField::Tags, FacetField::Tree],
+        ..raw_search_params(1)
+    };
+
+    let (results, stats) = searcher
+        .search_aggregated_with_stats("rust", &params)
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    let tags = &stats.facets[&FacetField::Tags];
+    assert_eq!(tags["rust"], 3);
+    assert_eq!(tags["programming"], 1);
+    assert_eq!(tags["async"], 1);
+    let trees = &stats.facets[&FacetField::Tree];
+    assert_eq!(trees["local"], 2);
+    assert_eq!(trees["global"], 1);
+
+    let (_, stats) = searcher
+        .search_aggregated_with_stats("rust", &raw_search_params(10))
+        .unwrap();
+    assert!(stats.facets.is_empty());
+}
+
+#[test]
+fn query_terms_come_from_the_parsed_query() {
+    let temp = TempDir::new().unwrap();
+    create_test_index(&temp);
+    let mut searcher = searcher(&temp, 1.0);
+    searcher.set_query_macros(HashMap::from([(
+        "concurrency".to_string(),
+        "async OR futures".to_string(),
+    )]));
+    let mut terms = |query: &str| {
+        let expr = searcher.parse_query(query).unwrap().unwrap();
+        searcher.tokenize_query(&expr)
+    };
+
+    assert_eq!(terms("rust @concurrency"), ["rust", "async", "futur"]);
+    assert_eq!(
+        terms("\"mail @concurrency\" title:rust"),
+        ["mail", "concurr"]
+    );
+    assert_eq!(terms("ops@example tokio~1"), ["op", "exampl", "tokio"]);
+}
+
+#[test]
+fn query_macros_expand_saved_queries() {
+    let temp = TempDir::new().unwrap();
+    let docs = create_test_index(&temp);
+    let mut searcher = searcher(&temp, 1.0);
+    searcher.set_query_macros(HashMap::from([
+        ("concurrency".to_string(), "async OR futures".to_string()),
+        (
+            "local_rust".to_string(),
+            "rust -@concurrency tree:local".to_string(),
+        ),
+    ]));
+    let params = raw_search_params(10);
+
+    let results = candidates(searcher.search_aggregated("@concurrency", &params).unwrap());
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].id, docs[1].id);
+    assert!(
+        results[0]
+            .match_ranges
+            .iter()
+            .any(|r| results[0].body[r.clone()].eq_ignore_ascii_case("async"))
+    );
+
+    let results = candidates(searcher.search_aggregated("@local_rust", &params).unwrap());
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].id, docs[0].id);
+
+    // Names that aren't saved queries search as ordinary words
+    let results = candidates(searcher.search_aggregated("@async", &params).unwrap());
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].id, docs[1].id);
+}
+
+#[test]
+fn lenient_search_repairs_queries() {
+    let temp = TempDir::new().unwrap();
+    let docs = create_test_index(&temp);
+    let mut searcher = searcher(&temp, 1.0);
+    let params = raw_search_params(10);
+
+    assert!(searcher.search_aggregated("(async OR", &params).is_err());
+
+    let parsed = searcher.parse_query_lenient("(async OR");
+    let expr = parsed.expr.unwrap();
+    let results = candidates(searcher.search_aggregated_expr(&expr, &params).unwrap());
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].id, docs[1].id);
+    let messages: Vec<&str> = parsed.diagnostics.iter().map(QueryError::message).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "expected closing parenthesis",
+            "unexpected OR (needs expressions on both sides)"
+        ]
+    );
+
+    assert!(searcher.parse_query_lenient("async").diagnostics.is_empty());
+}
+
+#[test]
+fn synonyms_expand_terms_with_lower_weight() {
+    let temp = TempDir::new().unwrap();
+    let docs = create_test_index(&temp);
+    let mut trees = make_trees();
+    trees[0].synonyms = [("fallible".to_string(), vec!["result".to_string()])].into();
+    trees[1].synonyms = [("failure".to_string(), vec!["result".to_string()])].into();
+    let mut searcher = Searcher::open(temp.path(), "english", &trees, 1.0, 0).unwrap();
+    searcher.set_synonyms(&[("performance".to_string(), vec!["futures".to_string()])].into());
+    let params = raw_search_params(10);
+
+    // Both words appear once in the index, so the synonym match ranks below the original
+    let (results, stats) = searcher
+        .search_aggregated_with_stats("performance", &params)
+        .unwrap();
+    let results = candidates(results);
+    let ids: Vec<&str> = results.iter().map(|c| c.id.as_str()).collect();
+    assert_eq!(ids, vec![docs[0].id.as_str(), docs[1].id.as_str()]);
+    assert!(results[0].score > results[1].score);
+    assert_eq!(stats.synonym_expansions[0].synonyms, vec!["futures"]);
+
+    // Tree synonyms only match documents in their own tree
+    let results = candidates(searcher.search_aggregated("fallible", &params).unwrap());
+    assert!(results.is_empty());
+    let (results, stats) = searcher
+        .search_aggregated_with_stats("failure", &params)
+        .unwrap();
+    let results = candidates(results);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].id, docs[2].id);
+    assert_eq!(stats.synonym_expansions[0].tree.as_deref(), Some("global"));
+
+    // Field-scoped terms expand within the field
+    let results = candidates(
+        searcher
+            .search_aggregated("body:performance", &params)
+            .unwrap(),
+    );
+    assert_eq!(results.len(), 2);
+}
+
+#[test]
+fn modified_filters_by_mtime() {
+    let temp = TempDir::new().unwrap();
+    let mut docs = create_test_index(&temp);
+    // 2025-01-15, 2025-03-15, and now.
+    let dates = [1_736_899_200, 1_741_996_800];
+    for (doc, secs) in docs.iter_mut().zip(dates) {
+        doc.mtime = SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+    }
+    docs[2].mtime = SystemTime::now();
+    let (_temp, mut searcher) = build_index_with_docs(&docs);
+    let params = raw_search_params(10);
+
+    let mut ids = |query: &str| {
+        let mut ids: Vec<String> = candidates(searcher.search_aggregated(query, &params).unwrap())
+            .into_iter()
+            .map(|c| c.id)
+            .collect();
+        ids.sort();
+        ids
+    };
+
+    assert_eq!(ids("rust modified:2025-01"), vec![docs[0].id.clone()]);
+    assert_eq!(
+        ids("rust modified:2025-01..2025-03"),
+        vec![docs[1].id.clone(), docs[0].id.clone()]
+    );
+    assert_eq!(
+        ids("rust modified:>2025-01"),
+        vec![docs[2].id.clone(), docs[1].id.clone()]
+    );
+    assert_eq!(ids("modified:<7d"), vec![docs[2].id.clone()]);
+    assert_eq!(ids("rust -modified:<7d").len(), 2);
+}
+
 #[test]
 fn term_idf_behaves_for_common_rare_and_unknown() {
     let temp = TempDir::new().unwrap();
@@ -579,6 +1128,8 @@ mod mlt_tests {
                 byte_end: 200,
                 sibling_count: 1,
                 mtime: SystemTime::UNIX_EPOCH,
+                meta: Metadata::new(),
+                links: Vec::new(),
             },
             ChunkDocument {
                 id: "local:docs/rust-ownership.md".to_string(),
@@ -598,6 +1149,8 @@ mod mlt_tests {
                 byte_end: 200,
                 sibling_count: 1,
                 mtime: SystemTime::UNIX_EPOCH,
+                meta: Metadata::new(),
+                links: Vec::new(),
             },
             ChunkDocument {
                 id: "local:docs/python-intro.md".to_string(),
@@ -617,6 +1170,8 @@ mod mlt_tests {
                 byte_end: 200,
                 sibling_count: 1,
                 mtime: SystemTime::UNIX_EPOCH,
+                meta: Metadata::new(),
+                links: Vec::new(),
             },
             ChunkDocument {
                 id: "global:docs/rust-web.md".to_string(),
@@ -636,6 +1191,8 @@ mod mlt_tests {
                 byte_end: 200,
                 sibling_count: 1,
                 mtime: SystemTime::UNIX_EPOCH,
+                meta: Metadata::new(),
+                links: Vec::new(),
             },
         ];
 
@@ -884,6 +1441,8 @@ mod pipeline_integration_tests {
                 byte_end: 50,
                 sibling_count: 1,
                 mtime: SystemTime::UNIX_EPOCH,
+                meta: Metadata::new(),
+                links: Vec::new(),
             },
             ChunkDocument {
                 id: "local:docs/doc.md#rust-intro".to_string(),
@@ -900,6 +1459,8 @@ mod pipeline_integration_tests {
                 byte_end: 120,
                 sibling_count: 2,
                 mtime: SystemTime::UNIX_EPOCH,
+                meta: Metadata::new(),
+                links: Vec::new(),
             },
             ChunkDocument {
                 id: "local:docs/doc.md#rust-ownership".to_string(),
@@ -916,6 +1477,8 @@ mod pipeline_integration_tests {
                 byte_end: 180,
                 sibling_count: 2,
                 mtime: SystemTime::UNIX_EPOCH,
+                meta: Metadata::new(),
+                links: Vec::new(),
             },
             // Another document for comparison
             ChunkDocument {
@@ -933,6 +1496,8 @@ mod pipeline_integration_tests {
                 byte_end: 50,
                 sibling_count: 1,
                 mtime: SystemTime::UNIX_EPOCH,
+                meta: Metadata::new(),
+                links: Vec::new(),
             },
         ];
 
@@ -1013,7 +1578,7 @@ mod pipeline_integration_tests {
             "Should find the rust document in expr search"
         );
 
-        // 3. Both entry points should use the same pipeline (process_candidates)
+        // 3. Both entry points should use the same pipeline (process_candidates_with_stats)
         // even if scoring differences lead to different aggregation outcomes.
         // The key verification is that both complete without error and return results.
         assert!(
@@ -1085,3 +1650,130 @@ mod pipeline_integration_tests {
         );
     }
 }
+
+mod neighborhood_tests {
+    use super::*;
+    use crate::Neighborhood;
+
+    /// Builds a chunk of `local:docs/guide.md`; an empty slug names the document node.
+    fn chunk(
+        slug: &str,
+        parent: Option<&str>,
+        position: usize,
+        sibling_count: usize,
+    ) -> ChunkDocument {
+        let doc_id = "local:docs/guide.md".to_string();
+        let id_for = |slug: &str| {
+            if slug.is_empty() {
+                doc_id.clone()
+            } else {
+                format!("{doc_id}#{slug}")
+            }
+        };
+        ChunkDocument {
+            id: id_for(slug),
+            doc_id: doc_id.clone(),
+            parent_id: parent.map(id_for),
+            hierarchy: vec![slug.to_string()],
+            depth: if slug.is_empty() { 0 } else { 1 },
+            tags: vec![],
+            path: "docs/guide.md".to_string(),
+            tree: "local".to_string(),
+            body: format!("Body of {slug}."),
+            position,
+            byte_start: position * 10,
+            byte_end: position * 10 + 10,
+            sibling_count,
+            mtime: SystemTime::UNIX_EPOCH,
+            meta: Metadata::new(),
+            links: Vec::new(),
+        }
+    }
+
+    /// Indexes a document with two sections, the first holding two subsections.
+    fn guide_searcher() -> (TempDir, Searcher) {
+        build_index_with_docs(&[
+            chunk("", None, 0, 1),
+            chunk("b", Some(""), 4, 2),
+            chunk("a", Some(""), 1, 2),
+            chunk("a-2", Some("a"), 3, 2),
+            chunk("a-1", Some("a"), 2, 2),
+        ])
+    }
+
+    /// Returns the slugs of the retrieved chunks, with `-` for the document node.
+    fn slugs(searcher: &Searcher, id: &str, neighborhood: &Neighborhood) -> Vec<String> {
+        searcher
+            .get_neighborhood(id, neighborhood)
+            .unwrap()
+            .into_iter()
+            .map(|c| c.id.split_once('#').map_or("-", |(_, s)| s).to_string())
+            .collect()
+    }
+
+    #[test]
+    fn empty_neighborhood_returns_target() {
+        let (_temp, searcher) = guide_searcher();
+        let id = "local:docs/guide.md#a-1";
+        assert_eq!(slugs(&searcher, id, &Neighborhood::default()), ["a-1"]);
+    }
+
+    #[test]
+    fn parent_siblings_and_children() {
+        let (_temp, searcher) = guide_searcher();
+        let id = "local:docs/guide.md#a";
+
+        let parent = Neighborhood {
+            parent: true,
+            ..Default::default()
+        };
+        assert_eq!(slugs(&searcher, id, &parent), ["-", "a"]);
+
+        let siblings = Neighborhood {
+            siblings: true,
+            ..Default::default()
+        };
+        assert_eq!(slugs(&searcher, id, &siblings), ["a", "b"]);
+
+        let children = Neighborhood {
+            children: true,
+            ..Default::default()
+        };
+        assert_eq!(slugs(&searcher, id, &children), ["a", "a-1", "a-2"]);
+    }
+
+    #[test]
+    fn adjacent_chunks_follow_document_order() {
+        let (_temp, searcher) = guide_searcher();
+        let id = "local:docs/guide.md#a-1";
+
+        let around = Neighborhood {
+            prev: 1,
+            next: 5,
+            ..Default::default()
+        };
+        assert_eq!(slugs(&searcher, id, &around), ["a", "a-1", "a-2", "b"]);
+    }
+
+    #[test]
+    fn document_node_has_no_parent_or_siblings() {
+        let (_temp, searcher) = guide_searcher();
+        let all = Neighborhood {
+            parent: true,
+            siblings: true,
+            ..Default::default()
+        };
+        assert_eq!(slugs(&searcher, "local:docs/guide.md", &all), ["-"]);
+    }
+
+    #[test]
+    fn missing_chunk_returns_empty() {
+        let (_temp, searcher) = guide_searcher();
+        assert!(
+            searcher
+                .get_neighborhood("local:docs/guide.md#nope", &Neighborhood::default())
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -1,7 +1,11 @@
 //! Data structures returned by search.
 
-use std::{collections::HashMap, ops::Range};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Range,
+};
 
+use ra_document::Metadata;
 use serde::Serialize;
 
 /// A serializable byte range.
@@ -107,6 +111,12 @@ pub struct SearchCandidate {
     pub byte_end: u64,
     /// Number of siblings including this node.
     pub sibling_count: u64,
+    /// Other frontmatter keys of the chunk's document.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub meta: Metadata,
+    /// IDs of the chunks this chunk links to.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<String>,
     /// Search relevance score (after boosting).
     pub score: f32,
     /// Optional snippet with query terms highlighted.
@@ -139,6 +149,9 @@ pub struct SearchCandidate {
     /// Detailed match information for verbose output.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub match_details: Option<MatchDetails>,
+    /// ID of the result whose link pulled this chunk into the results.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub via_link: Option<String>,
 }
 
 impl SearchCandidate {
@@ -3,13 +3,15 @@
 //! Determines the current state of the index relative to configuration
 //! and provides functions for reading/writing the stored config hash.
 
-use std::{fs, io, path::Path};
+use std::{collections::BTreeMap, fs, io, path::Path};
 
 use ra_config::Config;
 
 use crate::{
+    IndexError,
     config_hash::compute_config_hash,
-    location::{config_hash_path, index_directory},
+    location::{config_hash_path, index_directory, manifest_path},
+    manifest::Manifest,
 };
 
 /// Status of the search index.
@@ -97,6 +99,17 @@ pub fn detect_index_status(config: &Config) -> IndexStatus {
     }
 }
 
+/// Returns the number of indexed files per tree, as recorded in the manifest.
+///
+/// Returns an empty map if no index location can be determined or no manifest exists yet.
+pub fn indexed_file_counts(config: &Config) -> Result<BTreeMap<String, usize>, IndexError> {
+    let Some(index_dir) = index_directory(config) else {
+        return Ok(BTreeMap::new());
+    };
+    let manifest = Manifest::load(&manifest_path(&index_dir))?;
+    Ok(manifest.file_counts_by_tree())
+}
+
 /// Checks if an index exists at the given path.
 #[cfg(test)]
 pub fn index_exists(index_dir: &Path) -> bool {
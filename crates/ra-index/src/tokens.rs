@@ -0,0 +1,121 @@
+//! Approximate token counting for result budgets.
+//!
+//! Token counts use the `cl100k_base` encoding. Different models use different
+//! tokenizers, but cl100k_base typically lands within 10-20% of other modern tokenizers,
+//! which is close enough for fitting results into a context window.
+
+use tiktoken_rs::cl100k_base_singleton;
+
+use crate::result::SearchResult;
+
+/// Counts the tokens in a piece of text.
+pub fn count_tokens(text: &str) -> usize {
+    cl100k_base_singleton().encode_ordinary(text).len()
+}
+
+/// Selects results in order until `limit` is reached, skipping any result that would push
+/// the running total over `max_tokens`.
+///
+/// `token_count` measures a result given the tokens left in the budget, returning None if it
+/// does not fit; callers typically count the full chunk content that will be shown to the
+/// reader, and can reject a result cheaply before measuring it in full. Returns the selected
+/// results and the number of tokens they use.
+pub fn fill_token_budget<T>(
+    results: Vec<SearchResult>,
+    limit: usize,
+    max_tokens: usize,
+    token_count: T,
+) -> (Vec<SearchResult>, usize)
+where
+    T: Fn(&SearchResult, usize) -> Option<usize>,
+{
+    let mut selected = Vec::new();
+    let mut used = 0;
+
+    for result in results {
+        if selected.len() >= limit || used == max_tokens {
+            break;
+        }
+        let Some(tokens) = token_count(&result, max_tokens - used) else {
+            continue;
+        };
+        used += tokens;
+        selected.push(result);
+    }
+
+    (selected, used)
+}
+
+#[cfg(test)]
+mod test {
+    use ra_document::Metadata;
+
+    use super::*;
+    use crate::search::SearchCandidate;
+
+    fn body_tokens(result: &SearchResult, remaining: usize) -> Option<usize> {
+        Some(count_tokens(&result.candidate().body)).filter(|&tokens| tokens <= remaining)
+    }
+
+    fn make_result(id: &str, body: &str) -> SearchResult {
+        SearchResult::single(SearchCandidate {
+            id: id.to_string(),
+            doc_id: id.to_string(),
+            parent_id: None,
+            hierarchy: vec![id.to_string()],
+            depth: 0,
+            tree: "docs".to_string(),
+            path: format!("{id}.md"),
+            body: body.to_string(),
+            position: 0,
+            byte_start: 0,
+            byte_end: body.len() as u64,
+            sibling_count: 1,
+            meta: Metadata::new(),
+            links: Vec::new(),
+            score: 1.0,
+            snippet: None,
+            match_ranges: vec![],
+            hierarchy_match_ranges: vec![],
+            path_match_ranges: vec![],
+            match_details: None,
+            via_link: None,
+        })
+    }
+
+    #[test]
+    fn counts_tokens() {
+        assert_eq!(count_tokens(""), 0);
+        assert_eq!(count_tokens("hello world"), 2);
+    }
+
+    #[test]
+    fn skips_results_that_overflow() {
+        let long = "word ".repeat(50);
+        let results = vec![
+            make_result("a", "hello world"),
+            make_result("b", &long),
+            make_result("c", "hello again"),
+        ];
+
+        let (selected, used) = fill_token_budget(results, 10, 10, body_tokens);
+
+        let ids: Vec<_> = selected.iter().map(|r| r.candidate().id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "c"]);
+        assert_eq!(used, 4);
+    }
+
+    #[test]
+    fn respects_limit() {
+        let results = vec![
+            make_result("a", "one"),
+            make_result("b", "two"),
+            make_result("c", "three"),
+        ];
+
+        let (selected, used) = fill_token_budget(results, 2, 1000, body_tokens);
+
+        assert_eq!(selected.len(), 2);
+        assert_eq!(used, 2);
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::{fs, path::Path, time::UNIX_EPOCH};
 
+use ra_document::tag_hierarchy;
 use tantivy::{
     DateTime, Index, IndexWriter as TantivyIndexWriter, TantivyDocument, directory::MmapDirectory,
 };
@@ -10,6 +11,7 @@ use crate::{
     analyzer::{RA_TOKENIZER, build_analyzer_from_name},
     document::ChunkDocument,
     error::IndexError,
+    meta,
     schema::IndexSchema,
 };
 
@@ -121,9 +123,27 @@ impl IndexWriter {
         let tags_str = doc.tags.join(" ");
         tantivy_doc.add_text(self.schema.tags, &tags_str);
 
+        // Add each tag untokenized as well, for exact `tag:` filters and facet counts. Nested
+        // tags also add their ancestors, so `tag:area` matches `area/subarea`.
+        let mut exact_tags: Vec<&str> = Vec::new();
+        for tag in &doc.tags {
+            for tag in tag_hierarchy(tag) {
+                if !exact_tags.contains(&tag) {
+                    exact_tags.push(tag);
+                    tantivy_doc.add_text(self.schema.tag, tag);
+                }
+            }
+        }
+
         tantivy_doc.add_text(self.schema.path, &doc.path);
         tantivy_doc.add_text(self.schema.tree, &doc.tree);
         tantivy_doc.add_text(self.schema.body, &doc.body);
+        if !doc.meta.is_empty() {
+            tantivy_doc.add_object(self.schema.meta, meta::to_object(&doc.meta));
+        }
+        for link in &doc.links {
+            tantivy_doc.add_text(self.schema.links, link);
+        }
 
         // Hierarchical metadata
         tantivy_doc.add_u64(self.schema.depth, doc.depth as u64);
@@ -203,6 +223,7 @@ impl IndexWriter {
 mod test {
     use std::{path::Path, time::SystemTime};
 
+    use ra_document::Metadata;
     use tantivy::Index;
     use tempfile::TempDir;
 
@@ -230,6 +251,8 @@ mod test {
             byte_end: 150,
             sibling_count: 2,
             mtime: SystemTime::UNIX_EPOCH,
+            meta: Metadata::new(),
+            links: Vec::new(),
         }
     }
 
@@ -277,6 +300,8 @@ mod test {
                 byte_end: 50,
                 sibling_count: 1,
                 mtime: SystemTime::UNIX_EPOCH,
+                meta: Metadata::new(),
+                links: Vec::new(),
             },
             ChunkDocument {
                 id: "local:b.md#two".to_string(),
@@ -293,6 +318,8 @@ mod test {
                 byte_end: 60,
                 sibling_count: 1,
                 mtime: SystemTime::UNIX_EPOCH,
+                meta: Metadata::new(),
+                links: Vec::new(),
             },
         ];
 
@@ -4,15 +4,55 @@
 
 use std::fmt;
 
+use serde::{Deserialize, Serialize};
+
+use crate::{date::DateFilter, filter::ChunkFilter, json::JsonExpr};
+
 /// A parsed query expression.
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Serializes as the JSON described in [`parse_json`](crate::parse_json).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "JsonExpr", into = "JsonExpr")]
 pub enum QueryExpr {
     /// A single search term.
     Term(String),
 
+    /// A term with its own fuzzy matching edit distance: `term~N`. `~0` matches exactly.
+    Fuzzy {
+        /// The search term.
+        term: String,
+        /// The maximum edit distance, overriding the configured fuzzy distance.
+        distance: u8,
+    },
+
+    /// A term with wildcards: `*` matches any run of characters and `?` matches one.
+    Wildcard(String),
+
     /// An exact phrase (sequence of terms).
     Phrase(Vec<String>),
 
+    /// A regular expression matched against a field's text. Only valid inside a field.
+    Regex(String),
+
+    /// A phrase whose words may be spread apart or reordered: `"a b"~N`.
+    Proximity {
+        /// The words of the phrase.
+        words: Vec<String>,
+        /// How many positions the words may move in total to match.
+        slop: u32,
+    },
+
+    /// Two terms or phrases within a number of words of each other, in either order:
+    /// `a NEAR/N b`.
+    Near {
+        /// The first operand.
+        left: Box<Self>,
+        /// The second operand.
+        right: Box<Self>,
+        /// The maximum number of words between the operands.
+        distance: u32,
+    },
+
     /// Negation: results must NOT match this expression.
     Not(Box<Self>),
 
@@ -37,6 +77,53 @@ pub enum QueryExpr {
         /// The boost factor (e.g., 2.5 means 2.5x the normal score).
         factor: f32,
     },
+
+    /// Modification-time filter: matches chunks whose file was modified within a date range.
+    Modified(DateFilter),
+
+    /// Structural filter: matches chunks by depth, document, ID or whether they are a root.
+    Filter(ChunkFilter),
+}
+
+/// A comparison operator prefixing a filter value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    /// No operator: the value itself.
+    Eq,
+    /// `<`
+    Lt,
+    /// `<=`
+    Le,
+    /// `>`
+    Gt,
+    /// `>=`
+    Ge,
+}
+
+impl Comparison {
+    /// Splits a leading comparison operator from `text`, defaulting to `Eq`.
+    pub fn split_prefix(text: &str) -> (Self, &str) {
+        [
+            ("<=", Self::Le),
+            (">=", Self::Ge),
+            ("<", Self::Lt),
+            (">", Self::Gt),
+        ]
+        .into_iter()
+        .find_map(|(symbol, op)| text.strip_prefix(symbol).map(|rest| (op, rest)))
+        .unwrap_or((Self::Eq, text))
+    }
+
+    /// Returns the operator as written in a query.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Eq => "",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+        }
+    }
 }
 
 impl QueryExpr {
@@ -87,7 +174,24 @@ impl QueryExpr {
         let prefix = "  ".repeat(indent);
         match self {
             Self::Term(s) => writeln!(f, "{prefix}Term({s:?})"),
+            Self::Fuzzy { term, distance } => {
+                writeln!(f, "{prefix}Fuzzy({term:?}, distance={distance})")
+            }
+            Self::Wildcard(s) => writeln!(f, "{prefix}Wildcard({s:?})"),
             Self::Phrase(words) => writeln!(f, "{prefix}Phrase({words:?})"),
+            Self::Regex(pattern) => writeln!(f, "{prefix}Regex({pattern:?})"),
+            Self::Proximity { words, slop } => {
+                writeln!(f, "{prefix}Proximity({words:?}, slop={slop})")
+            }
+            Self::Near {
+                left,
+                right,
+                distance,
+            } => {
+                writeln!(f, "{prefix}Near({distance})")?;
+                left.fmt_tree(f, indent + 1)?;
+                right.fmt_tree(f, indent + 1)
+            }
             Self::Not(inner) => {
                 writeln!(f, "{prefix}Not")?;
                 inner.fmt_tree(f, indent + 1)
@@ -114,6 +218,8 @@ impl QueryExpr {
                 writeln!(f, "{prefix}Boost({factor})")?;
                 expr.fmt_tree(f, indent + 1)
             }
+            Self::Modified(filter) => writeln!(f, "{prefix}Modified({filter})"),
+            Self::Filter(filter) => writeln!(f, "{prefix}Filter({filter})"),
         }
     }
 
@@ -137,8 +243,14 @@ impl QueryExpr {
     /// Helper to recursively collect terms.
     fn collect_terms(&self, terms: &mut Vec<String>) {
         match self {
-            Self::Term(s) => terms.push(s.clone()),
-            Self::Phrase(words) => terms.extend(words.iter().cloned()),
+            Self::Term(s) | Self::Fuzzy { term: s, .. } => terms.push(s.clone()),
+            Self::Phrase(words) | Self::Proximity { words, .. } => {
+                terms.extend(words.iter().cloned())
+            }
+            Self::Near { left, right, .. } => {
+                left.collect_terms(terms);
+                right.collect_terms(terms);
+            }
             Self::Not(inner) => inner.collect_terms(terms),
             Self::And(exprs) | Self::Or(exprs) => {
                 for expr in exprs {
@@ -147,14 +259,28 @@ impl QueryExpr {
             }
             Self::Field { expr, .. } => expr.collect_terms(terms),
             Self::Boost { expr, .. } => expr.collect_terms(terms),
+            Self::Wildcard(_) | Self::Regex(_) | Self::Modified(_) | Self::Filter(_) => {}
         }
     }
 
     /// Internal helper for query string formatting.
     fn fmt_query_string(&self, in_field: bool) -> String {
         match self {
-            Self::Term(s) => s.clone(),
-            Self::Phrase(words) => format!("\"{}\"", words.join(" ")),
+            Self::Term(s) => quote_term(s),
+            Self::Wildcard(s) => s.clone(),
+            Self::Fuzzy { term, distance } => format!("{term}~{distance}"),
+            Self::Phrase(words) => format!("\"{}\"", phrase_text(words)),
+            Self::Regex(pattern) => format!("/{}/", pattern.replace('/', "\\/")),
+            Self::Proximity { words, slop } => format!("\"{}\"~{slop}", phrase_text(words)),
+            Self::Near {
+                left,
+                right,
+                distance,
+            } => format!(
+                "{} NEAR/{distance} {}",
+                left.fmt_query_string(in_field),
+                right.fmt_query_string(in_field)
+            ),
             Self::Not(inner) => format!("-{}", inner.fmt_query_string(in_field)),
             Self::And(exprs) => {
                 if exprs.is_empty() {
@@ -188,10 +314,36 @@ impl QueryExpr {
             Self::Boost { expr, factor } => {
                 format!("{}^{}", expr.fmt_query_string(in_field), factor)
             }
+            Self::Modified(filter) => format!("modified:{filter}"),
+            Self::Filter(filter) => filter.to_string(),
         }
     }
 }
 
+/// Quotes a term that would not read back as the same single term, such as one containing
+/// spaces or query syntax, or one spelling a keyword like `OR`.
+fn quote_term(term: &str) -> String {
+    let upper = term.to_ascii_uppercase();
+    let needs_quotes = term.is_empty()
+        || term.contains(|c: char| c.is_whitespace() || "()\"^:*?".contains(c))
+        || term.starts_with(['-', '/', '@'])
+        || upper == "OR"
+        || upper.starts_with("NEAR/")
+        || term
+            .rsplit_once('~')
+            .is_some_and(|(_, count)| count.bytes().all(|b| b.is_ascii_digit()));
+    if needs_quotes {
+        format!("\"{}\"", term.replace('"', ""))
+    } else {
+        term.to_string()
+    }
+}
+
+/// Joins phrase words for quoting, dropping quote characters that would end the phrase early.
+fn phrase_text(words: &[String]) -> String {
+    words.join(" ").replace('"', "")
+}
+
 impl fmt::Display for QueryExpr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.fmt_tree(f, 0)
@@ -248,6 +400,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn comparison_prefixes() {
+        assert_eq!(Comparison::split_prefix("<=2"), (Comparison::Le, "2"));
+        assert_eq!(Comparison::split_prefix(">2"), (Comparison::Gt, "2"));
+        assert_eq!(Comparison::split_prefix("2"), (Comparison::Eq, "2"));
+    }
+
     #[test]
     fn or_single_element_unwraps() {
         let single = QueryExpr::or(vec![QueryExpr::Term("a".into())]);
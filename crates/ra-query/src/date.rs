@@ -0,0 +1,449 @@
+//! Date filters for modification-time queries.
+//!
+//! A date is either a calendar period at year, month or day granularity (`2025`, `2025-01`,
+//! `2025-01-15`, always UTC), or an age relative to the time the query runs (`12h`, `30d`,
+//! `2w`, `6m`, `1y`). A filter compares against a single date (`>2025-01-01`, `<30d`) or spans
+//! a range between two dates (`2025-01..2025-03`, `..2024`).
+//!
+//! Filters are resolved to half-open `[start, end)` intervals of Unix seconds only when
+//! compiled, so relative ages are measured from the moment the query runs.
+
+use std::fmt;
+
+use chrono::{Days, Months, NaiveDate, NaiveTime};
+
+use crate::ast::Comparison;
+
+/// Seconds in a day.
+const DAY: i64 = 86_400;
+
+/// A unit for relative ages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateUnit {
+    /// Hours (`h`).
+    Hour,
+    /// Days (`d`).
+    Day,
+    /// Weeks (`w`).
+    Week,
+    /// Months of 30 days (`m`).
+    Month,
+    /// Years of 365 days (`y`).
+    Year,
+}
+
+impl DateUnit {
+    /// Parses a unit suffix.
+    fn from_suffix(suffix: &str) -> Option<Self> {
+        match suffix {
+            "h" => Some(Self::Hour),
+            "d" => Some(Self::Day),
+            "w" => Some(Self::Week),
+            "m" => Some(Self::Month),
+            "y" => Some(Self::Year),
+            _ => None,
+        }
+    }
+
+    /// Returns the unit suffix.
+    fn suffix(self) -> char {
+        match self {
+            Self::Hour => 'h',
+            Self::Day => 'd',
+            Self::Week => 'w',
+            Self::Month => 'm',
+            Self::Year => 'y',
+        }
+    }
+
+    /// Returns the length of the unit in seconds.
+    fn seconds(self) -> i64 {
+        match self {
+            Self::Hour => 3_600,
+            Self::Day => DAY,
+            Self::Week => 7 * DAY,
+            Self::Month => 30 * DAY,
+            Self::Year => 365 * DAY,
+        }
+    }
+}
+
+/// A date in a filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateValue {
+    /// A UTC calendar period: a whole year, month or day.
+    ///
+    /// The fields must name a real date, as [`DateFilter::parse`] ensures.
+    Calendar {
+        /// Year.
+        year: i32,
+        /// Month (1-12), if the period is a month or a day.
+        month: Option<u32>,
+        /// Day of the month, if the period is a day.
+        day: Option<u32>,
+    },
+    /// The instant a given age before now.
+    Ago {
+        /// Number of units.
+        amount: u32,
+        /// Unit of the age.
+        unit: DateUnit,
+    },
+}
+
+impl DateValue {
+    /// Parses a calendar date or relative age.
+    fn parse(text: &str) -> Result<Self, String> {
+        if let Some(value) = Self::parse_ago(text) {
+            return Ok(value);
+        }
+        Self::parse_calendar(text)
+    }
+
+    /// Parses a relative age such as `30d`, returning `None` if `text` is not of that form.
+    fn parse_ago(text: &str) -> Option<Self> {
+        let split = text.find(|c: char| !c.is_ascii_digit())?;
+        let (digits, suffix) = text.split_at(split);
+        let unit = DateUnit::from_suffix(suffix)?;
+        let amount = digits.parse().ok()?;
+        Some(Self::Ago { amount, unit })
+    }
+
+    /// Parses a calendar date: `YYYY`, `YYYY-MM` or `YYYY-MM-DD`.
+    fn parse_calendar(text: &str) -> Result<Self, String> {
+        let invalid = || {
+            format!("invalid date '{text}' (expected YYYY, YYYY-MM, YYYY-MM-DD or an age like 30d)")
+        };
+        let parts: Vec<&str> = text.split('-').collect();
+        let widths = [4, 2, 2];
+        if parts.len() > widths.len()
+            || parts
+                .iter()
+                .zip(widths)
+                .any(|(p, w)| p.len() != w || !p.bytes().all(|b| b.is_ascii_digit()))
+        {
+            return Err(invalid());
+        }
+
+        let year: i32 = parts[0].parse().map_err(|_| invalid())?;
+        let month: Option<u32> = parts
+            .get(1)
+            .map(|p| p.parse())
+            .transpose()
+            .map_err(|_| invalid())?;
+        let day: Option<u32> = parts
+            .get(2)
+            .map(|p| p.parse())
+            .transpose()
+            .map_err(|_| invalid())?;
+
+        if let Some(m) = month
+            && !(1..=12).contains(&m)
+        {
+            return Err(format!("invalid month in date '{text}'"));
+        }
+        if let (Some(m), Some(d)) = (month, day)
+            && NaiveDate::from_ymd_opt(year, m, d).is_none()
+        {
+            return Err(format!("invalid day in date '{text}'"));
+        }
+
+        Ok(Self::Calendar { year, month, day })
+    }
+
+    /// Returns the `[start, end)` interval this date covers, in Unix seconds.
+    ///
+    /// A relative age is a single instant, so its start and end are equal.
+    fn period(&self, now: i64) -> (i64, i64) {
+        match *self {
+            Self::Calendar { year, month, day } => {
+                let start = NaiveDate::from_ymd_opt(year, month.unwrap_or(1), day.unwrap_or(1))
+                    .expect("calendar dates are validated when parsed");
+                let end = match (month, day) {
+                    (Some(_), Some(_)) => start.checked_add_days(Days::new(1)),
+                    (Some(_), None) => start.checked_add_months(Months::new(1)),
+                    _ => start.checked_add_months(Months::new(12)),
+                }
+                .expect("four-digit years are far from chrono's limits");
+                (unix_seconds(start), unix_seconds(end))
+            }
+            Self::Ago { amount, unit } => {
+                let instant = now - i64::from(amount) * unit.seconds();
+                (instant, instant)
+            }
+        }
+    }
+}
+
+impl fmt::Display for DateValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Calendar { year, month, day } => {
+                write!(f, "{year:04}")?;
+                if let Some(m) = month {
+                    write!(f, "-{m:02}")?;
+                }
+                if let Some(d) = day {
+                    write!(f, "-{d:02}")?;
+                }
+                Ok(())
+            }
+            Self::Ago { amount, unit } => write!(f, "{amount}{}", unit.suffix()),
+        }
+    }
+}
+
+/// A filter on modification time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateFilter {
+    /// Compares against a single date.
+    ///
+    /// For calendar dates the comparison is chronological: `>2025-01` means after January
+    /// 2025, and a bare date means within that period. For relative ages it compares age:
+    /// `<30d` means modified less than 30 days ago, and a bare age means within that age.
+    Compare {
+        /// The comparison operator.
+        op: Comparison,
+        /// The date compared against.
+        value: DateValue,
+    },
+    /// Spans from the start of one date to the end of another, oldest first.
+    ///
+    /// Either side may be omitted for an open-ended range.
+    Range {
+        /// The oldest date in the range.
+        from: Option<DateValue>,
+        /// The newest date in the range.
+        to: Option<DateValue>,
+    },
+}
+
+impl DateFilter {
+    /// Parses a filter such as `>=2025-01`, `<30d`, `2025-01..2025-03` or `2025`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        if let Some((from, to)) = text.split_once("..") {
+            if from.is_empty() && to.is_empty() {
+                return Err("date range needs at least one bound".into());
+            }
+            let bound = |s: &str| (!s.is_empty()).then(|| DateValue::parse(s)).transpose();
+            return Ok(Self::Range {
+                from: bound(from)?,
+                to: bound(to)?,
+            });
+        }
+
+        let (op, rest) = Comparison::split_prefix(text);
+        if rest.is_empty() {
+            return Err(format!("expected date after '{text}'"));
+        }
+        Ok(Self::Compare {
+            op,
+            value: DateValue::parse(rest)?,
+        })
+    }
+
+    /// Resolves the filter to a half-open `[start, end)` interval of Unix seconds, given the
+    /// current time. `None` leaves that side of the interval unbounded.
+    pub fn bounds(&self, now: i64) -> (Option<i64>, Option<i64>) {
+        match self {
+            Self::Compare {
+                op,
+                value: value @ DateValue::Calendar { .. },
+            } => {
+                let (start, end) = value.period(now);
+                match op {
+                    Comparison::Eq => (Some(start), Some(end)),
+                    Comparison::Gt => (Some(end), None),
+                    Comparison::Ge => (Some(start), None),
+                    Comparison::Lt => (None, Some(start)),
+                    Comparison::Le => (None, Some(end)),
+                }
+            }
+            Self::Compare {
+                op,
+                value: value @ DateValue::Ago { .. },
+            } => {
+                let (instant, _) = value.period(now);
+                match op {
+                    Comparison::Eq | Comparison::Le => (Some(instant), None),
+                    Comparison::Lt => (Some(instant + 1), None),
+                    Comparison::Gt => (None, Some(instant)),
+                    Comparison::Ge => (None, Some(instant + 1)),
+                }
+            }
+            Self::Range { from, to } => {
+                (from.map(|v| v.period(now).0), to.map(|v| v.period(now).1))
+            }
+        }
+    }
+}
+
+impl fmt::Display for DateFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Compare { op, value } => write!(f, "{}{value}", op.symbol()),
+            Self::Range { from, to } => {
+                if let Some(from) = from {
+                    write!(f, "{from}")?;
+                }
+                write!(f, "..")?;
+                if let Some(to) = to {
+                    write!(f, "{to}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Returns the Unix time of midnight UTC at the start of `date`.
+fn unix_seconds(date: NaiveDate) -> i64 {
+    date.and_time(NaiveTime::MIN).and_utc().timestamp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2025-06-15T00:00:00Z.
+    const NOW: i64 = 1_749_945_600;
+
+    fn calendar(year: i32, month: Option<u32>, day: Option<u32>) -> DateValue {
+        DateValue::Calendar { year, month, day }
+    }
+
+    /// Returns the Unix time of midnight UTC at the start of a date.
+    fn midnight(year: i32, month: u32, day: u32) -> i64 {
+        unix_seconds(NaiveDate::from_ymd_opt(year, month, day).unwrap())
+    }
+
+    #[test]
+    fn unix_seconds_at_midnight() {
+        assert_eq!(midnight(1970, 1, 1), 0);
+        assert_eq!(midnight(2000, 3, 1), 11_017 * DAY);
+        assert_eq!(midnight(2025, 6, 15), NOW);
+        assert_eq!(midnight(1969, 12, 31), -DAY);
+    }
+
+    #[test]
+    fn parses_calendar_dates() {
+        assert_eq!(
+            DateValue::parse("2025").unwrap(),
+            calendar(2025, None, None)
+        );
+        assert_eq!(
+            DateValue::parse("2025-01").unwrap(),
+            calendar(2025, Some(1), None)
+        );
+        assert_eq!(
+            DateValue::parse("2024-02-29").unwrap(),
+            calendar(2024, Some(2), Some(29))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_dates() {
+        for text in [
+            "25",
+            "2025-1",
+            "2025-13",
+            "2025-02-29",
+            "2025-01-01-01",
+            "soon",
+            "30x",
+        ] {
+            assert!(DateValue::parse(text).is_err(), "{text} should be rejected");
+        }
+    }
+
+    #[test]
+    fn parses_relative_ages() {
+        assert_eq!(
+            DateValue::parse("30d").unwrap(),
+            DateValue::Ago {
+                amount: 30,
+                unit: DateUnit::Day
+            }
+        );
+        assert_eq!(
+            DateValue::parse("2w").unwrap(),
+            DateValue::Ago {
+                amount: 2,
+                unit: DateUnit::Week
+            }
+        );
+    }
+
+    #[test]
+    fn parses_filters() {
+        assert_eq!(
+            DateFilter::parse(">=2025-01").unwrap(),
+            DateFilter::Compare {
+                op: Comparison::Ge,
+                value: calendar(2025, Some(1), None)
+            }
+        );
+        assert_eq!(
+            DateFilter::parse("2025-01..2025-03").unwrap(),
+            DateFilter::Range {
+                from: Some(calendar(2025, Some(1), None)),
+                to: Some(calendar(2025, Some(3), None))
+            }
+        );
+        assert_eq!(
+            DateFilter::parse("..2024").unwrap(),
+            DateFilter::Range {
+                from: None,
+                to: Some(calendar(2024, None, None))
+            }
+        );
+        assert!(DateFilter::parse("..").is_err());
+        assert!(DateFilter::parse(">=").is_err());
+    }
+
+    #[test]
+    fn calendar_bounds() {
+        let jan = midnight(2025, 1, 1);
+        let feb = midnight(2025, 2, 1);
+        let bounds = |text: &str| DateFilter::parse(text).unwrap().bounds(NOW);
+
+        assert_eq!(bounds("2025-01"), (Some(jan), Some(feb)));
+        assert_eq!(bounds(">2025-01"), (Some(feb), None));
+        assert_eq!(bounds(">=2025-01"), (Some(jan), None));
+        assert_eq!(bounds("<2025-01"), (None, Some(jan)));
+        assert_eq!(bounds("<=2025-01"), (None, Some(feb)));
+        assert_eq!(bounds("2025-01-01"), (Some(jan), Some(jan + DAY)));
+        assert_eq!(
+            bounds("2025-01..2025-03"),
+            (Some(jan), Some(midnight(2025, 4, 1)))
+        );
+        assert_eq!(bounds("2024-12"), (Some(midnight(2024, 12, 1)), Some(jan)));
+    }
+
+    #[test]
+    fn relative_bounds_compare_age() {
+        let month_ago = NOW - 30 * DAY;
+        let bounds = |text: &str| DateFilter::parse(text).unwrap().bounds(NOW);
+
+        assert_eq!(bounds("<30d"), (Some(month_ago + 1), None));
+        assert_eq!(bounds("30d"), (Some(month_ago), None));
+        assert_eq!(bounds(">30d"), (None, Some(month_ago)));
+        assert_eq!(bounds("30d..1w"), (Some(month_ago), Some(NOW - 7 * DAY)));
+        assert_eq!(bounds("1y.."), (Some(NOW - 365 * DAY), None));
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for text in [
+            ">2025-01-01",
+            "<=2025-03",
+            "2025",
+            "<30d",
+            "12h",
+            "2025-01..2025-03",
+            "..6m",
+        ] {
+            assert_eq!(DateFilter::parse(text).unwrap().to_string(), text);
+        }
+    }
+}
@@ -171,20 +171,45 @@ impl QueryError {
         }
     }
 
+    /// Returns the byte position in the query where the error occurred, if known.
+    pub fn position(&self) -> Option<usize> {
+        match &self.kind {
+            QueryErrorKind::Lex { position, .. } => Some(*position),
+            QueryErrorKind::Parse { position, .. } => *position,
+            QueryErrorKind::Compile { .. } => None,
+        }
+    }
+
     /// Returns a suggestion for common errors.
     pub fn suggestion(&self) -> Option<&'static str> {
         match &self.kind {
             QueryErrorKind::Lex { message, .. } if message.contains("unclosed quote") => {
                 Some("Add a closing quote (\") to complete the phrase")
             }
+            QueryErrorKind::Lex { message, .. } if message.contains("invalid regex") => {
+                Some("Escape regex metacharacters such as ( [ { . * + ? with a backslash")
+            }
+            QueryErrorKind::Parse { message, .. } if message.contains("query macro cycle") => {
+                Some("A saved query cannot refer to itself, directly or through other macros")
+            }
+            QueryErrorKind::Parse { message, .. } if message.contains("unmatched closing") => {
+                Some("Remove the extra ) or add an opening ( before it")
+            }
             QueryErrorKind::Parse { message, .. } if message.contains("closing parenthesis") => {
                 Some("Add a closing parenthesis ) to match the opening one")
             }
             QueryErrorKind::Parse { message, .. } if message.contains("OR") => {
                 Some("OR requires expressions on both sides, e.g., 'rust OR golang'")
             }
-            QueryErrorKind::Compile { message } if message.contains("unknown field") => {
-                Some("Valid fields are: title, tags, body, path, tree")
+            QueryErrorKind::Parse { message, .. } if message.contains("trailing '-'") => {
+                Some("Put the term to exclude right after '-', e.g., '-deprecated'")
+            }
+            QueryErrorKind::Parse { message, .. } | QueryErrorKind::Compile { message }
+                if message.contains("unknown field") =>
+            {
+                Some(
+                    "Valid fields are: title, tags, tag, body, path, tree, modified, depth, doc, id, root, meta.KEY",
+                )
             }
             _ => None,
         }
@@ -204,14 +229,8 @@ impl fmt::Display for QueryError {
 
         // If we have a query and position, show it with a pointer
         if let Some(query) = &self.query {
-            let position = match &self.kind {
-                QueryErrorKind::Lex { position, .. } => Some(*position),
-                QueryErrorKind::Parse { position, .. } => *position,
-                QueryErrorKind::Compile { .. } => None,
-            };
-
             writeln!(f, "  {}", query)?;
-            if let Some(pos) = position {
+            if let Some(pos) = self.position() {
                 let clamped = pos.min(query.len());
                 writeln!(f, "  {}^", " ".repeat(clamped))?;
             }
@@ -299,6 +318,15 @@ mod tests {
         assert!(err.suggestion().unwrap().contains("OR requires"));
     }
 
+    #[test]
+    fn regex_error_suggestions() {
+        let err = QueryError::lex("invalid regex: unclosed group", 10, "body:/retr(y/");
+        let display = err.to_string();
+        assert!(display.contains("invalid regex: unclosed group"));
+        assert!(display.contains("          ^"));
+        assert!(display.contains("hint: Escape regex metacharacters"));
+    }
+
     #[test]
     fn compile_error_display() {
         let err = QueryError::compile("unknown field: foo");
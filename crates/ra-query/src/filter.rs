@@ -0,0 +1,131 @@
+//! Structural filters on a chunk's place in the index.
+//!
+//! These select chunks by where they sit rather than by what they say: their heading depth
+//! (`depth:<=2`), the document they belong to (`doc:docs:api/auth.md`), their own ID
+//! (`id:docs:api/auth.md#login`), or whether they are a whole document (`root:true`).
+
+use std::fmt;
+
+use crate::ast::Comparison;
+
+/// Field names that introduce a [`ChunkFilter`].
+pub const FILTER_FIELDS: [&str; 4] = ["depth", "doc", "id", "root"];
+
+/// A filter on a chunk's position in the document hierarchy or its identity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChunkFilter {
+    /// Heading depth compared against a value: 0 for a document, 1-6 for h1-h6.
+    Depth {
+        /// The comparison to apply.
+        op: Comparison,
+        /// The depth to compare against.
+        depth: u64,
+    },
+    /// Chunks of the document with this ID (`tree:path`).
+    Doc(String),
+    /// The chunk with this ID (`tree:path#slug`, or `tree:path` for a whole document).
+    Id(String),
+    /// Document-level chunks if true, sections if false.
+    Root(bool),
+}
+
+impl ChunkFilter {
+    /// Parses the value of a `depth:`, `doc:`, `id:` or `root:` field.
+    pub fn parse(field: &str, value: &str) -> Result<Self, String> {
+        match field {
+            "depth" => {
+                let (op, number) = Comparison::split_prefix(value);
+                let depth = number.parse().map_err(|_| {
+                    format!("invalid depth '{value}': expected a number such as 2 or <=2")
+                })?;
+                Ok(Self::Depth { op, depth })
+            }
+            "doc" | "id" if value.is_empty() => Err(format!("expected an ID after '{field}:'")),
+            "doc" => Ok(Self::Doc(value.to_string())),
+            "id" => Ok(Self::Id(value.to_string())),
+            "root" => match value.to_ascii_lowercase().as_str() {
+                "true" => Ok(Self::Root(true)),
+                "false" => Ok(Self::Root(false)),
+                _ => Err(format!("invalid root '{value}': expected true or false")),
+            },
+            _ => Err(format!("unknown filter: {field}")),
+        }
+    }
+}
+
+impl fmt::Display for ChunkFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Depth { op, depth } => write!(f, "depth:{}{depth}", op.symbol()),
+            Self::Doc(id) => write!(f, "doc:{}", quote_if_spaced(id)),
+            Self::Id(id) => write!(f, "id:{}", quote_if_spaced(id)),
+            Self::Root(root) => write!(f, "root:{root}"),
+        }
+    }
+}
+
+/// Quotes an ID containing whitespace so it reads back as a single value.
+fn quote_if_spaced(id: &str) -> String {
+    if id.contains(char::is_whitespace) {
+        format!("\"{id}\"")
+    } else {
+        id.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_depth() {
+        assert_eq!(
+            ChunkFilter::parse("depth", "<=2").unwrap(),
+            ChunkFilter::Depth {
+                op: Comparison::Le,
+                depth: 2
+            }
+        );
+        assert_eq!(
+            ChunkFilter::parse("depth", "1").unwrap(),
+            ChunkFilter::Depth {
+                op: Comparison::Eq,
+                depth: 1
+            }
+        );
+        assert!(ChunkFilter::parse("depth", "deep").is_err());
+        assert!(ChunkFilter::parse("depth", "<-1").is_err());
+    }
+
+    #[test]
+    fn parses_ids_and_root() {
+        assert_eq!(
+            ChunkFilter::parse("doc", "docs:api/auth.md").unwrap(),
+            ChunkFilter::Doc("docs:api/auth.md".into())
+        );
+        assert_eq!(
+            ChunkFilter::parse("id", "docs:api/auth.md#login").unwrap(),
+            ChunkFilter::Id("docs:api/auth.md#login".into())
+        );
+        assert!(ChunkFilter::parse("doc", "").is_err());
+        assert_eq!(
+            ChunkFilter::parse("root", "TRUE").unwrap(),
+            ChunkFilter::Root(true)
+        );
+        assert!(ChunkFilter::parse("root", "yes").is_err());
+    }
+
+    #[test]
+    fn displays_as_query_syntax() {
+        let filters = [
+            ChunkFilter::parse("depth", ">=2").unwrap(),
+            ChunkFilter::Doc("notes:my file.md".into()),
+            ChunkFilter::Root(false),
+        ];
+        let shown: Vec<String> = filters.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            shown,
+            vec!["depth:>=2", "doc:\"notes:my file.md\"", "root:false"]
+        );
+    }
+}
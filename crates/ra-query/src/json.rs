@@ -0,0 +1,501 @@
+//! JSON representation of query expressions.
+//!
+//! Callers that build queries from structured data can send a [`QueryExpr`] as JSON instead
+//! of writing query syntax. Each expression is an object with one key naming its kind, which
+//! holds its main value, and any parameters of that kind alongside:
+//!
+//! | Expression | JSON |
+//! |------------|------|
+//! | Term | `{"term": "rust"}` |
+//! | Fuzzy term | `{"fuzzy": "kubernetes", "distance": 2}` |
+//! | Wildcard | `{"wildcard": "auth*"}` |
+//! | Phrase | `{"phrase": ["error", "handling"]}` |
+//! | Proximity | `{"proximity": ["token", "refresh"], "slop": 5}` |
+//! | NEAR | `{"near": [{"term": "retry"}, {"term": "backoff"}], "distance": 3}` |
+//! | Regex | `{"regex": "retr(y\|ies)"}`, inside a field |
+//! | Negation | `{"not": {"term": "deprecated"}}` |
+//! | AND, OR | `{"and": [...]}`, `{"or": [...]}` |
+//! | Field | `{"field": "tags", "expr": {"phrase": ["a", "b"]}}` |
+//! | Boost | `{"boost": 2.5, "expr": {"term": "rust"}}` |
+//! | Modified | `{"modified": "<30d"}` |
+//! | Structure | `{"depth": "<=2"}` or `{"depth": 2}`, `{"doc": "docs:api.md"}`, `{"id": "..."}`, `{"root": true}` |
+//!
+//! Strings are search text, never query syntax: `{"term": "a OR b"}` searches for the words
+//! `a`, `or` and `b` in sequence.
+
+use regex_syntax::Parser as RegexParser;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ast::{Comparison, QueryExpr},
+    date::DateFilter,
+    error::QueryError,
+    filter::ChunkFilter,
+    parser::MAX_FUZZY_DISTANCE,
+};
+
+/// Keys that name an expression's kind.
+const KINDS: [&str; 17] = [
+    "term",
+    "fuzzy",
+    "wildcard",
+    "phrase",
+    "proximity",
+    "near",
+    "regex",
+    "not",
+    "and",
+    "or",
+    "field",
+    "boost",
+    "modified",
+    "depth",
+    "doc",
+    "id",
+    "root",
+];
+
+/// A query expression as a JSON object: one kind key plus that kind's parameters.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JsonExpr {
+    /// A term.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    term: Option<String>,
+    /// A term with its own edit distance, given by `distance`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fuzzy: Option<String>,
+    /// A wildcard pattern.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    wildcard: Option<String>,
+    /// The words of an exact phrase.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    phrase: Option<Vec<String>>,
+    /// The words of a phrase matched with `slop`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proximity: Option<Vec<String>>,
+    /// The two operands of a NEAR, within `distance` words.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    near: Option<Vec<QueryExpr>>,
+    /// A regular expression.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    regex: Option<String>,
+    /// A negated expression.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    not: Option<Box<QueryExpr>>,
+    /// Expressions that must all match.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    and: Option<Vec<QueryExpr>>,
+    /// Expressions of which at least one must match.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    or: Option<Vec<QueryExpr>>,
+    /// A field name, searched for `expr`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    field: Option<String>,
+    /// A boost factor, applied to `expr`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    boost: Option<f32>,
+    /// A modification-time filter, in query syntax such as `<30d`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modified: Option<String>,
+    /// A heading depth filter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    depth: Option<JsonDepth>,
+    /// A document ID filter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    doc: Option<String>,
+    /// A chunk ID filter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    /// A root filter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    root: Option<bool>,
+    /// Parameter: edit distance of `fuzzy`, or word distance of `near`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    distance: Option<u32>,
+    /// Parameter: slop of `proximity`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    slop: Option<u32>,
+    /// Parameter: the expression of `field` or `boost`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expr: Option<Box<QueryExpr>>,
+}
+
+/// A depth filter value: an exact depth, or a comparison such as `<=2`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum JsonDepth {
+    /// An exact depth.
+    Exact(u64),
+    /// A depth with an optional comparison prefix.
+    Compare(String),
+}
+
+impl JsonExpr {
+    /// Returns the kind keys and parameter keys present, in declaration order.
+    fn keys(&self) -> (Vec<&'static str>, Vec<&'static str>) {
+        let kinds = [
+            self.term.is_some(),
+            self.fuzzy.is_some(),
+            self.wildcard.is_some(),
+            self.phrase.is_some(),
+            self.proximity.is_some(),
+            self.near.is_some(),
+            self.regex.is_some(),
+            self.not.is_some(),
+            self.and.is_some(),
+            self.or.is_some(),
+            self.field.is_some(),
+            self.boost.is_some(),
+            self.modified.is_some(),
+            self.depth.is_some(),
+            self.doc.is_some(),
+            self.id.is_some(),
+            self.root.is_some(),
+        ];
+        let kinds = KINDS
+            .into_iter()
+            .zip(kinds)
+            .filter_map(|(name, present)| present.then_some(name))
+            .collect();
+        let params = [
+            ("distance", self.distance.is_some()),
+            ("slop", self.slop.is_some()),
+            ("expr", self.expr.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(name, present)| present.then_some(name))
+        .collect();
+        (kinds, params)
+    }
+}
+
+/// Returns `value`, or an error saying that `kind` needs `param`.
+fn required<T>(value: Option<T>, kind: &str, param: &str) -> Result<T, String> {
+    value.ok_or_else(|| format!("'{kind}' needs '{param}'"))
+}
+
+/// Returns `text` if it is not empty.
+fn non_empty(text: String, kind: &str) -> Result<String, String> {
+    if text.is_empty() {
+        Err(format!("'{kind}' cannot be empty"))
+    } else {
+        Ok(text)
+    }
+}
+
+/// Returns `items` if there are any.
+fn non_empty_list<T>(items: Vec<T>, kind: &str) -> Result<Vec<T>, String> {
+    if items.is_empty() {
+        Err(format!("'{kind}' cannot be empty"))
+    } else {
+        Ok(items)
+    }
+}
+
+impl TryFrom<JsonExpr> for QueryExpr {
+    type Error = String;
+
+    fn try_from(json: JsonExpr) -> Result<Self, String> {
+        let (kinds, params) = json.keys();
+        let kind = match kinds.as_slice() {
+            [kind] => *kind,
+            [] => return Err("expected an expression such as {\"term\": \"rust\"}".into()),
+            several => {
+                return Err(format!(
+                    "an expression has one kind, found: {}",
+                    several.join(", ")
+                ));
+            }
+        };
+        let allowed: &[&str] = match kind {
+            "fuzzy" | "near" => &["distance"],
+            "proximity" => &["slop"],
+            "field" | "boost" => &["expr"],
+            _ => &[],
+        };
+        if let Some(param) = params.iter().find(|param| !allowed.contains(param)) {
+            return Err(format!("'{param}' does not apply to '{kind}'"));
+        }
+
+        if let Some(term) = json.term {
+            return Ok(Self::Term(non_empty(term, kind)?));
+        }
+        if let Some(term) = json.fuzzy {
+            let distance = required(json.distance, kind, "distance")?;
+            let distance = u8::try_from(distance)
+                .ok()
+                .filter(|&d| d <= MAX_FUZZY_DISTANCE)
+                .ok_or_else(|| {
+                    format!("fuzzy distance must be at most {MAX_FUZZY_DISTANCE}, got {distance}")
+                })?;
+            return Ok(Self::Fuzzy {
+                term: non_empty(term, kind)?,
+                distance,
+            });
+        }
+        if let Some(pattern) = json.wildcard {
+            return Ok(Self::Wildcard(non_empty(pattern, kind)?));
+        }
+        if let Some(words) = json.phrase {
+            return Ok(Self::Phrase(non_empty_list(words, kind)?));
+        }
+        if let Some(words) = json.proximity {
+            return Ok(Self::Proximity {
+                words: non_empty_list(words, kind)?,
+                slop: required(json.slop, kind, "slop")?,
+            });
+        }
+        if let Some(operands) = json.near {
+            let distance = required(json.distance, kind, "distance")?;
+            let Ok([left, right]) = <[Self; 2]>::try_from(operands) else {
+                return Err("'near' needs exactly two operands".into());
+            };
+            if ![&left, &right]
+                .iter()
+                .all(|e| matches!(e, Self::Term(_) | Self::Phrase(_)))
+            {
+                return Err("NEAR operands must be terms or phrases".into());
+            }
+            return Ok(Self::Near {
+                left: Box::new(left),
+                right: Box::new(right),
+                distance,
+            });
+        }
+        if let Some(pattern) = json.regex {
+            let pattern = non_empty(pattern, kind)?;
+            RegexParser::new()
+                .parse(&pattern)
+                .map_err(|e| format!("invalid regex: {e}"))?;
+            return Ok(Self::Regex(pattern));
+        }
+        if let Some(inner) = json.not {
+            return Ok(Self::Not(inner));
+        }
+        if let Some(exprs) = json.and {
+            return Ok(Self::And(non_empty_list(exprs, kind)?));
+        }
+        if let Some(exprs) = json.or {
+            return Ok(Self::Or(non_empty_list(exprs, kind)?));
+        }
+        if let Some(name) = json.field {
+            return Ok(Self::Field {
+                name: non_empty(name, kind)?,
+                expr: required(json.expr, kind, "expr")?,
+            });
+        }
+        if let Some(factor) = json.boost {
+            if !(factor.is_finite() && factor > 0.0) {
+                return Err(format!("boost must be a positive number, got {factor}"));
+            }
+            return Ok(Self::Boost {
+                expr: required(json.expr, kind, "expr")?,
+                factor,
+            });
+        }
+        if let Some(filter) = json.modified {
+            return DateFilter::parse(&filter).map(Self::Modified);
+        }
+        if let Some(depth) = json.depth {
+            let value = match depth {
+                JsonDepth::Exact(depth) => depth.to_string(),
+                JsonDepth::Compare(text) => text,
+            };
+            return ChunkFilter::parse("depth", &value).map(Self::Filter);
+        }
+        if let Some(id) = json.doc {
+            return ChunkFilter::parse("doc", &id).map(Self::Filter);
+        }
+        if let Some(id) = json.id {
+            return ChunkFilter::parse("id", &id).map(Self::Filter);
+        }
+        let root = required(json.root, kind, "root")?;
+        Ok(Self::Filter(ChunkFilter::Root(root)))
+    }
+}
+
+impl From<QueryExpr> for JsonExpr {
+    fn from(expr: QueryExpr) -> Self {
+        match expr {
+            QueryExpr::Term(term) => Self {
+                term: Some(term),
+                ..Self::default()
+            },
+            QueryExpr::Fuzzy { term, distance } => Self {
+                fuzzy: Some(term),
+                distance: Some(u32::from(distance)),
+                ..Self::default()
+            },
+            QueryExpr::Wildcard(pattern) => Self {
+                wildcard: Some(pattern),
+                ..Self::default()
+            },
+            QueryExpr::Phrase(words) => Self {
+                phrase: Some(words),
+                ..Self::default()
+            },
+            QueryExpr::Proximity { words, slop } => Self {
+                proximity: Some(words),
+                slop: Some(slop),
+                ..Self::default()
+            },
+            QueryExpr::Near {
+                left,
+                right,
+                distance,
+            } => Self {
+                near: Some(vec![*left, *right]),
+                distance: Some(distance),
+                ..Self::default()
+            },
+            QueryExpr::Regex(pattern) => Self {
+                regex: Some(pattern),
+                ..Self::default()
+            },
+            QueryExpr::Not(inner) => Self {
+                not: Some(inner),
+                ..Self::default()
+            },
+            QueryExpr::And(exprs) => Self {
+                and: Some(exprs),
+                ..Self::default()
+            },
+            QueryExpr::Or(exprs) => Self {
+                or: Some(exprs),
+                ..Self::default()
+            },
+            QueryExpr::Field { name, expr } => Self {
+                field: Some(name),
+                expr: Some(expr),
+                ..Self::default()
+            },
+            QueryExpr::Boost { expr, factor } => Self {
+                boost: Some(factor),
+                expr: Some(expr),
+                ..Self::default()
+            },
+            QueryExpr::Modified(filter) => Self {
+                modified: Some(filter.to_string()),
+                ..Self::default()
+            },
+            QueryExpr::Filter(filter) => Self::from(filter),
+        }
+    }
+}
+
+impl From<ChunkFilter> for JsonExpr {
+    fn from(filter: ChunkFilter) -> Self {
+        match filter {
+            ChunkFilter::Depth {
+                op: Comparison::Eq,
+                depth,
+            } => Self {
+                depth: Some(JsonDepth::Exact(depth)),
+                ..Self::default()
+            },
+            ChunkFilter::Depth { op, depth } => Self {
+                depth: Some(JsonDepth::Compare(format!("{}{depth}", op.symbol()))),
+                ..Self::default()
+            },
+            ChunkFilter::Doc(id) => Self {
+                doc: Some(id),
+                ..Self::default()
+            },
+            ChunkFilter::Id(id) => Self {
+                id: Some(id),
+                ..Self::default()
+            },
+            ChunkFilter::Root(root) => Self {
+                root: Some(root),
+                ..Self::default()
+            },
+        }
+    }
+}
+
+/// Parses a query expression from its JSON representation.
+///
+/// Errors point at the position in `input` where the JSON was found to be invalid.
+pub fn parse_json(input: &str) -> Result<QueryExpr, QueryError> {
+    serde_json::from_str(input).map_err(|err| {
+        let location = format!(" at line {} column {}", err.line(), err.column());
+        let message = err.to_string();
+        let message = message.strip_suffix(&location).unwrap_or(&message);
+        let position = input
+            .split_inclusive('\n')
+            .take(err.line().saturating_sub(1))
+            .map(str::len)
+            .sum::<usize>()
+            + err.column().saturating_sub(1);
+        QueryError::parse(
+            format!("invalid JSON query: {message}"),
+            Some(position),
+            Some(input.to_string()),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn parses_documented_shape() {
+        let expr =
+            parse_json(r#"{"and":[{"term":"x"},{"field":"tags","expr":{"phrase":["a","b"]}}]}"#)
+                .unwrap();
+        assert_eq!(expr, parse("x tags:\"a b\"").unwrap().unwrap());
+    }
+
+    #[test]
+    fn round_trips_every_kind() {
+        let query = "kubernetes~2 auth* \"token refresh\"~5 (retry NEAR/3 backoff) \
+                     body:/retr(y|ies)/ -deprecated (rust OR go) title:guide^2.5 \
+                     modified:<30d depth:<=2 depth:1 doc:docs:api.md id:docs:api.md#login \
+                     root:true";
+        let expr = parse(query).unwrap().unwrap();
+        let json = serde_json::to_string(&expr).unwrap();
+        assert_eq!(parse_json(&json).unwrap(), expr);
+    }
+
+    #[test]
+    fn serializes_flat_objects() {
+        let expr = parse("retry NEAR/3 backoff depth:2").unwrap().unwrap();
+        assert_eq!(
+            serde_json::to_string(&expr).unwrap(),
+            r#"{"and":[{"near":[{"term":"retry"},{"term":"backoff"}],"distance":3},{"depth":2}]}"#
+        );
+    }
+
+    #[test]
+    fn strings_are_not_query_syntax() {
+        let expr = parse_json(r#"{"term": "a OR (b"}"#).unwrap();
+        assert_eq!(expr, QueryExpr::Term("a OR (b".into()));
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        let error = |input: &str| parse_json(input).unwrap_err().message().to_string();
+        assert!(error("{}").contains("expected an expression"));
+        assert!(error(r#"{"term": "a", "phrase": ["b"]}"#).contains("found: term, phrase"));
+        assert!(error(r#"{"term": "a", "slop": 2}"#).contains("'slop' does not apply to 'term'"));
+        assert!(error(r#"{"fuzzy": "a"}"#).contains("'fuzzy' needs 'distance'"));
+        assert!(error(r#"{"fuzzy": "a", "distance": 3}"#).contains("at most 2"));
+        assert!(error(r#"{"and": []}"#).contains("'and' cannot be empty"));
+        assert!(error(r#"{"near": [{"term": "a"}], "distance": 1}"#).contains("two operands"));
+        assert!(error(r#"{"modified": "soon"}"#).contains("invalid"));
+        assert!(error(r#"{"trem": "a"}"#).contains("unknown field `trem`"));
+    }
+
+    #[test]
+    fn errors_point_into_the_input() {
+        let err = parse_json("{\"and\": [\n  {\"term\": 1}]}").unwrap_err();
+        assert!(
+            err.message()
+                .starts_with("invalid JSON query: invalid type")
+        );
+        assert_eq!(err.position(), Some(21));
+    }
+}
@@ -0,0 +1,389 @@
+//! Lenient query parsing.
+//!
+//! A strict parse stops at the first mistake in a query. A lenient parse repairs what it can
+//! and searches for the rest, reporting each repair as a diagnostic:
+//!
+//! - A `)` without a matching `(` is dropped; a `(` left open is closed at the end.
+//! - An `OR` or `NEAR/n` missing an operand on either side is dropped.
+//! - A `-` with nothing to negate is dropped.
+//! - An unknown `field:` prefix is dropped, so its value searches every field.
+//! - An unclosed quote runs to the end of the query.
+//! - Anything else the parser rejects is dropped token by token until the rest parses.
+
+use std::collections::HashMap;
+
+use crate::{
+    ast::QueryExpr,
+    error::QueryError,
+    filter::FILTER_FIELDS,
+    lexer::{Token, tokenize_lenient},
+    parser::{MAX_FUZZY_DISTANCE, byte_position, parse_tokens},
+};
+
+/// Fields that search indexed text, besides `modified` and the [`FILTER_FIELDS`].
+const SEARCH_FIELDS: [&str; 7] = ["title", "hierarchy", "tags", "tag", "body", "path", "tree"];
+
+/// The result of a lenient parse.
+#[derive(Debug, Clone)]
+pub struct LenientParse {
+    /// The expression parsed from what was left after repairs, or `None` if nothing was.
+    pub expr: Option<QueryExpr>,
+    /// The problems repaired, in the order they were found. Each carries the query and the
+    /// position of the problem.
+    pub diagnostics: Vec<QueryError>,
+}
+
+/// A token stream being repaired, with the input position of each token.
+struct Repair<'a> {
+    /// The query being parsed.
+    input: &'a str,
+    /// Remaining tokens.
+    tokens: Vec<Token>,
+    /// Input byte position of each remaining token.
+    spans: Vec<usize>,
+    /// Problems repaired so far.
+    diagnostics: Vec<QueryError>,
+}
+
+impl Repair<'_> {
+    /// Records a problem at a byte position in the input.
+    fn report(&mut self, message: impl Into<String>, position: usize) {
+        self.diagnostics.push(QueryError::parse(
+            message,
+            Some(position),
+            Some(self.input.to_string()),
+        ));
+    }
+
+    /// Removes the token at `index`, recording why.
+    fn drop_token(&mut self, index: usize, message: impl Into<String>) {
+        self.report(message, self.spans[index]);
+        self.remove(index);
+    }
+
+    /// Removes the token at `index`.
+    fn remove(&mut self, index: usize) {
+        self.tokens.remove(index);
+        self.spans.remove(index);
+    }
+
+    /// Drops unmatched closing parentheses and unknown field prefixes, and closes
+    /// parentheses left open.
+    fn balance(&mut self) {
+        let mut open = Vec::new();
+        let mut index = 0;
+        while index < self.tokens.len() {
+            match &self.tokens[index] {
+                Token::LParen => open.push(self.spans[index]),
+                Token::RParen if open.pop().is_none() => {
+                    self.drop_token(index, "unmatched closing parenthesis");
+                    continue;
+                }
+                Token::FieldPrefix(name) if !self.is_known_field(index, name) => {
+                    let message = format!("unknown field: {name}");
+                    self.drop_token(index, message);
+                    continue;
+                }
+                _ => {}
+            }
+            index += 1;
+        }
+
+        for position in open.into_iter().rev() {
+            self.report("expected closing parenthesis", position);
+            self.tokens.push(Token::RParen);
+            self.spans.push(self.input.len());
+        }
+    }
+
+    /// Returns true if the field prefix at `index` names a field or a `meta.` key, or is part of
+    /// a `tree:path` ID after a filter field such as `doc:`.
+    fn is_known_field(&self, index: usize, name: &str) -> bool {
+        let after_filter = index
+            .checked_sub(1)
+            .and_then(|prev| match &self.tokens[prev] {
+                Token::FieldPrefix(prev) => Some(FILTER_FIELDS.contains(&prev.as_str())),
+                _ => None,
+            })
+            .unwrap_or(false);
+        after_filter
+            || name == "modified"
+            || name
+                .strip_prefix("meta.")
+                .is_some_and(|key| !key.is_empty())
+            || SEARCH_FIELDS.contains(&name)
+            || FILTER_FIELDS.contains(&name)
+    }
+
+    /// Drops operators missing an operand, prefixes missing a value and empty groups, until
+    /// none are left.
+    fn prune(&mut self) {
+        while let Some((index, message)) = (0..self.tokens.len())
+            .find_map(|index| self.dangling(index).map(|message| (index, message)))
+        {
+            if self.tokens[index] == Token::LParen {
+                // An empty group goes as a whole
+                self.remove(index + 1);
+            }
+            self.drop_token(index, message);
+        }
+    }
+
+    /// Describes what is wrong with the token at `index` if it lacks something it needs.
+    fn dangling(&self, index: usize) -> Option<String> {
+        let prev = index.checked_sub(1).map(|prev| &self.tokens[prev]);
+        let next = self.tokens.get(index + 1);
+        let ends_operand = prev.is_some_and(ends_operand);
+        let starts_operand = next.is_some_and(starts_operand);
+        match &self.tokens[index] {
+            Token::Or if !ends_operand || !starts_operand => {
+                Some("unexpected OR (needs expressions on both sides)".into())
+            }
+            Token::Near(_) if !ends_operand || !starts_operand => {
+                Some("unexpected NEAR (needs terms on both sides)".into())
+            }
+            Token::Not if !starts_operand => Some("trailing '-' (needs a term after it)".into()),
+            Token::Boost(_) if !ends_operand => {
+                Some("unexpected boost (needs expression before it)".into())
+            }
+            Token::LParen if next == Some(&Token::RParen) => Some("empty parentheses".into()),
+            Token::FieldPrefix(name) if !next.is_some_and(is_value) => {
+                Some(format!("expected a value after '{name}:'"))
+            }
+            _ => None,
+        }
+    }
+
+    /// Parses the tokens, dropping the token each parse error points at until the rest
+    /// parses. A fuzzy distance that is too large is lowered to the maximum instead.
+    fn parse(&mut self, macros: &HashMap<String, String>) -> Option<QueryExpr> {
+        while !self.tokens.is_empty() {
+            let err = match parse_tokens(self.tokens.clone(), macros) {
+                Ok(expr) => return expr,
+                Err(err) => err,
+            };
+            let index = err
+                .token_index
+                .filter(|&index| index < self.tokens.len())
+                .unwrap_or(self.tokens.len() - 1);
+            let position = byte_position(&self.spans, Some(index), self.input);
+
+            match (
+                index.checked_sub(1).map(|i| &self.tokens[i]),
+                &self.tokens[index],
+            ) {
+                (Some(Token::Term(_)), Token::Tilde(count))
+                    if *count > u32::from(MAX_FUZZY_DISTANCE) =>
+                {
+                    self.report(err.message, position);
+                    self.tokens[index] = Token::Tilde(u32::from(MAX_FUZZY_DISTANCE));
+                }
+                // A bad field value takes its field prefix with it
+                (Some(Token::FieldPrefix(_)), _) => {
+                    self.drop_token(index, err.message);
+                    self.remove(index - 1);
+                }
+                _ => self.drop_token(index, err.message),
+            }
+        }
+        None
+    }
+}
+
+/// Returns true if `token` can end an operand, so an operator may follow it.
+fn ends_operand(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Term(_)
+            | Token::Wildcard(_)
+            | Token::Phrase(_)
+            | Token::Regex(_)
+            | Token::Macro(_)
+            | Token::RParen
+            | Token::Boost(_)
+            | Token::Tilde(_)
+    )
+}
+
+/// Returns true if `token` can start an operand.
+fn starts_operand(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Term(_)
+            | Token::Wildcard(_)
+            | Token::Phrase(_)
+            | Token::Macro(_)
+            | Token::LParen
+            | Token::FieldPrefix(_)
+            | Token::Not
+    )
+}
+
+/// Returns true if `token` can be the value after a field prefix.
+fn is_value(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Term(_)
+            | Token::Wildcard(_)
+            | Token::Phrase(_)
+            | Token::Regex(_)
+            | Token::Macro(_)
+            | Token::LParen
+            | Token::FieldPrefix(_)
+    )
+}
+
+/// Parses a query string as far as possible, repairing mistakes instead of failing.
+///
+/// Returns the best-effort expression along with a diagnostic for each repair, so that a
+/// search can go ahead and the mistakes be shown as warnings. `@name` references expand from
+/// `macros` as in [`parse_with_macros`](crate::parse_with_macros). A query that parses
+/// strictly gives the same expression and no diagnostics.
+pub fn parse_lenient(input: &str, macros: &HashMap<String, String>) -> LenientParse {
+    let (tokens, lex_errors) = tokenize_lenient(input);
+    let (tokens, spans) = tokens.into_iter().unzip();
+    let mut repair = Repair {
+        input,
+        tokens,
+        spans,
+        diagnostics: lex_errors.into_iter().map(QueryError::from).collect(),
+    };
+
+    repair.balance();
+    repair.prune();
+    let expr = repair.parse(macros);
+
+    let mut diagnostics = repair.diagnostics;
+    diagnostics.sort_by_key(|d| d.position().unwrap_or(input.len()));
+    LenientParse { expr, diagnostics }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    /// Parses leniently without macros, returning the expression as query syntax and the
+    /// diagnostic messages.
+    fn lenient(input: &str) -> (Option<String>, Vec<String>) {
+        let parsed = parse_lenient(input, &HashMap::new());
+        let messages = parsed
+            .diagnostics
+            .iter()
+            .map(|d| d.message().to_string())
+            .collect();
+        (parsed.expr.map(|e| e.to_query_string()), messages)
+    }
+
+    /// Returns the query syntax of a strict parse.
+    fn strict(input: &str) -> Option<String> {
+        parse(input).unwrap().map(|e| e.to_query_string())
+    }
+
+    #[test]
+    fn valid_queries_parse_as_strict() {
+        for query in [
+            "rust",
+            "title:guide (rust OR golang) -deprecated",
+            "doc:docs:api/auth.md retry NEAR/3 backoff",
+            "\"a b\"~2 kubernetes~1^2",
+        ] {
+            let (expr, diagnostics) = lenient(query);
+            assert_eq!(expr, strict(query), "{query}");
+            assert!(diagnostics.is_empty(), "{query}: {diagnostics:?}");
+        }
+        assert_eq!(lenient(""), (None, vec![]));
+    }
+
+    #[test]
+    fn repairs_unbalanced_parentheses() {
+        let (expr, diagnostics) = lenient("(rust OR golang");
+        assert_eq!(expr, strict("(rust OR golang)"));
+        assert_eq!(diagnostics, vec!["expected closing parenthesis"]);
+
+        let (expr, diagnostics) = lenient("rust) async");
+        assert_eq!(expr, strict("rust async"));
+        assert_eq!(diagnostics, vec!["unmatched closing parenthesis"]);
+    }
+
+    #[test]
+    fn drops_dangling_operators() {
+        assert_eq!(lenient("rust OR").0, strict("rust"));
+        assert_eq!(lenient("OR rust").0, strict("rust"));
+        assert_eq!(lenient("rust OR OR go").0, strict("rust OR go"));
+        assert_eq!(lenient("(rust OR) go").0, strict("rust go"));
+        assert_eq!(lenient("retry NEAR/3").0, strict("retry"));
+
+        let (expr, diagnostics) = lenient("rust -");
+        assert_eq!(expr, strict("rust"));
+        assert_eq!(diagnostics, vec!["trailing '-' (needs a term after it)"]);
+
+        assert_eq!(lenient("() rust").0, strict("rust"));
+        assert_eq!(
+            lenient("OR -"),
+            (
+                None,
+                vec![
+                    "unexpected OR (needs expressions on both sides)".into(),
+                    "trailing '-' (needs a term after it)".into(),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn unknown_fields_search_all_fields() {
+        let (expr, diagnostics) = lenient("author:alice title:guide");
+        assert_eq!(expr, strict("alice title:guide"));
+        assert_eq!(diagnostics, vec!["unknown field: author"]);
+
+        let (expr, diagnostics) = lenient("meta.status:draft");
+        assert_eq!(expr, strict("meta.status:draft"));
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn drops_what_the_parser_rejects() {
+        let (expr, diagnostics) = lenient("rust modified:yesterday");
+        assert_eq!(expr, strict("rust"));
+        assert_eq!(diagnostics.len(), 1);
+
+        let (expr, diagnostics) = lenient("kubernetes~5");
+        assert_eq!(expr, strict("kubernetes~2"));
+        assert!(diagnostics[0].contains("at most 2"));
+
+        let (expr, diagnostics) = lenient("\"error handling");
+        assert_eq!(expr, strict("\"error handling\""));
+        assert_eq!(diagnostics, vec!["unclosed quote"]);
+    }
+
+    #[test]
+    fn diagnostics_point_into_the_query() {
+        let parsed = parse_lenient("rust) -", &HashMap::new());
+        let positions: Vec<Option<usize>> = parsed
+            .diagnostics
+            .iter()
+            .map(QueryError::position)
+            .collect();
+        assert_eq!(positions, vec![Some(4), Some(6)]);
+        assert!(parsed.diagnostics[0].suggestion().is_some());
+        assert_eq!(parsed.diagnostics[0].query.as_deref(), Some("rust) -"));
+    }
+
+    #[test]
+    fn cyclic_macros_are_dropped() {
+        let macros = HashMap::from([
+            ("sec".to_string(), "auth".to_string()),
+            ("loop".to_string(), "@loop".to_string()),
+        ]);
+        let parsed = parse_lenient("@sec @loop rust", &macros);
+        assert_eq!(
+            parsed.expr.map(|e| e.to_query_string()),
+            strict("auth rust")
+        );
+        assert_eq!(
+            parsed.diagnostics[0].message(),
+            "query macro cycle: @loop -> @loop"
+        );
+    }
+}
@@ -4,6 +4,8 @@
 
 use std::{iter::Peekable, str::Chars};
 
+use regex_syntax::{Error as RegexError, Parser as RegexParser};
+
 use crate::error::LexError;
 
 /// A token in the query language.
@@ -12,9 +14,16 @@ pub enum Token {
     /// A bare word (search term).
     Term(String),
 
+    /// A term containing `*` or `?` wildcards.
+    Wildcard(String),
+
     /// A quoted phrase (the quotes are stripped, content preserved).
     Phrase(String),
 
+    /// A regular expression literal after a field prefix (e.g., `body:/retr(y|ies)/`
+    /// produces Regex("retr(y|ies)")). Escaped slashes are unescaped.
+    Regex(String),
+
     /// The OR keyword.
     Or,
 
@@ -32,8 +41,22 @@ pub enum Token {
 
     /// Boost operator with factor (e.g., "^2.5" produces Boost(2.5)).
     Boost(f32),
+
+    /// Tilde operator with a count (e.g., `"a b"~5` produces Phrase("a b"), Tilde(5), and
+    /// `pod~0` produces Term("pod"), Tilde(0)).
+    Tilde(u32),
+
+    /// Proximity operator with a distance (e.g., "NEAR/3" produces Near(3)).
+    Near(u32),
+
+    /// A reference to a named query from configuration (e.g., "@security" produces
+    /// Macro("security")).
+    Macro(String),
 }
 
+/// A token and the byte position in the input where it starts.
+pub type Spanned = (Token, usize);
+
 /// Tokenizes a query string.
 struct Lexer<'a> {
     /// The original input string.
@@ -42,15 +65,21 @@ struct Lexer<'a> {
     chars: Peekable<Chars<'a>>,
     /// Current byte position in input.
     position: usize,
+    /// Whether to record errors and skip past them instead of stopping.
+    lenient: bool,
+    /// Errors skipped past in lenient mode.
+    diagnostics: Vec<LexError>,
 }
 
 impl<'a> Lexer<'a> {
     /// Creates a new lexer for the given input.
-    fn new(input: &'a str) -> Self {
+    fn new(input: &'a str, lenient: bool) -> Self {
         Self {
             input,
             chars: input.chars().peekable(),
             position: 0,
+            lenient,
+            diagnostics: Vec::new(),
         }
     }
 
@@ -59,12 +88,56 @@ impl<'a> Lexer<'a> {
         LexError::new(message, position, self.input)
     }
 
-    /// Tokenizes the entire input, returning all tokens or an error.
-    fn tokenize(mut self) -> Result<Vec<Token>, LexError> {
+    /// Records an error in lenient mode, where lexing goes on past the characters in error,
+    /// or returns it otherwise.
+    fn recover(&mut self, err: LexError) -> Result<(), LexError> {
+        if self.lenient {
+            self.diagnostics.push(err);
+            Ok(())
+        } else {
+            Err(err)
+        }
+    }
+
+    /// Tokenizes the entire input, returning all tokens with their positions or an error.
+    fn tokenize(&mut self) -> Result<Vec<Spanned>, LexError> {
         let mut tokens = Vec::new();
 
-        while let Some(token) = self.next_token()? {
-            tokens.push(token);
+        loop {
+            self.skip_whitespace();
+            let start = self.position;
+            let token = match self.next_token() {
+                Ok(Some(token)) => token,
+                Ok(None) => break,
+                Err(err) => {
+                    self.recover(err)?;
+                    continue;
+                }
+            };
+            // A macro name with no saved query is read as a term, so it may be fuzzy too
+            let takes_tilde = matches!(
+                token,
+                Token::Phrase(_) | Token::Term(_) | Token::Wildcard(_) | Token::Macro(_)
+            );
+            let is_field = matches!(token, Token::FieldPrefix(_));
+            tokens.push((token, start));
+
+            let start = self.position;
+            if takes_tilde && self.chars.peek() == Some(&'~') {
+                match self.read_tilde() {
+                    Ok(token) => tokens.push((token, start)),
+                    Err(err) => self.recover(err)?,
+                }
+            } else if is_field && self.regex_follows() {
+                match self.read_regex() {
+                    Ok(token) => tokens.push((token, start)),
+                    Err(err) => {
+                        // The field prefix has nothing left to apply to
+                        self.recover(err)?;
+                        tokens.pop();
+                    }
+                }
+            }
         }
 
         Ok(tokens)
@@ -115,11 +188,97 @@ impl<'a> Lexer<'a> {
                     self.advance();
                 }
                 None => {
-                    // Unclosed quote
-                    return Err(self.error_at("unclosed quote", start_pos));
+                    // Unclosed quote; in lenient mode the phrase runs to the end of input
+                    self.recover(self.error_at("unclosed quote", start_pos))?;
+                    return Ok(Some(Token::Phrase(content)));
+                }
+            }
+        }
+    }
+
+    /// Returns true if a `/.../` regex literal starts here.
+    ///
+    /// The closing slash must end the field value, so that a value such as `/usr/bin` is still
+    /// read as a term.
+    fn regex_follows(&self) -> bool {
+        let mut chars = self.chars.clone();
+        if chars.next() != Some('/') {
+            return false;
+        }
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\\' => {
+                    chars.next();
+                }
+                '/' => {
+                    return chars
+                        .peek()
+                        .is_none_or(|&ch| ch.is_whitespace() || ch == ')' || ch == '^');
                 }
+                _ => {}
             }
         }
+        false
+    }
+
+    /// Reads a `/.../` regular expression literal and checks that it compiles.
+    ///
+    /// `\/` stands for a literal slash; every other escape is passed through to the regex.
+    fn read_regex(&mut self) -> Result<Token, LexError> {
+        let start_pos = self.position;
+        self.advance(); // consume opening slash
+
+        let mut pattern = String::new();
+        // Input position of each pattern character, for locating regex syntax errors.
+        let mut offsets = Vec::new();
+
+        loop {
+            let char_pos = self.position;
+            match self.chars.peek() {
+                Some(&'/') => {
+                    self.advance(); // consume closing slash
+                    break;
+                }
+                Some(&'\\') => {
+                    self.advance();
+                    match self.chars.peek() {
+                        Some(&'/') => {}
+                        Some(_) => {
+                            offsets.push((pattern.len(), char_pos));
+                            pattern.push('\\');
+                        }
+                        None => return Err(self.error_at("unclosed regex", start_pos)),
+                    }
+                }
+                Some(_) => {}
+                None => return Err(self.error_at("unclosed regex", start_pos)),
+            }
+            if let Some(&ch) = self.chars.peek() {
+                offsets.push((pattern.len(), self.position));
+                pattern.push(ch);
+                self.advance();
+            }
+        }
+
+        if pattern.is_empty() {
+            return Err(self.error_at("empty regex", start_pos));
+        }
+
+        if let Err(err) = RegexParser::new().parse(&pattern) {
+            let (message, offset) = match &err {
+                RegexError::Parse(e) => (e.kind().to_string(), e.span().start.offset),
+                RegexError::Translate(e) => (e.kind().to_string(), e.span().start.offset),
+                other => (other.to_string(), 0),
+            };
+            let position = offsets
+                .iter()
+                .rev()
+                .find(|(pattern_offset, _)| *pattern_offset <= offset)
+                .map_or(start_pos, |&(_, input_pos)| input_pos);
+            return Err(self.error_at(format!("invalid regex: {message}"), position));
+        }
+
+        Ok(Token::Regex(pattern))
     }
 
     /// Reads a term, keyword (OR), or field prefix.
@@ -131,6 +290,11 @@ impl<'a> Lexer<'a> {
                 break;
             }
 
+            // A trailing ~N is a fuzziness suffix; any other tilde is part of the word
+            if ch == '~' && !word.is_empty() && self.tilde_count_follows() {
+                break;
+            }
+
             // Check for field prefix (word ending in colon)
             if ch == ':' {
                 self.advance(); // consume the colon
@@ -154,6 +318,27 @@ impl<'a> Lexer<'a> {
             return Ok(Some(Token::Or));
         }
 
+        // Check for NEAR/n operator (case-insensitive)
+        if let Some(distance) = word
+            .get(..5)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("NEAR/"))
+            .and_then(|_| word[5..].parse().ok())
+        {
+            return Ok(Some(Token::Near(distance)));
+        }
+
+        // An @ followed by a name refers to a saved query
+        if let Some(name) = word.strip_prefix('@')
+            && is_macro_name(name)
+        {
+            return Ok(Some(Token::Macro(name.to_string())));
+        }
+
+        // Words with wildcards need at least one literal character to anchor them
+        if word.contains(['*', '?']) && word.chars().any(char::is_alphanumeric) {
+            return Ok(Some(Token::Wildcard(word)));
+        }
+
         Ok(Some(Token::Term(word)))
     }
 
@@ -185,6 +370,44 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    /// Reads a tilde operator (~N).
+    fn read_tilde(&mut self) -> Result<Token, LexError> {
+        let start_pos = self.position;
+        self.advance(); // consume '~'
+
+        let mut number = String::new();
+        while let Some(&ch) = self.chars.peek() {
+            if ch.is_ascii_digit() {
+                number.push(ch);
+                self.advance();
+            } else {
+                break;
+            }
+        }
+
+        if number.is_empty() {
+            return Err(self.error_at("expected number after '~'", start_pos));
+        }
+
+        match number.parse::<u32>() {
+            Ok(count) => Ok(Token::Tilde(count)),
+            Err(_) => Err(self.error_at(format!("invalid '~' value: {}", number), start_pos)),
+        }
+    }
+
+    /// Checks whether the upcoming `~` is followed by digits that end the word.
+    fn tilde_count_follows(&self) -> bool {
+        let mut rest = self.chars.clone().skip(1).peekable();
+        let mut has_digits = false;
+        while rest.next_if(char::is_ascii_digit).is_some() {
+            has_digits = true;
+        }
+        has_digits
+            && rest
+                .peek()
+                .is_none_or(|&ch| ch.is_whitespace() || ch == ')' || ch == '^')
+    }
+
     /// Skips whitespace characters.
     fn skip_whitespace(&mut self) {
         while let Some(&ch) = self.chars.peek() {
@@ -204,9 +427,35 @@ impl<'a> Lexer<'a> {
     }
 }
 
+/// Returns true if `name` can be referenced as `@name`: ASCII letters, digits, `_` and `-`,
+/// starting with a letter.
+fn is_macro_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
 /// Convenience function to tokenize a query string.
 pub fn tokenize(input: &str) -> Result<Vec<Token>, LexError> {
-    Lexer::new(input).tokenize()
+    let tokens = tokenize_spanned(input)?;
+    Ok(tokens.into_iter().map(|(token, _)| token).collect())
+}
+
+/// Tokenizes a query string, keeping the position of each token.
+pub fn tokenize_spanned(input: &str) -> Result<Vec<Spanned>, LexError> {
+    Lexer::new(input, false).tokenize()
+}
+
+/// Tokenizes a query string, skipping past errors instead of stopping at the first.
+///
+/// Returns the tokens that could be read along with the errors skipped. An unclosed quote
+/// runs to the end of the input; a bad regex is dropped along with its field prefix; a `^` or
+/// `~` without a valid number is dropped.
+pub fn tokenize_lenient(input: &str) -> (Vec<Spanned>, Vec<LexError>) {
+    let mut lexer = Lexer::new(input, true);
+    let tokens = lexer.tokenize().unwrap_or_default();
+    (tokens, lexer.diagnostics)
 }
 
 #[cfg(test)]
@@ -377,6 +626,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn field_with_date_filter() {
+        assert_eq!(
+            tokenize("modified:>=2025-01-01 modified:2025-01..2025-03").unwrap(),
+            vec![
+                Token::FieldPrefix("modified".into()),
+                Token::Term(">=2025-01-01".into()),
+                Token::FieldPrefix("modified".into()),
+                Token::Term("2025-01..2025-03".into())
+            ]
+        );
+    }
+
     #[test]
     fn field_with_phrase() {
         assert_eq!(
@@ -438,6 +700,168 @@ mod tests {
         assert!(err.message.contains("expected number"));
     }
 
+    #[test]
+    fn phrase_slop() {
+        assert_eq!(
+            tokenize("\"token refresh\"~5^2").unwrap(),
+            vec![
+                Token::Phrase("token refresh".into()),
+                Token::Tilde(5),
+                Token::Boost(2.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn phrase_slop_missing_number() {
+        let err = tokenize("\"token refresh\"~ rust").unwrap_err();
+        assert!(err.message.contains("expected number after '~'"));
+        assert_eq!(err.position, 15);
+    }
+
+    #[test]
+    fn tilde_inside_term_is_literal() {
+        assert_eq!(
+            tokenize("~/notes").unwrap(),
+            vec![Token::Term("~/notes".into())]
+        );
+        assert_eq!(
+            tokenize("a~b c~2d").unwrap(),
+            vec![Token::Term("a~b".into()), Token::Term("c~2d".into())]
+        );
+    }
+
+    #[test]
+    fn term_fuzziness() {
+        assert_eq!(
+            tokenize("kubernetes~2 (pod~0)^2 title:api~1").unwrap(),
+            vec![
+                Token::Term("kubernetes".into()),
+                Token::Tilde(2),
+                Token::LParen,
+                Token::Term("pod".into()),
+                Token::Tilde(0),
+                Token::RParen,
+                Token::Boost(2.0),
+                Token::FieldPrefix("title".into()),
+                Token::Term("api".into()),
+                Token::Tilde(1)
+            ]
+        );
+    }
+
+    #[test]
+    fn near_operator() {
+        assert_eq!(
+            tokenize("retry NEAR/3 backoff near/10 jitter").unwrap(),
+            vec![
+                Token::Term("retry".into()),
+                Token::Near(3),
+                Token::Term("backoff".into()),
+                Token::Near(10),
+                Token::Term("jitter".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn near_without_distance_is_term() {
+        assert_eq!(
+            tokenize("near NEAR/x").unwrap(),
+            vec![Token::Term("near".into()), Token::Term("NEAR/x".into())]
+        );
+    }
+
+    #[test]
+    fn wildcard_terms() {
+        assert_eq!(
+            tokenize("auth* config? path:api/v?/*").unwrap(),
+            vec![
+                Token::Wildcard("auth*".into()),
+                Token::Wildcard("config?".into()),
+                Token::FieldPrefix("path".into()),
+                Token::Wildcard("api/v?/*".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn bare_wildcards_are_terms() {
+        assert_eq!(
+            tokenize("* ?").unwrap(),
+            vec![Token::Term("*".into()), Token::Term("?".into())]
+        );
+    }
+
+    #[test]
+    fn regex_after_field() {
+        assert_eq!(
+            tokenize(r"body:/retr(y|ies)\s+policy/ path:/^api\/v[0-9]/^2").unwrap(),
+            vec![
+                Token::FieldPrefix("body".into()),
+                Token::Regex(r"retr(y|ies)\s+policy".into()),
+                Token::FieldPrefix("path".into()),
+                Token::Regex("^api/v[0-9]".into()),
+                Token::Boost(2.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn slash_outside_field_is_literal() {
+        assert_eq!(
+            tokenize("/usr/bin").unwrap(),
+            vec![Token::Term("/usr/bin".into())]
+        );
+    }
+
+    #[test]
+    fn slash_value_without_closing_slash_is_term() {
+        assert_eq!(
+            tokenize(r"path:/usr/bin body:/retry\/").unwrap(),
+            vec![
+                Token::FieldPrefix("path".into()),
+                Token::Term("/usr/bin".into()),
+                Token::FieldPrefix("body".into()),
+                Token::Term(r"/retry\/".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_regex_reports_position() {
+        let err = tokenize(r"body:/a\/b(c/").unwrap_err();
+        assert!(err.message.starts_with("invalid regex:"));
+        assert_eq!(err.position, 10);
+
+        let err = tokenize("body:/x{2,1}/").unwrap_err();
+        assert!(err.message.starts_with("invalid regex:"));
+        assert_eq!(err.position, 7);
+    }
+
+    #[test]
+    fn macro_reference() {
+        assert_eq!(
+            tokenize("@security -@legacy_api (@v2-docs)").unwrap(),
+            vec![
+                Token::Macro("security".into()),
+                Token::Not,
+                Token::Macro("legacy_api".into()),
+                Token::LParen,
+                Token::Macro("v2-docs".into()),
+                Token::RParen,
+            ]
+        );
+        assert_eq!(
+            tokenize("@ @2fa user@example").unwrap(),
+            vec![
+                Token::Term("@".into()),
+                Token::Term("@2fa".into()),
+                Token::Term("user@example".into()),
+            ]
+        );
+    }
+
     #[test]
     fn boost_after_group() {
         assert_eq!(
@@ -451,4 +875,30 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn spans_mark_token_starts() {
+        let tokens = tokenize_spanned("  title:\"a b\"~2 -x").unwrap();
+        let spans: Vec<usize> = tokens.iter().map(|(_, start)| *start).collect();
+        assert_eq!(spans, vec![2, 8, 13, 16, 17]);
+    }
+
+    #[test]
+    fn lenient_skips_errors() {
+        let (tokens, errors) = tokenize_lenient("rust^ body:/retr(y/ \"open phrase");
+        let tokens: Vec<Token> = tokens.into_iter().map(|(token, _)| token).collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Term("rust".into()),
+                Token::Phrase("open phrase".into())
+            ]
+        );
+        let messages: Vec<&str> = errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages.len(), 3);
+        assert!(messages[0].contains("expected number after '^'"));
+        assert!(messages[1].contains("invalid regex"));
+        assert_eq!(messages[2], "unclosed quote");
+        assert!(tokenize("\"open phrase").is_err());
+    }
 }
@@ -4,11 +4,23 @@
 //!
 //! - **Terms**: `rust` - words that must appear
 //! - **Phrases**: `"error handling"` - exact sequences
+//! - **Fuzziness**: `kubernetes~2`, `api~0` - per-term edit distance
+//! - **Proximity**: `"token refresh"~5`, `retry NEAR/3 backoff` - words close together
 //! - **Negation**: `-deprecated` - terms that must NOT appear
 //! - **OR**: `rust OR golang` - alternatives
 //! - **Grouping**: `(a b) OR (c d)` - precedence control
 //! - **Fields**: `title:guide` - search specific fields
+//! - **Regexes**: `body:/retr(y|ies)/` - match a field's text
 //! - **Boosting**: `rust^2.5` - adjust term importance
+//! - **Dates**: `modified:<30d` - filter by modification time
+//! - **Structure**: `depth:<=2`, `doc:docs:api/auth.md`, `root:true` - filter by position
+//! - **Macros**: `@security` - expand a saved query (see [`parse_with_macros`])
+//!
+//! Queries can also be written as JSON, which [`parse_json`] reads and [`QueryExpr`] serializes
+//! to, so that callers building queries from data need not escape query syntax.
+//!
+//! [`parse_lenient`] repairs common mistakes, such as unbalanced parentheses or a dangling
+//! `OR`, instead of failing, and reports each repair as a diagnostic.
 //!
 //! # Example
 //!
@@ -22,10 +34,18 @@
 #![warn(missing_docs)]
 
 mod ast;
+mod date;
 mod error;
+mod filter;
+mod json;
+mod lenient;
 mod lexer;
 mod parser;
 
-pub use ast::QueryExpr;
+pub use ast::{Comparison, QueryExpr};
+pub use date::{DateFilter, DateUnit, DateValue};
 pub use error::{QueryError, QueryErrorKind};
-pub use parser::parse;
+pub use filter::ChunkFilter;
+pub use json::parse_json;
+pub use lenient::{LenientParse, parse_lenient};
+pub use parser::{MAX_FUZZY_DISTANCE, parse, parse_with_macros};
@@ -7,42 +7,74 @@
 //! ```text
 //! query      → or_expr
 //! or_expr    → and_expr ("OR" and_expr)*
-//! and_expr   → unary+
+//! and_expr   → near_expr+
+//! near_expr  → unary ("NEAR/n" unary)?
 //! unary      → "-" unary | primary
-//! primary    → TERM | PHRASE | field_expr | "(" or_expr ")"
-//! field_expr → FIELD_PREFIX (TERM | PHRASE | "(" or_expr ")")
+//! primary    → term | WILDCARD | phrase | MACRO | field_expr | "(" or_expr ")"
+//! term       → TERM ("~" NUMBER)?
+//! phrase     → PHRASE ("~" NUMBER)?
+//! field_expr → FIELD_PREFIX (term | WILDCARD | REGEX | phrase | MACRO | "(" or_expr ")")
+//!            | "modified:" DATE_FILTER
+//!            | ("depth:" | "doc:" | "id:" | "root:") FILTER_VALUE
 //! ```
 //!
+//! A `DATE_FILTER` is a single TERM holding a comparison or range over dates, such as
+//! `>2025-01-01`, `2025-01..2025-03` or `<30d`. A `REGEX` is a `/.../` literal, which the
+//! lexer only produces directly after a field prefix, and only when the closing slash ends the
+//! value. The `NUMBER` after a term is its fuzzy
+//! edit distance, at most [`MAX_FUZZY_DISTANCE`]; after a phrase it is the slop.
+//!
+//! A `FILTER_VALUE` is a TERM or PHRASE. Since IDs contain a colon (`docs:api/auth.md`), which
+//! the lexer reads as a field prefix, a FIELD_PREFIX followed by a TERM is joined back into one
+//! value.
+//!
+//! A `MACRO` is an `@name` reference to a saved query. Its body is parsed in place and
+//! behaves like a parenthesized group, so `-@legacy` and `title:@security^2` work as they would
+//! with the body written out. Macros may refer to other macros, but not to themselves. An
+//! `@name` that names no saved query, such as `@Override`, is an ordinary term.
+//!
 //! # Precedence (highest to lowest)
 //!
 //! 1. Grouping: `(...)`
 //! 2. Field prefix: `field:`
 //! 3. Negation: `-`
-//! 4. AND (implicit, between adjacent terms)
-//! 5. OR (explicit keyword)
+//! 4. NEAR/n (between two terms or phrases)
+//! 5. AND (implicit, between adjacent terms)
+//! 6. OR (explicit keyword)
 
-use std::mem;
+use std::{collections::HashMap, mem};
 
 use crate::{
     ast::QueryExpr,
+    date::DateFilter,
     error::{ParseError, QueryError},
-    lexer::{Token, tokenize},
+    filter::{ChunkFilter, FILTER_FIELDS},
+    lexer::{Token, tokenize, tokenize_spanned},
 };
 
+/// Largest edit distance a `term~N` may ask for.
+pub const MAX_FUZZY_DISTANCE: u8 = 2;
+
 /// Recursive descent parser for query expressions.
-struct Parser {
+struct Parser<'a> {
     /// Token stream to parse.
     tokens: Vec<Token>,
     /// Current position in token stream.
     position: usize,
+    /// Saved queries that `@name` references expand to.
+    macros: &'a HashMap<String, String>,
+    /// Macros being expanded around this parse, outermost first, for cycle detection.
+    expanding: Vec<String>,
 }
 
-impl Parser {
+impl<'a> Parser<'a> {
     /// Creates a new parser from a token stream.
-    fn new(tokens: Vec<Token>) -> Self {
+    fn new(tokens: Vec<Token>, macros: &'a HashMap<String, String>) -> Self {
         Self {
             tokens,
             position: 0,
+            macros,
+            expanding: Vec::new(),
         }
     }
 
@@ -77,30 +109,75 @@ impl Parser {
         Ok(left)
     }
 
-    /// Parses: and_expr → unary+
+    /// Parses: and_expr → near_expr+
     fn parse_and_expr(&mut self) -> Result<QueryExpr, ParseError> {
         let mut exprs = Vec::new();
 
-        // Parse at least one unary expression
-        exprs.push(self.parse_unary()?);
+        // Parse at least one near expression
+        exprs.push(self.parse_near_expr()?);
 
         // Continue parsing while we see tokens that can start a unary
         while self.can_start_unary() {
-            exprs.push(self.parse_unary()?);
+            exprs.push(self.parse_near_expr()?);
         }
 
         Ok(QueryExpr::and(exprs))
     }
 
+    /// Parses: near_expr → unary ("NEAR/n" unary)?
+    ///
+    /// Both operands must be plain terms or exact phrases, and NEAR cannot be chained.
+    fn parse_near_expr(&mut self) -> Result<QueryExpr, ParseError> {
+        let left_pos = self.position;
+        let left = self.parse_unary()?;
+
+        let Some(Token::Near(distance)) = self.peek().cloned() else {
+            return Ok(left);
+        };
+        Self::check_near_operand(&left, left_pos)?;
+        self.advance(); // consume NEAR/n
+
+        let right_pos = self.position;
+        let right = self.parse_unary()?;
+        Self::check_near_operand(&right, right_pos)?;
+
+        if matches!(self.peek(), Some(Token::Near(_))) {
+            return Err(ParseError::new(
+                "NEAR cannot be chained; group with AND instead",
+                Some(self.position),
+            ));
+        }
+
+        Ok(QueryExpr::Near {
+            left: Box::new(left),
+            right: Box::new(right),
+            distance,
+        })
+    }
+
+    /// Checks that a NEAR operand is a term or exact phrase.
+    fn check_near_operand(expr: &QueryExpr, position: usize) -> Result<(), ParseError> {
+        if matches!(expr, QueryExpr::Term(_) | QueryExpr::Phrase(_)) {
+            Ok(())
+        } else {
+            Err(ParseError::new(
+                "NEAR operands must be terms or phrases",
+                Some(position),
+            ))
+        }
+    }
+
     /// Checks if the current token can start a unary expression.
     fn can_start_unary(&self) -> bool {
         matches!(
             self.peek(),
             Some(Token::Term(_))
+                | Some(Token::Wildcard(_))
                 | Some(Token::Phrase(_))
                 | Some(Token::Not)
                 | Some(Token::LParen)
                 | Some(Token::FieldPrefix(_))
+                | Some(Token::Macro(_))
         )
     }
 
@@ -125,18 +202,22 @@ impl Parser {
         self.parse_primary()
     }
 
-    /// Parses: primary → TERM | PHRASE | field_expr | "(" or_expr ")"
+    /// Parses: primary → TERM | PHRASE | MACRO | field_expr | "(" or_expr ")"
     ///
     /// After parsing the primary expression, checks for an optional boost suffix.
     fn parse_primary(&mut self) -> Result<QueryExpr, ParseError> {
         let expr = match self.peek().cloned() {
-            Some(Token::Term(_)) | Some(Token::Phrase(_)) => self.parse_term_or_phrase(),
+            Some(Token::Term(_)) | Some(Token::Wildcard(_)) | Some(Token::Phrase(_)) => {
+                self.parse_term_or_phrase()?
+            }
 
             Some(Token::FieldPrefix(name)) => {
                 self.advance();
                 self.parse_field_expr(name)?
             }
 
+            Some(Token::Macro(name)) => self.parse_macro(&name)?,
+
             Some(Token::LParen) => self.parse_group("expected closing parenthesis")?,
 
             Some(Token::RParen) => {
@@ -165,6 +246,24 @@ impl Parser {
                 ));
             }
 
+            Some(Token::Near(_)) => {
+                return Err(ParseError::new(
+                    "unexpected NEAR (needs term or phrase before it)",
+                    Some(self.position),
+                ));
+            }
+
+            Some(Token::Tilde(_)) => {
+                return Err(ParseError::new("unexpected '~'", Some(self.position)));
+            }
+
+            Some(Token::Regex(_)) => {
+                return Err(ParseError::new(
+                    "unexpected regex (needs a field prefix before it)",
+                    Some(self.position),
+                ));
+            }
+
             None => {
                 return Err(ParseError::new("unexpected end of query", None));
             }
@@ -175,14 +274,28 @@ impl Parser {
 
     /// Parses the expression after a field prefix.
     fn parse_field_expr(&mut self, name: String) -> Result<QueryExpr, ParseError> {
+        if name == "modified" {
+            return self.parse_date_filter();
+        }
+        if FILTER_FIELDS.contains(&name.as_str()) {
+            return self.parse_chunk_filter(&name);
+        }
+
         let expr = match self.peek().cloned() {
-            Some(Token::Term(_)) | Some(Token::Phrase(_)) => self.parse_term_or_phrase(),
+            Some(Token::Term(_)) | Some(Token::Wildcard(_)) | Some(Token::Phrase(_)) => {
+                self.parse_term_or_phrase()?
+            }
+            Some(Token::Regex(pattern)) => {
+                self.advance();
+                QueryExpr::Regex(pattern)
+            }
+            Some(Token::Macro(macro_name)) => self.parse_macro(&macro_name)?,
             Some(Token::LParen) => {
                 self.parse_group("expected closing parenthesis after field expression")?
             }
             _ => {
                 return Err(ParseError::new(
-                    format!("expected term, phrase, or group after '{}:'", name),
+                    format!("expected term, phrase, regex, or group after '{}:'", name),
                     Some(self.position),
                 ));
             }
@@ -194,24 +307,153 @@ impl Parser {
         })
     }
 
-    /// Parses a TERM or PHRASE token into a QueryExpr, consuming the token.
-    fn parse_term_or_phrase(&mut self) -> QueryExpr {
-        match self.peek().cloned() {
+    /// Parses the date filter after a `modified:` prefix.
+    fn parse_date_filter(&mut self) -> Result<QueryExpr, ParseError> {
+        let Some(Token::Term(text)) = self.peek().cloned() else {
+            return Err(ParseError::new(
+                "expected date after 'modified:'",
+                Some(self.position),
+            ));
+        };
+        let filter =
+            DateFilter::parse(&text).map_err(|e| ParseError::new(e, Some(self.position)))?;
+        self.advance();
+        Ok(QueryExpr::Modified(filter))
+    }
+
+    /// Parses the value after a `depth:`, `doc:`, `id:` or `root:` prefix.
+    fn parse_chunk_filter(&mut self, name: &str) -> Result<QueryExpr, ParseError> {
+        let position = self.position;
+        let value = match (self.peek().cloned(), self.tokens.get(position + 1).cloned()) {
+            (Some(Token::Term(text)), _) => {
+                self.advance();
+                text
+            }
+            (Some(Token::Phrase(text)), _) => {
+                self.advance();
+                text.split_whitespace().collect::<Vec<_>>().join(" ")
+            }
+            (Some(Token::FieldPrefix(tree)), Some(Token::Term(rest))) => {
+                self.advance();
+                self.advance();
+                format!("{tree}:{rest}")
+            }
+            _ => {
+                return Err(ParseError::new(
+                    format!("expected value after '{name}:'"),
+                    Some(position),
+                ));
+            }
+        };
+        let filter =
+            ChunkFilter::parse(name, &value).map_err(|e| ParseError::new(e, Some(position)))?;
+        Ok(QueryExpr::Filter(filter))
+    }
+
+    /// Parses a TERM, WILDCARD or PHRASE token into a QueryExpr, consuming the token and any
+    /// fuzziness suffix on a term or slop suffix on a phrase.
+    fn parse_term_or_phrase(&mut self) -> Result<QueryExpr, ParseError> {
+        let expr = match self.peek().cloned() {
             Some(Token::Term(text)) => {
                 self.advance();
-                QueryExpr::Term(text)
+                let Some(&Token::Tilde(count)) = self.peek() else {
+                    return Ok(QueryExpr::Term(text));
+                };
+                let distance = u8::try_from(count)
+                    .ok()
+                    .filter(|&d| d <= MAX_FUZZY_DISTANCE)
+                    .ok_or_else(|| {
+                        ParseError::new(
+                            format!(
+                                "fuzzy distance must be at most {MAX_FUZZY_DISTANCE}, got ~{count}"
+                            ),
+                            Some(self.position),
+                        )
+                    })?;
+                self.advance();
+                QueryExpr::Fuzzy {
+                    term: text,
+                    distance,
+                }
+            }
+            Some(Token::Wildcard(pattern)) => {
+                self.advance();
+                if let Some(Token::Tilde(_)) = self.peek() {
+                    return Err(ParseError::new(
+                        "'~' cannot follow a wildcard term",
+                        Some(self.position),
+                    ));
+                }
+                QueryExpr::Wildcard(pattern)
             }
             Some(Token::Phrase(text)) => {
                 self.advance();
                 let words: Vec<String> = text.split_whitespace().map(String::from).collect();
-                if words.is_empty() {
-                    QueryExpr::Term(String::new())
-                } else {
-                    QueryExpr::Phrase(words)
+                let slop = match self.peek() {
+                    Some(&Token::Tilde(slop)) => {
+                        self.advance();
+                        Some(slop)
+                    }
+                    _ => None,
+                };
+                match (words.is_empty(), slop) {
+                    (true, _) => QueryExpr::Term(String::new()),
+                    (false, Some(slop)) => QueryExpr::Proximity { words, slop },
+                    (false, None) => QueryExpr::Phrase(words),
                 }
             }
-            _ => unreachable!("parse_term_or_phrase called on non-term/phrase token"),
+            _ => unreachable!("parse_term_or_phrase called on non-term/wildcard/phrase token"),
+        };
+        Ok(expr)
+    }
+
+    /// Expands an `@name` reference by parsing the saved query it names, consuming the token.
+    ///
+    /// Errors inside the saved query are reported against the reference. A name with no saved
+    /// query is parsed as the term `@name`.
+    fn parse_macro(&mut self, name: &str) -> Result<QueryExpr, ParseError> {
+        let position = self.position;
+        let error = |message: String| ParseError::new(message, Some(position));
+
+        let Some(body) = self.macros.get(name) else {
+            self.tokens[position] = Token::Term(format!("@{name}"));
+            return self.parse_term_or_phrase();
+        };
+        if self.expanding.iter().any(|n| n == name) {
+            let chain: Vec<String> = self
+                .expanding
+                .iter()
+                .map(String::as_str)
+                .chain([name])
+                .map(|n| format!("@{n}"))
+                .collect();
+            return Err(error(format!("query macro cycle: {}", chain.join(" -> "))));
         }
+
+        // Errors from a nested macro already say which macro they came from
+        let in_macro = |message: String| {
+            if message.contains("query macro") {
+                error(message)
+            } else {
+                error(format!("in query macro @{name}: {message}"))
+            }
+        };
+        let tokens = tokenize(body).map_err(|e| in_macro(e.message))?;
+        let mut expanding = self.expanding.clone();
+        expanding.push(name.to_string());
+        let inner = Parser {
+            tokens,
+            position: 0,
+            macros: self.macros,
+            expanding,
+        };
+        let expr = inner
+            .parse()
+            .map_err(|e| in_macro(e.message))?
+            .ok_or_else(|| error(format!("query macro @{name} is empty")))?;
+
+        self.advance(); // consume @name
+        Ok(expr)
     }
 
     /// Parses a parenthesized group, consuming the surrounding parentheses.
@@ -252,8 +494,41 @@ impl Parser {
 /// Returns `Ok(None)` for empty queries, `Ok(Some(expr))` for valid queries,
 /// or `Err(QueryError)` for invalid syntax.
 pub fn parse(input: &str) -> Result<Option<QueryExpr>, QueryError> {
-    let tokens = tokenize(input).map_err(QueryError::from)?;
-    Parser::new(tokens).parse().map_err(QueryError::from)
+    parse_with_macros(input, &HashMap::new())
+}
+
+/// Parses a query string into an AST, expanding `@name` references to saved queries.
+///
+/// `macros` maps each name to the query it stands for. A name that is not in `macros` is an
+/// ordinary term, and a macro that refers back to itself is an error.
+pub fn parse_with_macros(
+    input: &str,
+    macros: &HashMap<String, String>,
+) -> Result<Option<QueryExpr>, QueryError> {
+    let (tokens, spans): (Vec<Token>, Vec<usize>) = tokenize_spanned(input)
+        .map_err(QueryError::from)?
+        .into_iter()
+        .unzip();
+    parse_tokens(tokens, macros).map_err(|err| {
+        let position = byte_position(&spans, err.token_index, input);
+        QueryError::parse(err.message, Some(position), Some(input.to_string()))
+    })
+}
+
+/// Parses a token stream into an AST, expanding `@name` references from `macros`.
+pub fn parse_tokens(
+    tokens: Vec<Token>,
+    macros: &HashMap<String, String>,
+) -> Result<Option<QueryExpr>, ParseError> {
+    Parser::new(tokens, macros).parse()
+}
+
+/// Returns the byte position in `input` of the token at `token_index`, or the end of the
+/// input for errors past the last token.
+pub fn byte_position(spans: &[usize], token_index: Option<usize>, input: &str) -> usize {
+    token_index
+        .and_then(|index| spans.get(index).copied())
+        .unwrap_or(input.len())
 }
 
 #[cfg(test)]
@@ -561,6 +836,263 @@ mod tests {
         );
     }
 
+    fn near(left: QueryExpr, right: QueryExpr, distance: u32) -> QueryExpr {
+        QueryExpr::Near {
+            left: Box::new(left),
+            right: Box::new(right),
+            distance,
+        }
+    }
+
+    #[test]
+    fn phrase_with_slop() {
+        assert_eq!(
+            parse("\"token refresh\"~5").unwrap(),
+            Some(QueryExpr::Proximity {
+                words: vec!["token".into(), "refresh".into()],
+                slop: 5
+            })
+        );
+    }
+
+    #[test]
+    fn phrase_with_slop_and_boost() {
+        assert_eq!(
+            parse("body:\"token refresh\"~5^2").unwrap(),
+            Some(boost(
+                field(
+                    "body",
+                    QueryExpr::Proximity {
+                        words: vec!["token".into(), "refresh".into()],
+                        slop: 5
+                    }
+                ),
+                2.0
+            ))
+        );
+    }
+
+    #[test]
+    fn near_terms() {
+        assert_eq!(
+            parse("retry NEAR/3 backoff").unwrap(),
+            Some(near(term("retry"), term("backoff"), 3))
+        );
+    }
+
+    #[test]
+    fn near_binds_tighter_than_and_and_or() {
+        assert_eq!(
+            parse("http retry NEAR/3 backoff OR jitter").unwrap(),
+            Some(or(vec![
+                and(vec![term("http"), near(term("retry"), term("backoff"), 3)]),
+                term("jitter")
+            ]))
+        );
+    }
+
+    #[test]
+    fn near_phrase_operand() {
+        assert_eq!(
+            parse("\"token refresh\" NEAR/5 expiry").unwrap(),
+            Some(near(phrase(&["token", "refresh"]), term("expiry"), 5))
+        );
+    }
+
+    #[test]
+    fn near_query_string_round_trip() {
+        let query = "retry NEAR/3 backoff \"token refresh\"~4";
+        let expr = parse(query).unwrap().unwrap();
+        assert_eq!(expr.to_query_string(), query);
+        assert_eq!(parse(&expr.to_query_string()).unwrap(), Some(expr));
+    }
+
+    #[test]
+    fn wildcards() {
+        let wildcard = |s: &str| QueryExpr::Wildcard(s.into());
+        assert_eq!(
+            parse("auth* -config? path:api*").unwrap(),
+            Some(and(vec![
+                wildcard("auth*"),
+                not(wildcard("config?")),
+                field("path", wildcard("api*"))
+            ]))
+        );
+        assert_eq!(
+            parse("max_*^2").unwrap(),
+            Some(boost(wildcard("max_*"), 2.0))
+        );
+        assert_eq!(
+            parse("auth*").unwrap().unwrap().extract_terms(),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            parse("auth* OR config?")
+                .unwrap()
+                .unwrap()
+                .to_query_string(),
+            "(auth* OR config?)"
+        );
+    }
+
+    #[test]
+    fn term_fuzziness() {
+        let fuzzy = |term: &str, distance| QueryExpr::Fuzzy {
+            term: term.into(),
+            distance,
+        };
+        assert_eq!(
+            parse("kubernetes~2 -pod~0 title:api~1^2").unwrap(),
+            Some(and(vec![
+                fuzzy("kubernetes", 2),
+                not(fuzzy("pod", 0)),
+                boost(field("title", fuzzy("api", 1)), 2.0)
+            ]))
+        );
+        let expr = parse("kubernetes~2 OR pod~0").unwrap().unwrap();
+        assert_eq!(expr.extract_terms(), vec!["kubernetes", "pod"]);
+        assert_eq!(expr.to_query_string(), "(kubernetes~2 OR pod~0)");
+        assert_eq!(parse(&expr.to_query_string()).unwrap(), Some(expr));
+    }
+
+    #[test]
+    fn error_term_fuzziness() {
+        let err = parse("kubernetes~3").unwrap_err();
+        assert!(err.message().contains("fuzzy distance must be at most 2"));
+
+        let err = parse("auth*~1").unwrap_err();
+        assert!(err.message().contains("cannot follow a wildcard"));
+
+        let err = parse("pod~0 NEAR/2 api").unwrap_err();
+        assert!(err.message().contains("terms or phrases"));
+    }
+
+    #[test]
+    fn regexes() {
+        let regex = |s: &str| QueryExpr::Regex(s.into());
+        assert_eq!(
+            parse(r"body:/retr(y|ies)\s+policy/ -path:/^api\/v[0-9]/").unwrap(),
+            Some(and(vec![
+                field("body", regex(r"retr(y|ies)\s+policy")),
+                not(field("path", regex("^api/v[0-9]")))
+            ]))
+        );
+        let expr = parse(r"path:/^api\/v[0-9]/").unwrap().unwrap();
+        assert!(expr.extract_terms().is_empty());
+        assert_eq!(expr.to_query_string(), r"path:/^api\/v[0-9]/");
+        assert_eq!(parse(&expr.to_query_string()).unwrap(), Some(expr));
+    }
+
+    #[test]
+    fn chunk_filters() {
+        let filter =
+            |name: &str, value: &str| QueryExpr::Filter(ChunkFilter::parse(name, value).unwrap());
+        assert_eq!(
+            parse("auth depth:<=2 doc:docs:api/auth.md").unwrap(),
+            Some(and(vec![
+                term("auth"),
+                filter("depth", "<=2"),
+                filter("doc", "docs:api/auth.md")
+            ]))
+        );
+        assert_eq!(
+            parse("id:docs:api/auth.md#login OR (root:true -doc:\"notes:my file.md\")").unwrap(),
+            Some(or(vec![
+                filter("id", "docs:api/auth.md#login"),
+                and(vec![
+                    filter("root", "true"),
+                    not(filter("doc", "notes:my file.md"))
+                ])
+            ]))
+        );
+
+        let expr = parse("depth:>1 doc:\"notes:my file.md\"").unwrap().unwrap();
+        assert!(expr.extract_terms().is_empty());
+        assert_eq!(expr.to_query_string(), "depth:>1 doc:\"notes:my file.md\"");
+        assert_eq!(parse(&expr.to_query_string()).unwrap(), Some(expr));
+    }
+
+    #[test]
+    fn error_chunk_filters() {
+        let err = parse("depth:deep").unwrap_err();
+        assert!(err.message().contains("invalid depth"));
+
+        let err = parse("root:maybe").unwrap_err();
+        assert!(err.message().contains("expected true or false"));
+
+        let err = parse("doc:(a OR b)").unwrap_err();
+        assert!(err.message().contains("expected value after 'doc:'"));
+    }
+
+    #[test]
+    fn error_invalid_regex() {
+        let err = parse("title:guide body:/retr(y|ies/").unwrap_err();
+        assert!(err.message().starts_with("invalid regex:"));
+        assert!(matches!(
+            err.kind,
+            crate::QueryErrorKind::Lex { position: 22, .. }
+        ));
+        assert!(err.to_string().contains("hint:"));
+    }
+
+    #[test]
+    fn error_near_operands() {
+        let err = parse("NEAR/3 backoff").unwrap_err();
+        assert!(err.message().contains("unexpected NEAR"));
+
+        let err = parse("retry NEAR/3").unwrap_err();
+        assert!(err.message().contains("end of query"));
+
+        let err = parse("(a b) NEAR/3 c").unwrap_err();
+        assert!(err.message().contains("terms or phrases"));
+
+        let err = parse("a NEAR/3 -b").unwrap_err();
+        assert!(err.message().contains("terms or phrases"));
+
+        let err = parse("a NEAR/3 b NEAR/3 c").unwrap_err();
+        assert!(err.message().contains("chained"));
+    }
+
+    fn modified(text: &str) -> QueryExpr {
+        QueryExpr::Modified(DateFilter::parse(text).unwrap())
+    }
+
+    #[test]
+    fn modified_filters() {
+        assert_eq!(
+            parse("modified:>2025-01-01").unwrap(),
+            Some(modified(">2025-01-01"))
+        );
+        assert_eq!(
+            parse("modified:2025-01..2025-03").unwrap(),
+            Some(modified("2025-01..2025-03"))
+        );
+        assert_eq!(
+            parse("auth modified:<30d").unwrap(),
+            Some(and(vec![term("auth"), modified("<30d")]))
+        );
+        assert_eq!(parse("-modified:<1w").unwrap(), Some(not(modified("<1w"))));
+    }
+
+    #[test]
+    fn modified_round_trips() {
+        let expr = parse("rust modified:>=2025-01").unwrap().unwrap();
+        assert_eq!(expr.to_query_string(), "rust modified:>=2025-01");
+        assert!(expr.extract_terms() == vec!["rust".to_string()]);
+    }
+
+    #[test]
+    fn error_invalid_date() {
+        let err = parse("modified:>2025-13").unwrap_err();
+        assert!(err.message().contains("invalid month"));
+
+        let err = parse("modified:\"last week\"").unwrap_err();
+        assert!(err.message().contains("expected date"));
+
+        let err = parse("modified:").unwrap_err();
+        assert!(err.message().contains("expected date"));
+    }
+
     #[test]
     fn performance_many_queries() {
         // Verify parsing is fast enough for practical use
@@ -654,6 +1186,82 @@ mod tests {
         );
     }
 
+    fn macros(entries: &[(&str, &str)]) -> HashMap<String, String> {
+        entries
+            .iter()
+            .map(|(name, body)| (name.to_string(), body.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn macros_expand_in_place() {
+        let defs = macros(&[
+            ("security", "(auth OR token) -deprecated"),
+            ("secure_api", "@security api"),
+        ]);
+        let security = and(vec![
+            or(vec![term("auth"), term("token")]),
+            not(term("deprecated")),
+        ]);
+
+        assert_eq!(
+            parse_with_macros("@security rust", &defs).unwrap(),
+            Some(and(vec![security.clone(), term("rust")]))
+        );
+        assert_eq!(
+            parse_with_macros("-@security", &defs).unwrap(),
+            Some(not(security.clone()))
+        );
+        assert_eq!(
+            parse_with_macros("title:@security^2", &defs).unwrap(),
+            Some(boost(field("title", security.clone()), 2.0))
+        );
+        assert_eq!(
+            parse_with_macros("@secure_api", &defs).unwrap(),
+            Some(and(vec![security, term("api")]))
+        );
+    }
+
+    #[test]
+    fn unknown_macros_are_terms() {
+        let defs = macros(&[("security", "auth")]);
+        assert_eq!(
+            parse_with_macros("@Override annotation", &defs).unwrap(),
+            Some(and(vec![term("@Override"), term("annotation")]))
+        );
+        assert_eq!(
+            parse("title:@decorator~1 -@mention").unwrap(),
+            Some(and(vec![
+                field(
+                    "title",
+                    QueryExpr::Fuzzy {
+                        term: "@decorator".into(),
+                        distance: 1,
+                    },
+                ),
+                not(term("@mention")),
+            ]))
+        );
+    }
+
+    #[test]
+    fn error_macro_cycle() {
+        let defs = macros(&[("a", "x @b"), ("b", "y OR @a"), ("self", "@self")]);
+        let err = parse_with_macros("rust @a", &defs).unwrap_err();
+        assert_eq!(err.message(), "query macro cycle: @a -> @b -> @a");
+        let err = parse_with_macros("@self", &defs).unwrap_err();
+        assert_eq!(err.message(), "query macro cycle: @self -> @self");
+    }
+
+    #[test]
+    fn error_inside_macro_names_it() {
+        let defs = macros(&[("broken", "(auth OR"), ("empty", "  ")]);
+        let err = parse_with_macros("@broken", &defs).unwrap_err();
+        assert!(err.message().starts_with("in query macro @broken: "));
+        let err = parse_with_macros("@empty", &defs).unwrap_err();
+        assert_eq!(err.message(), "query macro @empty is empty");
+    }
+
     #[test]
     fn error_boost_at_start() {
         let err = parse("^2.5 rust").unwrap_err();
@@ -671,4 +1279,19 @@ mod tests {
         assert!(query_str.contains("rust^2.5"));
         assert!(query_str.contains("\"error handling\"^3"));
     }
+
+    #[test]
+    fn query_string_quotes_terms_with_syntax() {
+        let expr = and(vec![term("a OR b"), term("or"), term("x:y"), term("plain")]);
+        assert_eq!(expr.to_query_string(), "\"a OR b\" \"or\" \"x:y\" plain");
+        assert_eq!(
+            parse(&expr.to_query_string()).unwrap(),
+            Some(and(vec![
+                phrase(&["a", "OR", "b"]),
+                phrase(&["or"]),
+                phrase(&["x:y"]),
+                term("plain"),
+            ]))
+        );
+    }
 }
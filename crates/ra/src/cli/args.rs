@@ -9,6 +9,11 @@ fn parse_algorithm(s: &str) -> Result<ra_context::KeywordAlgorithm, String> {
     s.parse()
 }
 
+/// Parse a facet field name from a string.
+fn parse_facet(s: &str) -> Result<ra_index::FacetField, String> {
+    s.parse()
+}
+
 /// Top-level CLI options.
 #[derive(Parser)]
 #[command(name = "ra")]
@@ -42,6 +47,10 @@ pub struct SearchParamsArgs {
     #[arg(long)]
     pub aggregation_threshold: Option<f32>,
 
+    /// Token budget for results; results that would overflow it are skipped
+    #[arg(long)]
+    pub max_tokens: Option<usize>,
+
     /// Verbosity level (-v for summary, -vv for full details)
     #[arg(short = 'v', long, action = clap::ArgAction::Count)]
     pub verbose: u8,
@@ -85,9 +94,13 @@ pub struct ExplainArgs {
 #[derive(Args, Debug, Clone)]
 pub struct SearchCommand {
     /// Search queries
-    #[arg(required = true)]
+    #[arg(required_unless_present = "query_json", conflicts_with = "query_json")]
     pub queries: Vec<String>,
 
+    /// Query as JSON instead of query syntax ('-' reads it from stdin)
+    #[arg(long, value_name = "JSON", conflicts_with = "lenient")]
+    pub query_json: Option<String>,
+
     #[command(flatten)]
     /// Search parameter overrides.
     pub params: SearchParamsArgs,
@@ -103,6 +116,18 @@ pub struct SearchCommand {
     /// Fuzzy matching edit distance (0=exact, 1-2=fuzzy) [default: 1]
     #[arg(short = 'f', long)]
     pub fuzzy: Option<u8>,
+
+    /// Repair query syntax mistakes and warn about them instead of failing
+    #[arg(long)]
+    pub lenient: bool,
+
+    /// Count matching documents per value of these fields (tags, tree)
+    #[arg(long, value_name = "FIELDS", value_delimiter = ',', value_parser = parse_facet)]
+    pub facets: Vec<ra_index::FacetField>,
+
+    /// Add chunks linked from the top N results, at a discounted score
+    #[arg(long, value_name = "N")]
+    pub follow_links: Option<usize>,
 }
 
 /// Arguments for `ra context`.
@@ -136,6 +161,10 @@ pub struct ContextCommand {
     /// Fuzzy matching edit distance (0=exact, 1-2=fuzzy) [default: 1]
     #[arg(short = 'f', long)]
     pub fuzzy: Option<u8>,
+
+    /// Add chunks linked from the top N results, at a discounted score
+    #[arg(long, value_name = "N")]
+    pub follow_links: Option<usize>,
 }
 
 /// Arguments for `ra get`.
@@ -145,9 +174,40 @@ pub struct GetCommand {
     pub id: String,
 
     /// Return full document even if ID specifies a chunk
-    #[arg(long)]
+    #[arg(long, conflicts_with_all = ["parent", "siblings", "children", "next", "prev"])]
     pub full_document: bool,
 
+    /// Also return the chunk's parent
+    #[arg(long)]
+    pub parent: bool,
+
+    /// Also return chunks that share the chunk's parent
+    #[arg(long)]
+    pub siblings: bool,
+
+    /// Also return the chunk's direct children
+    #[arg(long)]
+    pub children: bool,
+
+    /// Also return the N chunks that follow in document order
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub next: usize,
+
+    /// Also return the N chunks that precede in document order
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub prev: usize,
+
+    /// Output in JSON format
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Arguments for `ra links`.
+#[derive(Args, Debug, Clone)]
+pub struct LinksCommand {
+    /// Chunk or document ID (tree:path#slug or tree:path)
+    pub id: String,
+
     /// Output in JSON format
     #[arg(long)]
     pub json: bool,
@@ -232,6 +292,18 @@ pub struct AgentsCommand {
     #[arg(long)]
     pub stdout: bool,
 
+    /// Show unified diff of pending changes without writing files
+    #[arg(long, conflicts_with_all = ["stdout", "check"])]
+    pub diff: bool,
+
+    /// Suppress diff output when writing or checking
+    #[arg(long)]
+    pub quiet: bool,
+
+    /// Exit non-zero if agent files on disk are out of date
+    #[arg(long, conflicts_with = "stdout")]
+    pub check: bool,
+
     /// Generate CLAUDE.md
     #[arg(long)]
     pub claude: bool,
@@ -245,6 +317,41 @@ pub struct AgentsCommand {
     pub all: bool,
 }
 
+/// Arguments for `ra serve`.
+#[derive(Args, Debug, Clone)]
+pub struct ServeCommand {
+    /// Address to listen on (use port 0 to pick a free port)
+    #[arg(long, default_value = "127.0.0.1:7681")]
+    pub listen: String,
+}
+
+/// Arguments for `ra watch`.
+#[derive(Args, Debug, Clone)]
+pub struct WatchCommand {
+    /// Milliseconds to wait for changes to settle before reindexing
+    #[arg(long, default_value = "500")]
+    pub debounce: u64,
+}
+
+/// Arguments for `ra tui`.
+#[derive(Args, Debug, Clone)]
+pub struct TuiCommand {
+    /// Initial query
+    pub query: Vec<String>,
+
+    /// Maximum results to return after aggregation [default: 10]
+    #[arg(short = 'n', long)]
+    pub limit: Option<usize>,
+
+    /// Start with results limited to this tree
+    #[arg(short = 't', long)]
+    pub tree: Option<String>,
+
+    /// Fuzzy matching edit distance (0=exact, 1-2=fuzzy) [default: 1]
+    #[arg(short = 'f', long)]
+    pub fuzzy: Option<u8>,
+}
+
 /// Supported `ra` subcommands.
 #[derive(Subcommand)]
 pub enum Commands {
@@ -253,10 +360,15 @@ pub enum Commands {
 QUERY SYNTAX:
   term              Term must appear
   term1 term2       Both terms (implicit AND)
+  pre*  con?ig      Wildcard term (* any run, ? one character)
+  term~N            Term within N edits, overriding --fuzzy (~0 is exact)
   \"phrase\"          Exact phrase match
+  \"phrase\"~N        Phrase words up to N positions apart
+  a NEAR/N b        Terms within N words, either order
   -term             Term must NOT appear
   term1 OR term2    Either term
   (expr)            Grouping
+  @name             Saved query from the [queries] config table
 
 FIELD QUERIES:
   title:term        Search in titles only
@@ -264,6 +376,20 @@ FIELD QUERIES:
   tags:term         Search in tags only
   path:term         Search in file paths only
   tree:name         Filter to specific tree
+  tag:name          Filter to an exact tag
+  meta.key:value    Filter by a frontmatter value
+  body:/regex/      Regular expression over a field's text (\\/ for a slash)
+
+DATE FILTERS:
+  modified:>2025-01-01        Modified after a day (also <, >=, <=)
+  modified:2025-01..2025-03   Modified from January through March 2025
+  modified:<30d               Modified less than 30 days ago (h, d, w, m, y)
+
+STRUCTURE FILTERS:
+  depth:<=2                   Heading depth (0 document, 1-6 h1-h6; also <, >, >=)
+  root:true                   Whole documents only (root:false for sections)
+  doc:docs:api/auth.md        Chunks of one document
+  id:docs:api/auth.md#login   One chunk
 
 EXAMPLES:
   ra search rust async
@@ -271,7 +397,8 @@ EXAMPLES:
   ra search 'rust -deprecated'
   ra search 'rust OR golang'
   ra search 'title:guide (rust OR golang)'
-  ra search 'tree:docs authentication'")]
+  ra search 'tree:docs authentication'
+  ra search 'deploy modified:<2w'")]
     Search(SearchCommand),
 
     /// Get relevant context for files being worked on
@@ -280,6 +407,17 @@ EXAMPLES:
     /// Retrieve a specific chunk or document by ID
     Get(GetCommand),
 
+    /// Show the links from and to a chunk or document
+    #[command(after_help = "\
+Links are markdown links to indexed files and [[wiki-links]], resolved to chunk
+IDs when the linking file is indexed. For a document ID, links from and to any
+of its chunks are shown, except links within the document itself.
+
+EXAMPLES:
+  ra links docs:guide.md#setup
+  ra links docs:guide.md --json")]
+    Links(LinksCommand),
+
     /// Find documents similar to a source document or file
     #[command(
         name = "likethis",
@@ -333,6 +471,43 @@ EXAMPLES:
 
     /// Generate AGENTS.md, CLAUDE.md, GEMINI.md
     Agents(AgentsCommand),
+
+    /// Serve search tools to agents over MCP (stdio)
+    Mcp,
+
+    /// Serve search endpoints over local HTTP
+    Serve(ServeCommand),
+
+    /// Watch trees and keep the index up to date
+    Watch(WatchCommand),
+
+    /// Explore search results interactively
+    #[command(after_help = "\
+KEYS:
+  Typing            Edit the query; results update as you type
+  Up/Down           Select a result
+  PgUp/PgDn         Scroll the preview
+  Tab/Shift-Tab     Cycle the tree filter
+  Ctrl-P            Show the selected chunk's parent
+  Ctrl-S            List the selected chunk's siblings
+  Ctrl-U            Clear the query
+  Esc               Go back, or quit from the search view
+  Ctrl-C            Quit")]
+    Tui(TuiCommand),
+
+    /// Answer JSONL requests from stdin with one JSON response per line
+    #[command(after_help = "\
+REQUESTS:
+  Each line is a JSON object with an \"op\" field and that operation's parameters:
+
+  {\"op\": \"search\", \"queries\": \"rust async\", \"limit\": 5}
+  {\"op\": \"context\", \"files\": [\"src/main.rs\"]}
+  {\"op\": \"get\", \"id\": \"docs:guide.md#install\", \"next\": 1}
+  {\"op\": \"likethis\", \"source\": \"docs:guide.md\"}
+
+  An optional \"ref\" value is copied to the response. Failed requests produce
+  {\"error\": \"...\"} and processing continues.")]
+    Batch,
 }
 
 /// What to list with `ra ls`.
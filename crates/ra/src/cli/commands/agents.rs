@@ -1,14 +1,237 @@
 //! Implementation of `ra agents`.
+//!
+//! Agent files are built by concatenating the optional `.agents.md` templates from the project
+//! root and the home directory, then appending generated instructions that describe the
+//! configured trees and show how to query them.
 
-use std::process::ExitCode;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
 
-use crate::cli::args::AgentsCommand;
+use ra_config::{Config, global_config_path};
+use similar::TextDiff;
+
+use crate::cli::{args::AgentsCommand, context::CommandContext};
+
+/// Template file name looked up in the project root and home directory.
+const TEMPLATE_FILENAME: &str = ".agents.md";
+
+/// Agent file that is always generated.
+const AGENTS_FILE: &str = "AGENTS.md";
+
+/// Agent file generated with `--claude` or `--all`.
+const CLAUDE_FILE: &str = "CLAUDE.md";
+
+/// Agent file generated with `--gemini` or `--all`.
+const GEMINI_FILE: &str = "GEMINI.md";
+
+/// Comparison between an agent file on disk and its generated content.
+struct AgentFile {
+    /// Path the file is written to.
+    path: PathBuf,
+    /// Current contents on disk, if the file exists.
+    existing: Option<String>,
+}
+
+impl AgentFile {
+    /// Reads the current state of the agent file at `path`.
+    fn load(path: PathBuf) -> Self {
+        let existing = fs::read_to_string(&path).ok();
+        Self { path, existing }
+    }
+
+    /// Returns true if the file on disk differs from `content`.
+    fn is_stale(&self, content: &str) -> bool {
+        self.existing.as_deref() != Some(content)
+    }
+
+    /// Returns a unified diff from the file on disk to `content`.
+    fn diff(&self, content: &str) -> String {
+        let old = self.existing.as_deref().unwrap_or("");
+        let name = self.path.display().to_string();
+        let old_header = if self.existing.is_some() {
+            name.as_str()
+        } else {
+            "/dev/null"
+        };
+        TextDiff::from_lines(old, content)
+            .unified_diff()
+            .header(old_header, &name)
+            .to_string()
+    }
+}
 
 /// Generates agent instruction files.
-pub fn run(cmd: &AgentsCommand) -> ExitCode {
-    println!(
-        "agents (stdout={}, claude={}, gemini={}, all={})",
-        cmd.stdout, cmd.claude, cmd.gemini, cmd.all
-    );
+pub fn run(ctx: &CommandContext, cmd: &AgentsCommand) -> ExitCode {
+    let root = project_root(ctx);
+    let content = match generate(&ctx.config, &root) {
+        Ok(content) => content,
+        Err(message) => {
+            eprintln!("error: {message}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if cmd.stdout {
+        print!("{content}");
+        return ExitCode::SUCCESS;
+    }
+
+    let files: Vec<AgentFile> = target_files(cmd)
+        .into_iter()
+        .map(|name| AgentFile::load(root.join(name)))
+        .collect();
+
+    if cmd.check {
+        return check(&files, &content, cmd.quiet);
+    }
+
+    for file in &files {
+        if !file.is_stale(&content) {
+            if !cmd.diff && !cmd.quiet {
+                println!("{} is up to date", file.path.display());
+            }
+            continue;
+        }
+
+        if !cmd.quiet || cmd.diff {
+            print!("{}", file.diff(&content));
+        }
+        if cmd.diff {
+            continue;
+        }
+
+        if let Err(e) = fs::write(&file.path, &content) {
+            eprintln!("error: failed to write {}: {e}", file.path.display());
+            return ExitCode::FAILURE;
+        }
+        println!("Wrote {}", file.path.display());
+    }
+
     ExitCode::SUCCESS
 }
+
+/// Reports stale agent files, failing if any differ from the generated content.
+fn check(files: &[AgentFile], content: &str, quiet: bool) -> ExitCode {
+    let mut stale = false;
+    for file in files.iter().filter(|file| file.is_stale(content)) {
+        stale = true;
+        eprintln!("stale: {}", file.path.display());
+        if !quiet {
+            print!("{}", file.diff(content));
+        }
+    }
+
+    if stale {
+        eprintln!("run 'ra agents' to regenerate agent files");
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Returns the directory agent files are written to.
+///
+/// This is the directory holding the most specific configuration file, falling back to the
+/// current directory when no configuration was found.
+fn project_root(ctx: &CommandContext) -> PathBuf {
+    ctx.config
+        .config_root
+        .clone()
+        .unwrap_or_else(|| ctx.cwd.clone())
+}
+
+/// Returns the agent file names selected by the command flags.
+fn target_files(cmd: &AgentsCommand) -> Vec<&'static str> {
+    let mut files = vec![AGENTS_FILE];
+    if cmd.claude || cmd.all {
+        files.push(CLAUDE_FILE);
+    }
+    if cmd.gemini || cmd.all {
+        files.push(GEMINI_FILE);
+    }
+    files
+}
+
+/// Returns the template paths to concatenate, in order.
+fn template_paths(root: &Path) -> Vec<PathBuf> {
+    let mut paths = vec![root.join(TEMPLATE_FILENAME)];
+    if let Some(home) = global_config_path().and_then(|p| p.parent().map(Path::to_path_buf)) {
+        let global = home.join(TEMPLATE_FILENAME);
+        if !paths.contains(&global) {
+            paths.push(global);
+        }
+    }
+    paths
+}
+
+/// Builds the agent file content from templates and generated instructions.
+fn generate(config: &Config, root: &Path) -> Result<String, String> {
+    let mut sections = Vec::new();
+    for path in template_paths(root) {
+        if !path.exists() {
+            continue;
+        }
+        let template = fs::read_to_string(&path)
+            .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+        let template = template.trim();
+        if !template.is_empty() {
+            sections.push(template.to_string());
+        }
+    }
+    sections.push(instructions(config));
+
+    let mut content = sections.join("\n\n");
+    content.push('\n');
+    Ok(content)
+}
+
+/// Generates the ra usage instructions appended after the templates.
+fn instructions(config: &Config) -> String {
+    let mut out = String::from(
+        "## Knowledge Base\n\
+         \n\
+         This project uses `ra` to search its documentation. Search the knowledge base before \
+         making design decisions, adopting unfamiliar APIs or conventions, or when a task \
+         mentions a concept you have not seen in this codebase.\n",
+    );
+
+    out.push_str("\n### Trees\n\n");
+    if config.trees.is_empty() {
+        out.push_str("No trees are configured. Run `ra init` and add tree definitions.\n");
+    } else {
+        for tree in &config.trees {
+            let scope = if tree.is_global { "global" } else { "local" };
+            out.push_str(&format!("- `{}` ({scope})\n", tree.name));
+        }
+    }
+
+    out.push_str(
+        "\n### Usage\n\
+         \n\
+         ```sh\n\
+         # Search by keywords, \"phrases\", -exclusions and OR\n\
+         ra search \"error handling\"\n",
+    );
+    if let Some(tree) = config.trees.first() {
+        out.push_str(&format!(
+            "\n# Restrict a search to one tree\nra search --tree {} \"configuration\"\n",
+            tree.name
+        ));
+    }
+    out.push_str(
+        "\n# Find documentation relevant to the files you are working on\n\
+         ra context src/main.rs\n\
+         \n\
+         # Retrieve a result by its ID\n\
+         ra get tree:path/to/doc.md#section\n\
+         ```\n\
+         \n\
+         Use `--max-tokens` to keep results within a context budget, and `-o json` for \
+         structured output.\n",
+    );
+
+    out
+}
@@ -0,0 +1,196 @@
+//! Implementation of `ra batch`.
+//!
+//! Reads newline-delimited JSON requests from stdin and writes one JSON response per line to
+//! stdout, in request order. The index is brought up to date and opened once, so many queries
+//! can be answered without paying the startup cost of a process per query.
+
+use std::{
+    io::{self, BufRead, Write},
+    process::ExitCode,
+};
+
+use ra::Workspace;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::tools::{self, ContextRequest, GetRequest, LikeThisRequest, SearchRequest, ToolError};
+use crate::cli::context::CommandContext;
+
+/// A request line: an operation, its parameters, and an optional reference echoed in the
+/// response.
+#[derive(Deserialize)]
+struct BatchLine {
+    /// Caller-chosen value copied to the response, for matching responses to requests.
+    #[serde(default, rename = "ref")]
+    reference: Option<Value>,
+    /// The operation to run.
+    #[serde(flatten)]
+    request: BatchRequest,
+}
+
+/// An operation, selected by the `op` field.
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum BatchRequest {
+    /// Search the knowledge base.
+    Search(SearchRequest),
+    /// Find documentation relevant to source files.
+    Context(ContextRequest),
+    /// Retrieve a chunk or document by ID.
+    Get(GetRequest),
+    /// Find content similar to a chunk or file.
+    Likethis(LikeThisRequest),
+}
+
+/// A response line.
+#[derive(Serialize)]
+struct BatchResponse {
+    /// The request's `ref`, if it had one.
+    #[serde(rename = "ref", skip_serializing_if = "Option::is_none")]
+    reference: Option<Value>,
+    /// The operation's JSON output, or an `error` object.
+    #[serde(flatten)]
+    body: Value,
+}
+
+/// Answers JSONL requests from stdin until end of input.
+pub fn run(ctx: &CommandContext) -> ExitCode {
+    if let Err(code) = ctx.require_trees(true) {
+        return code;
+    }
+    let mut workspace = match Workspace::from_config(ctx.config.clone()) {
+        Ok(workspace) => workspace,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(e) = workspace.refresh() {
+        eprintln!("error: indexing failed: {e}");
+        return ExitCode::FAILURE;
+    }
+    workspace.set_auto_refresh(false);
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    match process(&mut workspace, stdin.lock(), stdout.lock()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Answers each non-blank input line with one output line, flushing after every response.
+fn process(
+    workspace: &mut Workspace,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = answer(workspace, &line);
+        let json = serde_json::to_string(&response).map_err(io::Error::other)?;
+        writeln!(output, "{json}")?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+/// Parses and runs one request line.
+fn answer(workspace: &mut Workspace, line: &str) -> BatchResponse {
+    let BatchLine { reference, request } = match serde_json::from_str(line) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            return BatchResponse {
+                reference: reference_of(line),
+                body: error(&format!("invalid request: {e}")),
+            };
+        }
+    };
+
+    let result = match request {
+        BatchRequest::Search(req) => to_value(tools::search(workspace, req)),
+        BatchRequest::Context(req) => to_value(tools::context(workspace, &req)),
+        BatchRequest::Get(req) => to_value(tools::get(workspace, &req)),
+        BatchRequest::Likethis(req) => to_value(tools::likethis(workspace, &req)),
+    };
+
+    BatchResponse {
+        reference,
+        body: result.unwrap_or_else(|message| error(&message)),
+    }
+}
+
+/// Recovers the `ref` of a request that failed to parse, if the line is a JSON object.
+fn reference_of(line: &str) -> Option<Value> {
+    serde_json::from_str::<Value>(line)
+        .ok()?
+        .get_mut("ref")
+        .map(Value::take)
+}
+
+/// Converts an operation's output to a JSON value, or its failure to a message.
+fn to_value<T: Serialize>(result: Result<T, ToolError>) -> Result<Value, String> {
+    let output = result.map_err(|e| e.to_string())?;
+    serde_json::to_value(output).map_err(|e| format!("failed to serialize JSON: {e}"))
+}
+
+/// Builds an error body.
+fn error(message: &str) -> Value {
+    serde_json::json!({ "error": message })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_operations() {
+        let line: BatchLine =
+            serde_json::from_str(r#"{"op": "search", "ref": 7, "queries": "rust"}"#).unwrap();
+        assert_eq!(line.reference, Some(Value::from(7)));
+        assert!(matches!(line.request, BatchRequest::Search(_)));
+
+        let line: BatchLine =
+            serde_json::from_str(r#"{"op": "likethis", "source": "docs:a.md"}"#).unwrap();
+        assert!(line.reference.is_none());
+        assert!(matches!(line.request, BatchRequest::Likethis(_)));
+
+        let line: BatchLine =
+            serde_json::from_str(r#"{"op": "get", "ref": "r1", "id": "docs:a.md"}"#).unwrap();
+        assert_eq!(line.reference, Some(Value::from("r1")));
+        assert!(matches!(line.request, BatchRequest::Get(_)));
+    }
+
+    #[test]
+    fn rejects_unknown_operations() {
+        assert!(serde_json::from_str::<BatchLine>(r#"{"op": "delete"}"#).is_err());
+        assert!(serde_json::from_str::<BatchLine>(r#"{"queries": "rust"}"#).is_err());
+    }
+
+    #[test]
+    fn recovers_reference_from_invalid_requests() {
+        assert_eq!(
+            reference_of(r#"{"ref": "a", "op": "nope"}"#),
+            Some(Value::from("a"))
+        );
+        assert_eq!(reference_of("not json"), None);
+    }
+
+    #[test]
+    fn responses_flatten_body() {
+        let response = BatchResponse {
+            reference: Some(Value::from(1)),
+            body: error("boom"),
+        };
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"ref":1,"error":"boom"}"#
+        );
+    }
+}
@@ -3,11 +3,12 @@
 use std::{path::Path, process::ExitCode};
 
 use comfy_table::{Cell, Table, presets::UTF8_FULL_CONDENSED};
+use ra::SearchOptions;
 use ra_config::MatchedRules;
 use ra_index::{ContextAnalysisResult, ContextSearch, ContextWarning, PipelineStats, SearchParams};
 use serde::Serialize;
 
-use super::shared::SearchParamsOverrides;
+use super::shared::context_search_options;
 use crate::cli::{
     args::{ContextCommand, OutputMode},
     context::CommandContext,
@@ -16,10 +17,9 @@ use crate::cli::{
 
 /// Analyzes source files and searches for relevant context.
 pub fn run(ctx: &mut CommandContext, cmd: &ContextCommand) -> ExitCode {
-    let context_settings = ctx.config.context.clone();
-    let search_defaults = ctx.config.search.clone();
+    let config = ctx.config.clone();
 
-    let max_terms = cmd.terms.unwrap_or(context_settings.terms);
+    let max_terms = cmd.terms.unwrap_or(config.context.terms);
     let algorithm = cmd.algorithm.unwrap_or_default();
 
     let searcher = match ctx.searcher(cmd.fuzzy, true) {
@@ -28,7 +28,7 @@ pub fn run(ctx: &mut CommandContext, cmd: &ContextCommand) -> ExitCode {
     };
 
     let mut context_search =
-        match ContextSearch::with_algorithm(searcher, &context_settings, max_terms, algorithm) {
+        match ContextSearch::with_algorithm(searcher, &config.context, max_terms, algorithm) {
             Ok(cs) => cs,
             Err(e) => {
                 eprintln!("error: failed to initialize context search: {e}");
@@ -58,18 +58,11 @@ pub fn run(ctx: &mut CommandContext, cmd: &ContextCommand) -> ExitCode {
         return ExitCode::FAILURE;
     }
 
-    let overrides = SearchParamsOverrides {
-        limit: cmd.params.limit,
-        aggregation_pool_size: cmd.params.aggregation_pool_size,
-        cutoff_ratio: cmd.params.cutoff_ratio,
-        aggregation_threshold: cmd.params.aggregation_threshold,
-        no_aggregation: cmd.params.no_aggregation,
-        trees: cmd.params.trees.clone(),
-        verbose: cmd.params.verbose,
+    let options = SearchOptions {
+        follow_links: cmd.follow_links,
+        ..context_search_options(&cmd.params, &analysis.merged_rules.search)
     };
-
-    let params =
-        overrides.build_params_with_rule_overrides(&search_defaults, &analysis.merged_rules.search);
+    let params = options.params(&config);
 
     let (results, analysis, stats) = if analysis.query_expr.is_some() {
         match context_search.search_with_analysis_stats(analysis, &params) {
@@ -100,6 +93,7 @@ pub fn run(ctx: &mut CommandContext, cmd: &ContextCommand) -> ExitCode {
     output_aggregated_results(
         &results,
         &query_display,
+        &[],
         &cmd.output,
         cmd.params.verbose,
         context_search.searcher(),
@@ -211,6 +205,12 @@ fn output_context_explain(
             params.aggregation_threshold
         );
         println!("   Phase 4: limit = {}", params.limit);
+        if let Some(max_tokens) = params.max_tokens {
+            println!("   Phase 4: max_tokens = {max_tokens}");
+        }
+        if let Some(follow_links) = params.follow_links {
+            println!("   Phase 5: follow_links = {follow_links}");
+        }
         println!(
             "   Aggregation = {}",
             if params.disable_aggregation {
@@ -293,6 +293,12 @@ fn output_context_explain(
         println!("  After aggregation:   {}", stats.post_aggregation_count);
         println!("  After elbow cutoff:  {}", stats.post_elbow_count);
         println!("  Final results:       {}", stats.final_count);
+        if stats.linked_count > 0 {
+            println!("  Linked chunks:       {}", stats.linked_count);
+        }
+        if let Some(tokens) = stats.tokens_used {
+            println!("  Tokens used:         {tokens}");
+        }
         println!();
         println!("  Elbow: {}", format_elbow_reason(&stats.elbow.reason));
         println!();
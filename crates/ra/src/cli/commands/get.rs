@@ -3,7 +3,7 @@
 use std::process::ExitCode;
 
 use ra_document::ChunkId;
-use ra_index::{SearchCandidate, SearchResult};
+use ra_index::{Neighborhood, SearchCandidate, SearchResult};
 
 use crate::cli::{
     args::{GetCommand, OutputMode, OutputOptions},
@@ -11,7 +11,7 @@ use crate::cli::{
     output::output_aggregated_results,
 };
 
-/// Retrieves a chunk or document by ID.
+/// Retrieves a chunk or document by ID, optionally with its surrounding chunks.
 pub fn run(ctx: &mut CommandContext, cmd: &GetCommand) -> ExitCode {
     let chunk_id: ChunkId = match cmd.id.parse() {
         Ok(id) => id,
@@ -31,7 +31,23 @@ pub fn run(ctx: &mut CommandContext, cmd: &GetCommand) -> ExitCode {
         Err(code) => return code,
     };
 
-    let results: Vec<SearchCandidate> = if cmd.full_document || slug.is_none() {
+    let neighborhood = Neighborhood {
+        parent: cmd.parent,
+        siblings: cmd.siblings,
+        children: cmd.children,
+        next: cmd.next,
+        prev: cmd.prev,
+    };
+
+    let results: Vec<SearchCandidate> = if !neighborhood.is_empty() {
+        match searcher.get_neighborhood(&cmd.id, &neighborhood) {
+            Ok(r) => r,
+            Err(e) => {
+                eprintln!("error: failed to retrieve chunks: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    } else if cmd.full_document || slug.is_none() {
         match searcher.get_by_path(tree, path) {
             Ok(r) => r,
             Err(e) => {
@@ -65,5 +81,5 @@ pub fn run(ctx: &mut CommandContext, cmd: &GetCommand) -> ExitCode {
         },
     };
 
-    output_aggregated_results(&aggregated, &cmd.id, &output, 0, searcher, None)
+    output_aggregated_results(&aggregated, &cmd.id, &[], &output, 0, searcher, None)
 }
@@ -64,6 +64,10 @@ fn cmd_inspect_doc(
         }
     };
 
+    for warning in &result.warnings {
+        eprintln!("warning: {warning}");
+    }
+
     let doc = &result.document;
 
     println!(
@@ -74,6 +78,9 @@ fn cmd_inspect_doc(
     if !doc.tags.is_empty() {
         println!("{}", dim(&format!("tags: {}", doc.tags.join(", "))));
     }
+    for (key, value) in &doc.meta {
+        println!("{}", dim(&format!("meta.{key}: {value}")));
+    }
 
     let chunks = doc.extract_chunks();
 
@@ -2,11 +2,15 @@
 
 use std::{path::Path, process::ExitCode};
 
+use ra::LikeThisSource;
 use ra_document::parse_file;
-use ra_index::{MoreLikeThisExplanation, MoreLikeThisParams, SearchParams, SearchResult, Searcher};
+use ra_index::{
+    MoreLikeThisExplanation, MoreLikeThisParams, PipelineStats, SearchParams, SearchResult,
+    Searcher,
+};
 use serde::Serialize;
 
-use super::shared::SearchParamsOverrides;
+use super::shared::search_options;
 use crate::cli::{
     args::{LikeThisCommand, OutputMode},
     context::CommandContext,
@@ -26,23 +30,15 @@ pub fn run(ctx: &mut CommandContext, cmd: &LikeThisCommand) -> ExitCode {
         stop_words: Vec::new(),
     };
 
-    let overrides = SearchParamsOverrides {
-        limit: cmd.params.limit,
-        aggregation_pool_size: cmd.params.aggregation_pool_size,
-        cutoff_ratio: cmd.params.cutoff_ratio,
-        aggregation_threshold: cmd.params.aggregation_threshold,
-        no_aggregation: cmd.params.no_aggregation,
-        trees: cmd.params.trees.clone(),
-        verbose: cmd.params.verbose,
-    };
-    let search_params = overrides.build_params(&ctx.config.search);
+    let search_params = search_options(&cmd.params).params(&ctx.config);
 
     let searcher = match ctx.searcher(None, true) {
         Ok(s) => s,
         Err(code) => return code,
     };
 
-    let is_chunk_id = is_chunk_id_format(&cmd.source);
+    let source = LikeThisSource::parse(&cmd.source);
+    let is_chunk_id = matches!(source, LikeThisSource::Id(_));
 
     if cmd.explain.explain {
         let json = matches!(cmd.output.mode, OutputMode::Json);
@@ -56,110 +52,41 @@ pub fn run(ctx: &mut CommandContext, cmd: &LikeThisCommand) -> ExitCode {
         );
     }
 
-    let results = if is_chunk_id {
-        match searcher.search_more_like_this_by_id(&cmd.source, &mlt_params, &search_params) {
-            Ok(r) => r,
-            Err(e) => {
-                eprintln!("error: {e}");
-                return ExitCode::FAILURE;
-            }
-        }
-    } else {
-        match search_more_like_this_by_file(&cmd.source, &mlt_params, &search_params, searcher) {
-            Ok(r) => r,
-            Err(code) => return code,
+    let (results, stats) = match find_similar(searcher, &source, &mlt_params, &search_params) {
+        Ok(r) => r,
+        Err(message) => {
+            eprintln!("error: {message}");
+            return ExitCode::FAILURE;
         }
     };
 
-    let display_source = if is_chunk_id {
-        cmd.source.clone()
-    } else {
-        format!("file:{}", cmd.source)
-    };
-
     output_aggregated_results(
         &results,
-        &display_source,
+        &source.label(),
+        &[],
         &cmd.output,
         cmd.params.verbose,
         searcher,
-        None,
+        Some(&stats),
     )
 }
 
-/// Determines if a source string looks like a chunk ID (contains ':').
-fn is_chunk_id_format(source: &str) -> bool {
-    if source.contains(':') {
-        if source.len() >= 2 && source.chars().nth(1) == Some(':') {
-            return false;
-        }
-        return true;
-    }
-    false
-}
-
-/// Searches for similar documents using a file path as source.
-fn search_more_like_this_by_file(
-    file_path: &str,
+/// Finds results similar to a chunk or file.
+fn find_similar(
+    searcher: &mut Searcher,
+    source: &LikeThisSource,
     mlt_params: &MoreLikeThisParams,
     search_params: &SearchParams,
-    searcher: &mut Searcher,
-) -> Result<Vec<SearchResult>, ExitCode> {
-    let path = Path::new(file_path);
-
-    if !path.exists() {
-        eprintln!("error: file not found: {file_path}");
-        return Err(ExitCode::FAILURE);
-    }
-
-    if ra_index::is_binary_file(path) {
-        eprintln!("error: binary file not supported: {file_path}");
-        return Err(ExitCode::FAILURE);
-    }
-
-    let parsed = match parse_file(path, "likethis") {
-        Ok(p) => p,
-        Err(e) => {
-            eprintln!("error: failed to parse file: {e}");
-            return Err(ExitCode::FAILURE);
+) -> Result<(Vec<SearchResult>, PipelineStats), String> {
+    match source {
+        LikeThisSource::Id(id) => {
+            searcher.search_more_like_this_by_id_with_stats(id, mlt_params, search_params)
+        }
+        LikeThisSource::File(path) => {
+            searcher.search_more_like_this_by_file_with_stats(path, mlt_params, search_params)
         }
-    };
-
-    let doc = &parsed.document;
-
-    let mut fields: Vec<(&str, String)> = Vec::new();
-
-    if !doc.title.is_empty() {
-        fields.push(("title", doc.title.clone()));
-    }
-
-    let chunks = doc.extract_chunks();
-    let body: String = chunks
-        .iter()
-        .map(|c| c.body.as_str())
-        .collect::<Vec<_>>()
-        .join("\n\n");
-    if !body.is_empty() {
-        fields.push(("body", body));
-    }
-
-    if !doc.tags.is_empty() {
-        fields.push(("tags", doc.tags.join(" ")));
-    }
-
-    if fields.is_empty() {
-        eprintln!("error: no content extracted from file");
-        return Err(ExitCode::FAILURE);
     }
-
-    let exclude_doc_ids = searcher.compute_exclude_doc_ids(&[path]);
-
-    searcher
-        .search_more_like_this_by_fields(fields, mlt_params, search_params, &exclude_doc_ids)
-        .map_err(|e| {
-            eprintln!("error: {e}");
-            ExitCode::FAILURE
-        })
+    .map_err(|e| e.to_string())
 }
 
 /// Handles --explain mode for likethis command.
@@ -297,6 +224,9 @@ fn output_likethis_explain(
             search_params.aggregation_threshold
         );
         println!("   Phase 4: limit = {}", search_params.limit);
+        if let Some(max_tokens) = search_params.max_tokens {
+            println!("   Phase 4: max_tokens = {max_tokens}");
+        }
         println!(
             "   Aggregation = {}",
             if search_params.disable_aggregation {
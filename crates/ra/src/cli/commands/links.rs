@@ -0,0 +1,158 @@
+//! Implementation of `ra links`.
+
+use std::process::ExitCode;
+
+use ra_document::ChunkId;
+use ra_index::{IndexError, SearchCandidate, Searcher};
+use serde::Serialize;
+
+use crate::cli::{
+    args::LinksCommand,
+    context::CommandContext,
+    output::{breadcrumb, dim, header, subheader},
+};
+
+/// A linked chunk in JSON output.
+#[derive(Serialize)]
+struct JsonLink {
+    /// Chunk ID.
+    id: String,
+    /// Breadcrumb of the chunk, absent if the chunk is not in the index.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    breadcrumb: Option<String>,
+}
+
+impl JsonLink {
+    /// Builds a link entry from an ID and the chunk it names, if indexed.
+    fn new(id: String, chunk: Option<&SearchCandidate>) -> Self {
+        Self {
+            id,
+            breadcrumb: chunk.map(SearchCandidate::breadcrumb),
+        }
+    }
+}
+
+/// JSON output for `ra links`.
+#[derive(Serialize)]
+struct JsonLinks {
+    /// The chunk or document whose links are shown.
+    id: String,
+    /// Chunks the target links to.
+    outgoing: Vec<JsonLink>,
+    /// Chunks that link to the target.
+    backlinks: Vec<JsonLink>,
+}
+
+/// Shows the outgoing links and backlinks of a chunk or document.
+pub fn run(ctx: &mut CommandContext, cmd: &LinksCommand) -> ExitCode {
+    if ChunkId::parse(&cmd.id).is_err() {
+        eprintln!("error: invalid ID format: {}", cmd.id);
+        eprintln!("Expected format: tree:path#slug or tree:path");
+        return ExitCode::FAILURE;
+    }
+
+    let searcher = match ctx.searcher(None, false) {
+        Ok(s) => s,
+        Err(code) => return code,
+    };
+
+    let target = match searcher.get_by_id(&cmd.id) {
+        Ok(Some(target)) => target,
+        Ok(None) => {
+            eprintln!("error: not found: {}", cmd.id);
+            return ExitCode::FAILURE;
+        }
+        Err(e) => {
+            eprintln!("error: failed to retrieve chunk: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let (outgoing, backlinks) = match lookup_links(searcher, &cmd.id) {
+        Ok(links) => links,
+        Err(e) => {
+            eprintln!("error: failed to retrieve links: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if cmd.json {
+        let output = JsonLinks {
+            id: cmd.id.clone(),
+            outgoing: outgoing
+                .into_iter()
+                .map(|(id, chunk)| JsonLink::new(id, chunk.as_ref()))
+                .collect(),
+            backlinks: backlinks
+                .into_iter()
+                .map(|chunk| JsonLink::new(chunk.id.clone(), Some(&chunk)))
+                .collect(),
+        };
+        match serde_json::to_string_pretty(&output) {
+            Ok(json) => println!("{json}"),
+            Err(e) => {
+                eprintln!("error: failed to serialize links: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    println!(
+        "{} {} {}",
+        header(&target.id),
+        dim("—"),
+        breadcrumb(&target.breadcrumb())
+    );
+    println!();
+    println!(
+        "{}",
+        subheader(&format!("Outgoing links ({}):", outgoing.len()))
+    );
+    for (id, chunk) in &outgoing {
+        print_link(id, chunk.as_ref());
+    }
+    println!();
+    println!(
+        "{}",
+        subheader(&format!("Backlinks ({}):", backlinks.len()))
+    );
+    for chunk in &backlinks {
+        print_link(&chunk.id, Some(chunk));
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// A linked chunk ID with the chunk it names, if that chunk is indexed.
+type ResolvedLink = (String, Option<SearchCandidate>);
+
+/// Looks up the outgoing links and backlinks of a chunk or document.
+fn lookup_links(
+    searcher: &Searcher,
+    id: &str,
+) -> Result<(Vec<ResolvedLink>, Vec<SearchCandidate>), IndexError> {
+    let outgoing = searcher
+        .get_outgoing_links(id)?
+        .into_iter()
+        .map(|link| {
+            let chunk = searcher.get_by_id(&link)?;
+            Ok((link, chunk))
+        })
+        .collect::<Result<Vec<_>, IndexError>>()?;
+    let backlinks = searcher.get_backlinks(id)?;
+    Ok((outgoing, backlinks))
+}
+
+/// Prints one linked chunk, marking links to chunks that are not indexed.
+fn print_link(id: &str, chunk: Option<&SearchCandidate>) {
+    match chunk {
+        Some(chunk) => println!(
+            "  {} {} {}",
+            header(id),
+            dim("—"),
+            breadcrumb(&chunk.breadcrumb())
+        ),
+        None => println!("  {} {}", header(id), dim("(not indexed)")),
+    }
+}
@@ -0,0 +1,141 @@
+//! Implementation of `ra mcp`.
+//!
+//! Serves the knowledge base to agents over the Model Context Protocol using the stdio
+//! transport. Every tool call brings the index up to date before searching, so results
+//! always reflect the files on disk.
+
+use std::{
+    process::ExitCode,
+    sync::{Arc, Mutex},
+};
+
+use ra::Workspace;
+use rmcp::{
+    ServerHandler, ServiceExt,
+    handler::server::wrapper::Parameters,
+    model::{CallToolResult, ContentBlock, Implementation, ServerCapabilities, ServerConfig},
+    tool, tool_handler, tool_router,
+    transport::stdio,
+};
+use serde::Serialize;
+use tokio::runtime::Builder;
+
+use super::tools::{self, ContextRequest, GetRequest, SearchRequest, ToolError};
+use crate::cli::context::CommandContext;
+
+/// Instructions sent to clients when they connect.
+const INSTRUCTIONS: &str = "ra is a knowledge base search tool. Use `search` to find \
+documentation by query, `context` to find documentation relevant to source files, `get` to \
+retrieve a chunk or document by ID, and `list_sources` to see the available trees.";
+
+/// Runs the MCP server on stdin/stdout until the client disconnects.
+pub fn run(ctx: &CommandContext) -> ExitCode {
+    if let Err(code) = ctx.require_trees(true) {
+        return code;
+    }
+
+    let runtime = match Builder::new_current_thread().enable_all().build() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("error: failed to start async runtime: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let workspace = match Workspace::from_config(ctx.config.clone()) {
+        Ok(workspace) => workspace,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let server = McpServer {
+        workspace: Arc::new(Mutex::new(workspace)),
+    };
+    match runtime.block_on(serve_stdio(server)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: mcp server failed: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Serves the given handler over stdio and waits for the session to end.
+async fn serve_stdio(server: McpServer) -> Result<(), String> {
+    let service = server.serve(stdio()).await.map_err(|e| e.to_string())?;
+    service.waiting().await.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// MCP handler exposing ra's search tools.
+#[derive(Clone)]
+struct McpServer {
+    /// Workspace shared by tool calls, which refreshes the index before each one.
+    workspace: Arc<Mutex<Workspace>>,
+}
+
+#[tool_router]
+impl McpServer {
+    /// Runs a tool against the shared workspace and converts its outcome into an MCP result.
+    fn call<T: Serialize>(
+        &self,
+        tool: impl FnOnce(&mut Workspace) -> Result<T, ToolError>,
+    ) -> CallToolResult {
+        let result = match self.workspace.lock() {
+            Ok(mut workspace) => tool(&mut workspace).map_err(|e| e.to_string()),
+            Err(_) => Err("workspace is unavailable after an earlier failure".to_string()),
+        };
+        tool_result(result)
+    }
+
+    /// Searches the knowledge base.
+    #[tool(
+        description = "Search the knowledge base. Multiple queries are combined with OR. \
+                       Supports the ra query syntax: terms, \"phrases\", -exclusions, OR, \
+                       grouping and field:term queries. Syntax mistakes are repaired where \
+                       possible and reported as warnings alongside the results."
+    )]
+    async fn search(&self, Parameters(request): Parameters<SearchRequest>) -> CallToolResult {
+        self.call(|workspace| tools::search(workspace, request))
+    }
+
+    /// Finds documentation relevant to source files.
+    #[tool(description = "Get relevant context from the knowledge base for files being worked on.")]
+    async fn context(&self, Parameters(request): Parameters<ContextRequest>) -> CallToolResult {
+        self.call(|workspace| tools::context(workspace, &request))
+    }
+
+    /// Retrieves a chunk or document by ID.
+    #[tool(
+        description = "Retrieve a specific document or chunk by ID (tree:path#slug or tree:path)."
+    )]
+    async fn get(&self, Parameters(request): Parameters<GetRequest>) -> CallToolResult {
+        self.call(|workspace| tools::get(workspace, &request))
+    }
+
+    /// Lists configured trees.
+    #[tool(description = "List available knowledge trees and their statistics.")]
+    async fn list_sources(&self) -> CallToolResult {
+        self.call(tools::list_sources)
+    }
+}
+
+#[tool_handler]
+impl ServerHandler for McpServer {
+    fn get_info(&self) -> ServerConfig {
+        ServerConfig::new(ServerCapabilities::builder().enable_tools().build())
+            .with_server_info(Implementation::new("ra", env!("CARGO_PKG_VERSION")))
+            .with_instructions(INSTRUCTIONS)
+    }
+}
+
+/// Converts a tool outcome into an MCP result, serializing successes as JSON text.
+fn tool_result<T: Serialize>(result: Result<T, String>) -> CallToolResult {
+    match result.and_then(|value| {
+        serde_json::to_string_pretty(&value).map_err(|e| format!("failed to serialize JSON: {e}"))
+    }) {
+        Ok(json) => CallToolResult::success(vec![ContentBlock::text(json)]),
+        Err(message) => CallToolResult::error(vec![ContentBlock::text(message)]),
+    }
+}
@@ -1,17 +1,24 @@
 //! Command implementations and dispatch.
 
 pub mod agents;
+pub mod batch;
 pub mod config;
 pub mod context;
 pub mod get;
 pub mod init;
 pub mod inspect;
 pub mod likethis;
+pub mod links;
 pub mod ls;
+pub mod mcp;
 pub mod search;
+pub mod serve;
 mod shared;
 pub mod status;
+mod tools;
+pub mod tui;
 pub mod update;
+pub mod watch;
 
 use std::process::ExitCode;
 
@@ -24,12 +31,18 @@ pub fn run(command: Commands, ctx: &mut CommandContext) -> ExitCode {
         Commands::Context(cmd) => context::run(ctx, &cmd),
         Commands::Get(cmd) => get::run(ctx, &cmd),
         Commands::LikeThis(cmd) => likethis::run(ctx, &cmd),
+        Commands::Links(cmd) => links::run(ctx, &cmd),
         Commands::Inspect { what } => inspect::run(ctx, what),
         Commands::Init(cmd) => init::run(ctx, &cmd),
         Commands::Update => update::run(ctx),
         Commands::Status => status::run(ctx),
         Commands::Config => config::run(ctx),
         Commands::Ls(cmd) => ls::run(ctx, &cmd),
-        Commands::Agents(cmd) => agents::run(&cmd),
+        Commands::Agents(cmd) => agents::run(ctx, &cmd),
+        Commands::Mcp => mcp::run(ctx),
+        Commands::Serve(cmd) => serve::run(ctx, &cmd),
+        Commands::Watch(cmd) => watch::run(ctx, &cmd),
+        Commands::Tui(cmd) => tui::run(ctx, &cmd),
+        Commands::Batch => batch::run(ctx),
     }
 }
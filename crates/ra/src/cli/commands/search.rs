@@ -1,120 +1,273 @@
 //! Implementation of `ra search`.
 
-use std::process::ExitCode;
+use std::{
+    io::{self, Read},
+    process::ExitCode,
+};
 
-use ra_index::parse_query;
+use ra::SearchOptions;
+use ra_index::{
+    MAX_EXPANSIONS, PipelineStats, QueryExpr, SYNONYM_WEIGHT, SearchParams, SynonymExpansion,
+    TermExpansion, parse_query_json,
+};
+use serde::Serialize;
 
-use super::shared::SearchParamsOverrides;
+use super::shared::{combine_queries, search_options, search_parsed};
 use crate::cli::{
-    args::SearchCommand,
+    args::{OutputMode, SearchCommand},
     context::CommandContext,
     output::{dim, format_elbow_reason, output_aggregated_results, subheader},
 };
 
+/// JSON output for search explain mode.
+#[derive(Serialize)]
+struct JsonSearchExplain {
+    /// The query as searched.
+    query: String,
+    /// The parsed query, in the form `--query-json` accepts.
+    ast: Option<QueryExpr>,
+    /// Search parameters used for the query.
+    search_params: SearchParams,
+    /// Pipeline statistics from executing the search.
+    pipeline: PipelineStats,
+}
+
 /// Searches the index and prints matching chunks.
 pub fn run(ctx: &mut CommandContext, cmd: &SearchCommand) -> ExitCode {
-    let overrides = SearchParamsOverrides {
-        limit: cmd.params.limit,
-        aggregation_pool_size: cmd.params.aggregation_pool_size,
-        cutoff_ratio: cmd.params.cutoff_ratio,
-        aggregation_threshold: cmd.params.aggregation_threshold,
-        no_aggregation: cmd.params.no_aggregation,
-        trees: cmd.params.trees.clone(),
-        verbose: cmd.params.verbose,
+    let options = SearchOptions {
+        facets: cmd.facets.clone(),
+        follow_links: cmd.follow_links,
+        ..search_options(&cmd.params)
     };
+    let params = options.params(&ctx.config);
 
-    let params = overrides.build_params(&ctx.config.search);
+    let json_expr = match cmd.query_json.as_deref().map(read_query_json).transpose() {
+        Ok(expr) => expr,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
 
     let searcher = match ctx.searcher(cmd.fuzzy, true) {
         Ok(s) => s,
         Err(code) => return code,
     };
 
-    let combined_query = if cmd.queries.len() == 1 {
-        cmd.queries[0].clone()
-    } else {
-        cmd.queries
-            .iter()
-            .map(|q| format!("({q})"))
-            .collect::<Vec<_>>()
-            .join(" OR ")
+    let (combined_query, lenient, searched) = match &json_expr {
+        Some(expr) => (
+            expr.to_query_string(),
+            None,
+            searcher.search_aggregated_expr_with_stats(expr, &params),
+        ),
+        None if cmd.lenient => {
+            let combined_query = combine_queries(&cmd.queries);
+            let parsed = searcher.parse_query_lenient(&combined_query);
+            let searched = search_parsed(searcher, parsed.expr.as_ref(), &params);
+            (combined_query, Some(parsed), searched)
+        }
+        None => {
+            let combined_query = combine_queries(&cmd.queries);
+            let searched = searcher.search_aggregated_with_stats(&combined_query, &params);
+            (combined_query, None, searched)
+        }
     };
-
-    let (results, stats) = match searcher.search_aggregated_with_stats(&combined_query, &params) {
+    let (results, stats) = match searched {
         Ok(r) => r,
         Err(e) => {
             eprintln!("error: search failed: {e}");
             return ExitCode::FAILURE;
         }
     };
+    let (lenient_expr, diagnostics) = match lenient {
+        Some(parsed) => (parsed.expr, parsed.diagnostics),
+        None => (None, Vec::new()),
+    };
+    for diagnostic in &diagnostics {
+        eprintln!("warning: {diagnostic}");
+    }
 
     if cmd.explain.explain {
-        println!("{}", subheader("Query:"));
-        println!("   {combined_query}");
-        println!();
-
-        match parse_query(&combined_query) {
-            Ok(Some(expr)) => {
-                println!("{}", subheader("Parsed AST:"));
-                let expr_str = expr.to_string();
-                for line in expr_str.lines() {
-                    println!("   {line}");
-                }
-                println!();
-            }
-            Ok(None) => {
-                println!("{}", dim("(empty query)"));
-                println!();
-            }
+        let parsed = match json_expr {
+            Some(expr) => Ok(Some(expr)),
+            None if cmd.lenient => Ok(lenient_expr),
+            None => searcher.parse_query(&combined_query),
+        };
+        let ast = match parsed {
+            Ok(ast) => ast,
             Err(e) => {
                 eprintln!("error: {e}");
                 return ExitCode::FAILURE;
             }
-        }
-
-        println!("{}", subheader("Search Parameters:"));
-        println!(
-            "   Phase 1: candidate_limit = {}",
-            params.effective_candidate_limit()
-        );
-        println!("   Phase 2: cutoff_ratio = {}", params.cutoff_ratio);
-        println!(
-            "   Phase 2: aggregation_pool_size = {}",
-            params.aggregation_pool_size
-        );
-        println!(
-            "   Phase 3: aggregation_threshold = {}",
-            params.aggregation_threshold
-        );
-        println!("   Phase 4: limit = {}", params.limit);
-        println!(
-            "   Aggregation = {}",
-            if params.disable_aggregation {
-                "disabled"
-            } else {
-                "enabled"
-            }
-        );
-        println!();
-
-        println!("{}", subheader("Pipeline Statistics:"));
-        println!("  Raw candidates:      {}", stats.raw_candidate_count);
-        println!("  After aggregation:   {}", stats.post_aggregation_count);
-        println!("  After elbow cutoff:  {}", stats.post_elbow_count);
-        println!("  Final results:       {}", stats.final_count);
-        println!();
-        println!("  Elbow: {}", format_elbow_reason(&stats.elbow.reason));
-        println!();
-
-        return ExitCode::SUCCESS;
+        };
+        let json = matches!(cmd.output.mode, OutputMode::Json);
+        return output_search_explain(combined_query, ast, &params, &stats, json);
     }
 
     output_aggregated_results(
         &results,
         &combined_query,
+        &diagnostics,
         &cmd.output,
         cmd.params.verbose,
         searcher,
         Some(&stats),
     )
 }
+
+/// Reads the argument of `--query-json`, from stdin if it is `-`.
+fn read_query_json(arg: &str) -> Result<QueryExpr, String> {
+    let mut input = arg.to_string();
+    if arg == "-" {
+        input.clear();
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| format!("failed to read query from stdin: {e}"))?;
+    }
+    parse_query_json(&input).map_err(|e| e.to_string())
+}
+
+/// Outputs explain mode information for a search.
+fn output_search_explain(
+    query: String,
+    ast: Option<QueryExpr>,
+    params: &SearchParams,
+    stats: &PipelineStats,
+    json: bool,
+) -> ExitCode {
+    if json {
+        let json_output = JsonSearchExplain {
+            query,
+            ast,
+            search_params: params.clone(),
+            pipeline: stats.clone(),
+        };
+        match serde_json::to_string_pretty(&json_output) {
+            Ok(json_str) => println!("{json_str}"),
+            Err(e) => {
+                eprintln!("error: failed to serialize JSON: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    println!("{}", subheader("Query:"));
+    println!("   {query}");
+    println!();
+
+    match ast {
+        Some(expr) => {
+            println!("{}", subheader("Parsed AST:"));
+            let expr_str = expr.to_string();
+            for line in expr_str.lines() {
+                println!("   {line}");
+            }
+            println!();
+        }
+        None => {
+            println!("{}", dim("(empty query)"));
+            println!();
+        }
+    }
+
+    if !stats.wildcard_expansions.is_empty() {
+        println!("{}", subheader("Wildcard Expansions:"));
+        for expansion in &stats.wildcard_expansions {
+            println!("   {}", format_expansion(expansion));
+        }
+        println!();
+    }
+
+    if !stats.synonym_expansions.is_empty() {
+        println!("{}", subheader("Synonym Expansions:"));
+        for expansion in &stats.synonym_expansions {
+            println!("   {}", format_synonyms(expansion));
+        }
+        println!();
+    }
+
+    println!("{}", subheader("Search Parameters:"));
+    println!(
+        "   Phase 1: candidate_limit = {}",
+        params.effective_candidate_limit()
+    );
+    println!("   Phase 2: cutoff_ratio = {}", params.cutoff_ratio);
+    println!(
+        "   Phase 2: aggregation_pool_size = {}",
+        params.aggregation_pool_size
+    );
+    println!(
+        "   Phase 3: aggregation_threshold = {}",
+        params.aggregation_threshold
+    );
+    println!("   Phase 4: limit = {}", params.limit);
+    if let Some(max_tokens) = params.max_tokens {
+        println!("   Phase 4: max_tokens = {max_tokens}");
+    }
+    if let Some(follow_links) = params.follow_links {
+        println!("   Phase 5: follow_links = {follow_links}");
+    }
+    println!(
+        "   Aggregation = {}",
+        if params.disable_aggregation {
+            "disabled"
+        } else {
+            "enabled"
+        }
+    );
+    println!();
+
+    println!("{}", subheader("Pipeline Statistics:"));
+    println!("  Raw candidates:      {}", stats.raw_candidate_count);
+    println!("  After aggregation:   {}", stats.post_aggregation_count);
+    println!("  After elbow cutoff:  {}", stats.post_elbow_count);
+    println!("  Final results:       {}", stats.final_count);
+    if stats.linked_count > 0 {
+        println!("  Linked chunks:       {}", stats.linked_count);
+    }
+    if let Some(tokens) = stats.tokens_used {
+        println!("  Tokens used:         {tokens}");
+    }
+    println!();
+    println!("  Elbow: {}", format_elbow_reason(&stats.elbow.reason));
+    println!();
+
+    ExitCode::SUCCESS
+}
+
+/// Formats a wildcard expansion for `--explain`.
+fn format_expansion(expansion: &TermExpansion) -> String {
+    let terms = if expansion.terms.is_empty() {
+        dim("(no matching terms; searched as literal text)")
+    } else {
+        expansion.terms.join(", ")
+    };
+    let truncated = if expansion.truncated {
+        format!(
+            " {}",
+            dim(&format!("(first {MAX_EXPANSIONS} by frequency)"))
+        )
+    } else {
+        String::new()
+    };
+    format!("{} → {terms}{truncated}", expansion.pattern)
+}
+
+/// Formats a synonym expansion for `--explain` as the OR group the term was rewritten to.
+fn format_synonyms(expansion: &SynonymExpansion) -> String {
+    let synonyms = expansion
+        .synonyms
+        .iter()
+        .map(|s| format!("{s}^{SYNONYM_WEIGHT}"))
+        .collect::<Vec<_>>()
+        .join(" OR ");
+    match &expansion.tree {
+        Some(tree) => format!(
+            "{} → {synonyms} {}",
+            expansion.term,
+            dim(&format!("(tree {tree} only)"))
+        ),
+        None => format!("{} → {} OR {synonyms}", expansion.term, expansion.term),
+    }
+}
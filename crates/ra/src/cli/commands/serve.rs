@@ -0,0 +1,200 @@
+//! Implementation of `ra serve`.
+//!
+//! Runs a local HTTP server that keeps one [`Workspace`] open across requests. Query endpoints
+//! take a JSON body and return the same JSON shapes as `--output json`; listing endpoints are
+//! plain `GET`s. A file watcher reindexes changed files on a background thread, and the
+//! workspace's reader picks up each commit shortly after it lands, so requests never wait on
+//! indexing.
+
+use std::{
+    collections::BTreeSet,
+    path::PathBuf,
+    process::ExitCode,
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration,
+};
+
+use notify_debouncer_mini::{
+    DebounceEventResult, Debouncer, new_debouncer, notify::RecommendedWatcher,
+};
+use ra::Workspace;
+use ra_config::Config;
+use ra_index::{Indexer, SilentReporter};
+use serde::{Serialize, de::DeserializeOwned};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use super::{
+    tools::{self, ToolError},
+    watch::watch_paths,
+};
+use crate::cli::{args::ServeCommand, context::CommandContext};
+
+/// How long file changes must settle before the background indexer applies them.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Response produced by a route: an HTTP status code and a JSON body.
+type RouteResponse = (u16, String);
+
+/// JSON body returned for failed requests.
+#[derive(Serialize)]
+struct JsonError<'a> {
+    /// Description of what went wrong.
+    error: &'a str,
+}
+
+/// Serves search endpoints over HTTP until the process is stopped.
+pub fn run(ctx: &CommandContext, cmd: &ServeCommand) -> ExitCode {
+    if let Err(code) = ctx.require_trees(true) {
+        return code;
+    }
+
+    let mut workspace = match Workspace::from_config(ctx.config.clone()) {
+        Ok(workspace) => workspace,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    workspace.set_auto_refresh(false);
+
+    let server = match Server::http(&cmd.listen) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("error: failed to listen on {}: {e}", cmd.listen);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // Dropping the debouncer stops the watcher, so it lives as long as the server
+    let _debouncer = match index_in_background(&ctx.config) {
+        Ok(debouncer) => debouncer,
+        Err(message) => {
+            eprintln!("error: {message}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match server.server_addr().to_ip() {
+        Some(addr) => println!("Listening on http://{addr}"),
+        None => println!("Listening on {}", cmd.listen),
+    }
+
+    for request in server.incoming_requests() {
+        handle(&mut workspace, request);
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Brings the index up to date, then keeps it current from a file watcher on a background
+/// thread.
+///
+/// Changes seen while the initial refresh runs are queued and applied after it.
+fn index_in_background(config: &Config) -> Result<Debouncer<RecommendedWatcher>, String> {
+    let (tx, rx) = mpsc::channel();
+    let mut debouncer =
+        new_debouncer(DEBOUNCE, tx).map_err(|e| format!("failed to start file watcher: {e}"))?;
+    watch_paths(&mut debouncer, config, &[])?;
+
+    Indexer::new(config)
+        .and_then(|indexer| indexer.refresh(&mut SilentReporter))
+        .map_err(|e| format!("indexing failed: {e}"))?;
+    let config = config.clone();
+    thread::spawn(move || apply_changes(&config, &rx));
+
+    Ok(debouncer)
+}
+
+/// Reindexes the paths in each debounced change set until the watcher stops.
+fn apply_changes(config: &Config, rx: &Receiver<DebounceEventResult>) {
+    let indexer = match Indexer::new(config) {
+        Ok(indexer) => indexer,
+        Err(e) => {
+            eprintln!("error: failed to initialize indexer: {e}");
+            return;
+        }
+    };
+    for result in rx {
+        match result {
+            Ok(events) => {
+                let paths: BTreeSet<PathBuf> = events.into_iter().map(|event| event.path).collect();
+                let paths: Vec<PathBuf> = paths.into_iter().collect();
+                if let Err(e) = indexer.update_paths(&paths, &mut SilentReporter) {
+                    eprintln!("error: indexing failed: {e}");
+                }
+            }
+            Err(e) => eprintln!("warning: file watcher error: {e}"),
+        }
+    }
+}
+
+/// Answers a single request.
+fn handle(workspace: &mut Workspace, mut request: Request) {
+    let mut body = String::new();
+    let (status, json) = match request.as_reader().read_to_string(&mut body) {
+        Ok(_) => route(workspace, request.method(), request.url(), &body),
+        Err(e) => error_response(400, &format!("failed to read request body: {e}")),
+    };
+
+    let content_type =
+        Header::from_bytes("Content-Type", "application/json").expect("static header is valid");
+    let response = Response::from_string(json)
+        .with_status_code(status)
+        .with_header(content_type);
+    if let Err(e) = request.respond(response) {
+        eprintln!("warning: failed to send response: {e}");
+    }
+}
+
+/// Dispatches a request to its endpoint.
+fn route(workspace: &mut Workspace, method: &Method, url: &str, body: &str) -> RouteResponse {
+    let path = url.split('?').next().unwrap_or(url);
+    let expected = match path {
+        "/search" | "/context" | "/get" | "/likethis" => Method::Post,
+        "/ls/trees" | "/ls/docs" | "/ls/chunks" => Method::Get,
+        _ => return error_response(404, &format!("unknown endpoint: {path}")),
+    };
+    if *method != expected {
+        return error_response(405, &format!("{path} expects {expected}"));
+    }
+
+    match path {
+        "/search" => respond(parse(body).and_then(|req| tools::search(workspace, req))),
+        "/context" => respond(parse(body).and_then(|req| tools::context(workspace, &req))),
+        "/get" => respond(parse(body).and_then(|req| tools::get(workspace, &req))),
+        "/likethis" => respond(parse(body).and_then(|req| tools::likethis(workspace, &req))),
+        "/ls/trees" => respond(tools::list_sources(workspace)),
+        "/ls/docs" => respond(tools::list_docs(workspace)),
+        _ => respond(tools::list_chunks(workspace)),
+    }
+}
+
+/// Parses a JSON request body.
+fn parse<T: DeserializeOwned>(body: &str) -> Result<T, ToolError> {
+    serde_json::from_str(body).map_err(|e| ToolError::Invalid(format!("invalid request: {e}")))
+}
+
+/// Converts an endpoint outcome into a response.
+///
+/// Failures are reported as 400 for invalid requests, 404 for missing chunks, documents or
+/// files, and 500 for index and I/O errors.
+fn respond<T: Serialize>(result: Result<T, ToolError>) -> RouteResponse {
+    let json = result.and_then(|value| {
+        serde_json::to_string_pretty(&value)
+            .map_err(|e| ToolError::Failed(format!("failed to serialize JSON: {e}")))
+    });
+    match json {
+        Ok(json) => (200, json),
+        Err(error @ ToolError::Invalid(_)) => error_response(400, &error.to_string()),
+        Err(error @ ToolError::NotFound(_)) => error_response(404, &error.to_string()),
+        Err(error @ ToolError::Failed(_)) => error_response(500, &error.to_string()),
+    }
+}
+
+/// Builds an error response with a JSON body.
+fn error_response(status: u16, message: &str) -> RouteResponse {
+    let json =
+        serde_json::to_string(&JsonError { error: message }).unwrap_or_else(|_| String::from("{}"));
+    (status, json)
+}
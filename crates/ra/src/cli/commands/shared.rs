@@ -1,74 +1,68 @@
 //! Shared helpers for command implementations.
 
-use ra_config::SearchDefaults;
-use ra_index::SearchParams;
+use ra::SearchOptions;
+use ra_config::SearchOverrides;
+use ra_index::{IndexError, PipelineStats, QueryExpr, SearchParams, SearchResult, Searcher};
 
-/// CLI options for search parameters that can override config defaults.
+use crate::cli::args::SearchParamsArgs;
+
+/// Converts the shared search flags into library search options.
 ///
 /// Used by `search`, `context`, and `likethis` commands.
-pub struct SearchParamsOverrides {
-    /// Maximum results to return after aggregation.
-    pub limit: Option<usize>,
-    /// Maximum candidates to pass through Phase 2 into aggregation.
-    pub aggregation_pool_size: Option<usize>,
-    /// Score ratio threshold for relevance cutoff.
-    pub cutoff_ratio: Option<f32>,
-    /// Sibling ratio threshold for aggregation.
-    pub aggregation_threshold: Option<f32>,
-    /// Whether to disable hierarchical aggregation.
-    pub no_aggregation: bool,
-    /// Limit results to specific trees.
-    pub trees: Vec<String>,
-    /// Verbosity level for match details.
-    pub verbose: u8,
+pub fn search_options(args: &SearchParamsArgs) -> SearchOptions {
+    SearchOptions {
+        limit: args.limit,
+        max_tokens: args.max_tokens,
+        trees: args.trees.clone(),
+        aggregation_pool_size: args.aggregation_pool_size,
+        cutoff_ratio: args.cutoff_ratio,
+        aggregation_threshold: args.aggregation_threshold,
+        disable_aggregation: args.no_aggregation,
+        verbosity: args.verbose,
+        ..SearchOptions::default()
+    }
+}
+
+/// Converts the shared search flags into library search options, filling unset values from
+/// the search settings of matching context rules.
+pub fn context_search_options(args: &SearchParamsArgs, rules: &SearchOverrides) -> SearchOptions {
+    SearchOptions {
+        limit: args.limit.or(rules.limit),
+        aggregation_pool_size: args.aggregation_pool_size.or(rules.aggregation_pool_size),
+        cutoff_ratio: args.cutoff_ratio.or(rules.cutoff_ratio),
+        aggregation_threshold: args.aggregation_threshold.or(rules.aggregation_threshold),
+        ..search_options(args)
+    }
 }
 
-impl SearchParamsOverrides {
-    /// Builds `SearchParams` by applying CLI overrides to config defaults.
-    pub fn build_params<D: SearchDefaults>(&self, defaults: &D) -> SearchParams {
-        SearchParams {
-            candidate_limit: None,
-            cutoff_ratio: self.cutoff_ratio.unwrap_or_else(|| defaults.cutoff_ratio()),
-            aggregation_pool_size: self
-                .aggregation_pool_size
-                .unwrap_or_else(|| defaults.aggregation_pool_size()),
-            aggregation_threshold: self
-                .aggregation_threshold
-                .unwrap_or_else(|| defaults.aggregation_threshold()),
-            disable_aggregation: self.no_aggregation,
-            limit: self.limit.unwrap_or_else(|| defaults.limit()),
-            trees: self.trees.clone(),
-            verbosity: self.verbose,
-        }
+/// Combines multiple queries into one, joining them with OR.
+///
+/// A single query is returned unchanged; multiple queries are each wrapped in parentheses.
+pub fn combine_queries(queries: &[String]) -> String {
+    if queries.len() == 1 {
+        queries[0].clone()
+    } else {
+        queries
+            .iter()
+            .map(|q| format!("({q})"))
+            .collect::<Vec<_>>()
+            .join(" OR ")
     }
+}
 
-    /// Builds `SearchParams` with rule-based overrides applied.
-    pub fn build_params_with_rule_overrides<D: SearchDefaults>(
-        &self,
-        defaults: &D,
-        rule_overrides: &ra_config::SearchOverrides,
-    ) -> SearchParams {
-        SearchParams {
-            candidate_limit: None,
-            cutoff_ratio: self
-                .cutoff_ratio
-                .or(rule_overrides.cutoff_ratio)
-                .unwrap_or_else(|| defaults.cutoff_ratio()),
-            aggregation_pool_size: self
-                .aggregation_pool_size
-                .or(rule_overrides.aggregation_pool_size)
-                .unwrap_or_else(|| defaults.aggregation_pool_size()),
-            limit: self
-                .limit
-                .or(rule_overrides.limit)
-                .unwrap_or_else(|| defaults.limit()),
-            aggregation_threshold: self
-                .aggregation_threshold
-                .or(rule_overrides.aggregation_threshold)
-                .unwrap_or_else(|| defaults.aggregation_threshold()),
-            disable_aggregation: self.no_aggregation,
-            trees: self.trees.clone(),
-            verbosity: self.verbose,
-        }
+/// Searches a parsed query, such as the repaired expression from a lenient parse.
+///
+/// A query that parsed to nothing finds nothing.
+pub fn search_parsed(
+    searcher: &mut Searcher,
+    expr: Option<&QueryExpr>,
+    params: &SearchParams,
+) -> Result<(Vec<SearchResult>, PipelineStats), IndexError> {
+    match expr {
+        Some(expr) => searcher.search_aggregated_expr_with_stats(expr, params),
+        None => Ok((
+            Vec::new(),
+            PipelineStats::empty(params.cutoff_ratio, params.aggregation_pool_size),
+        )),
     }
 }
@@ -0,0 +1,314 @@
+//! Request types and handlers shared by the `mcp` and `serve` commands.
+//!
+//! Each handler runs against a caller-provided [`Workspace`] and returns the same JSON shapes
+//! as `--output json`, with failures reported as a [`ToolError`] message.
+
+use std::path::PathBuf;
+
+use ra::{
+    DocumentInfo, FacetField, LikeThisSource, Neighborhood, SearchOptions, SearchResult, Workspace,
+};
+use ra_index::IndexError;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::shared::combine_queries;
+use crate::cli::output::JsonSearchOutput;
+
+/// A failed request, classified so that callers such as `serve` can report it appropriately.
+#[derive(Debug, Error)]
+pub enum ToolError {
+    /// The request is malformed, such as an unknown facet or a query that cannot be parsed.
+    #[error("{0}")]
+    Invalid(String),
+    /// The requested chunk, document or file does not exist.
+    #[error("{0}")]
+    NotFound(String),
+    /// The operation failed, such as on an index or I/O error.
+    #[error("{0}")]
+    Failed(String),
+}
+
+impl From<ra::Error> for ToolError {
+    fn from(error: ra::Error) -> Self {
+        let message = error.to_string();
+        match error {
+            ra::Error::NotFound(_) | ra::Error::FileNotFound(_) => Self::NotFound(message),
+            ra::Error::InvalidId(_)
+            | ra::Error::NoAnalyzableFiles
+            | ra::Error::Index(IndexError::Query(_) | IndexError::InvalidSource(_)) => {
+                Self::Invalid(message)
+            }
+            ra::Error::Config(_) | ra::Error::Index(_) | ra::Error::NoTrees => {
+                Self::Failed(message)
+            }
+        }
+    }
+}
+
+/// One query or a list of queries to combine with OR.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum QueryList {
+    /// A single query string.
+    One(String),
+    /// Multiple queries, joined with OR.
+    Many(Vec<String>),
+}
+
+/// Parameters for a search.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SearchRequest {
+    /// Query string, or a list of queries joined with OR.
+    queries: QueryList,
+    /// Maximum number of results to return.
+    limit: Option<usize>,
+    /// Token budget for the results; results that would overflow it are skipped.
+    max_tokens: Option<usize>,
+    /// Limit results to specific trees.
+    #[serde(default)]
+    trees: Vec<String>,
+    /// Count matching documents per value of these fields (`tags`, `tree`).
+    #[serde(default)]
+    facets: Vec<String>,
+    /// Add chunks linked from this many of the top results, at a discounted score.
+    follow_links: Option<usize>,
+}
+
+/// Parameters for a context search.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ContextRequest {
+    /// Source files to find relevant documentation for.
+    files: Vec<String>,
+    /// Maximum number of results to return.
+    limit: Option<usize>,
+    /// Token budget for the results; results that would overflow it are skipped.
+    max_tokens: Option<usize>,
+    /// Limit results to specific trees.
+    #[serde(default)]
+    trees: Vec<String>,
+    /// Add chunks linked from this many of the top results, at a discounted score.
+    follow_links: Option<usize>,
+}
+
+/// Parameters for retrieving a chunk or document.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetRequest {
+    /// Chunk or document ID (`tree:path#slug` or `tree:path`).
+    id: String,
+    /// Return the entire document even if the ID names a chunk.
+    #[serde(default)]
+    full_document: bool,
+    /// Also return the chunk's parent.
+    #[serde(default)]
+    parent: bool,
+    /// Also return chunks that share the chunk's parent.
+    #[serde(default)]
+    siblings: bool,
+    /// Also return the chunk's direct children.
+    #[serde(default)]
+    children: bool,
+    /// Also return this many chunks following the chunk in document order.
+    #[serde(default)]
+    next: usize,
+    /// Also return this many chunks preceding the chunk in document order.
+    #[serde(default)]
+    prev: usize,
+}
+
+/// Parameters for a similarity search.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct LikeThisRequest {
+    /// Chunk ID (`tree:path#slug` or `tree:path`) or file path to find similar content for.
+    source: String,
+    /// Maximum number of results to return.
+    limit: Option<usize>,
+    /// Token budget for the results; results that would overflow it are skipped.
+    max_tokens: Option<usize>,
+    /// Limit results to specific trees.
+    #[serde(default)]
+    trees: Vec<String>,
+}
+
+/// A configured knowledge tree.
+#[derive(Serialize)]
+struct JsonSource {
+    /// Tree name.
+    name: String,
+    /// Tree root path.
+    path: String,
+    /// Whether the tree is `global` or `local`.
+    scope: &'static str,
+    /// Number of indexed files in the tree.
+    files: usize,
+}
+
+/// JSON output listing configured trees.
+#[derive(Serialize)]
+pub struct JsonSources {
+    /// Configured trees.
+    sources: Vec<JsonSource>,
+}
+
+/// JSON output listing indexed documents.
+#[derive(Serialize)]
+pub struct JsonDocs {
+    /// Indexed documents, in index order.
+    docs: Vec<DocumentInfo>,
+}
+
+/// An indexed chunk.
+#[derive(Serialize)]
+struct JsonChunk {
+    /// Chunk ID.
+    id: String,
+    /// Chunk title.
+    title: String,
+    /// Body size in bytes.
+    size: usize,
+}
+
+/// JSON output listing indexed chunks.
+#[derive(Serialize)]
+pub struct JsonChunks {
+    /// Indexed chunks, in index order.
+    chunks: Vec<JsonChunk>,
+}
+
+/// Builds search options from request parameters.
+fn options(limit: Option<usize>, max_tokens: Option<usize>, trees: &[String]) -> SearchOptions {
+    SearchOptions {
+        limit,
+        max_tokens,
+        trees: trees.to_vec(),
+        ..SearchOptions::default()
+    }
+}
+
+/// Searches the knowledge base.
+pub fn search(
+    workspace: &mut Workspace,
+    request: SearchRequest,
+) -> Result<JsonSearchOutput, ToolError> {
+    let queries = match request.queries {
+        QueryList::One(query) => vec![query],
+        QueryList::Many(queries) => queries,
+    };
+    if queries.is_empty() {
+        return Err(ToolError::Invalid("no queries provided".to_string()));
+    }
+    let facets = request
+        .facets
+        .iter()
+        .map(|facet| facet.parse::<FacetField>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(ToolError::Invalid)?;
+    let options = SearchOptions {
+        facets,
+        follow_links: request.follow_links,
+        ..options(request.limit, request.max_tokens, &request.trees)
+    };
+
+    // Agents write queries with small mistakes; search what can be salvaged and say what
+    // was repaired rather than fail the call
+    let (response, diagnostics) = workspace
+        .search_lenient(&combine_queries(&queries), &options)
+        .map_err(ToolError::from)?;
+
+    Ok(JsonSearchOutput::from_response(response).with_warnings(&diagnostics))
+}
+
+/// Finds documentation relevant to source files.
+pub fn context(
+    workspace: &mut Workspace,
+    request: &ContextRequest,
+) -> Result<JsonSearchOutput, ToolError> {
+    let files: Vec<PathBuf> = request.files.iter().map(PathBuf::from).collect();
+    let options = SearchOptions {
+        follow_links: request.follow_links,
+        ..options(request.limit, request.max_tokens, &request.trees)
+    };
+    let response = workspace
+        .context(&files, &options)
+        .map_err(ToolError::from)?;
+
+    Ok(JsonSearchOutput::from_response(response))
+}
+
+/// Retrieves a chunk or document by ID, optionally with its surrounding chunks.
+pub fn get(workspace: &mut Workspace, request: &GetRequest) -> Result<JsonSearchOutput, ToolError> {
+    let neighborhood = Neighborhood {
+        parent: request.parent,
+        siblings: request.siblings,
+        children: request.children,
+        next: request.next,
+        prev: request.prev,
+    };
+
+    let chunks = if request.full_document {
+        workspace.get_document(&request.id)
+    } else if !neighborhood.is_empty() {
+        workspace.get_neighborhood(&request.id, &neighborhood)
+    } else {
+        workspace.get(&request.id)
+    }
+    .map_err(ToolError::from)?;
+
+    let results: Vec<SearchResult> = chunks.into_iter().map(SearchResult::Single).collect();
+    Ok(JsonSearchOutput::single(&request.id, &results, None))
+}
+
+/// Finds content similar to a chunk or file, using default MoreLikeThis parameters.
+pub fn likethis(
+    workspace: &mut Workspace,
+    request: &LikeThisRequest,
+) -> Result<JsonSearchOutput, ToolError> {
+    let response = workspace
+        .like_this(
+            &LikeThisSource::parse(&request.source),
+            &options(request.limit, request.max_tokens, &request.trees),
+        )
+        .map_err(ToolError::from)?;
+
+    Ok(JsonSearchOutput::from_response(response))
+}
+
+/// Lists configured trees with their indexed file counts.
+pub fn list_sources(workspace: &mut Workspace) -> Result<JsonSources, ToolError> {
+    let sources = workspace
+        .trees()
+        .map_err(ToolError::from)?
+        .into_iter()
+        .map(|tree| JsonSource {
+            name: tree.name,
+            path: tree.path.display().to_string(),
+            scope: if tree.global { "global" } else { "local" },
+            files: tree.files,
+        })
+        .collect();
+
+    Ok(JsonSources { sources })
+}
+
+/// Lists indexed documents.
+pub fn list_docs(workspace: &mut Workspace) -> Result<JsonDocs, ToolError> {
+    let docs = workspace.list().map_err(ToolError::from)?;
+    Ok(JsonDocs { docs })
+}
+
+/// Lists indexed chunks.
+pub fn list_chunks(workspace: &mut Workspace) -> Result<JsonChunks, ToolError> {
+    let chunks = workspace
+        .list_chunks()
+        .map_err(ToolError::from)?
+        .into_iter()
+        .map(|chunk| JsonChunk {
+            title: chunk.title().to_string(),
+            size: chunk.body.len(),
+            id: chunk.id,
+        })
+        .collect();
+
+    Ok(JsonChunks { chunks })
+}
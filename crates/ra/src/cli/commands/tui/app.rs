@@ -0,0 +1,532 @@
+//! State and key handling for `ra tui`.
+
+use std::mem;
+
+use ra_config::Config;
+use ra_index::{SearchParams, SearchResult, Searcher};
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    text::Text,
+};
+
+use crate::cli::output::format_elbow_summary;
+
+/// Number of lines the preview scrolls per page key.
+const PAGE_LINES: u16 = 10;
+
+/// What the result list is showing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum View {
+    /// Results for the current query.
+    Search,
+    /// The parent of the chunk with this ID.
+    Parent(String),
+    /// The siblings of the chunk with this ID.
+    Siblings(String),
+}
+
+/// A message shown in the status line.
+#[derive(Debug, Clone, Default)]
+pub struct Status {
+    /// Message text.
+    pub message: String,
+    /// Whether the message reports an error.
+    pub error: bool,
+}
+
+/// A result list the user navigated away from, restored by going back.
+struct Saved {
+    /// The view that was showing.
+    view: View,
+    /// Its results.
+    results: Vec<SearchResult>,
+    /// The selected result index.
+    selected: usize,
+}
+
+/// Interactive session state.
+pub struct App {
+    /// The query being edited.
+    pub query: String,
+    /// Names of the configured trees, in config order.
+    trees: Vec<String>,
+    /// Index into `trees` of the tree filter, or `None` to search all trees.
+    tree: Option<usize>,
+    /// Search parameters, apart from the tree filter.
+    params: SearchParams,
+    /// What the result list is showing.
+    pub view: View,
+    /// Results in the list.
+    pub results: Vec<SearchResult>,
+    /// Index of the selected result.
+    pub selected: usize,
+    /// Vertical scroll offset of the preview pane.
+    pub scroll: u16,
+    /// Rendered preview of the selected result, kept until the selection changes so that
+    /// redraws don't read the source file again.
+    pub preview: Option<Text<'static>>,
+    /// Status line message.
+    pub status: Status,
+    /// Views to return to with Esc, most recent last.
+    history: Vec<Saved>,
+    /// Whether the query or tree filter changed since the last search.
+    dirty: bool,
+    /// Whether the user asked to quit.
+    pub quit: bool,
+}
+
+impl App {
+    /// Creates a session with an initial query and optional tree filter.
+    pub fn new(
+        config: &Config,
+        query: String,
+        tree: Option<&str>,
+        params: SearchParams,
+    ) -> Result<Self, String> {
+        let trees: Vec<String> = config.trees.iter().map(|t| t.name.clone()).collect();
+        let tree = match tree {
+            Some(name) => Some(
+                trees
+                    .iter()
+                    .position(|t| t == name)
+                    .ok_or_else(|| format!("unknown tree: {name}"))?,
+            ),
+            None => None,
+        };
+
+        Ok(Self {
+            query,
+            trees,
+            tree,
+            params,
+            view: View::Search,
+            results: Vec::new(),
+            selected: 0,
+            scroll: 0,
+            preview: None,
+            status: Status::default(),
+            history: Vec::new(),
+            dirty: true,
+            quit: false,
+        })
+    }
+
+    /// Returns the name of the tree filter, if one is selected.
+    pub fn tree(&self) -> Option<&str> {
+        self.tree.map(|i| self.trees[i].as_str())
+    }
+
+    /// Returns the selected result, if any.
+    pub fn selected_result(&self) -> Option<&SearchResult> {
+        self.results.get(self.selected)
+    }
+
+    /// Returns true if a search is pending.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Applies a key press.
+    pub fn handle_key(&mut self, key: KeyEvent, searcher: &Searcher) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('c') if ctrl => self.quit = true,
+            KeyCode::Char('p') if ctrl => self.show_parent(searcher),
+            KeyCode::Char('s') if ctrl => self.show_siblings(searcher),
+            KeyCode::Char('u') if ctrl => self.edit_query(String::clear),
+            KeyCode::Char(c) if !ctrl => self.edit_query(|q| q.push(c)),
+            KeyCode::Backspace => self.edit_query(|q| {
+                q.pop();
+            }),
+            KeyCode::Up => self.select(self.selected.saturating_sub(1)),
+            KeyCode::Down => self.select(self.selected + 1),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(PAGE_LINES),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(PAGE_LINES),
+            KeyCode::Tab => self.cycle_tree(true),
+            KeyCode::BackTab => self.cycle_tree(false),
+            KeyCode::Esc => self.back(),
+            _ => {}
+        }
+    }
+
+    /// Runs the current query if it changed since the last search.
+    pub fn refresh(&mut self, searcher: &mut Searcher) {
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+
+        let expr = match searcher.parse_query(&self.query) {
+            Ok(Some(expr)) => expr,
+            Ok(None) => {
+                self.set_results(Vec::new());
+                self.set_status("Type to search", false);
+                return;
+            }
+            Err(e) => {
+                self.set_status(&e.to_string(), true);
+                return;
+            }
+        };
+
+        let params = SearchParams {
+            trees: self.tree().map(str::to_string).into_iter().collect(),
+            ..self.params.clone()
+        };
+        match searcher.search_aggregated_expr_with_stats(&expr, &params) {
+            Ok((results, stats)) => {
+                let message = format!(
+                    "{} results, {}",
+                    results.len(),
+                    format_elbow_summary(&stats.elbow.reason)
+                );
+                self.set_results(results);
+                self.set_status(&message, false);
+            }
+            Err(e) => self.set_status(&format!("search failed: {e}"), true),
+        }
+    }
+
+    /// Edits the query and schedules a search, returning to the search view.
+    fn edit_query(&mut self, edit: impl FnOnce(&mut String)) {
+        self.return_to_search();
+        edit(&mut self.query);
+        self.dirty = true;
+    }
+
+    /// Moves the selection, clamped to the result list.
+    fn select(&mut self, index: usize) {
+        let index = index.min(self.results.len().saturating_sub(1));
+        if index != self.selected {
+            self.selected = index;
+            self.selection_changed();
+        }
+    }
+
+    /// Replaces the result list, selecting the first result.
+    fn set_results(&mut self, results: Vec<SearchResult>) {
+        self.results = results;
+        self.selected = 0;
+        self.selection_changed();
+    }
+
+    /// Scrolls the preview back to the top and drops the cached preview.
+    fn selection_changed(&mut self) {
+        self.scroll = 0;
+        self.preview = None;
+    }
+
+    /// Sets the status line message.
+    fn set_status(&mut self, message: &str, error: bool) {
+        self.status = Status {
+            message: message.to_string(),
+            error,
+        };
+    }
+
+    /// Cycles the tree filter through all trees and then back to none.
+    fn cycle_tree(&mut self, forward: bool) {
+        let count = self.trees.len();
+        if count == 0 {
+            return;
+        }
+        self.tree = match (self.tree, forward) {
+            (None, true) => Some(0),
+            (None, false) => Some(count - 1),
+            (Some(i), true) if i + 1 < count => Some(i + 1),
+            (Some(i), false) if i > 0 => Some(i - 1),
+            (Some(_), _) => None,
+        };
+        self.return_to_search();
+        self.dirty = true;
+    }
+
+    /// Replaces the list with the selected chunk's parent.
+    fn show_parent(&mut self, searcher: &Searcher) {
+        let Some(result) = self.selected_result() else {
+            return;
+        };
+        let candidate = result.candidate();
+        let id = candidate.id.clone();
+        let Some(parent_id) = candidate.parent_id.clone() else {
+            self.set_status(&format!("{id} is a document and has no parent"), true);
+            return;
+        };
+
+        match searcher.get_by_id(&parent_id) {
+            Ok(Some(parent)) => {
+                self.navigate(View::Parent(id), vec![SearchResult::Single(parent)]);
+                self.set_status(&format!("Parent of {}", self.view_subject()), false);
+            }
+            Ok(None) => self.set_status(&format!("parent not found: {parent_id}"), true),
+            Err(e) => self.set_status(&format!("failed to retrieve parent: {e}"), true),
+        }
+    }
+
+    /// Replaces the list with the selected chunk and its siblings, in document order.
+    fn show_siblings(&mut self, searcher: &Searcher) {
+        let Some(result) = self.selected_result() else {
+            return;
+        };
+        let candidate = result.candidate();
+        let id = candidate.id.clone();
+        if candidate.parent_id.is_none() {
+            self.set_status(&format!("{id} is a document and has no siblings"), true);
+            return;
+        }
+
+        let parent_id = candidate.parent_id.clone();
+        match searcher.get_by_path(&candidate.tree, &candidate.path) {
+            Ok(chunks) => {
+                let mut siblings: Vec<_> = chunks
+                    .into_iter()
+                    .filter(|c| c.parent_id == parent_id)
+                    .collect();
+                siblings.sort_by_key(|c| c.position);
+                let selected = siblings.iter().position(|c| c.id == id).unwrap_or(0);
+                let count = siblings.len();
+
+                self.navigate(
+                    View::Siblings(id),
+                    siblings.into_iter().map(SearchResult::Single).collect(),
+                );
+                self.selected = selected;
+                self.selection_changed();
+                self.set_status(
+                    &format!("{count} siblings of {}", self.view_subject()),
+                    false,
+                );
+            }
+            Err(e) => self.set_status(&format!("failed to retrieve siblings: {e}"), true),
+        }
+    }
+
+    /// Shows a new result list, saving the current one for Esc.
+    fn navigate(&mut self, view: View, results: Vec<SearchResult>) {
+        self.history.push(Saved {
+            view: mem::replace(&mut self.view, view),
+            results: mem::take(&mut self.results),
+            selected: self.selected,
+        });
+        self.set_results(results);
+    }
+
+    /// Returns to the previous result list, or quits from the search view.
+    fn back(&mut self) {
+        match self.history.pop() {
+            Some(saved) => {
+                self.view = saved.view;
+                self.results = saved.results;
+                self.selected = saved.selected;
+                self.selection_changed();
+                self.set_status("", false);
+            }
+            None => self.quit = true,
+        }
+    }
+
+    /// Discards navigation history, restoring the search results.
+    fn return_to_search(&mut self) {
+        let Some(first) = self.history.drain(..).next() else {
+            return;
+        };
+        self.view = first.view;
+        self.results = first.results;
+        self.selected = first.selected;
+        self.selection_changed();
+    }
+
+    /// Returns the chunk ID the current navigation view is about.
+    fn view_subject(&self) -> &str {
+        match &self.view {
+            View::Search => "",
+            View::Parent(id) | View::Siblings(id) => id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ra_index::{Indexer, SilentReporter, open_searcher};
+    use ratatui::crossterm::event::KeyEvent;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::test_support::project;
+
+    /// Builds an indexed two-tree fixture and returns its config and searcher.
+    fn fixture() -> (TempDir, Config, Searcher) {
+        let (dir, config) = project(&[
+            (
+                "docs/guide.md",
+                "# Guide\n\nIntro.\n\n## Install\n\nRun cargo install.\n\n\
+                 ## Usage\n\nRun ra search.\n\n## Config\n\nEdit cargo settings.\n",
+            ),
+            ("notes/cargo.md", "# Cargo Notes\n\nCargo workspaces.\n"),
+        ]);
+        Indexer::new(&config)
+            .unwrap()
+            .full_reindex(&mut SilentReporter)
+            .unwrap();
+        let searcher = open_searcher(&config, None).unwrap();
+        (dir, config, searcher)
+    }
+
+    /// Returns a key event without modifiers.
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    /// Returns a key event with Ctrl held.
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    /// Types a string into the app one key at a time.
+    fn type_str(app: &mut App, searcher: &Searcher, text: &str) {
+        for c in text.chars() {
+            app.handle_key(key(KeyCode::Char(c)), searcher);
+        }
+    }
+
+    /// Returns the IDs of the listed results.
+    fn ids(app: &App) -> Vec<&str> {
+        app.results
+            .iter()
+            .map(|r| r.candidate().id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn typing_updates_results() {
+        let (_dir, config, mut searcher) = fixture();
+        let mut app = App::new(&config, String::new(), None, SearchParams::default()).unwrap();
+        app.refresh(&mut searcher);
+        assert!(app.results.is_empty());
+
+        type_str(&mut app, &searcher, "install");
+        assert!(app.is_dirty());
+        app.refresh(&mut searcher);
+        assert_eq!(ids(&app), vec!["docs:guide.md#install"]);
+        assert!(!app.status.error);
+    }
+
+    #[test]
+    fn parse_errors_keep_results() {
+        let (_dir, config, mut searcher) = fixture();
+        let mut app = App::new(&config, "install".into(), None, SearchParams::default()).unwrap();
+        app.refresh(&mut searcher);
+
+        type_str(&mut app, &searcher, " (");
+        app.refresh(&mut searcher);
+        assert!(app.status.error);
+        assert_eq!(ids(&app), vec!["docs:guide.md#install"]);
+    }
+
+    #[test]
+    fn tab_cycles_tree_filter() {
+        let (_dir, config, mut searcher) = fixture();
+        let mut app = App::new(&config, "cargo".into(), None, SearchParams::default()).unwrap();
+        app.refresh(&mut searcher);
+        assert!(ids(&app).iter().any(|id| id.starts_with("docs:")));
+        assert!(ids(&app).iter().any(|id| id.starts_with("notes:")));
+
+        app.handle_key(key(KeyCode::Tab), &searcher);
+        app.handle_key(key(KeyCode::Tab), &searcher);
+        assert_eq!(app.tree(), Some("notes"));
+        app.refresh(&mut searcher);
+        assert!(ids(&app).iter().all(|id| id.starts_with("notes:")));
+
+        app.handle_key(key(KeyCode::Tab), &searcher);
+        assert_eq!(app.tree(), None);
+        app.handle_key(key(KeyCode::BackTab), &searcher);
+        assert_eq!(app.tree(), Some("notes"));
+    }
+
+    #[test]
+    fn unknown_initial_tree_is_an_error() {
+        let (_dir, config, _searcher) = fixture();
+        assert!(
+            App::new(
+                &config,
+                String::new(),
+                Some("nope"),
+                SearchParams::default()
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn parent_and_siblings_navigate_and_go_back() {
+        let (_dir, config, mut searcher) = fixture();
+        let mut app = App::new(&config, "install".into(), None, SearchParams::default()).unwrap();
+        app.refresh(&mut searcher);
+
+        app.handle_key(ctrl('s'), &searcher);
+        assert_eq!(app.view, View::Siblings("docs:guide.md#install".into()));
+        assert_eq!(
+            ids(&app),
+            vec![
+                "docs:guide.md#install",
+                "docs:guide.md#usage",
+                "docs:guide.md#config"
+            ]
+        );
+        assert_eq!(app.selected, 0);
+
+        app.handle_key(key(KeyCode::Down), &searcher);
+        app.handle_key(ctrl('p'), &searcher);
+        assert_eq!(app.view, View::Parent("docs:guide.md#usage".into()));
+        assert_eq!(ids(&app), vec!["docs:guide.md#guide"]);
+
+        app.handle_key(ctrl('p'), &searcher);
+        assert_eq!(ids(&app), vec!["docs:guide.md"]);
+        app.handle_key(ctrl('p'), &searcher);
+        assert!(app.status.error);
+        assert_eq!(ids(&app), vec!["docs:guide.md"]);
+
+        app.handle_key(key(KeyCode::Esc), &searcher);
+        app.handle_key(key(KeyCode::Esc), &searcher);
+        assert_eq!(app.selected, 1);
+        app.handle_key(key(KeyCode::Esc), &searcher);
+        assert_eq!(app.view, View::Search);
+        assert_eq!(ids(&app), vec!["docs:guide.md#install"]);
+        assert!(!app.quit);
+
+        app.handle_key(key(KeyCode::Esc), &searcher);
+        assert!(app.quit);
+    }
+
+    #[test]
+    fn typing_returns_to_search_view() {
+        let (_dir, config, mut searcher) = fixture();
+        let mut app = App::new(&config, "install".into(), None, SearchParams::default()).unwrap();
+        app.refresh(&mut searcher);
+        app.handle_key(ctrl('p'), &searcher);
+        assert_ne!(app.view, View::Search);
+
+        app.handle_key(key(KeyCode::Backspace), &searcher);
+        assert_eq!(app.view, View::Search);
+        assert_eq!(app.query, "instal");
+        assert!(app.is_dirty());
+    }
+
+    #[test]
+    fn changing_selection_drops_cached_preview() {
+        let (_dir, config, mut searcher) = fixture();
+        let mut app = App::new(&config, "install".into(), None, SearchParams::default()).unwrap();
+        app.refresh(&mut searcher);
+        app.handle_key(ctrl('s'), &searcher);
+
+        app.preview = Some(Text::raw("install"));
+        app.handle_key(key(KeyCode::Up), &searcher);
+        assert!(app.preview.is_some());
+        app.handle_key(key(KeyCode::Down), &searcher);
+        assert!(app.preview.is_none());
+
+        app.preview = Some(Text::raw("usage"));
+        app.handle_key(key(KeyCode::Esc), &searcher);
+        assert!(app.preview.is_none());
+    }
+}
@@ -0,0 +1,90 @@
+//! Implementation of `ra tui`.
+//!
+//! An interactive explorer for tuning a knowledge base: the query is re-run as it is edited,
+//! and the selected result is previewed with match highlighting. Results can be walked up to
+//! their parent chunk or across to their siblings, and the tree filter cycled without leaving
+//! the session.
+
+mod app;
+mod ui;
+
+use std::{io, process::ExitCode, time::Duration};
+
+use ra::SearchOptions;
+use ra_index::Searcher;
+use ratatui::{
+    DefaultTerminal,
+    crossterm::event::{self, Event, KeyEventKind},
+};
+
+use self::app::App;
+use crate::cli::{args::TuiCommand, context::CommandContext};
+
+/// Runs the interactive search explorer until the user quits.
+pub fn run(ctx: &mut CommandContext, cmd: &TuiCommand) -> ExitCode {
+    let options = SearchOptions {
+        limit: cmd.limit,
+        ..SearchOptions::default()
+    };
+    let params = options.params(&ctx.config);
+
+    let mut app = match App::new(
+        &ctx.config,
+        cmd.query.join(" "),
+        cmd.tree.as_deref(),
+        params,
+    ) {
+        Ok(app) => app,
+        Err(message) => {
+            eprintln!("error: {message}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let searcher = match ctx.searcher(cmd.fuzzy, true) {
+        Ok(s) => s,
+        Err(code) => return code,
+    };
+
+    let mut terminal = match ratatui::try_init() {
+        Ok(terminal) => terminal,
+        Err(e) => {
+            eprintln!("error: failed to initialize terminal: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let result = event_loop(&mut terminal, &mut app, searcher);
+    ratatui::restore();
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: terminal I/O failed: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Draws the interface and applies key presses until the user quits.
+///
+/// Searches run only once no further input is pending, so fast typing does not queue a
+/// search per keystroke.
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    app: &mut App,
+    searcher: &mut Searcher,
+) -> io::Result<()> {
+    while !app.quit {
+        if app.is_dirty() && !event::poll(Duration::ZERO)? {
+            app.refresh(searcher);
+        }
+        terminal.draw(|frame| ui::draw(frame, app, searcher))?;
+
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            app.handle_key(key, searcher);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,129 @@
+//! Rendering for `ra tui`.
+
+use ansi_to_tui::IntoText;
+use ra_highlight::{breadcrumb, dim, format_body};
+use ra_index::{SearchResult, Searcher};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Position, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, List, ListItem, ListState, Paragraph, Wrap},
+};
+
+use super::app::{App, View};
+use crate::cli::output::{aggregated_match_ranges, read_full_body};
+
+/// Key hints shown when there is no status message.
+const HINTS: &str = "Tab tree · ^P parent · ^S siblings · ^U clear · Esc back/quit";
+
+/// Draws the whole interface.
+pub fn draw(frame: &mut Frame<'_>, app: &mut App, searcher: &Searcher) {
+    let [query_area, main_area, status_area] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(1),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [list_area, preview_area] =
+        Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .areas(main_area);
+
+    draw_query(frame, app, query_area);
+    draw_results(frame, app, list_area);
+    draw_preview(frame, app, searcher, preview_area);
+    draw_status(frame, app, status_area);
+}
+
+/// Draws the query input box, with the tree filter in its title.
+fn draw_query(frame: &mut Frame<'_>, app: &App, area: Rect) {
+    let title = format!(" Query · tree: {} ", app.tree().unwrap_or("all"));
+    let input = Paragraph::new(app.query.as_str()).block(Block::bordered().title(title));
+    frame.render_widget(input, area);
+
+    let cursor_x = area.x + 1 + app.query.chars().count() as u16;
+    frame.set_cursor_position(Position::new(
+        cursor_x.min(area.right().saturating_sub(2)),
+        area.y + 1,
+    ));
+}
+
+/// Draws the result list.
+fn draw_results(frame: &mut Frame<'_>, app: &App, area: Rect) {
+    let title = match &app.view {
+        View::Search => format!(" Results ({}) ", app.results.len()),
+        View::Parent(id) => format!(" Parent of {id} "),
+        View::Siblings(id) => format!(" Siblings of {id} "),
+    };
+
+    let items: Vec<ListItem<'_>> = app.results.iter().map(|r| result_item(app, r)).collect();
+    let list = List::new(items)
+        .block(Block::bordered().title(title))
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+
+    let mut state = ListState::default().with_selected(Some(app.selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+/// Builds the list entry for a result: its ID, plus its score in the search view.
+fn result_item<'a>(app: &App, result: &'a SearchResult) -> ListItem<'a> {
+    let c = result.candidate();
+    let mut spans = vec![Span::raw(c.id.as_str())];
+    if app.view == View::Search {
+        spans.push(Span::styled(
+            format!(" {:.2}", c.score),
+            Style::new().fg(Color::DarkGray),
+        ));
+    }
+    if result.is_aggregated() {
+        spans.push(Span::styled(" [agg]", Style::new().fg(Color::DarkGray)));
+    }
+    ListItem::new(Line::from(spans))
+}
+
+/// Draws the selected result's breadcrumb, ID and highlighted body.
+///
+/// The preview is rendered once per selection and cached on the app.
+fn draw_preview(frame: &mut Frame<'_>, app: &mut App, searcher: &Searcher, area: Rect) {
+    let text = match app.results.get(app.selected) {
+        Some(result) => app
+            .preview
+            .get_or_insert_with(|| preview_text(result, searcher))
+            .clone(),
+        None => Text::default(),
+    };
+    let preview = Paragraph::new(text)
+        .block(Block::bordered().title(" Preview "))
+        .wrap(Wrap { trim: false })
+        .scroll((app.scroll, 0));
+    frame.render_widget(preview, area);
+}
+
+/// Renders a result with `ra-highlight` styling and converts it for display.
+fn preview_text(result: &SearchResult, searcher: &Searcher) -> Text<'static> {
+    let c = result.candidate();
+    let body = read_full_body(result, searcher);
+    let ranges = aggregated_match_ranges(result, &body);
+    let ansi = format!(
+        "{}\n{}\n\n{}",
+        breadcrumb(&c.breadcrumb()),
+        dim(&c.id),
+        format_body(&body, &ranges)
+    );
+    ansi.into_text().unwrap_or_else(|_| Text::raw(body))
+}
+
+/// Draws the status line: the latest message, or key hints.
+fn draw_status(frame: &mut Frame<'_>, app: &App, area: Rect) {
+    let line = if app.status.error {
+        Line::styled(app.status.message.as_str(), Style::new().fg(Color::Red))
+    } else if app.status.message.is_empty() {
+        Line::styled(HINTS, Style::new().fg(Color::DarkGray))
+    } else {
+        Line::from(vec![
+            Span::raw(app.status.message.as_str()),
+            Span::styled(format!("  {HINTS}"), Style::new().fg(Color::DarkGray)),
+        ])
+    };
+    frame.render_widget(Paragraph::new(line), area);
+}
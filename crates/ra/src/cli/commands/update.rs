@@ -59,6 +59,10 @@ impl ProgressReporter for ConsoleReporter {
         eprintln!("warning: failed to index {}: {}", path.display(), error);
     }
 
+    fn on_file_warning(&mut self, path: &Path, warning: &str) {
+        eprintln!("warning: {}: {}", path.display(), warning);
+    }
+
     fn on_file_removed(&mut self, path: &Path) {
         if self.verbose {
             println!("Removed: {}", path.display());
@@ -0,0 +1,200 @@
+//! Implementation of `ra watch`.
+//!
+//! Keeps the index current while running. Filesystem events under each tree are debounced and
+//! applied as targeted incremental updates. Changes to any `.ra.toml` reload the configuration,
+//! rebuilding the index when its indexing settings changed.
+
+use std::{
+    collections::BTreeSet,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::mpsc::{self, Receiver},
+    time::Duration,
+};
+
+use notify_debouncer_mini::{
+    DebounceEventResult, Debouncer, new_debouncer,
+    notify::{RecommendedWatcher, RecursiveMode},
+};
+use ra_config::{CONFIG_FILENAME, Config, discover_config_files};
+use ra_index::{IndexStats, Indexer, ProgressReporter, detect_index_status};
+
+use crate::cli::{args::WatchCommand, context::CommandContext};
+
+/// Watches configured trees and config files, updating the index as they change.
+pub fn run(ctx: &CommandContext, cmd: &WatchCommand) -> ExitCode {
+    if let Err(code) = ctx.require_trees(true) {
+        return code;
+    }
+
+    let timeout = Duration::from_millis(cmd.debounce);
+    let mut config = ctx.config.clone();
+
+    loop {
+        if let Err(message) = sync_index(&config) {
+            eprintln!("error: {message}");
+            return ExitCode::FAILURE;
+        }
+
+        let config_files = discover_config_files(&ctx.cwd);
+        let (tx, rx) = mpsc::channel();
+        let mut debouncer = match new_debouncer(timeout, tx) {
+            Ok(debouncer) => debouncer,
+            Err(e) => {
+                eprintln!("error: failed to start file watcher: {e}");
+                return ExitCode::FAILURE;
+            }
+        };
+        if let Err(message) = watch_paths(&mut debouncer, &config, &config_files) {
+            eprintln!("error: {message}");
+            return ExitCode::FAILURE;
+        }
+
+        println!("Watching for changes (Ctrl-C to stop)");
+        match watch_until_reload(&ctx.cwd, &config, &rx) {
+            Ok(reloaded) => {
+                println!("Configuration changed, reloading");
+                config = reloaded;
+            }
+            Err(message) => {
+                eprintln!("error: {message}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+}
+
+/// Brings the index up to date before watching, rebuilding it if the config hash changed.
+fn sync_index(config: &Config) -> Result<(), String> {
+    if detect_index_status(config).needs_rebuild() {
+        println!("Rebuilding search index...");
+    }
+    let stats = Indexer::new(config)
+        .and_then(|indexer| indexer.refresh(&mut WatchReporter { verbose: false }))
+        .map_err(|e| format!("indexing failed: {e}"))?;
+    if stats.total_changes() > 0 {
+        println!(
+            "Indexed {} files ({} chunks)",
+            stats.files_processed, stats.chunks_indexed
+        );
+    }
+    Ok(())
+}
+
+/// Registers watches for every tree and for the directories holding config files.
+///
+/// Config directories are watched non-recursively, and skipped when a tree watch already
+/// covers them.
+pub fn watch_paths(
+    debouncer: &mut Debouncer<RecommendedWatcher>,
+    config: &Config,
+    config_files: &[PathBuf],
+) -> Result<(), String> {
+    let watcher = debouncer.watcher();
+
+    for tree in &config.trees {
+        if !tree.path.exists() {
+            eprintln!(
+                "warning: tree '{}' does not exist: {}",
+                tree.name,
+                tree.path.display()
+            );
+            continue;
+        }
+        watcher
+            .watch(&tree.path, RecursiveMode::Recursive)
+            .map_err(|e| format!("failed to watch {}: {e}", tree.path.display()))?;
+        println!("Watching {}: {}", tree.name, tree.path.display());
+    }
+
+    let config_dirs: BTreeSet<&Path> = config_files.iter().filter_map(|p| p.parent()).collect();
+    for dir in config_dirs {
+        if config.trees.iter().any(|tree| dir.starts_with(&tree.path)) {
+            continue;
+        }
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("failed to watch {}: {e}", dir.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Applies debounced change sets until a config file changes.
+///
+/// Any `.ra.toml` in a watched directory counts, so that creating a new one is picked up as
+/// well as editing an existing one.
+///
+/// Returns the reloaded configuration. Invalid configuration is reported and ignored, leaving
+/// the current configuration in effect.
+fn watch_until_reload(
+    cwd: &Path,
+    config: &Config,
+    rx: &Receiver<DebounceEventResult>,
+) -> Result<Config, String> {
+    let indexer = Indexer::new(config).map_err(|e| format!("failed to initialize indexer: {e}"))?;
+
+    for result in rx {
+        let events = match result {
+            Ok(events) => events,
+            Err(e) => {
+                eprintln!("warning: file watcher error: {e}");
+                continue;
+            }
+        };
+
+        let paths: BTreeSet<PathBuf> = events.into_iter().map(|event| event.path).collect();
+        if paths
+            .iter()
+            .any(|path| path.file_name() == Some(OsStr::new(CONFIG_FILENAME)))
+        {
+            match Config::load(cwd) {
+                Ok(reloaded) => return Ok(reloaded),
+                Err(e) => {
+                    eprintln!("warning: ignoring invalid configuration: {e}");
+                    continue;
+                }
+            }
+        }
+
+        let paths: Vec<PathBuf> = paths.into_iter().collect();
+        if let Err(e) = indexer.update_paths(&paths, &mut WatchReporter { verbose: true }) {
+            eprintln!("error: indexing failed: {e}");
+        }
+    }
+
+    Err("file watcher stopped unexpectedly".to_string())
+}
+
+/// Progress reporter for watch mode.
+struct WatchReporter {
+    /// Whether to print each file as it is indexed or removed.
+    verbose: bool,
+}
+
+impl ProgressReporter for WatchReporter {
+    fn on_file_start(&mut self, _path: &Path, _current: usize, _total: usize) {}
+
+    fn on_file_done(&mut self, path: &Path, chunks: usize) {
+        if self.verbose {
+            println!("Indexed {} ({chunks} chunks)", path.display());
+        }
+    }
+
+    fn on_file_error(&mut self, path: &Path, error: &str) {
+        eprintln!("warning: failed to index {}: {}", path.display(), error);
+    }
+
+    fn on_file_warning(&mut self, path: &Path, warning: &str) {
+        eprintln!("warning: {}: {}", path.display(), warning);
+    }
+
+    fn on_file_removed(&mut self, path: &Path) {
+        if self.verbose {
+            println!("Removed {}", path.display());
+        }
+    }
+
+    fn on_complete(&mut self, _stats: &IndexStats) {}
+}
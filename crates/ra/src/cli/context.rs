@@ -68,14 +68,10 @@ impl CommandContext {
         fuzzy_override: Option<u8>,
         show_init_hint: bool,
     ) -> Result<&mut Searcher, ExitCode> {
-        if self.searcher.is_some() {
-            return Ok(self.searcher.as_mut().expect("searcher checked"));
+        if self.searcher.is_none() {
+            self.require_trees(show_init_hint)?;
+            self.searcher = Some(ensure_index_fresh(&self.config, fuzzy_override)?);
         }
-
-        self.require_trees(show_init_hint)?;
-
-        let searcher = ensure_index_fresh(&self.config, fuzzy_override)?;
-        self.searcher = Some(searcher);
         Ok(self.searcher.as_mut().expect("searcher just set"))
     }
 }
@@ -2,9 +2,12 @@
 
 use std::{collections::HashSet, ops::Range, process::ExitCode};
 
+use ra::SearchResponse;
 pub use ra_highlight::{breadcrumb, dim, header, subheader, warning};
 use ra_highlight::{format_body, theme};
-use ra_index::{ElbowReason, PipelineStats, SearchResult, Searcher, merge_ranges};
+use ra_index::{
+    ElbowReason, FacetCounts, PipelineStats, QueryError, SearchResult, Searcher, merge_ranges,
+};
 use serde::Serialize;
 
 use crate::cli::args::{OutputMode, OutputOptions};
@@ -18,15 +21,83 @@ struct JsonQueryResults {
     results: Vec<SearchResult>,
     /// Total matches returned.
     total_matches: usize,
+    /// Tokens used by the results, when a token budget was set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tokens_used: Option<usize>,
+    /// Mistakes repaired in the query, when it was searched leniently.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<JsonQueryWarning>,
+    /// Matching document counts per value of each requested facet.
+    #[serde(skip_serializing_if = "FacetCounts::is_empty")]
+    facets: FacetCounts,
+}
+
+/// A mistake repaired in a query searched leniently.
+#[derive(Serialize)]
+struct JsonQueryWarning {
+    /// What was wrong.
+    message: String,
+    /// Byte position of the mistake in the query.
+    position: Option<usize>,
+    /// How to fix the mistake, when there is a common fix.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hint: Option<&'static str>,
 }
 
 /// JSON output for search-like commands.
 #[derive(Serialize)]
-struct JsonSearchOutput {
+pub struct JsonSearchOutput {
     /// Results grouped by query.
     queries: Vec<JsonQueryResults>,
 }
 
+impl JsonSearchOutput {
+    /// Builds the output for a single query and its results.
+    ///
+    /// Token usage is taken from pipeline statistics when available.
+    pub fn single(query: &str, results: &[SearchResult], stats: Option<&PipelineStats>) -> Self {
+        Self {
+            queries: vec![JsonQueryResults {
+                query: query.to_string(),
+                total_matches: results.len(),
+                tokens_used: stats.and_then(|s| s.tokens_used),
+                results: results.to_vec(),
+                warnings: Vec::new(),
+                facets: stats.map(|s| s.facets.clone()).unwrap_or_default(),
+            }],
+        }
+    }
+
+    /// Builds the output for a library search response.
+    pub fn from_response(response: SearchResponse) -> Self {
+        Self {
+            queries: vec![JsonQueryResults {
+                query: response.query,
+                total_matches: response.total_matches,
+                tokens_used: response.tokens_used,
+                results: response.results,
+                warnings: Vec::new(),
+                facets: response.facets,
+            }],
+        }
+    }
+
+    /// Adds the mistakes repaired in a leniently searched query as warnings.
+    pub fn with_warnings(mut self, diagnostics: &[QueryError]) -> Self {
+        for query in &mut self.queries {
+            query.warnings = diagnostics
+                .iter()
+                .map(|d| JsonQueryWarning {
+                    message: d.message().to_string(),
+                    position: d.position(),
+                    hint: d.suggestion(),
+                })
+                .collect();
+        }
+        self
+    }
+}
+
 /// Rendering style for aggregated search results.
 #[derive(Clone, Copy)]
 enum DisplayMode {
@@ -39,7 +110,7 @@ enum DisplayMode {
 }
 
 /// Retrieves the full body for a search result, falling back to the indexed body.
-fn read_full_body(result: &SearchResult, searcher: &Searcher) -> String {
+pub fn read_full_body(result: &SearchResult, searcher: &Searcher) -> String {
     let c = result.candidate();
     searcher
         .read_full_content(&c.tree, &c.path, c.byte_start, c.byte_end)
@@ -50,19 +121,14 @@ fn read_full_body(result: &SearchResult, searcher: &Searcher) -> String {
 pub fn output_aggregated_results(
     results: &[SearchResult],
     query: &str,
+    warnings: &[QueryError],
     output: &OutputOptions,
     verbose: u8,
     searcher: &Searcher,
     stats: Option<&PipelineStats>,
 ) -> ExitCode {
     if matches!(output.mode, OutputMode::Json) {
-        let json_output = JsonSearchOutput {
-            queries: vec![JsonQueryResults {
-                query: query.to_string(),
-                total_matches: results.len(),
-                results: results.to_vec(),
-            }],
-        };
+        let json_output = JsonSearchOutput::single(query, results, stats).with_warnings(warnings);
         match serde_json::to_string_pretty(&json_output) {
             Ok(json_str) => println!("{json_str}"),
             Err(e) => {
@@ -132,12 +198,19 @@ fn output_text_results(
         ];
 
         if let Some(stats) = stats {
+            if let Some(tokens) = stats.tokens_used {
+                summary_parts.push(format!("{tokens} tokens"));
+            }
             summary_parts.push(format_elbow_summary(&stats.elbow.reason));
         }
 
         println!("{}", dim(&format!("─── {} ───", summary_parts.join(", "))));
     }
 
+    if let Some(stats) = stats {
+        print_facets(&stats.facets);
+    }
+
     if matches!(mode, DisplayMode::List) {
         println!();
     }
@@ -145,6 +218,19 @@ fn output_text_results(
     ExitCode::SUCCESS
 }
 
+/// Prints matching document counts for each requested facet, most common values first.
+fn print_facets(facets: &FacetCounts) {
+    for (field, counts) in facets {
+        let mut counts: Vec<_> = counts.iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        let values: Vec<String> = counts
+            .iter()
+            .map(|(value, count)| format!("{value} ({count})"))
+            .collect();
+        println!("{}", dim(&format!("{field}: {}", values.join(", "))));
+    }
+}
+
 /// Prints detailed pipeline statistics.
 fn print_pipeline_stats(stats: &PipelineStats) {
     println!("{}", dim("Pipeline:"));
@@ -158,6 +244,12 @@ fn print_pipeline_stats(stats: &PipelineStats) {
             stats.final_count
         ))
     );
+    if stats.linked_count > 0 {
+        println!(
+            "{}",
+            dim(&format!("  Linked chunks added: {}", stats.linked_count))
+        );
+    }
     println!(
         "{}",
         dim(&format!(
@@ -218,6 +310,12 @@ fn format_aggregated_result(
             "─── {} [aggregated: {} matches] ───\n",
             header_id, count
         ));
+    } else if let Some(source) = &c.via_link {
+        output.push_str(&format!(
+            "─── {} {} ───\n",
+            header_id,
+            dim(&format!("[via link from {source}]"))
+        ));
     } else {
         output.push_str(&format!("─── {} ───\n", header_id));
     }
@@ -483,7 +581,7 @@ fn highlight_breadcrumb_title(
 }
 
 /// Computes highlight ranges for an aggregated result by mapping child matches into parent body.
-fn aggregated_match_ranges(result: &SearchResult, full_body: &str) -> Vec<Range<usize>> {
+pub fn aggregated_match_ranges(result: &SearchResult, full_body: &str) -> Vec<Range<usize>> {
     match result {
         SearchResult::Single(candidate) => candidate.match_ranges.clone(),
         SearchResult::Aggregated {
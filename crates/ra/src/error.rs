@@ -0,0 +1,39 @@
+//! Error types for the ra library.
+
+use std::path::PathBuf;
+
+use ra_config::ConfigError;
+use ra_index::IndexError;
+use thiserror::Error;
+
+/// Errors that can occur when working with a [`Workspace`](crate::Workspace).
+#[derive(Debug, Error)]
+pub enum Error {
+    /// Configuration could not be loaded.
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    /// Indexing or searching failed.
+    #[error(transparent)]
+    Index(#[from] IndexError),
+
+    /// The configuration defines no trees to search.
+    #[error("no trees defined in configuration")]
+    NoTrees,
+
+    /// A chunk or document ID could not be parsed.
+    #[error("invalid ID format: {0} (expected tree:path#slug or tree:path)")]
+    InvalidId(String),
+
+    /// A file passed to a context search does not exist.
+    #[error("file not found: {}", .0.display())]
+    FileNotFound(PathBuf),
+
+    /// None of the files passed to a context search could be analyzed.
+    #[error("no analyzable files provided")]
+    NoAnalyzableFiles,
+
+    /// No chunk or document exists with the given ID.
+    #[error("not found: {0}")]
+    NotFound(String),
+}
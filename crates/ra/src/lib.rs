@@ -7,5 +7,23 @@
 //! agent tasks. Because the full corpus may exceed practical context limits, ra indexes
 //! these documents and exposes search tools that agents use to retrieve relevant
 //! context on demand.
+//!
+//! The [`Workspace`] type is the entry point for embedding ra in other tools: it loads
+//! configuration from a directory, keeps the index fresh, and answers the same queries as the
+//! `search`, `context`, `get`, `likethis` and `ls` commands.
 
 #![warn(missing_docs)]
+
+mod error;
+#[cfg(test)]
+mod test_support;
+mod workspace;
+
+pub use error::Error;
+pub use ra_config::Config;
+pub use ra_index::{
+    FacetCounts, FacetField, Neighborhood, QueryError, QueryExpr, SearchCandidate, SearchResult,
+};
+pub use workspace::{
+    DocumentInfo, LikeThisSource, SearchOptions, SearchResponse, TreeInfo, Workspace,
+};
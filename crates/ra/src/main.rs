@@ -1,6 +1,8 @@
 //! Command-line interface for the `ra` research assistant tool.
 
 mod cli;
+#[cfg(test)]
+mod test_support;
 
 use std::process::ExitCode;
 
@@ -33,7 +35,6 @@ fn main() -> ExitCode {
                 commands::run(cli::args::Commands::Inspect { what }, &mut ctx)
             }
         },
-        cli::args::Commands::Agents(cmd) => cli::commands::agents::run(&cmd),
         other => {
             let mut ctx = match CommandContext::load() {
                 Ok(ctx) => ctx,
@@ -0,0 +1,33 @@
+//! Test helpers shared by the library and command unit tests.
+//!
+//! Declared by both `lib.rs` and `main.rs` behind `cfg(test)`, so it never reaches the public API.
+
+use std::{collections::BTreeSet, fs};
+
+use ra_config::Config;
+use tempfile::TempDir;
+
+/// Writes `files` into a temporary project and loads its configuration.
+///
+/// Each file path is relative to the project root; its first component names a tree, which is
+/// configured in the project's `.ra.toml`. Nothing is indexed.
+pub fn project(files: &[(&str, &str)]) -> (TempDir, Config) {
+    let dir = TempDir::new().unwrap();
+    let mut trees = BTreeSet::new();
+    for (path, content) in files {
+        trees.insert(path.split('/').next().unwrap());
+        let path = dir.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+    }
+
+    let config_path = dir.path().join(".ra.toml");
+    let toml: String = trees
+        .iter()
+        .map(|tree| format!("[tree.{tree}]\npath = \"{tree}\"\n\n"))
+        .collect();
+    fs::write(&config_path, toml).unwrap();
+
+    let config = Config::load_from_files(&[config_path]).unwrap();
+    (dir, config)
+}
@@ -0,0 +1,618 @@
+//! The [`Workspace`] facade for embedding ra in other tools.
+//!
+//! A workspace owns a loaded configuration and a searcher over its index. Every query first
+//! brings the index up to date with the files on disk, so callers never deal with indexing
+//! directly.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use ra_config::{Config, SearchOverrides};
+use ra_context::KeywordAlgorithm;
+use ra_document::ChunkId;
+use ra_index::{
+    ContextSearch, FacetCounts, FacetField, Indexer, MoreLikeThisParams, Neighborhood,
+    PipelineStats, QueryError, QueryExpr, SearchCandidate, SearchParams, SearchResult, Searcher,
+    SilentReporter, indexed_file_counts, is_binary_file, open_searcher,
+};
+use serde::Serialize;
+
+use crate::Error;
+
+/// Options shared by the query methods of a [`Workspace`].
+///
+/// Unset values fall back to the `[search]` configuration, and for context searches to any
+/// matching context rule.
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    /// Maximum number of results to return.
+    pub limit: Option<usize>,
+    /// Token budget for the results; results that would overflow it are skipped.
+    pub max_tokens: Option<usize>,
+    /// Limit results to these trees. If empty, all trees are searched.
+    pub trees: Vec<String>,
+    /// Maximum candidates to pass into hierarchical aggregation.
+    pub aggregation_pool_size: Option<usize>,
+    /// Score ratio threshold for the relevance cutoff.
+    pub cutoff_ratio: Option<f32>,
+    /// Sibling ratio threshold for hierarchical aggregation.
+    pub aggregation_threshold: Option<f32>,
+    /// Whether to skip hierarchical aggregation.
+    pub disable_aggregation: bool,
+    /// Fields to count matching documents by.
+    pub facets: Vec<FacetField>,
+    /// Add chunks linked from this many of the top results.
+    pub follow_links: Option<usize>,
+    /// Verbosity level for match details.
+    pub verbosity: u8,
+}
+
+impl SearchOptions {
+    /// Builds search parameters from these options, falling back to the `[search]` settings of
+    /// `config`.
+    pub fn params(&self, config: &Config) -> SearchParams {
+        self.params_with_rules(config, &SearchOverrides::default())
+    }
+
+    /// Builds search parameters from these options, then `rules`, then config defaults.
+    fn params_with_rules(&self, config: &Config, rules: &SearchOverrides) -> SearchParams {
+        let defaults = &config.search;
+        SearchParams {
+            candidate_limit: None,
+            cutoff_ratio: self
+                .cutoff_ratio
+                .or(rules.cutoff_ratio)
+                .unwrap_or(defaults.cutoff_ratio),
+            aggregation_pool_size: self
+                .aggregation_pool_size
+                .or(rules.aggregation_pool_size)
+                .unwrap_or(defaults.aggregation_pool_size),
+            aggregation_threshold: self
+                .aggregation_threshold
+                .or(rules.aggregation_threshold)
+                .unwrap_or(defaults.aggregation_threshold),
+            disable_aggregation: self.disable_aggregation,
+            limit: self.limit.or(rules.limit).unwrap_or(defaults.limit),
+            max_tokens: self.max_tokens,
+            trees: self.trees.clone(),
+            facets: self.facets.clone(),
+            follow_links: self.follow_links,
+            verbosity: self.verbosity,
+        }
+    }
+}
+
+/// Results of a search, context or similarity query.
+///
+/// Serializes to the same shape as one entry of the CLI's `--output json` query list.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResponse {
+    /// The query that was executed.
+    pub query: String,
+    /// Matching results, best first.
+    pub results: Vec<SearchResult>,
+    /// Number of results returned.
+    pub total_matches: usize,
+    /// Tokens used by the results, when a token budget was set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens_used: Option<usize>,
+    /// Matching document counts per value of each requested facet.
+    #[serde(skip_serializing_if = "FacetCounts::is_empty")]
+    pub facets: FacetCounts,
+}
+
+impl SearchResponse {
+    /// Builds a response from a query, its results and pipeline statistics.
+    fn new(query: String, results: Vec<SearchResult>, stats: &PipelineStats) -> Self {
+        Self {
+            query,
+            total_matches: results.len(),
+            tokens_used: stats.tokens_used,
+            results,
+            facets: stats.facets.clone(),
+        }
+    }
+}
+
+/// The source of a similarity search.
+#[derive(Debug, Clone)]
+pub enum LikeThisSource {
+    /// An indexed chunk or document ID (`tree:path#slug` or `tree:path`).
+    Id(String),
+    /// A file on disk, which need not be indexed.
+    File(PathBuf),
+}
+
+impl LikeThisSource {
+    /// Interprets a source as `ra likethis` does: anything with a colon is an ID, unless the
+    /// colon follows a Windows drive letter.
+    pub fn parse(source: &str) -> Self {
+        let is_id = source.contains(':') && source.chars().nth(1) != Some(':');
+        if is_id {
+            Self::Id(source.to_string())
+        } else {
+            Self::File(PathBuf::from(source))
+        }
+    }
+
+    /// Returns the label reported as the query: the ID, or `file:<path>`.
+    pub fn label(&self) -> String {
+        match self {
+            Self::Id(id) => id.clone(),
+            Self::File(path) => format!("file:{}", path.display()),
+        }
+    }
+}
+
+/// A configured knowledge tree.
+#[derive(Debug, Clone, Serialize)]
+pub struct TreeInfo {
+    /// Tree name.
+    pub name: String,
+    /// Absolute path to the tree root.
+    pub path: PathBuf,
+    /// Whether the tree is defined in the global config.
+    pub global: bool,
+    /// Number of indexed files in the tree.
+    pub files: usize,
+}
+
+/// An indexed document.
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentInfo {
+    /// Document ID (`tree:path`).
+    pub id: String,
+    /// Document title.
+    pub title: String,
+    /// Number of chunks in the document.
+    pub chunks: usize,
+    /// Total body size across all chunks, in bytes.
+    pub size: usize,
+}
+
+/// A configured ra workspace: its configuration plus a searcher over its index.
+///
+/// The index is created on first use and, unless [`Workspace::set_auto_refresh`] turns it off,
+/// refreshed before every query. Each query method returns serde-serializable types.
+///
+/// ```no_run
+/// use std::path::Path;
+///
+/// use ra::{SearchOptions, Workspace};
+///
+/// let mut workspace = Workspace::open(Path::new("."))?;
+/// let response = workspace.search("error handling", &SearchOptions::default())?;
+/// for result in &response.results {
+///     println!("{}", result.candidate().id);
+/// }
+/// # Ok::<(), ra::Error>(())
+/// ```
+pub struct Workspace {
+    /// The loaded configuration.
+    config: Config,
+    /// Searcher over the index, opened on first use.
+    searcher: Option<Searcher>,
+    /// Whether queries bring the index up to date before running.
+    auto_refresh: bool,
+}
+
+impl Workspace {
+    /// Opens the workspace for `dir`, discovering and merging `.ra.toml` files as the CLI does.
+    ///
+    /// Returns [`Error::NoTrees`] if the resulting configuration defines no trees.
+    pub fn open(dir: &Path) -> Result<Self, Error> {
+        Self::from_config(Config::load(dir)?)
+    }
+
+    /// Creates a workspace from an already loaded configuration.
+    ///
+    /// Returns [`Error::NoTrees`] if the configuration defines no trees.
+    pub fn from_config(config: Config) -> Result<Self, Error> {
+        if config.trees.is_empty() {
+            return Err(Error::NoTrees);
+        }
+        Ok(Self {
+            config,
+            searcher: None,
+            auto_refresh: true,
+        })
+    }
+
+    /// Returns the workspace configuration.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Sets whether query methods bring the index up to date before running, which is on by
+    /// default.
+    ///
+    /// Long-running callers that index some other way, such as from a file watcher, turn this
+    /// off; the searcher still picks up their commits shortly after they land.
+    pub fn set_auto_refresh(&mut self, auto_refresh: bool) {
+        self.auto_refresh = auto_refresh;
+    }
+
+    /// Brings the index up to date with the files on disk.
+    ///
+    /// Query methods call this automatically unless auto refresh is off; it is exposed for
+    /// callers that want to pay the indexing cost up front.
+    pub fn refresh(&mut self) -> Result<(), Error> {
+        refresh_index(&self.config, self.searcher.as_ref())
+    }
+
+    /// Searches the knowledge base using the query syntax of `ra search`.
+    pub fn search(
+        &mut self,
+        query: &str,
+        options: &SearchOptions,
+    ) -> Result<SearchResponse, Error> {
+        let params = options.params(&self.config);
+        let searcher = fresh_searcher(&self.config, self.auto_refresh, &mut self.searcher)?;
+        let (results, stats) = searcher.search_aggregated_with_stats(query, &params)?;
+        Ok(SearchResponse::new(query.to_string(), results, &stats))
+    }
+
+    /// Searches like [`Workspace::search`], but repairs mistakes in the query such as
+    /// unbalanced parentheses instead of failing, and returns the repairs made.
+    pub fn search_lenient(
+        &mut self,
+        query: &str,
+        options: &SearchOptions,
+    ) -> Result<(SearchResponse, Vec<QueryError>), Error> {
+        let params = options.params(&self.config);
+        let searcher = fresh_searcher(&self.config, self.auto_refresh, &mut self.searcher)?;
+        let parsed = searcher.parse_query_lenient(query);
+        let (results, stats) = match &parsed.expr {
+            Some(expr) => searcher.search_aggregated_expr_with_stats(expr, &params)?,
+            None => (
+                Vec::new(),
+                PipelineStats::empty(params.cutoff_ratio, params.aggregation_pool_size),
+            ),
+        };
+        let response = SearchResponse::new(query.to_string(), results, &stats);
+        Ok((response, parsed.diagnostics))
+    }
+
+    /// Searches the knowledge base with a query expression, such as one deserialized from the
+    /// JSON that `ra search --query-json` accepts.
+    ///
+    /// The response's query is the expression written in query syntax.
+    pub fn search_expr(
+        &mut self,
+        expr: &QueryExpr,
+        options: &SearchOptions,
+    ) -> Result<SearchResponse, Error> {
+        let params = options.params(&self.config);
+        let searcher = fresh_searcher(&self.config, self.auto_refresh, &mut self.searcher)?;
+        let (results, stats) = searcher.search_aggregated_expr_with_stats(expr, &params)?;
+        Ok(SearchResponse::new(expr.to_query_string(), results, &stats))
+    }
+
+    /// Finds documentation relevant to source files, as `ra context` does.
+    ///
+    /// Binary files are skipped. The response's query is the generated search query.
+    pub fn context(
+        &mut self,
+        files: &[PathBuf],
+        options: &SearchOptions,
+    ) -> Result<SearchResponse, Error> {
+        if let Some(missing) = files.iter().find(|path| !path.exists()) {
+            return Err(Error::FileNotFound(missing.clone()));
+        }
+        let paths: Vec<&Path> = files
+            .iter()
+            .map(PathBuf::as_path)
+            .filter(|path| !is_binary_file(path))
+            .collect();
+
+        let searcher = fresh_searcher(&self.config, self.auto_refresh, &mut self.searcher)?;
+        let mut context_search = ContextSearch::with_algorithm(
+            searcher,
+            &self.config.context,
+            self.config.context.terms,
+            KeywordAlgorithm::default(),
+        )?;
+
+        let analysis = context_search.analyze(&paths, &options.trees);
+        if analysis.is_empty() {
+            return Err(Error::NoAnalyzableFiles);
+        }
+
+        let params = options.params_with_rules(&self.config, &analysis.merged_rules.search);
+        let (results, analysis, stats) =
+            context_search.search_with_analysis_stats(analysis, &params)?;
+        let query = analysis
+            .query_string()
+            .unwrap_or_else(|| String::from("(empty)"));
+        Ok(SearchResponse::new(query, results, &stats))
+    }
+
+    /// Retrieves a chunk by ID, or every chunk of a document given a `tree:path` ID.
+    pub fn get(&mut self, id: &str) -> Result<Vec<SearchCandidate>, Error> {
+        let chunk_id = parse_id(id)?;
+        let searcher = fresh_searcher(&self.config, self.auto_refresh, &mut self.searcher)?;
+        let chunks = match chunk_id.slug {
+            Some(_) => searcher.get_by_id(id)?.into_iter().collect(),
+            None => searcher.get_by_path(&chunk_id.doc_id.tree, &chunk_id.doc_id.path)?,
+        };
+        non_empty(id, chunks)
+    }
+
+    /// Retrieves every chunk of the document containing the chunk or document `id`.
+    pub fn get_document(&mut self, id: &str) -> Result<Vec<SearchCandidate>, Error> {
+        let chunk_id = parse_id(id)?;
+        let searcher = fresh_searcher(&self.config, self.auto_refresh, &mut self.searcher)?;
+        let chunks = searcher.get_by_path(&chunk_id.doc_id.tree, &chunk_id.doc_id.path)?;
+        non_empty(id, chunks)
+    }
+
+    /// Retrieves a chunk together with the surrounding chunks selected by `neighborhood`,
+    /// ordered by document position.
+    pub fn get_neighborhood(
+        &mut self,
+        id: &str,
+        neighborhood: &Neighborhood,
+    ) -> Result<Vec<SearchCandidate>, Error> {
+        parse_id(id)?;
+        let searcher = fresh_searcher(&self.config, self.auto_refresh, &mut self.searcher)?;
+        non_empty(id, searcher.get_neighborhood(id, neighborhood)?)
+    }
+
+    /// Finds content similar to an indexed chunk or a file, as `ra likethis` does.
+    pub fn like_this(
+        &mut self,
+        source: &LikeThisSource,
+        options: &SearchOptions,
+    ) -> Result<SearchResponse, Error> {
+        let params = options.params(&self.config);
+        let mlt_params = MoreLikeThisParams::default();
+        let searcher = fresh_searcher(&self.config, self.auto_refresh, &mut self.searcher)?;
+        let (results, stats) = match source {
+            LikeThisSource::Id(id) => {
+                searcher.search_more_like_this_by_id_with_stats(id, &mlt_params, &params)?
+            }
+            LikeThisSource::File(path) => {
+                searcher.search_more_like_this_by_file_with_stats(path, &mlt_params, &params)?
+            }
+        };
+        Ok(SearchResponse::new(source.label(), results, &stats))
+    }
+
+    /// Lists indexed documents in index order.
+    pub fn list(&mut self) -> Result<Vec<DocumentInfo>, Error> {
+        let searcher = fresh_searcher(&self.config, self.auto_refresh, &mut self.searcher)?;
+
+        let mut docs: Vec<DocumentInfo> = Vec::new();
+        let mut doc_index: HashMap<String, usize> = HashMap::new();
+        for chunk in searcher.list_all()? {
+            if let Some(&idx) = doc_index.get(&chunk.doc_id) {
+                docs[idx].chunks += 1;
+                docs[idx].size += chunk.body.len();
+            } else {
+                doc_index.insert(chunk.doc_id.clone(), docs.len());
+                docs.push(DocumentInfo {
+                    title: chunk.title().to_string(),
+                    chunks: 1,
+                    size: chunk.body.len(),
+                    id: chunk.doc_id,
+                });
+            }
+        }
+        Ok(docs)
+    }
+
+    /// Lists indexed chunks in index order.
+    pub fn list_chunks(&mut self) -> Result<Vec<SearchCandidate>, Error> {
+        let searcher = fresh_searcher(&self.config, self.auto_refresh, &mut self.searcher)?;
+        Ok(searcher.list_all()?)
+    }
+
+    /// Lists configured trees with their indexed file counts.
+    pub fn trees(&mut self) -> Result<Vec<TreeInfo>, Error> {
+        fresh_searcher(&self.config, self.auto_refresh, &mut self.searcher)?;
+        let counts = indexed_file_counts(&self.config)?;
+        Ok(self
+            .config
+            .trees
+            .iter()
+            .map(|tree| TreeInfo {
+                name: tree.name.clone(),
+                path: tree.path.clone(),
+                global: tree.is_global,
+                files: counts.get(&tree.name).copied().unwrap_or(0),
+            })
+            .collect())
+    }
+}
+
+/// Returns the searcher, opening it on first use, after refreshing the index if `refresh` is
+/// set.
+fn fresh_searcher<'a>(
+    config: &Config,
+    refresh: bool,
+    slot: &'a mut Option<Searcher>,
+) -> Result<&'a mut Searcher, Error> {
+    if refresh {
+        refresh_index(config, slot.as_ref())?;
+    }
+    match slot {
+        Some(searcher) => Ok(searcher),
+        None => Ok(slot.insert(open_searcher(config, None)?)),
+    }
+}
+
+/// Brings the index up to date and reloads `searcher`, if open, so it sees the result.
+fn refresh_index(config: &Config, searcher: Option<&Searcher>) -> Result<(), Error> {
+    Indexer::new(config)?.refresh(&mut SilentReporter)?;
+    if let Some(searcher) = searcher {
+        searcher.reload()?;
+    }
+    Ok(())
+}
+
+/// Parses a chunk or document ID.
+fn parse_id(id: &str) -> Result<ChunkId, Error> {
+    id.parse().map_err(|_| Error::InvalidId(id.to_string()))
+}
+
+/// Returns `chunks`, or [`Error::NotFound`] if nothing matched `id`.
+fn non_empty(id: &str, chunks: Vec<SearchCandidate>) -> Result<Vec<SearchCandidate>, Error> {
+    if chunks.is_empty() {
+        Err(Error::NotFound(id.to_string()))
+    } else {
+        Ok(chunks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::test_support::project;
+
+    /// Creates a workspace with one `docs` tree holding two documents.
+    fn workspace() -> (TempDir, Workspace) {
+        let (dir, config) = project(&[
+            (
+                "docs/rust.md",
+                "# Rust Guide\n\nOwnership and borrowing.\n\n## Errors\n\nUse Result for errors.\n",
+            ),
+            (
+                "docs/python.md",
+                "# Python Guide\n\nDynamic typing and exceptions.\n",
+            ),
+        ]);
+        (dir, Workspace::from_config(config).unwrap())
+    }
+
+    #[test]
+    fn from_config_requires_trees() {
+        assert!(matches!(
+            Workspace::from_config(Config::default()),
+            Err(Error::NoTrees)
+        ));
+    }
+
+    #[test]
+    fn search_indexes_on_first_use() {
+        let (_dir, mut ws) = workspace();
+        let response = ws.search("ownership", &SearchOptions::default()).unwrap();
+
+        assert_eq!(response.query, "ownership");
+        assert_eq!(response.total_matches, response.results.len());
+        assert_eq!(response.results[0].candidate().doc_id, "docs:rust.md");
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert!(json["results"].is_array());
+        assert!(json.get("tokens_used").is_none());
+    }
+
+    #[test]
+    fn search_counts_facets() {
+        let (_dir, mut ws) = workspace();
+        let options = SearchOptions {
+            facets: vec![FacetField::Tree],
+            ..SearchOptions::default()
+        };
+        let response = ws.search("ownership", &options).unwrap();
+
+        assert_eq!(response.facets[&FacetField::Tree]["docs"], 1);
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["facets"]["tree"]["docs"], 1);
+    }
+
+    #[test]
+    fn search_expr_takes_json_queries() {
+        let (_dir, mut ws) = workspace();
+        let expr: QueryExpr =
+            serde_json::from_str(r#"{"or": [{"term": "ownership"}, {"term": "zzz"}]}"#).unwrap();
+        let response = ws.search_expr(&expr, &SearchOptions::default()).unwrap();
+
+        assert_eq!(response.query, "(ownership OR zzz)");
+        assert_eq!(response.results[0].candidate().doc_id, "docs:rust.md");
+    }
+
+    #[test]
+    fn search_sees_new_files() {
+        let (dir, mut ws) = workspace();
+        assert!(
+            ws.search("haskell", &SearchOptions::default())
+                .unwrap()
+                .results
+                .is_empty()
+        );
+
+        fs::write(
+            dir.path().join("docs/haskell.md"),
+            "# Haskell\n\nHaskell monads.\n",
+        )
+        .unwrap();
+        let response = ws.search("haskell", &SearchOptions::default()).unwrap();
+        assert_eq!(response.results[0].candidate().doc_id, "docs:haskell.md");
+    }
+
+    #[test]
+    fn get_chunk_and_document() {
+        let (_dir, mut ws) = workspace();
+
+        let chunk = ws.get("docs:rust.md#errors").unwrap();
+        assert_eq!(chunk.len(), 1);
+        assert_eq!(chunk[0].id, "docs:rust.md#errors");
+
+        let doc = ws.get_document("docs:rust.md#errors").unwrap();
+        assert!(doc.len() > 1);
+
+        assert!(matches!(ws.get("nonsense"), Err(Error::InvalidId(_))));
+        assert!(matches!(ws.get("docs:missing.md"), Err(Error::NotFound(_))));
+    }
+
+    #[test]
+    fn context_reports_missing_files() {
+        let (dir, mut ws) = workspace();
+        let missing = dir.path().join("missing.rs");
+        assert!(matches!(
+            ws.context(&[missing], &SearchOptions::default()),
+            Err(Error::FileNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn list_and_trees() {
+        let (_dir, mut ws) = workspace();
+
+        let docs = ws.list().unwrap();
+        let ids: Vec<&str> = docs.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(docs.len(), 2);
+        assert!(ids.contains(&"docs:rust.md"));
+        assert!(ids.contains(&"docs:python.md"));
+
+        let trees = ws.trees().unwrap();
+        assert_eq!(trees.len(), 1);
+        assert_eq!(trees[0].name, "docs");
+        assert_eq!(trees[0].files, 2);
+    }
+
+    #[test]
+    fn like_this_excludes_source() {
+        let (_dir, mut ws) = workspace();
+        let response = ws
+            .like_this(
+                &LikeThisSource::Id("docs:rust.md".to_string()),
+                &SearchOptions::default(),
+            )
+            .unwrap();
+
+        assert_eq!(response.query, "docs:rust.md");
+        assert!(
+            response
+                .results
+                .iter()
+                .all(|r| r.candidate().doc_id != "docs:rust.md")
+        );
+    }
+}
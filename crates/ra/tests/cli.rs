@@ -99,9 +99,10 @@ mod init {
     #[test]
     fn updates_gitignore_when_present() {
         let dir = temp_dir();
+        let home = temp_dir();
         fs::write(dir.path().join(".gitignore"), "*.log\n").unwrap();
 
-        ra_with_home(dir.path())
+        ra_with_home(home.path())
             .current_dir(dir.path())
             .arg("init")
             .assert()
@@ -129,8 +130,9 @@ mod init {
     #[test]
     fn prints_config_preview() {
         let dir = temp_dir();
+        let home = temp_dir();
 
-        let assert = ra_with_home(dir.path())
+        let assert = ra_with_home(home.path())
             .current_dir(dir.path())
             .arg("init")
             .assert()
@@ -261,6 +263,63 @@ mod inspect {
             .success();
     }
 
+    #[test]
+    fn shows_frontmatter_metadata() {
+        let dir = temp_dir();
+        fs::write(
+            dir.path().join("test.md"),
+            "---\nstatus: draft\naudience: [dev, ops]\n---\n# Hello\n\nWorld",
+        )
+        .unwrap();
+
+        let output = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["inspect", "doc", "test.md"])
+            .assert()
+            .success();
+        let stdout = strip_ansi(&String::from_utf8_lossy(&output.get_output().stdout));
+        assert!(stdout.contains("meta.status: draft"), "{stdout}");
+        assert!(stdout.contains("meta.audience: dev, ops"), "{stdout}");
+    }
+
+    #[test]
+    fn shows_toml_frontmatter() {
+        let dir = temp_dir();
+        fs::write(
+            dir.path().join("test.md"),
+            "+++\ntitle = \"Hugo Page\"\ntags = [\"hugo\"]\n+++\n\nBody",
+        )
+        .unwrap();
+
+        let output = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["inspect", "doc", "test.md"])
+            .assert()
+            .success();
+        let stdout = strip_ansi(&String::from_utf8_lossy(&output.get_output().stdout));
+        assert!(stdout.contains("Hugo Page"), "{stdout}");
+        assert!(stdout.contains("tags: hugo"), "{stdout}");
+    }
+
+    #[test]
+    fn warns_on_malformed_frontmatter() {
+        let dir = temp_dir();
+        fs::write(
+            dir.path().join("test.md"),
+            "---\ntitle: [unclosed\n---\n# Hello\n\nWorld",
+        )
+        .unwrap();
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["inspect", "doc", "test.md"])
+            .assert()
+            .success()
+            .stderr(predicate::str::contains(
+                "warning: malformed YAML frontmatter",
+            ));
+    }
+
     #[test]
     fn succeeds_on_text_file() {
         let dir = temp_dir();
@@ -543,6 +602,226 @@ path = "./docs"
             .stdout(predicate::str::contains("No results found"));
     }
 
+    #[test]
+    fn filters_by_modification_time() {
+        let dir = setup_indexed_dir();
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "programming modified:<1d"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Rust Programming"));
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "programming modified:<2020"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No results found"));
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "modified:2025-13"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("invalid month"));
+    }
+
+    #[test]
+    fn wildcard_terms() {
+        let dir = setup_indexed_dir();
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "pyth*"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Python Programming"))
+            .stdout(predicate::str::contains("Rust Programming").not());
+
+        let assert = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "--explain", "program*"])
+            .assert()
+            .success();
+        let plain = strip_ansi(&String::from_utf8_lossy(&assert.get_output().stdout));
+        assert!(plain.contains("Wildcard(\"program*\")"), "{plain}");
+        assert!(plain.contains("Wildcard Expansions:"), "{plain}");
+        assert!(plain.contains("program* → program"), "{plain}");
+    }
+
+    #[test]
+    fn per_term_fuzziness() {
+        let dir = setup_indexed_dir();
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "pythn"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Python Programming"));
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "pythn~0"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No results found"));
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "--fuzzy", "0", "pyhtno~2"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Python Programming"));
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "python~3"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("fuzzy distance must be at most 2"));
+    }
+
+    #[test]
+    fn structural_filters() {
+        let dir = setup_indexed_dir();
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "language doc:docs:python.md"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Python Programming"))
+            .stdout(predicate::str::contains("Rust Programming").not());
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "language -doc:docs:python.md depth:<=1"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Rust Programming"))
+            .stdout(predicate::str::contains("Python Programming").not());
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "language depth:>=5"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No results found"));
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "depth:deep"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("invalid depth 'deep'"));
+    }
+
+    #[test]
+    fn saved_query_macros() {
+        let dir = setup_indexed_dir();
+        let mut config = fs::read_to_string(dir.path().join(".ra.toml")).unwrap();
+        config.push_str(
+            r#"
+[queries]
+scripting = "python OR dynamic"
+loop = "language @loop"
+"#,
+        );
+        fs::write(dir.path().join(".ra.toml"), config).unwrap();
+
+        let output = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "language @scripting"])
+            .assert()
+            .success();
+        let stdout = strip_ansi(&String::from_utf8_lossy(&output.get_output().stdout));
+        assert!(stdout.contains("Python Programming"), "{stdout}");
+        assert!(!stdout.contains("Rust Programming"), "{stdout}");
+
+        let output = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "--explain", "rust -@scripting"])
+            .assert()
+            .success();
+        let stdout = strip_ansi(&String::from_utf8_lossy(&output.get_output().stdout));
+        assert!(stdout.contains("Term(\"python\")"), "{stdout}");
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "@loop"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "query macro cycle: @loop -> @loop",
+            ));
+    }
+
+    #[test]
+    fn synonym_expansion() {
+        let dir = setup_indexed_dir();
+        let mut config = fs::read_to_string(dir.path().join(".ra.toml")).unwrap();
+        config.push_str(
+            r#"
+[search.synonyms]
+py = "python"
+"#,
+        );
+        fs::write(dir.path().join(".ra.toml"), config).unwrap();
+
+        let output = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "py"])
+            .assert()
+            .success();
+        let stdout = strip_ansi(&String::from_utf8_lossy(&output.get_output().stdout));
+        assert!(stdout.contains("Python Programming"), "{stdout}");
+
+        let output = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "--explain", "py"])
+            .assert()
+            .success();
+        let stdout = strip_ansi(&String::from_utf8_lossy(&output.get_output().stdout));
+        assert!(stdout.contains("Synonym Expansions:"), "{stdout}");
+        assert!(stdout.contains("py → py OR python^0.5"), "{stdout}");
+    }
+
+    #[test]
+    fn regex_field_queries() {
+        let dir = setup_indexed_dir();
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args([
+                "search",
+                r"body:/(systems|scripting)\s+(language|programming)/",
+            ])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Rust Programming"))
+            .stdout(predicate::str::contains("Python Programming"));
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", r"language -path:/^py/"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Rust Programming"))
+            .stdout(predicate::str::contains("Python Programming").not());
+
+        let assert = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "body:/retr(y|ies/"])
+            .assert()
+            .failure();
+        let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+        assert!(stderr.contains("invalid regex: unclosed group"), "{stderr}");
+        assert!(stderr.contains("\n            ^"), "{stderr}");
+    }
+
     #[test]
     fn supports_multiple_queries() {
         let dir = setup_indexed_dir();
@@ -683,6 +962,70 @@ path = "./docs"
         );
     }
 
+    #[test]
+    fn respects_max_tokens() {
+        let dir = setup_indexed_dir();
+        fs::write(
+            dir.path().join("docs").join("long.md"),
+            format!(
+                "# Programming Guide\n\n{}",
+                "Programming languages come in many shapes. ".repeat(100)
+            ),
+        )
+        .unwrap();
+
+        let output = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args([
+                "search",
+                "--max-tokens",
+                "40",
+                "--cutoff-ratio",
+                "0",
+                "--output",
+                "json",
+                "programming",
+            ])
+            .assert()
+            .success();
+
+        let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let query = &json["queries"][0];
+        let tokens_used = query["tokens_used"].as_u64().unwrap();
+        assert!(
+            tokens_used > 0 && tokens_used <= 40,
+            "tokens_used: {tokens_used}"
+        );
+
+        let ids: Vec<_> = query["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["id"].as_str().unwrap().to_string())
+            .collect();
+        assert!(!ids.is_empty());
+        assert!(
+            !ids.iter().any(|id| id.starts_with("docs:long.md")),
+            "oversized result should be skipped: {ids:?}"
+        );
+    }
+
+    #[test]
+    fn json_omits_tokens_without_budget() {
+        let dir = setup_indexed_dir();
+
+        let output = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "--output", "json", "rust"])
+            .assert()
+            .success();
+
+        let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        assert!(json["queries"][0].get("tokens_used").is_none());
+    }
+
     #[test]
     fn triggers_auto_index_when_missing() {
         let dir = temp_dir();
@@ -736,48 +1079,248 @@ path = "./docs"
     }
 
     #[test]
-    fn query_unknown_field_error() {
+    fn lenient_search_repairs_query() {
         let dir = setup_indexed_dir();
 
-        // Unknown field should show hint about valid fields
-        ra_with_home(dir.path())
+        let output = ra_with_home(dir.path())
             .current_dir(dir.path())
-            .args(["search", "unknown:value"])
+            .args(["search", "--lenient", "(python OR"])
             .assert()
-            .failure()
-            .stderr(predicate::str::contains("unknown field"))
-            .stderr(predicate::str::contains("hint"));
-    }
-
-    #[test]
-    fn negation_excludes_results() {
-        let dir = setup_indexed_dir();
+            .success();
+        let stdout = strip_ansi(&String::from_utf8_lossy(&output.get_output().stdout));
+        let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+        assert!(stdout.contains("Python Programming"), "{stdout}");
+        assert!(
+            stderr.contains("warning: query syntax error: expected closing parenthesis"),
+            "{stderr}"
+        );
+        assert!(stderr.contains("hint: OR requires expressions"), "{stderr}");
 
-        // Search for programming but exclude python (as single query string)
-        ra_with_home(dir.path())
+        let output = ra_with_home(dir.path())
             .current_dir(dir.path())
-            .args(["search", "programming -python"])
+            .args(["search", "--lenient", "-o", "json", "author:python"])
             .assert()
-            .success()
-            .stdout(predicate::str::contains("Rust"))
-            .stdout(predicate::str::is_match("Python").unwrap().not());
+            .success();
+        let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+        let warnings = &json["queries"][0]["warnings"];
+        assert_eq!(warnings[0]["message"], "unknown field: author");
+        assert_eq!(warnings[0]["position"], 0);
+        assert!(json["queries"][0]["total_matches"].as_u64().unwrap() > 0);
     }
 
     #[test]
-    fn or_finds_either_term() {
+    fn query_json() {
         let dir = setup_indexed_dir();
+        let query = r#"{"and": [{"term": "python"}, {"field": "title", "expr": {"phrase": ["python", "programming"]}}]}"#;
 
-        // Search for rust OR python - should find both
         let output = ra_with_home(dir.path())
             .current_dir(dir.path())
-            .args(["search", "--output", "json", "rust OR python"])
+            .args(["search", "--query-json", query])
             .assert()
             .success();
+        let stdout = strip_ansi(&String::from_utf8_lossy(&output.get_output().stdout));
+        assert!(stdout.contains("Python Programming"), "{stdout}");
 
-        let stdout = String::from_utf8_lossy(&output.get_output().stdout);
-        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
-        let results = json["queries"][0]["results"].as_array().unwrap();
-        assert!(
+        // Explain emits the same structure the query was given in
+        let output = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "--explain", "-o", "json", "--query-json", "-"])
+            .write_stdin(query)
+            .assert()
+            .success();
+        let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+        let expected: serde_json::Value = serde_json::from_str(query).unwrap();
+        assert_eq!(json["ast"], expected);
+        assert_eq!(json["query"], "python title:\"python programming\"");
+
+        let output = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "--explain", "-o", "json", "title:python OR rust"])
+            .assert()
+            .success();
+        let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+        assert_eq!(
+            json["ast"],
+            serde_json::json!({"or": [
+                {"field": "title", "expr": {"term": "python"}},
+                {"term": "rust"}
+            ]})
+        );
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "--query-json", r#"{"term": "a", "slop": 1}"#])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("'slop' does not apply to 'term'"));
+    }
+
+    #[test]
+    fn tag_filter_and_facets() {
+        let dir = setup_indexed_dir();
+        let docs = dir.path().join("docs");
+        fs::write(
+            docs.join("tokio.md"),
+            "---\ntags: [rust-async, programming]\n---\n# Tokio\n\nAn async runtime for Rust programming.",
+        )
+        .unwrap();
+        fs::write(
+            docs.join("rust.md"),
+            "---\ntags: [programming]\n---\n# Rust Programming\n\nRust is a systems programming language focused on safety.",
+        )
+        .unwrap();
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .arg("update")
+            .assert()
+            .success();
+
+        let output = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "-o", "list", "tag:rust-async"])
+            .assert()
+            .success();
+        let stdout = strip_ansi(&String::from_utf8_lossy(&output.get_output().stdout));
+        assert!(stdout.contains("Tokio"), "{stdout}");
+        assert!(!stdout.contains("Rust Programming"), "{stdout}");
+
+        let output = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args([
+                "search",
+                "-o",
+                "json",
+                "--facets",
+                "tags,tree",
+                "programming",
+            ])
+            .assert()
+            .success();
+        let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+        assert_eq!(
+            json["queries"][0]["facets"],
+            serde_json::json!({
+                "tags": {"programming": 2, "rust-async": 1},
+                "tree": {"docs": 3}
+            })
+        );
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "--facets", "body", "rust"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("unknown facet: body"));
+    }
+
+    #[test]
+    fn inline_tags_are_indexed() {
+        let dir = setup_indexed_dir();
+        let docs = dir.path().join("docs");
+        fs::write(
+            docs.join("oncall.md"),
+            "# Oncall\n\nPager rotation notes. #ops/oncall\n\n`#notatag`",
+        )
+        .unwrap();
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .arg("update")
+            .assert()
+            .success();
+
+        let output = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "-o", "json", "--facets", "tags", "tags:ops"])
+            .assert()
+            .success();
+        let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+        assert_eq!(
+            json["queries"][0]["facets"]["tags"],
+            serde_json::json!({"ops": 1, "ops/oncall": 1})
+        );
+        assert_eq!(json["queries"][0]["results"][0]["id"], "docs:oncall.md");
+    }
+
+    #[test]
+    fn meta_filter() {
+        let dir = setup_indexed_dir();
+        let docs = dir.path().join("docs");
+        fs::write(
+            docs.join("rust.md"),
+            "---\nstatus: draft\nversion: 2\n---\n# Rust Programming\n\nRust is a systems programming language focused on safety.",
+        )
+        .unwrap();
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .arg("update")
+            .assert()
+            .success();
+
+        let output = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "-o", "json", "programming -meta.status:draft"])
+            .assert()
+            .success();
+        let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+        let results = json["queries"][0]["results"].as_array().unwrap();
+        assert!(!results.is_empty());
+        assert!(results.iter().all(|r| r["path"] == "python.md"), "{json}");
+
+        let output = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "-o", "json", "meta.version:2"])
+            .assert()
+            .success();
+        let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+        assert_eq!(
+            json["queries"][0]["results"][0]["meta"],
+            serde_json::json!({"status": "draft", "version": 2})
+        );
+    }
+
+    #[test]
+    fn query_unknown_field_error() {
+        let dir = setup_indexed_dir();
+
+        // Unknown field should show hint about valid fields
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "unknown:value"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("unknown field"))
+            .stderr(predicate::str::contains("hint"));
+    }
+
+    #[test]
+    fn negation_excludes_results() {
+        let dir = setup_indexed_dir();
+
+        // Search for programming but exclude python (as single query string)
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "programming -python"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Rust"))
+            .stdout(predicate::str::is_match("Python").unwrap().not());
+    }
+
+    #[test]
+    fn or_finds_either_term() {
+        let dir = setup_indexed_dir();
+
+        // Search for rust OR python - should find both
+        let output = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["search", "--output", "json", "rust OR python"])
+            .assert()
+            .success();
+
+        let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let results = json["queries"][0]["results"].as_array().unwrap();
+        assert!(
             results.len() >= 2,
             "Expected at least 2 results for OR query"
         );
@@ -1526,6 +2069,222 @@ path = "./docs"
         assert!(stdout.contains("Installation"));
         assert!(stdout.contains("Configuration"));
     }
+
+    /// Runs `ra get --json` with extra arguments and returns the result IDs in order.
+    fn get_ids(dir: &Path, args: &[&str]) -> Vec<String> {
+        let output = ra_with_home(dir)
+            .current_dir(dir)
+            .args(["get", "--json"])
+            .args(args)
+            .assert()
+            .success();
+        let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+        json["queries"][0]["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["id"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn siblings_are_ordered_by_position() {
+        let dir = setup_indexed_dir();
+
+        let ids = get_ids(dir.path(), &["--siblings", "docs:guide.md#installation"]);
+        assert_eq!(
+            ids,
+            [
+                "docs:guide.md#getting-started",
+                "docs:guide.md#installation",
+                "docs:guide.md#configuration"
+            ]
+        );
+    }
+
+    #[test]
+    fn next_prev_and_parent() {
+        let dir = setup_indexed_dir();
+
+        let ids = get_ids(dir.path(), &["--next", "1", "docs:guide.md#installation"]);
+        assert_eq!(
+            ids,
+            ["docs:guide.md#installation", "docs:guide.md#configuration"]
+        );
+
+        let ids = get_ids(
+            dir.path(),
+            &["--prev", "1", "--parent", "docs:guide.md#installation"],
+        );
+        assert_eq!(
+            ids,
+            [
+                "docs:guide.md",
+                "docs:guide.md#getting-started",
+                "docs:guide.md#installation"
+            ]
+        );
+    }
+
+    #[test]
+    fn children_of_document() {
+        let dir = setup_indexed_dir();
+
+        let ids = get_ids(dir.path(), &["--children", "docs:guide.md"]);
+        assert_eq!(ids.len(), 4);
+        assert_eq!(ids[0], "docs:guide.md");
+    }
+
+    #[test]
+    fn full_document_conflicts_with_neighborhood() {
+        let dir = setup_indexed_dir();
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["get", "--full-document", "--next", "1", "docs:guide.md"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("cannot be used with"));
+    }
+}
+
+mod links {
+    use serde_json::Value;
+
+    use super::*;
+
+    fn setup_indexed_dir() -> tempfile::TempDir {
+        let dir = temp_dir();
+        let docs = dir.path().join("docs");
+        fs::create_dir_all(docs.join("ops")).unwrap();
+
+        fs::write(
+            docs.join("guide.md"),
+            "# Guide\n\nIntro.\n\n## Setup\n\n\
+             Follow [[retry#Backoff]], see [install](ops/install.md) and [[#Guide]].\n",
+        )
+        .unwrap();
+        fs::write(
+            docs.join("ops/retry.md"),
+            "# Retry\n\nIntro.\n\n## Backoff\n\nWait longer. Back to [the guide](../guide.md#setup).\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join(".ra.toml"),
+            "[tree.docs]\npath = \"./docs\"\n",
+        )
+        .unwrap();
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .arg("update")
+            .assert()
+            .success();
+
+        dir
+    }
+
+    /// Runs `ra links --json` for `id` and returns the parsed output.
+    fn links_json(dir: &Path, id: &str) -> Value {
+        let output = ra_with_home(dir)
+            .current_dir(dir)
+            .args(["links", "--json", id])
+            .assert()
+            .success();
+        serde_json::from_slice(&output.get_output().stdout).unwrap()
+    }
+
+    /// Returns the IDs listed under `key` in `ra links` JSON output.
+    fn ids(json: &Value, key: &str) -> Vec<String> {
+        json[key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|link| link["id"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn shows_outgoing_links_and_backlinks() {
+        let dir = setup_indexed_dir();
+
+        let json = links_json(dir.path(), "docs:guide.md#setup");
+        assert_eq!(
+            ids(&json, "outgoing"),
+            vec!["docs:ops/retry.md#backoff", "docs:guide.md#guide"]
+        );
+        assert_eq!(ids(&json, "backlinks"), vec!["docs:ops/retry.md#backoff"]);
+        assert_eq!(json["outgoing"][0]["breadcrumb"], "Retry > Backoff");
+    }
+
+    #[test]
+    fn document_ids_cover_all_chunks() {
+        let dir = setup_indexed_dir();
+
+        let json = links_json(dir.path(), "docs:ops/retry.md");
+        assert_eq!(ids(&json, "outgoing"), vec!["docs:guide.md#setup"]);
+        assert_eq!(ids(&json, "backlinks"), vec!["docs:guide.md#setup"]);
+    }
+
+    #[test]
+    fn prints_links_as_text() {
+        let dir = setup_indexed_dir();
+
+        let output = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["links", "docs:ops/retry.md#backoff"])
+            .assert()
+            .success();
+        let stdout = strip_ansi(&String::from_utf8_lossy(&output.get_output().stdout));
+        assert!(stdout.contains("Outgoing links (1):"));
+        assert!(stdout.contains("Backlinks (1):"));
+        assert!(stdout.contains("docs:guide.md#setup"));
+    }
+
+    /// Runs `ra search --json` with extra arguments and returns the first query's results.
+    fn search_results(dir: &Path, args: &[&str]) -> Vec<Value> {
+        let output = ra_with_home(dir)
+            .current_dir(dir)
+            .args(["search", "--no-aggregation", "-o", "json"])
+            .args(args)
+            .assert()
+            .success();
+        let json: Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+        json["queries"][0]["results"].as_array().unwrap().clone()
+    }
+
+    #[test]
+    fn search_follows_links_from_top_results() {
+        let dir = setup_indexed_dir();
+
+        let results = search_results(dir.path(), &["follow"]);
+        assert_eq!(results.len(), 1);
+        assert!(results[0].get("via_link").is_none());
+
+        // The link to the enclosing "Guide" section overlaps the result and is not added
+        let results = search_results(dir.path(), &["--follow-links", "1", "follow"]);
+        let ids: Vec<&str> = results.iter().map(|r| r["id"].as_str().unwrap()).collect();
+        assert_eq!(
+            ids,
+            vec!["docs:guide.md#setup", "docs:ops/retry.md#backoff"]
+        );
+        assert_eq!(results[1]["via_link"], "docs:guide.md#setup");
+        let top = results[0]["score"].as_f64().unwrap();
+        let linked = results[1]["score"].as_f64().unwrap();
+        assert!((linked - top / 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn fails_for_unknown_id() {
+        let dir = setup_indexed_dir();
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["links", "docs:missing.md"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("not found"));
+    }
 }
 
 mod likethis {
@@ -1895,3 +2654,753 @@ path = "./examples"
             .success();
     }
 }
+
+mod mcp {
+    use super::*;
+
+    /// Builds newline-delimited JSON-RPC input for an MCP session ending with `calls`.
+    fn session(calls: &[serde_json::Value]) -> String {
+        let mut messages = vec![
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 0,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": "2025-06-18",
+                    "capabilities": {},
+                    "clientInfo": { "name": "test", "version": "0.0.0" }
+                }
+            }),
+            serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+        ];
+        for (i, call) in calls.iter().enumerate() {
+            messages.push(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": i + 1,
+                "method": "tools/call",
+                "params": call
+            }));
+        }
+        messages
+            .iter()
+            .map(|m| format!("{m}\n"))
+            .collect::<String>()
+    }
+
+    /// Parses server output and returns the tool result text for a request ID.
+    fn tool_text(stdout: &str, id: u64) -> (String, bool) {
+        let response = stdout
+            .lines()
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+            .find(|v| v["id"] == id)
+            .unwrap_or_else(|| panic!("no response for id {id}: {stdout}"));
+        let result = &response["result"];
+        let text = result["content"][0]["text"].as_str().unwrap().to_string();
+        (text, result["isError"].as_bool().unwrap_or(false))
+    }
+
+    fn setup_dir() -> tempfile::TempDir {
+        let dir = temp_dir();
+        let docs = dir.path().join("docs");
+        fs::create_dir(&docs).unwrap();
+        fs::write(
+            docs.join("guide.md"),
+            "# Guide\n\n## Installation\n\nRun the installer to get started.\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join(".ra.toml"),
+            "[tree.docs]\npath = \"./docs\"\n",
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn fails_without_trees() {
+        let dir = temp_dir();
+        fs::write(dir.path().join(".ra.toml"), "# empty config\n").unwrap();
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .arg("mcp")
+            .write_stdin("")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("no trees defined"));
+    }
+
+    #[test]
+    fn serves_tools_over_stdio() {
+        let dir = setup_dir();
+
+        let input = session(&[
+            serde_json::json!({ "name": "search", "arguments": { "queries": "installer" } }),
+            serde_json::json!({ "name": "get", "arguments": { "id": "docs:guide.md#installation" } }),
+            serde_json::json!({ "name": "list_sources", "arguments": {} }),
+            serde_json::json!({ "name": "get", "arguments": { "id": "docs:missing.md" } }),
+        ]);
+
+        let output = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .arg("mcp")
+            .write_stdin(input)
+            .assert()
+            .success();
+        let stdout = String::from_utf8_lossy(&output.get_output().stdout).to_string();
+
+        let (search, is_error) = tool_text(&stdout, 1);
+        assert!(!is_error);
+        let search: serde_json::Value = serde_json::from_str(&search).unwrap();
+        assert_eq!(
+            search["queries"][0]["results"][0]["id"],
+            "docs:guide.md#installation"
+        );
+
+        let (get, is_error) = tool_text(&stdout, 2);
+        assert!(!is_error);
+        assert!(get.contains("Run the installer"));
+
+        let (sources, is_error) = tool_text(&stdout, 3);
+        assert!(!is_error);
+        let sources: serde_json::Value = serde_json::from_str(&sources).unwrap();
+        assert_eq!(sources["sources"][0]["name"], "docs");
+        assert_eq!(sources["sources"][0]["files"], 1);
+
+        let (missing, is_error) = tool_text(&stdout, 4);
+        assert!(is_error);
+        assert!(missing.contains("not found"));
+    }
+
+    #[test]
+    fn picks_up_changes_between_calls() {
+        let dir = setup_dir();
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .arg("update")
+            .assert()
+            .success();
+        fs::write(
+            dir.path().join("docs").join("extra.md"),
+            "# Extra\n\nZebras are striped.\n",
+        )
+        .unwrap();
+
+        let input = session(&[serde_json::json!({
+            "name": "search",
+            "arguments": { "queries": ["zebras"] }
+        })]);
+
+        let output = ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .arg("mcp")
+            .write_stdin(input)
+            .assert()
+            .success();
+        let stdout = String::from_utf8_lossy(&output.get_output().stdout).to_string();
+
+        let (search, _) = tool_text(&stdout, 1);
+        assert!(search.contains("docs:extra.md"));
+    }
+}
+
+mod agents {
+    use super::*;
+
+    /// Creates a project with a single `docs` tree.
+    fn project() -> tempfile::TempDir {
+        let dir = temp_dir();
+        fs::create_dir(dir.path().join("docs")).unwrap();
+        fs::write(
+            dir.path().join(".ra.toml"),
+            "[tree.docs]\npath = \"./docs\"\n",
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn writes_agents_file_from_templates() {
+        let dir = project();
+        let home = temp_dir();
+        fs::write(dir.path().join(".agents.md"), "# Project rules\n").unwrap();
+        fs::write(home.path().join(".agents.md"), "# Personal rules\n").unwrap();
+
+        ra_with_home(home.path())
+            .current_dir(dir.path())
+            .arg("agents")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Wrote"));
+
+        let contents = fs::read_to_string(dir.path().join("AGENTS.md")).unwrap();
+        let project = contents.find("# Project rules").unwrap();
+        let personal = contents.find("# Personal rules").unwrap();
+        let generated = contents.find("## Knowledge Base").unwrap();
+        assert!(project < personal && personal < generated);
+        assert!(contents.contains("- `docs` (local)"));
+        assert!(!dir.path().join("CLAUDE.md").exists());
+    }
+
+    #[test]
+    fn all_writes_every_variant() {
+        let dir = project();
+        let home = temp_dir();
+
+        ra_with_home(home.path())
+            .current_dir(dir.path())
+            .args(["agents", "--all", "--quiet"])
+            .assert()
+            .success();
+
+        let agents = fs::read_to_string(dir.path().join("AGENTS.md")).unwrap();
+        for name in ["CLAUDE.md", "GEMINI.md"] {
+            assert_eq!(fs::read_to_string(dir.path().join(name)).unwrap(), agents);
+        }
+    }
+
+    #[test]
+    fn stdout_does_not_write() {
+        let dir = project();
+        let home = temp_dir();
+
+        ra_with_home(home.path())
+            .current_dir(dir.path())
+            .args(["agents", "--stdout"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("ra search"));
+
+        assert!(!dir.path().join("AGENTS.md").exists());
+    }
+
+    #[test]
+    fn diff_shows_changes_without_writing() {
+        let dir = project();
+        let home = temp_dir();
+
+        ra_with_home(home.path())
+            .current_dir(dir.path())
+            .args(["agents", "--diff"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("+- `docs` (local)"));
+
+        assert!(!dir.path().join("AGENTS.md").exists());
+    }
+
+    #[test]
+    fn check_fails_when_stale() {
+        let dir = project();
+        let home = temp_dir();
+
+        ra_with_home(home.path())
+            .current_dir(dir.path())
+            .args(["agents", "--check"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("stale"));
+
+        ra_with_home(home.path())
+            .current_dir(dir.path())
+            .arg("agents")
+            .assert()
+            .success();
+
+        ra_with_home(home.path())
+            .current_dir(dir.path())
+            .args(["agents", "--check"])
+            .assert()
+            .success();
+
+        fs::create_dir(dir.path().join("notes")).unwrap();
+        fs::write(
+            dir.path().join(".ra.toml"),
+            "[tree.docs]\npath = \"./docs\"\n\n[tree.notes]\npath = \"./notes\"\n",
+        )
+        .unwrap();
+
+        ra_with_home(home.path())
+            .current_dir(dir.path())
+            .args(["agents", "--check"])
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("+- `notes` (local)"));
+    }
+}
+
+mod watch {
+    use std::{
+        io::{BufRead, BufReader},
+        process::{Child, Command as StdCommand, Stdio},
+        sync::mpsc::{self, Receiver},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+
+    /// A running `ra watch` process that is killed when dropped.
+    struct WatchProcess {
+        /// The child process.
+        child: Child,
+        /// Lines read from the child's stdout.
+        lines: Receiver<String>,
+    }
+
+    impl WatchProcess {
+        /// Starts `ra watch` in `dir` and waits until it is watching.
+        fn start(dir: &Path, home: &Path) -> Self {
+            let mut child = StdCommand::new(env!("CARGO_BIN_EXE_ra"))
+                .args(["watch", "--debounce", "100"])
+                .current_dir(dir)
+                .env("HOME", home)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .unwrap();
+
+            let stdout = child.stdout.take().unwrap();
+            let (tx, lines) = mpsc::channel();
+            thread::spawn(move || {
+                for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                    if tx.send(line).is_err() {
+                        break;
+                    }
+                }
+            });
+
+            let process = Self { child, lines };
+            process.wait_for("Watching for changes");
+            process
+        }
+
+        /// Waits for an output line containing `needle`, panicking after a timeout.
+        fn wait_for(&self, needle: &str) {
+            let deadline = Instant::now() + Duration::from_secs(20);
+            while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+                match self.lines.recv_timeout(remaining) {
+                    Ok(line) if line.contains(needle) => return,
+                    Ok(_) => {}
+                    Err(_) => break,
+                }
+            }
+            panic!("timed out waiting for output containing {needle:?}");
+        }
+    }
+
+    impl Drop for WatchProcess {
+        fn drop(&mut self) {
+            self.child.kill().ok();
+            self.child.wait().ok();
+        }
+    }
+
+    /// Creates a project with a single `docs` tree.
+    fn project() -> tempfile::TempDir {
+        let dir = temp_dir();
+        let docs = dir.path().join("docs");
+        fs::create_dir(&docs).unwrap();
+        fs::write(docs.join("existing.md"), "# Existing\n\nOriginal text.").unwrap();
+        fs::write(
+            dir.path().join(".ra.toml"),
+            "[tree.docs]\npath = \"./docs\"\n",
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn fails_without_trees() {
+        let dir = temp_dir();
+        fs::write(dir.path().join(".ra.toml"), "# empty config\n").unwrap();
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .arg("watch")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("no trees defined"));
+    }
+
+    #[test]
+    fn indexes_changes_as_they_happen() {
+        let dir = project();
+        let home = temp_dir();
+        let watch = WatchProcess::start(dir.path(), home.path());
+
+        let docs = dir.path().join("docs");
+        fs::write(docs.join("added.md"), "# Added\n\nFreshly written.").unwrap();
+        watch.wait_for("added.md");
+
+        fs::remove_file(docs.join("existing.md")).unwrap();
+        watch.wait_for("Removed");
+    }
+
+    #[test]
+    fn reloads_when_config_changes() {
+        let dir = project();
+        let home = temp_dir();
+        let watch = WatchProcess::start(dir.path(), home.path());
+
+        let notes = dir.path().join("notes");
+        fs::create_dir(&notes).unwrap();
+        fs::write(notes.join("idea.md"), "# Idea").unwrap();
+        fs::write(
+            dir.path().join(".ra.toml"),
+            "[tree.docs]\npath = \"./docs\"\n\n[tree.notes]\npath = \"./notes\"\n",
+        )
+        .unwrap();
+
+        watch.wait_for("Configuration changed");
+        watch.wait_for("Indexed 1 files");
+        watch.wait_for("Watching notes");
+    }
+}
+
+mod serve {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpStream,
+        process::{Child, Command as StdCommand, Stdio},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+
+    /// A running `ra serve` process that is killed when dropped.
+    struct ServeProcess {
+        /// The child process.
+        child: Child,
+        /// Address the server is listening on.
+        addr: String,
+    }
+
+    impl ServeProcess {
+        /// Starts `ra serve` on a free port in `dir`.
+        fn start(dir: &Path) -> Self {
+            let mut child = StdCommand::new(env!("CARGO_BIN_EXE_ra"))
+                .args(["serve", "--listen", "127.0.0.1:0"])
+                .current_dir(dir)
+                .env("HOME", dir)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .unwrap();
+
+            // The address is printed once the index is ready, after the watched trees
+            let addr = BufReader::new(child.stdout.take().unwrap())
+                .lines()
+                .map(Result::unwrap)
+                .find_map(|line| {
+                    line.strip_prefix("Listening on http://")
+                        .map(str::to_string)
+                })
+                .expect("server exited before listening");
+
+            Self { child, addr }
+        }
+
+        /// Sends a request and returns the status code and parsed JSON body.
+        fn request(&self, method: &str, path: &str, body: &str) -> (u16, serde_json::Value) {
+            let mut stream = TcpStream::connect(&self.addr).unwrap();
+            write!(
+                stream,
+                "{method} {path} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                self.addr,
+                body.len()
+            )
+            .unwrap();
+
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            let status = response
+                .split_whitespace()
+                .nth(1)
+                .and_then(|code| code.parse().ok())
+                .unwrap();
+            let (_, json) = response.split_once("\r\n\r\n").unwrap();
+            (status, serde_json::from_str(json).unwrap())
+        }
+    }
+
+    impl Drop for ServeProcess {
+        fn drop(&mut self) {
+            self.child.kill().ok();
+            self.child.wait().ok();
+        }
+    }
+
+    /// Creates a project with a single indexed document.
+    fn setup_dir() -> tempfile::TempDir {
+        let dir = temp_dir();
+        let docs = dir.path().join("docs");
+        fs::create_dir(&docs).unwrap();
+        fs::write(
+            docs.join("guide.md"),
+            "# Guide\n\n## Installation\n\nRun the installer.\n\n## Usage\n\nUse the tool.\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join(".ra.toml"),
+            "[tree.docs]\npath = \"./docs\"\n",
+        )
+        .unwrap();
+        dir
+    }
+
+    #[test]
+    fn fails_without_trees() {
+        let dir = temp_dir();
+        fs::write(dir.path().join(".ra.toml"), "# empty config\n").unwrap();
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["serve", "--listen", "127.0.0.1:0"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("no trees defined"));
+    }
+
+    #[test]
+    fn serves_json_endpoints() {
+        let dir = setup_dir();
+        let server = ServeProcess::start(dir.path());
+
+        let (status, search) = server.request("POST", "/search", r#"{"queries": "installer"}"#);
+        assert_eq!(status, 200);
+        assert_eq!(
+            search["queries"][0]["results"][0]["id"],
+            "docs:guide.md#installation"
+        );
+
+        let (status, get) = server.request("POST", "/get", r#"{"id": "docs:guide.md#usage"}"#);
+        assert_eq!(status, 200);
+        assert_eq!(get["queries"][0]["results"][0]["id"], "docs:guide.md#usage");
+
+        let (status, trees) = server.request("GET", "/ls/trees", "");
+        assert_eq!(status, 200);
+        assert_eq!(trees["sources"][0]["files"], 1);
+
+        let (status, docs) = server.request("GET", "/ls/docs", "");
+        assert_eq!(status, 200);
+        assert_eq!(docs["docs"][0]["id"], "docs:guide.md");
+
+        let (status, chunks) = server.request("GET", "/ls/chunks", "");
+        assert_eq!(status, 200);
+        assert!(chunks["chunks"].as_array().unwrap().len() >= 2);
+    }
+
+    #[test]
+    fn reports_errors_as_json() {
+        let dir = setup_dir();
+        let server = ServeProcess::start(dir.path());
+
+        let (status, body) = server.request("POST", "/get", r#"{"id": "docs:missing.md"}"#);
+        assert_eq!(status, 404);
+        assert!(body["error"].as_str().unwrap().contains("not found"));
+
+        let (status, _) = server.request("POST", "/search", "not json");
+        assert_eq!(status, 400);
+
+        let (status, _) = server.request("POST", "/get", r#"{"id": "no-colon"}"#);
+        assert_eq!(status, 400);
+
+        let (status, _) = server.request("GET", "/search", "");
+        assert_eq!(status, 405);
+
+        let (status, _) = server.request("GET", "/nope", "");
+        assert_eq!(status, 404);
+    }
+
+    #[test]
+    fn sees_changes_between_requests() {
+        let dir = setup_dir();
+        let server = ServeProcess::start(dir.path());
+
+        let (_, before) = server.request("POST", "/search", r#"{"queries": "zebras"}"#);
+        assert!(
+            before["queries"][0]["results"]
+                .as_array()
+                .unwrap()
+                .is_empty()
+        );
+
+        fs::write(
+            dir.path().join("docs").join("extra.md"),
+            "# Extra\n\nZebras are striped.\n",
+        )
+        .unwrap();
+
+        // The change is indexed in the background, so poll until it shows up
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let id = loop {
+            let (_, after) = server.request("POST", "/search", r#"{"queries": "zebras"}"#);
+            if let Some(id) = after["queries"][0]["results"][0]["id"].as_str() {
+                break id.to_string();
+            }
+            assert!(Instant::now() < deadline, "change was never indexed");
+            thread::sleep(Duration::from_millis(100));
+        };
+        assert!(id.starts_with("docs:extra.md"));
+
+        let (status, similar) =
+            server.request("POST", "/likethis", r#"{"source": "docs:extra.md"}"#);
+        assert_eq!(status, 200);
+        assert_eq!(similar["queries"][0]["query"], "docs:extra.md");
+    }
+}
+
+mod tui {
+    use super::*;
+
+    #[test]
+    fn fails_without_trees() {
+        let dir = temp_dir();
+        fs::write(dir.path().join(".ra.toml"), "# empty config\n").unwrap();
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .arg("tui")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("no trees defined"));
+    }
+
+    #[test]
+    fn rejects_unknown_tree() {
+        let dir = temp_dir();
+        fs::create_dir(dir.path().join("docs")).unwrap();
+        fs::write(
+            dir.path().join(".ra.toml"),
+            "[tree.docs]\npath = \"docs\"\n",
+        )
+        .unwrap();
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .args(["tui", "--tree", "missing"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("unknown tree: missing"));
+    }
+}
+
+mod batch {
+    use serde_json::Value;
+
+    use super::*;
+
+    /// Creates an indexed directory with one `docs` tree.
+    fn setup_indexed_dir() -> tempfile::TempDir {
+        let dir = temp_dir();
+        let docs = dir.path().join("docs");
+        fs::create_dir(&docs).unwrap();
+        fs::write(
+            docs.join("guide.md"),
+            "# Guide\n\nIntro.\n\n## Install\n\nRun cargo install.\n\n## Usage\n\nRun ra search.\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join(".ra.toml"),
+            "[tree.docs]\npath = \"docs\"\n",
+        )
+        .unwrap();
+        dir
+    }
+
+    /// Runs `ra batch` with the given input and parses each output line.
+    fn run_batch(dir: &Path, input: &str) -> Vec<Value> {
+        let output = ra_with_home(dir)
+            .current_dir(dir)
+            .arg("batch")
+            .write_stdin(input)
+            .assert()
+            .success();
+        String::from_utf8_lossy(&output.get_output().stdout)
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn answers_each_request_in_order() {
+        let dir = setup_indexed_dir();
+        let input = concat!(
+            r#"{"op": "search", "ref": 1, "queries": "install"}"#,
+            "\n\n",
+            r#"{"op": "get", "ref": 2, "id": "docs:guide.md#install", "next": 1}"#,
+            "\n",
+            r#"{"op": "likethis", "source": "docs:guide.md#install"}"#,
+            "\n",
+        );
+
+        let responses = run_batch(dir.path(), input);
+        assert_eq!(responses.len(), 3);
+
+        assert_eq!(responses[0]["ref"], 1);
+        let results = &responses[0]["queries"][0]["results"];
+        assert_eq!(results[0]["id"], "docs:guide.md#install");
+
+        assert_eq!(responses[1]["ref"], 2);
+        let ids: Vec<&str> = responses[1]["queries"][0]["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, ["docs:guide.md#install", "docs:guide.md#usage"]);
+
+        assert!(responses[2].get("ref").is_none());
+        assert!(responses[2]["queries"].is_array());
+    }
+
+    #[test]
+    fn reports_errors_per_line() {
+        let dir = setup_indexed_dir();
+        let input = concat!(
+            "not json\n",
+            r#"{"op": "explode", "ref": "x"}"#,
+            "\n",
+            r#"{"op": "get", "id": "docs:missing.md"}"#,
+            "\n",
+            r#"{"op": "search", "queries": "usage"}"#,
+            "\n",
+        );
+
+        let responses = run_batch(dir.path(), input);
+        assert_eq!(responses.len(), 4);
+        assert!(
+            responses[0]["error"]
+                .as_str()
+                .unwrap()
+                .contains("invalid request")
+        );
+        assert_eq!(responses[1]["ref"], "x");
+        assert!(responses[1]["error"].is_string());
+        assert!(
+            responses[2]["error"]
+                .as_str()
+                .unwrap()
+                .contains("not found")
+        );
+        assert!(responses[3]["queries"].is_array());
+    }
+
+    #[test]
+    fn fails_without_trees() {
+        let dir = temp_dir();
+        fs::write(dir.path().join(".ra.toml"), "# empty config\n").unwrap();
+
+        ra_with_home(dir.path())
+            .current_dir(dir.path())
+            .arg("batch")
+            .write_stdin("")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("no trees defined"));
+    }
+}